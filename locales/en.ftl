@@ -0,0 +1,760 @@
+# Fisha UI strings (English). Copy this file to locales/<language>.ftl to add a translation.
+
+activity = Activity
+activity-appended = Appended
+activity-archived = Archived
+activity-commented = Commented
+activity-confirmed = Confirmed still valid
+activity-created = Created
+activity-cross-listed = Cross-listed
+activity-definition-changed = Definition changed
+activity-deleted = Deleted
+activity-described = Described
+activity-display-changed = Display changed
+activity-edited = Edited
+activity-flagged-for-review = Flagged for review
+activity-flags-changed = Flags changed
+activity-imported = Imported
+activity-merge-undone = Merge undone
+activity-merged = Merged
+activity-purged = Purged
+activity-rated = Rated
+activity-redone = Redone
+activity-reordered = Reordered
+activity-restored = Restored from backup
+activity-reviewed = Reviewed
+activity-snoozed = Snoozed
+activity-split = Split
+activity-undone = Undone
+activity-unlinked = Unlinked
+add-current-file = Add current file
+add-field = + Add field
+add-files = Add files…
+add-shortcut = Add a shortcut
+add-the-missing-columns-first = Add the missing columns to the header row first
+add-timestamp = Add timestamp
+add-webhook = Add webhook
+aging = ⏳ Aging
+aging-all-categories = Include inactive categories
+aging-all-categories-hint = Also list notes in categories with no activity in that time
+aging-archive = 📦 Archive
+aging-archive-hint = Keep the note as a historical record and stop listing it here
+aging-days = days without an edit or confirmation
+aging-hint = Old notes due for a check that they're still right
+aging-more = …and { $count } more
+aging-nothing-stale = No stale notes.
+aging-snooze = 💤 Snooze { $days } days
+aging-stale-after = Notes count as stale after
+aging-stale-notes = { $count } stale notes, oldest first
+aging-still-valid = ✔ Still valid
+aging-undated = undated
+all-categories = All categories
+all-fields-are-required = All fields are required
+all-subcategories = All subcategories
+also-show-this-concept-under-another = Also show this concept under another subcategory
+analyze = Analyze
+any-author = Any author
+any-category = Any category
+any-flag = Any flag
+append-to-note = Append to Note
+approve = ✔ Approve
+as-of-notes-that-month = As % of notes that month
+attached-to-changes-you-make-from = Attached to changes you make from now on
+author-label = Author:
+author-name-hint = Signs the comments you add to notes; empty uses the profile name
+author-name-label = Your name
+autosave-hint = Save changes to the open file this often. 0 turns autosave off
+autosave-label = Autosave every
+average-rating-per-category = Average rating per category
+back = ◀ Back
+backup-counts = { $categories } categories, { $notes } notes
+backup-unreadable = Can't be read
+branding = Branding
+break-length-label = Breaks:
+bridge-terms = Bridge Terms
+browse = Browse
+browse-columns-auto = Fit the width
+browse-columns-hint = Lay a concept's notes out in columns, each card going under the shortest one, so wide windows aren't one narrow stack
+browse-columns-label = Browse columns:
+browse-files = Browse…
+calendar-exported = Exported { $count } event(s) to { $path }
+calendar-fields-label = Date fields:
+calendar-no-date-fields = No custom field holds dates. Give notes a field such as Due with values like 2025-03-04 or 2025-03-04 14:30.
+cancel = Cancel
+cancel-n = Cancel (n)
+cancelled = Cancelled
+cancelling = Cancelling…
+cannot-be-fixed = Can't be fixed
+card-actions-on-hover = Show note buttons on hover
+card-spacing-label = Card spacing:
+categories = Categories
+categories-by-size = Categories by size
+categories-label = Categories:
+categories-none-selected-all-label = Categories (none selected = all):
+categories-notes = Categories (notes)
+categories-unit = categories
+category = Category
+category-label = Category:
+category-term-distribution = Category-Term Distribution
+changes-not-saved-yet = { $unsaved } changes not saved yet
+choose-font-file = Choose a .ttf, .otf or .ttc file
+choose-icon-file = Choose a PNG image
+choose-profile = Who's using Fisha?
+clean-up-notes = Clean up notes:
+cleanup-collapse-blank-lines = Collapse repeated blank lines
+cleanup-plain-quotes = Replace curly quotes with straight ones
+cleanup-strip-html = Strip HTML tags
+cleanup-trim = Trim spaces at line ends and around the note
+clear = Clear
+clear-dates = Clear dates
+clear-filter = Clear filter
+clear-recent-files = Clear Recent Files
+clear-recent-files-hint = Empty the list; favorites stay
+clear-tags = Clear tags
+click-a-concept-to-open-it = Click a concept to open it
+click-for-a-ranked-list = Click for a list ranked by note count
+click-to-show-the-whole-note = Click to show the whole note
+close = Close
+code-font-label = Code font:
+code-font-size-label = Code font size:
+collapse = Collapse
+collapse-all = Collapse all
+color-label = Color:
+comma-separated-e-g-rust-egui = comma-separated, e.g. rust, egui
+command-dismiss = Close window / clear search
+command-find = Find in notes
+command-palette = Command palette
+comment-hint = Add a comment for the other reviewers
+comment-thread = 💬 Comments ({ $count })
+compact-cards = Compact note cards
+compact-cards-hint = Show each note in Browse and Search as a single line; click it to see the whole card
+compare-categories = Compare Categories
+compressed-csv = Compressed CSV (.csv.gz, .zip)
+concept-aliases = aka { $aliases }
+concept-clusters = Concept Clusters
+concept-is-already-listed-there = Concept is already listed there
+concept-label = Concept:
+concepts-by-size = Concepts by size
+concepts-grouped-by-shared-terms-named = Concepts grouped by shared terms, named after their most distinctive words
+concepts-label = Concepts:
+converted-from-encoding = { $path } was converted from { $encoding } to UTF-8; saving writes it as UTF-8
+copied-as-quote = Copied to the clipboard as a Markdown quote
+copy-id = Copy ID
+could-not-open-an-email = Could not open an email: { $error }
+could-not-start-the-editor = Could not start the editor: { $error }
+coverage-covered = { $percent }% covered
+coverage-line = coverage
+coverage-share-axis = % of occurrences
+coverage-top-axis = top N words
+create = Create
+create-new-entry = Create New Entry
+create-profile = Create
+create-sample-fiche = Create sample fiche
+create-sample-fiche-hint = A few notes about science, languages, cooking and books to try things out on
+created-but-looks-like-it-contains = Entry created. It looks like it contains: { $kinds }
+created-from-label = Created from:
+created-on = Created { $date }
+created-to-label = to:
+cross-list = Cross-list…
+cross-list-button = Cross-list
+cross-list-concept = Cross-list Concept
+custom-theme-name = Custom theme { $number }
+daily-goal-off-hint = 0 turns the daily goal off
+daily-goal-reached-come-back-tomorrow = Daily goal reached. Come back tomorrow, or keep going below.
+data-file-hint = Opened by "Open this file", and by "Open the last file" until a file has been used
+data-file-label = Data file:
+data-file-not-found = { $path } was not found; starting with an empty fiche, which will be saved there
+date-hint = YYYY-MM-DD
+day-streak = 🔥 { $streak } day streak
+days-goal-met = Days goal met
+definition = 📌 Definition
+delete = Delete
+delete-comment = Delete comment
+delete-note = Delete note?
+delete-y = Delete (y)
+describe-what-belongs-here = Describe what belongs here...
+description-updated = Description updated
+details-characters = Characters
+details-characters-no-spaces = Characters without spaces
+details-created = Created
+details-id = ID
+details-id-hint = Derived from the note's path and text, so it changes when the note is edited
+details-minutes = { $minutes } min
+details-modified = Last edited
+details-reading-time = Reading time
+details-revisions = Revisions
+details-revisions-hint = The original plus the edits and appends to it recorded in the activity log
+details-unique-terms = Unique terms
+details-words = Words
+dismiss = Dismiss
+done = ✔ Done
+done-editing-externally = ✏ Done editing externally ({ $count })
+dont-save = Don't Save
+duplicates-off = Nothing, import every note
+duplicates-same-path = Notes already under the same concept
+duplicates-same-text = Notes with the same text anywhere (ignoring case, accents and spacing)
+edit = Edit
+edit-theme = Edit theme
+edit-theme-named = Edit theme { $name }
+edited-on = Edited { $date }
+editing-in-external-editor = Opened in the external editor; saves there update the note
+entropy = Entropy
+entry-created-successfully = Entry created successfully
+entry-deleted = Entry deleted
+entry-loaded-for-editing-modify-and = Entry loaded for editing. Modify and click Create to save.
+error-exporting = Error exporting: { $error }
+error-importing = Error importing: { $error }
+error-loading = Error loading: { $error }
+error-registering-file-types = Could not register file types: { $error }
+error-saving = Error saving: { $error }
+error-validating = Error validating: { $error }
+expand = Expand
+expand-all = Expand all
+export-calendar = Export Calendar
+export-calendar-hint = Notes with a date in a custom field (Due, Reminder, …) as events in an iCalendar file, to import into or subscribe to from your calendar app. Fields named like a reminder also get an alert.
+export-calendar-menu = Export Calendar (.ics)…
+export-feed = Export RSS Feed
+export-feed-hint = The most recently added notes as an RSS feed, for teammates to follow in a feed reader once it's published somewhere they can reach. Notes without a creation date are left out.
+export-feed-menu = Export RSS Feed…
+export-markdown = Export as Markdown
+export-markdown-hint = Categories, subcategories and concepts as # / ## / ### headings, one-line notes as bullets and longer ones as paragraphs, for publishing or reading in Obsidian.
+export-markdown-menu = Export as Markdown…
+export-pack = Export pack
+export-pack-hint = Pick the categories or subcategories to share. Their colors, descriptions, templates and cross-listings go along.
+export-pack-menu = Export Pack…
+export-report = Export Report…
+export-term-network = Export Term Network...
+exporting = Exporting to { $path }
+external-editor = External editor:
+external-editor-hint = A command that opens a file and stays running until it is closed, e.g. `code --wait` or `gedit`; the file path is added at the end. Leave empty to use $VISUAL, $EDITOR or the system default
+fallback-font-label = Fallback font:
+feed-exported = Exported { $count } feed(s) to { $path }
+feed-limit-label = Notes per feed:
+feed-link-hint = Web address the feed will be served from; feed readers link its notes back here
+feed-link-label = Published at:
+feed-per-category = One feed per category
+feed-per-category-hint = Pick a folder and write a feed file for each category into it
+fetch-page-titles = Fetch page titles
+fetch-page-titles-hint = Visit each page and show the link as its title
+fetching-titles = Fetching titles: { $done }/{ $total }
+fiche-pack = Fiche pack
+fields-label = Fields:
+file-changed-on-disk = { $file } was changed by another program.
+file-info = File Info
+file-info-author = Author
+file-info-by = By { $author }
+file-info-description = Description
+file-info-hint = Describes the whole file for whoever it's shared with. Shown in the title bar and the Library tab, and saved with the file.
+file-info-menu = File Info…
+file-info-schema = Schema version { $version }
+file-info-schema-version = Schema version
+file-info-title = Title
+file-info-updated = File info updated
+file-menu = File
+file-this = File This
+file-this-hint = Move the selected text, or all of it, into the Create form
+filter-by-tag = Show only notes with this tag
+filter-label = Filter:
+filter-notes = Filter this concept's notes
+filter-notes-hint = 🔍 Filter notes…
+finish = Finish
+fisha-or-csv = Fisha, SQLite or CSV
+flag-gold = Gold
+flag-label = Flag:
+flag-outdated = Outdated
+flag-todo = To do
+flag-verify = Verify
+focus-break-hint = Break
+focus-break-over = Break over
+focus-capture-hint = What did you learn in this session?
+focus-capture-title = What did you learn?
+focus-file-note = File note
+focus-length-label = Focus sessions:
+focus-note-filed = Filed under { $path }
+focus-pause = Pause
+focus-resume = Resume
+focus-running-hint = Focus session; when it ends you'll be asked what you learned
+focus-session-over = Focus session over, time for a break
+focus-skip = Skip
+focus-start = Start a focus session
+focus-start-hint = Start a { $minutes }-minute focus session; when it ends you'll be asked what you learned
+focus-stop = Stop the timer
+font-default = Default
+font-reset = Use the default
+fonts = Fonts
+found-in-at-least = Found in at least
+found-results = Found { $count } results
+found-results-in-files = Found { $count } results in this file and { $other } in other files
+goals = Goals
+graph = 🕸 Graph
+guided-tour = Guided tour
+highlight = Highlight
+highlight-blue = Blue
+highlight-green = Green
+highlight-none = None
+highlight-orange = Orange
+highlight-pink = Pink
+highlight-purple = Purple
+highlight-yellow = Yellow
+icon-hint = e.g. 📐
+icon-label = Icon:
+icon-not-loaded = Could not load icon { $path }: { $error }
+import = Import...
+import-contents = Where the notes go
+import-links = Import Links
+import-links-button = Import { $count } Links
+import-links-hint = Each link becomes a note under the concept below, e.g. the tabs copied from a browser.
+import-links-menu = Import Links from Clipboard…
+import-notes = Import notes
+import-pack = Import pack
+import-pack-menu = Import Pack…
+import-vault-menu = Import Markdown Folder…
+imported-links = Imported { $count } links into { $path }
+imported-notes-from-flagged-for-review = Imported { $count } notes from { $path_str } (flagged for review)
+imported-notes-with-possible-secrets = {" "}({ $count } may contain secrets)
+infinite-scroll-instead-of-pages = Infinite scroll instead of pages
+info-activity = Activity, last { $days } days: { $count }
+info-concepts = Concepts
+info-newest-note = Newest note
+info-notes = Notes
+info-oldest-note = Oldest note
+info-top-terms = Top terms
+insert-markers-at-blank-lines = Insert markers at blank lines
+insert-skeleton = Insert skeleton
+items-per-page-label = Items per page:
+keep-mine = Keep Mine
+keep-mine-hint = Keep this version; the next save writes it over the file
+keyboard-shortcuts = Keyboard Shortcuts
+keyboard-shortcuts-hint = Click + and press a key combination to add a shortcut; a shortcut already in use moves to the new command. Undo and redo leave text fields to their own undo.
+keyboard-shortcuts-menu = Keyboard shortcuts…
+knowledge-statistics-word-associations = Knowledge Statistics & Word Associations
+language-label = Language:
+library = Library
+library-concepts = Concepts
+library-empty = No files in the library yet. Add the current file or pick others to see them here.
+library-export = Export…
+library-export-hint = Save a copy as .fiche, .db or CSV
+library-exported = Exported { $file } to { $path }
+library-file = File
+library-file-open = { $name } (open)
+library-files-not-read = Could not read library files: { $files }
+library-merge-hint = Merge this file's notes into the open file, through the import wizard
+library-modified = Last modified
+library-notes = Notes
+library-open = Open
+library-unreadable = Could not read
+line = Line { $line }
+links-already-filed = { $count } already in this concept are left out
+links-found = { $count } links
+load = Load
+loaded = Loaded { $path_str }
+loaded-notes = Loaded { $count } notes
+local-graph = Local graph: { $concept }
+manage-library = Manage library…
+markdown-exported = Exported { $count } note(s) in { $files } file(s) to { $path }
+markdown-per-category = One file per category
+markdown-per-category-hint = Pick a folder and write a file for each category into it
+merge = Merge
+merge-changes = Merge
+merge-changes-hint = Bring in the notes added, removed or changed in the file, keeping the changes made here
+merge-concept = Merge Concept
+merge-into = Merge into…
+merge-of-undone = Merge of { $concept } undone
+merged-changes-from-disk = Merged changes from disk: { $added } added, { $removed } removed, { $updated } updated
+merged-into-moved-duplicates-dropped = Merged { $concept } into { $concept2 } ({ $moved } moved, { $duplicates } duplicates dropped)
+min-rating-label = Min rating:
+minimum-similarity-label = Minimum similarity:
+most-active-category = Most active category
+most-frequent-stop-word-candidates-label = Most frequent (stop word candidates):
+move-down = Move Down
+move-this-concept-s-notes-into = Move this concept's notes into another concept
+move-up = Move Up
+moved-to-the-create-form = Moved to the Create form
+name = Name
+name-label = Name:
+needs-review = ⚑ Needs review
+new-notes-in-this-subcategory-start = New notes anywhere in this subcategory start with this text, unless their concept has its own template.
+new-notes-under-this-concept-start = New notes under this concept start with this text.
+new-profile = New Profile…
+new-theme = New Theme…
+new-theme-hint = Start a theme from the current one's colors
+next = Next ▶
+no-activity-recorded = No activity recorded
+no-backups-yet = No backups of this file yet; one is taken each time it's saved.
+no-categories-yet = No categories yet
+no-clusters-at-this-threshold-try = No clusters at this threshold; try lowering it
+no-co-occurrences-found = No co-occurrences found
+no-data-loaded = No data loaded
+no-dated-notes-yet-notes-get = No dated notes yet; notes get a creation date when written in the Create tab
+no-goals-set = No goals set
+no-linked-concepts-add-concept-links = No linked concepts. Add [[Concept]] links or #tags to notes to connect them.
+no-matching-commands = No matching commands
+no-notes-look-like-they-contain-secrets = No notes look like they contain secrets
+no-problems-found-in-rows = No problems found in { $count } rows
+no-recent-files = No recent files
+no-terms-span-that-many-categories = No terms span that many categories
+no-words-yet = No words yet
+none = none
+not-loaded-click-to-load = Not loaded yet. Click to load this category.
+not-touched-yet-this-month-label = Not touched yet this month:
+note-details = Note Details
+note-editor-link-hint = Type [[ to link to a concept
+note-label = Note:
+note-looks-like-it-contains = ⚠ This note looks like it contains: { $kinds }
+note-no-longer-exists = Note no longer exists
+note-split-into-notes = Note split into { $count } notes
+note-template = Note template…
+note-template-hint = e.g. Date:, Role:, Questions:
+note-template-removed = Note template removed
+note-template-title = Note template
+note-template-updated = Note template updated
+note-tool-append = Append…
+note-tool-comment = Comment…
+note-tool-copy-as-quote = Copy as quote
+note-tool-details = Details…
+note-tool-edit-externally = Edit in external editor
+note-tool-flag-for-review = Flag for review
+note-tool-highlight = Highlight
+note-tool-indent = Keep indentation
+note-tool-mark-reviewed = Mark as reviewed
+note-tool-monospace = Use monospace font
+note-tool-no-indent = Don't keep indentation
+note-tool-no-wrap = Don't wrap lines
+note-tool-pin-definition = Pin as definition
+note-tool-proportional = Use proportional font
+note-tool-purge = Purge…
+note-tool-share-by-email = Share by email…
+note-tool-show-qr = Show QR
+note-tool-split = Split…
+note-tool-unpin-definition = Unpin definition
+note-tool-wrap = Wrap lines
+note-tools = Note tools
+note-updated = Note updated
+note-updated-from-the-external-editor = Note updated from the external editor
+notes = { $count } notes
+notes-captured = Notes captured
+notes-match = { $count } of { $total } notes
+notes-may-contain-secrets = { $count } notes may contain API keys, tokens or passwords
+notes-per-day-label = Notes per day:
+notes-studied-at-least-once-per = Notes studied at least once, per category
+notes-to-load = Notes to load: { $count }
+notes-will-be-changed = { $count } notes will be changed
+notes-will-be-moved-identical-notes = { $count } notes will be moved, { $duplicates } identical notes dropped
+notes-without-a-creation-date-are = { $undated } notes without a creation date are not shown
+nothing-left-to-study-today = Nothing left to study today
+nothing-to-redo = Nothing to redo
+nothing-to-undo = Nothing to undo
+now-also-appears-in = { $concept } now also appears in { $target_cat } > { $target_sub }
+numeric-fields = Numeric Fields
+occurrences = { $count } occurrences
+of-reviewed = { $review_done } of { $total } reviewed
+on-startup-label = On startup:
+only-csv-files-can-be-validated = Only CSV files can be validated
+only-in = Only in { $left_name }
+open = Open...
+open-file = Open File
+open-in-its-file = Open in its file
+open-in-its-file-unsaved = Opens that file; unsaved changes to this one are lost
+open-recent = Open Recent
+open-selectively = Open Selectively...
+open-with-fixes = Open with fixes
+opened-an-email-with-the-note = Opened a new email with the note in your mail app
+opened-with-fixes = Opened with { $count } problems fixed; save to keep the fixes
+order-label = Order:
+order-newest = Newest first
+order-oldest = Oldest first
+order-recently-edited = Recently edited
+order-stored = As stored
+overview = Overview
+pack-attachments-renamed = Attachments whose names were taken are stored as { $names }; notes that mention them still use the old names.
+pack-conflict-keep-both = Keep both, renaming the pack's
+pack-conflict-merge = Merge in new notes
+pack-conflict-replace = Replace them with the pack's
+pack-conflict-skip = Skip them
+pack-conflicts = { $count } concepts already exist here:
+pack-contents = { $categories } categories, { $concepts } concepts, { $notes } notes, { $attachments } attachments
+pack-exported = Exported { $count } notes to pack { $path }
+pack-flag-for-review = Flag imported notes for review
+pack-imported = Imported { $count } notes and { $attachments } attachments from { $name }
+pack-imported-conflicts =  ({ $merged } merged, { $renamed } renamed, { $skipped } skipped, { $replaced } replaced)
+pack-include-attachments = Include attachments ({ $count })
+pack-lands-as = Categories are named like “{ $category }”
+pack-namespace = Put under
+pack-namespace-hint = Namespace (optional)
+pack-no-conflicts = No concepts in this pack exist here yet.
+pack-title-hint = Onboarding notes
+page = Page
+pairs-appearing-together = Pairs appearing together
+paste-links-one-per-line = Paste links here, one per line
+pick-a-different-concept = Pick a different concept
+pick-a-target-concept-to-preview = Pick a target concept to preview the merge
+pick-the-categories-to-load = Pick the categories to load now; the others are listed in Browse and load when clicked.
+pick-two-different-categories = Pick two different categories
+pin-as-definition = Pin as the concept's definition
+pin-as-definition-hint = Shown first and highlighted in Browse; replaces the concept's current definition
+pin-file = Keep at the top as a favorite
+possible-secret = ⚠ Possible secret
+post-comment = Post
+press-a-shortcut = Press a shortcut… (Esc cancels)
+prev = ◀ Prev
+privacy = 🔒 Privacy
+privacy-hint = Mask note bodies for screen sharing, showing only the hierarchy and the first few words
+profile-keeps-its-own-settings-workspaces-draft = A profile keeps its own settings, workspaces, Create draft and data file
+profile-menu = Profile: { $name }
+publish = Publish
+publish-concept = Publish Concept
+publish-failed = Couldn't publish: { $error }
+publish-hint = Anyone with the link can read what's shown below.
+publish-needs-a-token = Enter a token for the service first
+publish-to = Publish concepts to
+publish-token = Token
+publish-token-hint-gist = GitHub token with the gist scope
+publish-token-hint-pastebin = Pastebin developer API key
+publish-token-saved-with-settings = Saved with your settings on this computer
+published = Published; link copied: { $url }
+publishing = Publishing…
+purge = Purge
+purge-note = Purge note
+purge-removes-the-note-and = Removes the note, every activity log entry quoting it and its study record, then saves the file right away. This cannot be undone.
+purged-note-and-activity-entries = Note purged, along with { $count } activity log entries
+put-a-line-containing-only-wherever = Put a line containing only { $split_marker } wherever the note should be split
+qr-code = QR code
+qr-note-text = Note text
+qr-path-only = Path only
+rate-of-5 = Rate { $star } of 5
+rated = ({ $count } rated)
+rating-label = Rating:
+ratings = Ratings
+recent-file-missing = { $path } no longer exists
+redo = Redo (Ctrl+Y)
+redo-change = Redo: { $change } (Ctrl+Y)
+redone = Redone: { $change }
+register-file-types = Open .fiche and .csv files with Fisha
+register-file-types-hint = Register this program as the handler for .fiche files and offer it for .csv files
+reload = Reload
+reload-hint = Open the file as it is now, dropping the changes made here since the last save
+remove = Remove
+remove-field = Remove field
+remove-from-library = Remove from library
+remove-shortcut = Remove this shortcut
+remove-this-cross-listing = Remove this cross-listing
+remove-webhook = Remove webhook
+removed-cross-listing-of = Removed cross-listing of { $concept }
+render-markdown = Render notes as Markdown
+render-markdown-hint = Show headings, lists, links and code in note text formatted in Browse, Search, Review and Study
+replaces-the-workspace-with-this-name = Replaces the workspace with this name
+reply = Reply
+report-exported = Exported report to { $path }
+report-title = { $file } report
+report-title-default = Knowledge report
+reset-shortcuts = Reset to defaults
+reset-to-color-blind-safe = Reset to color-blind safe
+restore = Restore
+restore-backup = Restore from Backup
+restore-backup-hint = Copies of this file taken each time it was saved. Restoring one replaces the notes in the window; the file itself changes when you save, and Undo brings the current notes back.
+restore-backup-menu = Restore from Backup…
+restored-backup = Restored the backup from { $taken }; save to keep it
+review = Review
+rows-checked-problems-found = { $rows } rows checked, { $count } problems found
+sample-fiche-created = Sample fiche created; save it to keep it
+save = Save
+save-as = Save As...
+save-workspace = Save Workspace
+save-workspace-as = Save Workspace As…
+saved-but-the-activity-log-failed = Saved, but the activity log failed: { $error }
+saved-but-the-snapshot-failed = Saved, but the backup copy failed: { $error }
+saved-to = Saved to { $path }
+saved-with-the-file = Saved with the file
+saved-workspace = Saved workspace { $name }
+saving = Saving to { $path }
+scope-label = Scope:
+scratchpad = 🗒 Scratchpad
+scratchpad-hint = Jot down text that isn't ready to be filed; it's kept between runs
+scratchpad-placeholder = Anything you don't know where to put yet…
+scroll-to-the-start-of-this-concept = Scroll to the start of this concept
+search = Search
+search-all-files = All files
+search-all-files-hint = Also search the files in File > Library
+search-flagged-notes = Search the notes with this flag
+search-label = Search:
+search-notes-with-both-terms = Search notes with both terms
+search-this-term-in-the-category = Search this term in the category
+secret-scan-report = Secret scan
+select-a-category-from-the-left = Select a category from the left panel
+select-a-subcategory-to-view-its = Select a subcategory to view its contents
+select-all = Select all
+select-none = Select none
+session-note-label = Session note:
+settings = Settings
+share-this-concept-s-notes-as-a-link = Upload this concept's notes as a secret Gist or unlisted paste and copy the link
+shared = Shared
+shared-tag = — shared tag
+show = Show
+show-all = Show all
+show-concepts-linked-to-this-one = Show concepts linked to this one
+show-guided-tour = Show guided tour
+show-local-graph = Show local graph
+show-more = Show { $count } more
+show-note = Show note
+show-notes-in-privacy-mode = Show notes in privacy mode
+show-raw = Raw
+show-raw-hint = Show this note's text as written
+show-rendered = Rendered
+show-rendered-hint = Show this note formatted as Markdown
+showing-of-notes = Showing { $shown } of { $count } notes
+similar-note-go-to = Show this concept in Browse
+similar-notes-exist = Similar notes exist:
+similar-notes-exist-hint = Notes sharing many of the words you're writing, rarer words counting more. Adding to one of them may be better than starting another fragment.
+similar-terms = — similar terms
+skip = Skip
+skip-on-import = Skip on import
+skip-tour = Skip tour
+skipped-duplicate-notes = {" "}({ $count } duplicates skipped)
+snapshots-kept-hint = Every save also copies the file into a .fisha_backups folder beside it; this many of the newest copies are kept (0 turns them off)
+snapshots-kept-label = Backups kept:
+sparkline-peak = Busiest day: { $count }
+split = Split
+split-note = Split Note
+start-empty = Start empty
+started-from-the-note-template = Started from the note template
+startup-empty = Start with an empty fiche
+startup-fixed-file = Open this file
+startup-last-file = Open the last file
+stats = Stats
+stats-color-alert = Alert
+stats-color-highlight = Highlight
+stats-color-primary = Primary
+stats-color-secondary = Secondary
+stats-colors = Stats colors
+stats-palette-color-blind-safe = Color-blind safe (Okabe-Ito)
+stats-palette-custom = Custom
+stats-palette-theme = Follow theme
+stats-panels = Stats panels
+stop-watching-the-editor-files = Stop picking up saves from the external editor
+study = Study
+study-coverage = Study Coverage
+subcategories = Subcategories
+subcategories-by-size = Subcategories by size
+subcategories-label = Subcategories:
+subcategory-info = ℹ Info
+subcategory-info-title = Subcategory Info
+subcategory-label = Subcategory:
+sum-and-average-per-category-click = Sum and average per category (click a field to sort)
+switched-to-workspace = Switched to workspace { $name }
+switching-profile-closes-the-file-unsaved = Switching profile closes the open file; unsaved changes will be lost
+tags-hint = Comma-separated, e.g. exam, onboarding
+tags-label = Tags:
+template = Template
+template-loaded-add-your-new-note = Template loaded. Add your new note and click Create.
+term = Term
+term-co-occurrences = Term Co-occurrences
+term-network-exported = Exported { $terms } terms and { $links } links to { $path }
+term-trends = Term Trends
+terms-label = Terms:
+terms-spread-evenly-over-many-categories = Terms spread evenly over many categories; often worth a category of their own. Click a term to see its notes.
+text-new-notes-in-this-subcategory-start-with = Text new notes in this subcategory start with
+text-new-notes-under-this-concept-start-with = Text new notes under this concept start with
+text-or-date-e-g-2025 = text or date, e.g. 2025-03
+text-to-add = Text to add...
+the-file-is-unchanged-until-you-save = The file itself is unchanged until you save
+the-template-note-has-a-structure = The template note has headings, labels or lists to reuse
+theme = Theme
+theme-accent = Accent
+theme-background = Background
+theme-color-blind-safe = Color-blind Safe
+theme-dark = Dark
+theme-editor = Theme Editor
+theme-error = Errors
+theme-faint-background = Faint background
+theme-high-contrast = High Contrast
+theme-hovered = Buttons under the pointer
+theme-light = Light
+theme-link = Links
+theme-name = Name:
+theme-pressed = Pressed buttons
+theme-selected = Selection
+theme-selection = Theme Selection
+theme-text = Text
+theme-warning = Warnings
+theme-widget = Buttons
+these-categories-share-much-of-their = 💡 These categories share much of their vocabulary; they may be worth merging.
+this-note-is-already-open-in-the-editor = This note is already open in the external editor
+today = { $done_today } / { $goal } today
+too-long-for-a-qr-code = This note is too long for a QR code (about 2,900 characters at most); show the path instead
+top-terms-per-category = Top terms per category
+total-notes-label = Total Notes:
+touch-every-category-monthly = Touch every category monthly
+tour-browse = Browse lists every note by category, subcategory and concept. Pick a category on the left and expand concepts to read their notes.
+tour-create = Create adds new notes. Type a new category, subcategory or concept name, or choose an existing one.
+tour-search = Search finds notes by words in any field. Narrow it down by category or minimum rating.
+tour-stats = Stats shows what the fiche is about: note counts, the most used terms and which terms appear together.
+tour-step = Step { $step } of { $count }
+translations-hint = Translations are <language>.ftl files in the locales folder
+type-a-command = Type a command
+type-a-page-number-to-jump-to = Type a page number to jump to; PageUp/PageDown turn pages
+undo = Undo (Ctrl+Z)
+undo-change = Undo: { $change } (Ctrl+Z)
+undo-merge = Undo merge
+undone = Undone: { $change }
+unique-terms = { $count } unique terms
+unique-terms-label = Unique Terms:
+unknown-author = Unknown
+unlink = Unlink
+unpin-file = Remove from favorites
+unsaved-changes = Unsaved changes
+unsaved-changes-autosave-hint = Changes since the last save; autosaved every { $minutes } min
+unsaved-changes-hint = Changes since the last save. File → Save keeps them
+unsaved-changes-indicator = ● Unsaved
+unsaved-changes-prompt = { $file } has changes that haven't been saved. Save them first?
+untitled = Untitled
+use-browse-selection = Use Browse selection
+validate-bad-rating = Rating '{ $value }' is not 1 to 5
+validate-duplicate = Same as line { $line }
+validate-empty-field = Empty { $column }
+validate-empty-note = Empty note
+validate-extra-fields = { $count } more fields than there are columns (a stray comma or quote?)
+validate-file = Validate File…
+validate-fix-bad-rating = Clear the rating
+validate-fix-drop-row = Drop the row
+validate-fix-empty-field = Fill in '{ $placeholder }'
+validate-fix-extra-fields = Join the extra fields into the note with commas
+validate-fix-invalid-utf8 = Replace unreadable bytes with �
+validate-fix-missing-fields = Leave the missing fields empty
+validate-invalid-utf8 = Not valid UTF-8 text
+validate-missing-column = Missing required column '{ $column }'
+validate-missing-fields = { $count } fewer fields than there are columns
+validation-report = Validation report
+value = Value
+vault-mapping-files = Folder → category, file → concept
+vault-mapping-headings = Folder → category, headings → subcategory and concept
+vault-mapping-label = Place notes by:
+vault-no-notes = No Markdown notes found in that folder
+vim-keybindings = Vim keybindings
+vim-keybindings-hint = j/k move through notes, / searches, dd deletes the highlighted note, : opens the command palette
+vs = vs
+wait-for-the-current-save-or-export = Wait for the current save or export to finish
+webhook-enabled = Send to this webhook
+webhook-event-created = Created
+webhook-event-deleted = Deleted
+webhook-event-edited = Edited
+webhook-failed = Webhook { $url } failed: { $error }
+webhook-send-test = Send test
+webhook-test-sent = Test message sent to { $url }
+webhook-url = Webhook URL
+webhooks-heading = Webhooks ({ $count })
+webhooks-hint = Each note created, edited or deleted is posted as JSON to these URLs (Slack incoming webhooks work as they are). Needs curl.
+weekly-summary = Weekly summary
+welcome-text = Fisha keeps notes on index cards, filed by category, subcategory and concept. There's no fiche here yet: start with a sample to look around, begin with an empty one, or open a file you already have.
+welcome-to-fisha = Welcome to Fisha
+whole-fiche = Whole fiche
+wiki-link = — wiki link
+window-icon-label = Window icon:
+window-title-label = Window title:
+word-frequency-distribution = Word Frequency Distribution
+word-frequency-summary = { $words } distinct words, { $occurrences } occurrences. Top 10 cover { $top10 }%, top 100 cover { $top100 }%.
+workspace-saves-the-open-file-selections-and = Saves the open file, the Browse selection, the current tab, and the search and Stats filters
+workspaces = Workspaces
+zipf-frequency-axis = frequency (log)
+zipf-law = Zipf's law
+zipf-rank-axis = rank (log)
+zipf-words = words
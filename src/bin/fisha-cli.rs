@@ -0,0 +1,27 @@
+//! Console entry point for `fisha <command>`. On Windows `fisha` is a GUI
+//! program, so anything it prints never reaches the terminal that started
+//! it; this runs the `fisha` next to it with the same arguments, attached to
+//! this console, and exits with its status. Elsewhere `fisha` works directly.
+
+use std::process::{Command, exit};
+
+fn main() {
+    let app = match std::env::current_exe() {
+        Ok(exe) => exe.with_file_name(format!("fisha{}", std::env::consts::EXE_SUFFIX)),
+        Err(e) => {
+            eprintln!("fisha: {}", e);
+            exit(1);
+        }
+    };
+    let mut args: Vec<_> = std::env::args_os().skip(1).collect();
+    if args.is_empty() {
+        args.push("help".into());
+    }
+    match Command::new(&app).args(args).status() {
+        Ok(status) => exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("fisha: could not run {}: {}", app.display(), e);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! iCalendar (.ics) files of dated notes, so deadlines kept in a fiche show
+//! up in a calendar app. Dates come from custom fields whose values are all
+//! dates (`Due`, `Reminder`, …); each filled one becomes an event on that day,
+//! or at that time when it has one. Fields named like a reminder also get an
+//! alarm when the event starts.
+
+use crate::{Microfiche, excerpt, note_key, note_path};
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::BTreeSet;
+
+/// When an event happens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum When {
+    Day(NaiveDate),
+    // Local time; calendars show it at that hour wherever they are
+    Time(NaiveDateTime),
+    Utc(chrono::DateTime<chrono::Utc>),
+}
+
+/// Read a field value as a date: `2025-03-04`, `2025-03-04 14:30`,
+/// `2025-03-04T14:30:00` or an RFC 3339 timestamp
+pub fn parse_date(value: &str) -> Option<When> {
+    let value = value.trim();
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(When::Day(day));
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(When::Utc(time.to_utc()));
+    }
+    ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"].iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(When::Time)
+}
+
+/// Custom fields holding dates: every filled value parses as one
+pub fn date_fields(fiche: &Microfiche) -> BTreeSet<String> {
+    let mut fields = BTreeSet::new();
+    let mut other = BTreeSet::new();
+    for category in fiche.categories.values() {
+        for subcat in &category.subcategories {
+            for concept in &subcat.concepts {
+                for note in &concept.notes {
+                    for (field, value) in note.fields.iter().filter(|(_, value)| !value.trim().is_empty()) {
+                        if parse_date(value).is_some() {
+                            fields.insert(field.clone());
+                        } else {
+                            other.insert(field.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    fields.retain(|field| !other.contains(field));
+    fields
+}
+
+/// Whether events from `field` should alert
+fn is_reminder(field: &str) -> bool {
+    field.to_lowercase().contains("remind")
+}
+
+/// What goes into a calendar
+pub struct Options<'a> {
+    pub name: &'a str,
+    // Fields read as dates
+    pub fields: &'a BTreeSet<String>,
+    // Only this category's notes; None for the whole fiche
+    pub category: Option<&'a str>,
+}
+
+/// The calendar, and how many events it holds
+pub fn ics(fiche: &Microfiche, options: &Options) -> (String, usize) {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    line(&mut out, "BEGIN:VCALENDAR");
+    line(&mut out, "VERSION:2.0");
+    line(&mut out, "PRODID:-//Fisha//Fisha//EN");
+    line(&mut out, "CALSCALE:GREGORIAN");
+    line(&mut out, &format!("X-WR-CALNAME:{}", escape(options.name)));
+
+    let mut count = 0;
+    for (cat, category) in &fiche.categories {
+        if options.category.is_some_and(|only| only != cat) {
+            continue;
+        }
+        for subcat in &category.subcategories {
+            for concept in &subcat.concepts {
+                for note in &concept.notes {
+                    for field in options.fields {
+                        let Some(when) = note.fields.get(field).and_then(|value| parse_date(value)) else {
+                            continue;
+                        };
+                        let path = note_path(cat, &subcat.name, &concept.name);
+                        let uid = note_key(cat, &subcat.name, &concept.name, &format!("{}\u{1f}{}", field, note.text));
+                        line(&mut out, "BEGIN:VEVENT");
+                        line(&mut out, &format!("UID:{}@fisha", uid));
+                        line(&mut out, &format!("DTSTAMP:{}", stamp));
+                        match when {
+                            When::Day(day) => {
+                                line(&mut out, &format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+                                line(&mut out, &format!("DTEND;VALUE=DATE:{}", day.succ_opt().unwrap_or(day).format("%Y%m%d")));
+                            },
+                            When::Time(time) => line(&mut out, &format!("DTSTART:{}", time.format("%Y%m%dT%H%M%S"))),
+                            When::Utc(time) => line(&mut out, &format!("DTSTART:{}", time.format("%Y%m%dT%H%M%SZ"))),
+                        }
+                        let summary = format!("{}: {} – {}", field, concept.name, excerpt(&note.text, 60));
+                        line(&mut out, &format!("SUMMARY:{}", escape(&summary)));
+                        line(&mut out, &format!("DESCRIPTION:{}", escape(&format!("{}\n\n{}", note.text, path))));
+                        let categories: Vec<String> = std::iter::once(cat.as_str())
+                            .chain(note.tags.iter().map(String::as_str))
+                            .map(escape)
+                            .collect();
+                        line(&mut out, &format!("CATEGORIES:{}", categories.join(",")));
+                        if is_reminder(field) {
+                            line(&mut out, "BEGIN:VALARM");
+                            line(&mut out, "ACTION:DISPLAY");
+                            line(&mut out, &format!("DESCRIPTION:{}", escape(&summary)));
+                            line(&mut out, "TRIGGER:PT0M");
+                            line(&mut out, "END:VALARM");
+                        }
+                        line(&mut out, "END:VEVENT");
+                        count += 1;
+                    }
+                }
+            }
+        }
+    }
+    line(&mut out, "END:VCALENDAR");
+    (out, count)
+}
+
+/// Escape text for a property value
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Append `content` as CRLF-ended lines of at most 75 bytes, continuations
+/// starting with a space, without splitting a character
+fn line(out: &mut String, content: &str) {
+    let mut width = 0;
+    for c in content.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
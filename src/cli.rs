@@ -0,0 +1,373 @@
+//! `fisha <command> …`: adding, searching, counting, exporting and deleting
+//! notes from a terminal or script, on the same files the app opens. Changes
+//! are saved back straight away and recorded in the file's activity log, so
+//! they show up in the Activity tab like the app's own.
+
+use crate::{ActivityAction, ActivityLog, FicheRow, JobProgress, Microfiche, SearchFilter, calendar, excerpt, markdown_export, note_path, timestamp_now};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::io::Read;
+
+pub const COMMANDS: [&str; 6] = ["add", "search", "stats", "export", "delete", "help"];
+
+const USAGE: &str = "\
+Usage: fisha <command> <file> [arguments]
+
+Commands:
+  add <file> <category> <subcategory> <concept> [text…]
+        Add a note; without text (or with -), read it from standard input
+        --rating <1-5>  --tag <tag> (repeatable)  --author <name>  --review
+  search <file> <query…>
+        Print matching notes, best match first
+        --category <name>  --tag <tag>  --author <name>  --min-rating <1-5>
+        --limit <n>  --json
+  stats <file> [--json]
+        Count categories, subcategories, concepts and notes
+  export <file> <output>
+        Write the fiche as .json, .fiche, .db, or CSV for any other extension;
+        .ics writes the notes' date fields as calendar events, and .md the
+        whole fiche as Markdown
+  delete <file> <category> <subcategory> <concept> <text…>
+        Delete the note with this text, or the one note of the concept
+        containing it
+";
+
+/// Whether `fisha` was started as a command rather than to open a file
+pub fn is_command(arg: &str) -> bool {
+    COMMANDS.contains(&arg)
+}
+
+/// Run `args` (starting with the command), exiting with 1 on failure
+pub fn run(args: Vec<String>) {
+    if let Err(e) = dispatch(args) {
+        eprintln!("fisha: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn dispatch(args: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let mut args = args.into_iter();
+    let command = args.next().unwrap_or_default();
+    let args = Args::parse(args)?;
+    match command.as_str() {
+        "add" => add(args),
+        "search" => search(args),
+        "stats" => stats(args),
+        "export" => export(args),
+        "delete" => delete(args),
+        _ => {
+            print!("{}", USAGE);
+            Ok(())
+        },
+    }
+}
+
+/// Options taking a value; any other `--name` is a switch
+const VALUE_OPTIONS: [&str; 6] = ["rating", "tag", "author", "category", "min-rating", "limit"];
+const SWITCHES: [&str; 2] = ["json", "review"];
+
+struct Args {
+    positional: Vec<String>,
+    options: BTreeMap<String, Vec<String>>,
+    switches: BTreeSet<String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args { positional: Vec::new(), options: BTreeMap::new(), switches: BTreeSet::new() };
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            // Everything after `--` is text, even if it starts with dashes
+            if arg == "--" {
+                parsed.positional.extend(args.by_ref());
+                break;
+            }
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg);
+                continue;
+            };
+            if VALUE_OPTIONS.contains(&name) {
+                let value = args.next().ok_or_else(|| format!("--{} needs a value", name))?;
+                parsed.options.entry(name.to_string()).or_default().push(value);
+            } else if SWITCHES.contains(&name) {
+                parsed.switches.insert(name.to_string());
+            } else {
+                return Err(format!("unknown option --{}", name));
+            }
+        }
+        Ok(parsed)
+    }
+    
+    /// The next positional argument, called `name` in the error if missing
+    fn take(&mut self, name: &str) -> Result<String, String> {
+        if self.positional.is_empty() {
+            return Err(format!("missing <{}>; see fisha help", name));
+        }
+        Ok(self.positional.remove(0))
+    }
+    
+    /// The rest of the positional arguments as one text
+    fn rest(&mut self) -> String {
+        std::mem::take(&mut self.positional).join(" ")
+    }
+    
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|values| values.last()).map(String::as_str)
+    }
+    
+    fn all(&self, name: &str) -> BTreeSet<String> {
+        self.options.get(name).into_iter().flatten().map(|value| value.trim().to_string()).collect()
+    }
+    
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.option(name)
+            .map(|value| value.parse().map_err(|_| format!("--{} must be a number, not {:?}", name, value)))
+            .transpose()
+    }
+    
+    fn switch(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+}
+
+/// Open `path`, or start an empty fiche if it doesn't exist yet
+fn open(path: &str, create: bool) -> Result<Microfiche, Box<dyn Error>> {
+    if create && !std::path::Path::new(path).exists() {
+        return Ok(Microfiche::new());
+    }
+    Microfiche::open(path).map_err(|e| format!("could not open {}: {}", path, e).into())
+}
+
+/// Save `fiche` back to `path` and append `action` to its activity log
+fn save(fiche: &Microfiche, path: &str, action: ActivityAction, note_path: String, text: &str) -> Result<(), Box<dyn Error>> {
+    fiche.save(path, &JobProgress::default())
+        .map_err(|e| format!("could not save {}: {}", path, e))?;
+    let mut activity = ActivityLog::load(path);
+    activity.record(action, note_path, excerpt(text, 80), "");
+    // The note is saved; a log that can't be written isn't worth failing over
+    if let Err(e) = activity.flush(path) {
+        eprintln!("fisha: could not update the activity log: {}", e);
+    }
+    Ok(())
+}
+
+fn add(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let file = args.take("file")?;
+    let category = args.take("category")?;
+    let subcategory = args.take("subcategory")?;
+    let concept = args.take("concept")?;
+    let mut text = args.rest();
+    if text.is_empty() || text == "-" {
+        text.clear();
+        std::io::stdin().read_to_string(&mut text)?;
+    }
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("the note is empty".into());
+    }
+    if [&category, &subcategory, &concept].iter().any(|name| name.trim().is_empty()) {
+        return Err("category, subcategory and concept can't be empty".into());
+    }
+    let rating = args.number::<u8>("rating")?;
+    if rating.is_some_and(|r| !(1..=5).contains(&r)) {
+        return Err("--rating must be between 1 and 5".into());
+    }
+    
+    let mut fiche = open(&file, true)?;
+    let (category, subcategory, concept) = (category.trim(), subcategory.trim(), concept.trim());
+    if fiche.concept(category, subcategory, concept).is_some_and(|c| c.notes.iter().any(|n| n.text == text)) {
+        return Err(format!("{} already has this note", note_path(category, subcategory, concept)).into());
+    }
+    fiche.add_row(FicheRow {
+        category: category.to_string(),
+        subcategory: subcategory.to_string(),
+        concept: concept.to_string(),
+        note: text.clone(),
+        rating,
+        needs_review: args.switch("review"),
+        created: Some(timestamp_now()),
+        modified: None,
+        fields: BTreeMap::new(),
+        display: Default::default(),
+        definition: false,
+        flags: BTreeSet::new(),
+        tags: args.all("tag").into_iter().filter(|tag| !tag.is_empty()).collect(),
+        author: args.option("author").unwrap_or_default().trim().to_string(),
+        comments: Vec::new(),
+    });
+    let path = note_path(category, subcategory, concept);
+    save(&fiche, &file, ActivityAction::Created, path.clone(), &text)?;
+    println!("Added to {}", path);
+    Ok(())
+}
+
+fn search(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let file = args.take("file")?;
+    let query = args.rest();
+    let filter = SearchFilter {
+        min_rating: args.number("min-rating")?.unwrap_or(0),
+        category: args.option("category").map(str::to_string),
+        tags: args.all("tag"),
+        author: args.option("author").map(|author| author.trim().to_string()),
+        ..Default::default()
+    };
+    if query.trim().is_empty() && !filter.is_active() {
+        return Err("missing <query>; see fisha help".into());
+    }
+    let fiche = open(&file, false)?;
+    let mut results = fiche.search(&query, &filter);
+    if let Some(limit) = args.number::<usize>("limit")? {
+        results.truncate(limit);
+    }
+    
+    if args.switch("json") {
+        // One object per line, for piping into jq and the like
+        for (cat, sub, con, note) in &results {
+            println!("{}", serde_json::json!({
+                "category": cat,
+                "subcategory": sub,
+                "concept": con,
+                "text": note.text,
+                "rating": note.rating,
+                "tags": note.tags,
+                "author": note.author,
+                "created": note.created,
+            }));
+        }
+    } else {
+        for (cat, sub, con, note) in &results {
+            println!("{}: {}", note_path(cat, sub, con), excerpt(&note.text, 100));
+        }
+        eprintln!("{} {}", results.len(), if results.len() == 1 { "note" } else { "notes" });
+    }
+    Ok(())
+}
+
+fn stats(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let file = args.take("file")?;
+    let fiche = open(&file, false)?;
+    let stats = fiche.stats();
+    let count = |key: &str| stats.get(key).copied().unwrap_or(0);
+    let mut by_category: Vec<(&str, usize)> = fiche.categories.iter()
+        .map(|(name, category)| {
+            let notes = category.subcategories.iter()
+                .flat_map(|s| &s.concepts)
+                .map(|c| c.notes.len())
+                .sum();
+            (name.as_str(), notes)
+        })
+        .collect();
+    by_category.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    
+    if args.switch("json") {
+        let categories: serde_json::Map<String, serde_json::Value> = by_category.iter()
+            .map(|(name, notes)| (name.to_string(), (*notes).into()))
+            .collect();
+        println!("{}", serde_json::json!({
+            "categories": count("categories"),
+            "subcategories": count("subcategories"),
+            "concepts": count("concepts"),
+            "notes": count("total_notes"),
+            "notes_by_category": categories,
+        }));
+    } else {
+        println!("Categories:    {}", count("categories"));
+        println!("Subcategories: {}", count("subcategories"));
+        println!("Concepts:      {}", count("concepts"));
+        println!("Notes:         {}", count("total_notes"));
+        if !by_category.is_empty() {
+            println!();
+            for (name, notes) in by_category {
+                println!("{:>6}  {}", notes, name);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn export(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let file = args.take("file")?;
+    let output = args.take("output")?;
+    let fiche = open(&file, false)?;
+    if output.to_lowercase().ends_with(".ics") {
+        let fields = calendar::date_fields(&fiche);
+        if fields.is_empty() {
+            return Err("no custom field holds dates to put in a calendar".into());
+        }
+        let (ics, count) = calendar::ics(&fiche, &calendar::Options { name: &crate::file_name(&file), fields: &fields, category: None });
+        std::fs::write(&output, ics).map_err(|e| format!("could not write {}: {}", output, e))?;
+        println!("Exported {} events to {}", count, output);
+        return Ok(());
+    }
+    if output.to_lowercase().ends_with(".md") {
+        let (text, count) = markdown_export::document(&fiche, &markdown_export::Options { category: None, subcategory: None });
+        std::fs::write(&output, text).map_err(|e| format!("could not write {}: {}", output, e))?;
+        println!("Exported {} notes to {}", count, output);
+        return Ok(());
+    }
+    if output.to_lowercase().ends_with(".json") {
+        let mut rows = Vec::new();
+        for (cat, category) in &fiche.categories {
+            for subcat in &category.subcategories {
+                for concept in &subcat.concepts {
+                    for note in &concept.notes {
+                        rows.push(serde_json::json!({
+                            "category": cat,
+                            "subcategory": subcat.name,
+                            "concept": concept.name,
+                            "text": note.text,
+                            "rating": note.rating,
+                            "needs_review": note.needs_review,
+                            "tags": note.tags,
+                            "author": note.author,
+                            "created": note.created,
+                            "modified": note.modified,
+                            "fields": note.fields,
+                        }));
+                    }
+                }
+            }
+        }
+        std::fs::write(&output, serde_json::to_string_pretty(&rows)?)
+            .map_err(|e| format!("could not write {}: {}", output, e))?;
+    } else {
+        fiche.save(&output, &JobProgress::default())
+            .map_err(|e| format!("could not write {}: {}", output, e))?;
+    }
+    println!("Exported {} notes to {}", fiche.note_count(), output);
+    Ok(())
+}
+
+fn delete(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let file = args.take("file")?;
+    let category = args.take("category")?;
+    let subcategory = args.take("subcategory")?;
+    let concept = args.take("concept")?;
+    let text = args.rest();
+    if text.trim().is_empty() {
+        return Err("missing <text>; see fisha help".into());
+    }
+    let mut fiche = open(&file, false)?;
+    let path = note_path(&category, &subcategory, &concept);
+    let notes = &fiche.concept(&category, &subcategory, &concept)
+        .ok_or_else(|| format!("no concept {}", path))?
+        .notes;
+    let target = match notes.iter().find(|n| n.text == text.trim()) {
+        Some(note) => note.text.clone(),
+        None => {
+            let matching: Vec<&str> = notes.iter()
+                .filter(|n| n.text.contains(text.trim()))
+                .map(|n| n.text.as_str())
+                .collect();
+            match matching.as_slice() {
+                [only] => only.to_string(),
+                [] => return Err(format!("no note in {} contains {:?}", path, text.trim()).into()),
+                several => return Err(format!("{} notes in {} contain {:?}; give more of the text", several.len(), path, text.trim()).into()),
+            }
+        },
+    };
+    fiche.delete_note(&category, &subcategory, &concept, &target);
+    save(&fiche, &file, ActivityAction::Deleted, path.clone(), &target)?;
+    println!("Deleted from {}: {}", path, excerpt(&target, 80));
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! Reading and writing data files that may be compressed, picked by
+//! extension: `.gz` is gzip, `.zip` holds a single CSV entry, anything else
+//! is read and written as-is.
+
+use flate2::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+fn has_extension(path: &str, extension: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// Name of the CSV entry written into `path` when it's a `.zip`
+fn zip_entry_name(path: &str) -> String {
+    let stem = Path::new(path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    if has_extension(&stem, "csv") { stem } else { format!("{}.csv", stem) }
+}
+
+/// Run `read` over the decompressed contents of `path`
+pub fn read_with<T>(path: &str, read: impl FnOnce(&mut dyn Read) -> Result<T, Box<dyn Error>>) -> Result<T, Box<dyn Error>> {
+    let file = BufReader::new(File::open(path)?);
+    if has_extension(path, "gz") {
+        read(&mut MultiGzDecoder::new(file))
+    } else if has_extension(path, "zip") {
+        let mut archive = ZipArchive::new(file)?;
+        let index = (0..archive.len())
+            .find(|&i| archive.name_for_index(i).is_some_and(|name| has_extension(name, "csv")))
+            .ok_or_else(|| format!("{} has no CSV file inside", path))?;
+        read(&mut archive.by_index(index)?)
+    } else {
+        let mut file = file;
+        read(&mut file)
+    }
+}
+
+/// Create `path` and let `write` fill it, compressing by the extension of
+/// `target`, the name it's saved under (a temporary file is renamed after)
+pub fn write_with(path: &str, target: &str, write: impl FnOnce(&mut dyn Write) -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+    let file = BufWriter::new(File::create(path)?);
+    if has_extension(target, "gz") {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        write(&mut encoder)?;
+        encoder.finish()?.flush()?;
+    } else if has_extension(target, "zip") {
+        let mut zip = ZipWriter::new(file);
+        zip.start_file(zip_entry_name(target), SimpleFileOptions::default().compression_method(CompressionMethod::Deflated))?;
+        write(&mut zip)?;
+        zip.finish()?.flush()?;
+    } else {
+        let mut file = file;
+        write(&mut file)?;
+        file.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! Editing a note in another program: the text goes to a temporary file, the
+//! editor is started on it, and every save is picked up by watching the file.
+//!
+//! The editor is the command set in Settings, else `$VISUAL` or `$EDITOR`,
+//! else the platform's default. Editors that hand the file to an already
+//! running window and exit at once (`xdg-open`, `code` without `--wait`) are
+//! watched until the user says they're done.
+
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+/// An editor exiting sooner than this has handed the file off to another process
+const HAND_OFF_TIME: Duration = Duration::from_secs(2);
+
+pub struct Session {
+    path: PathBuf,
+    child: Child,
+    started: Instant,
+    modified: Option<SystemTime>,
+    last_text: String,
+    /// The editor exited straight away, so only the user knows when editing ends
+    pub handed_off: bool,
+}
+
+impl Session {
+    /// Write `text` to a temporary file and open it with `command` (or the
+    /// default editor when empty)
+    pub fn start(command: &str, text: &str) -> Result<Session, String> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("fisha-{}-{}.md", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(name);
+        write_private(&path, text).map_err(|e| e.to_string())?;
+        
+        let command = if command.trim().is_empty() { default_command() } else { command.to_string() };
+        let mut parts = split_command(&command).into_iter();
+        let program = parts.next().ok_or("No editor command is set")?;
+        let child = Command::new(&program)
+            .args(parts)
+            .arg(&path)
+            .spawn()
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&path);
+                format!("{}: {}", program, e)
+            })?;
+        
+        Ok(Session {
+            modified: modified(&path),
+            path,
+            child,
+            started: Instant::now(),
+            last_text: text.to_string(),
+            handed_off: false,
+        })
+    }
+    
+    /// The file's text if it was saved with changes since the last call
+    pub fn poll(&mut self) -> Option<String> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let text = std::fs::read_to_string(&self.path).ok()?;
+        if text == self.last_text {
+            return None;
+        }
+        self.last_text = text.clone();
+        Some(text)
+    }
+    
+    /// Whether the editor has closed, so there will be no more saves
+    pub fn finished(&mut self) -> bool {
+        if self.handed_off {
+            return false;
+        }
+        match self.child.try_wait() {
+            Ok(Some(_)) if self.started.elapsed() < HAND_OFF_TIME => {
+                self.handed_off = true;
+                false
+            },
+            Ok(Some(_)) | Err(_) => true,
+            Ok(None) => false,
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Write `text` to a new file at `path` that only the current user can read
+/// (on Unix; elsewhere the temp directory is already per-user)
+fn write_private(path: &Path, text: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(path)?, text.as_bytes())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// `$VISUAL`, `$EDITOR`, or what the platform opens text files with
+pub fn default_command() -> String {
+    for var in ["VISUAL", "EDITOR"] {
+        if let Ok(command) = std::env::var(var)
+            && !command.trim().is_empty()
+        {
+            return command;
+        }
+    }
+    if cfg!(target_os = "windows") {
+        "notepad".to_string()
+    } else if cfg!(target_os = "macos") {
+        "open -W -t".to_string()
+    } else {
+        "xdg-open".to_string()
+    }
+}
+
+/// Split a command line on spaces, keeping "double-quoted parts" together
+fn split_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in command.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            },
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
@@ -0,0 +1,72 @@
+//! RSS 2.0 feeds of the most recently added notes, to put somewhere a feed
+//! reader can reach so others can follow a fiche, or one category of it.
+//! Notes without a creation date have no place in a feed and are left out.
+
+use crate::{Microfiche, Note, excerpt, note_key, note_path, xml_escape};
+
+/// What goes into a feed
+pub struct Options<'a> {
+    pub title: &'a str,
+    pub description: &'a str,
+    // Where the feed will be published; readers link items back to it
+    pub link: &'a str,
+    // Only this category's notes; None for the whole fiche
+    pub category: Option<&'a str>,
+    pub limit: usize,
+}
+
+/// The feed as XML, newest note first
+pub fn rss(fiche: &Microfiche, options: &Options) -> String {
+    let mut items: Vec<(chrono::DateTime<chrono::FixedOffset>, &str, &str, &str, &Note)> = Vec::new();
+    for (cat, category) in &fiche.categories {
+        if options.category.is_some_and(|only| only != cat) {
+            continue;
+        }
+        for subcat in &category.subcategories {
+            for concept in &subcat.concepts {
+                for note in &concept.notes {
+                    if let Some(created) = note.created.as_deref()
+                        && let Ok(created) = chrono::DateTime::parse_from_rfc3339(created)
+                    {
+                        items.push((created, cat, &subcat.name, &concept.name, note));
+                    }
+                }
+            }
+        }
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.0));
+    items.truncate(options.limit);
+    
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<rss version=\"2.0\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<channel>\n");
+    out.push_str(&format!("  <title>{}</title>\n", xml_escape(options.title)));
+    out.push_str(&format!("  <link>{}</link>\n", xml_escape(options.link)));
+    out.push_str(&format!("  <description>{}</description>\n", xml_escape(options.description)));
+    out.push_str(&format!("  <lastBuildDate>{}</lastBuildDate>\n", chrono::Local::now().to_rfc2822()));
+    out.push_str("  <generator>Fisha</generator>\n");
+    for (created, cat, sub, con, note) in items {
+        out.push_str("  <item>\n");
+        out.push_str(&format!("    <title>{}</title>\n", xml_escape(&format!("{}: {}", con, excerpt(&note.text, 60)))));
+        if !options.link.is_empty() {
+            out.push_str(&format!("    <link>{}</link>\n", xml_escape(options.link)));
+        }
+        // Readers show the description as HTML, so line breaks need tags
+        let body = xml_escape(&note.text).replace('\n', "&lt;br&gt;");
+        out.push_str(&format!("    <description>{}</description>\n", body));
+        out.push_str(&format!("    <category>{}</category>\n", xml_escape(&note_path(cat, sub, con))));
+        for tag in &note.tags {
+            out.push_str(&format!("    <category>{}</category>\n", xml_escape(tag)));
+        }
+        if !note.author.is_empty() {
+            out.push_str(&format!("    <dc:creator>{}</dc:creator>\n", xml_escape(&note.author)));
+        }
+        out.push_str(&format!("    <pubDate>{}</pubDate>\n", created.to_rfc2822()));
+        // Notes created in the same second (imports) are told apart by their text
+        let guid = note_key(cat, sub, con, &format!("{}\u{1f}{}", created.to_rfc3339(), note.text));
+        out.push_str(&format!("    <guid isPermaLink=\"false\">fisha-{}</guid>\n", guid));
+        out.push_str("  </item>\n");
+    }
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
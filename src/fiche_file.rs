@@ -0,0 +1,156 @@
+//! The `.fiche` container: a zip archive holding the notes as JSON together
+//! with everything that otherwise lives in sidecar files.
+//!
+//! ```text
+//! manifest.json   format name and version, when and by what it was saved
+//! fiche.json      categories, subcategories, concepts and notes
+//! meta.json       file info, colors, icons, descriptions, note templates,
+//!                 cross-listings, goals, study progress
+//! index.json      per-category counts, readable without loading the notes
+//! attachments/    files kept alongside the notes, stored as-is
+//! ```
+//!
+//! CSV stays the interchange format; the activity log is still written next
+//! to the file.
+
+use crate::{FicheMeta, JobProgress, Microfiche};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::{BufReader, BufWriter, Read, Write};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const EXTENSION: &str = "fiche";
+
+const FORMAT_NAME: &str = "fisha";
+/// Bump when `fiche.json` changes shape, and add a step to `MIGRATIONS`
+const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const DATA: &str = "fiche.json";
+const META: &str = "meta.json";
+const INDEX: &str = "index.json";
+const ATTACHMENTS_DIR: &str = "attachments/";
+
+/// `MIGRATIONS[i]` upgrades `fiche.json` from version `i + 1` to `i + 2`
+const MIGRATIONS: [fn(&mut serde_json::Value); (FORMAT_VERSION - 1) as usize] = [];
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    saved_at: String,
+    saved_by: String,
+}
+
+#[derive(Serialize)]
+struct IndexEntry<'a> {
+    category: &'a str,
+    subcategories: usize,
+    concepts: usize,
+    notes: usize,
+}
+
+/// Whether `path` should be read and written as a `.fiche` container
+pub fn is_fiche_path(path: &str) -> bool {
+    std::path::Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case(EXTENSION))
+}
+
+pub fn read(path: &str) -> Result<Microfiche, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(BufReader::new(std::fs::File::open(path)?))?;
+    
+    let manifest: Manifest = serde_json::from_reader(archive.by_name(MANIFEST)?)?;
+    if manifest.format != FORMAT_NAME {
+        return Err(format!("{} is not a Fisha file", path).into());
+    }
+    if manifest.version == 0 || manifest.version > FORMAT_VERSION {
+        return Err(format!(
+            "{} uses format version {}, this version of Fisha reads up to {}",
+            path, manifest.version, FORMAT_VERSION
+        ).into());
+    }
+    
+    let mut data: serde_json::Value = serde_json::from_reader(archive.by_name(DATA)?)?;
+    for migrate in &MIGRATIONS[(manifest.version - 1) as usize..] {
+        migrate(&mut data);
+    }
+    let mut fiche: Microfiche = serde_json::from_value(data)?;
+    
+    fiche.meta = match archive.by_name(META) {
+        Ok(entry) => serde_json::from_reader(entry)?,
+        Err(ZipError::FileNotFound) => FicheMeta::default(),
+        Err(e) => return Err(e.into()),
+    };
+    
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        if let Some(name) = entry.name().strip_prefix(ATTACHMENTS_DIR).map(str::to_string) {
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes)?;
+            fiche.attachments.insert(name, bytes);
+        }
+    }
+    
+    Ok(fiche)
+}
+
+/// Write `fiche` to `path`, counting one step of `progress` per note
+pub fn write(fiche: &Microfiche, path: &str, progress: &JobProgress) -> Result<(), Box<dyn Error>> {
+    let mut zip = ZipWriter::new(BufWriter::new(std::fs::File::create(path)?));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    
+    zip.start_file(MANIFEST, options)?;
+    serde_json::to_writer_pretty(&mut zip, &Manifest {
+        format: FORMAT_NAME.to_string(),
+        version: FORMAT_VERSION,
+        saved_at: chrono::Local::now().to_rfc3339(),
+        saved_by: format!("Fisha {}", env!("CARGO_PKG_VERSION")),
+    })?;
+    
+    // Streamed a category at a time, in the same layout as serializing the Microfiche
+    zip.start_file(DATA, options)?;
+    zip.write_all(b"{\"categories\":{")?;
+    for (i, (name, category)) in fiche.categories.iter().enumerate() {
+        if i > 0 {
+            zip.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut zip, name)?;
+        zip.write_all(b":")?;
+        serde_json::to_writer(&mut zip, category)?;
+        progress.advance(category.subcategories.iter().flat_map(|s| &s.concepts).map(|c| c.notes.len()).sum())?;
+    }
+    zip.write_all(b"}}")?;
+    
+    let mut meta = fiche.meta.clone();
+    meta.prune(&fiche.categories);
+    zip.start_file(META, options)?;
+    serde_json::to_writer(&mut zip, &meta)?;
+    
+    let mut index: Vec<IndexEntry> = fiche.categories.values()
+        .map(|category| IndexEntry {
+            category: &category.name,
+            subcategories: category.subcategories.len(),
+            concepts: category.subcategories.iter().map(|s| s.concepts.len()).sum(),
+            notes: category.subcategories.iter()
+                .flat_map(|s| &s.concepts)
+                .map(|c| c.notes.len())
+                .sum(),
+        })
+        .collect();
+    index.sort_by(|a, b| a.category.cmp(b.category));
+    zip.start_file(INDEX, options)?;
+    serde_json::to_writer_pretty(&mut zip, &index)?;
+    
+    for (name, bytes) in &fiche.attachments {
+        // Already-compressed files (images, PDFs) gain nothing from deflate
+        zip.start_file(format!("{}{}", ATTACHMENTS_DIR, name), options.compression_method(CompressionMethod::Stored))?;
+        zip.write_all(bytes)?;
+    }
+    
+    zip.finish()?.flush()?;
+    Ok(())
+}
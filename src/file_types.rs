@@ -0,0 +1,94 @@
+//! Registering Fisha as the handler for `.fiche` and `.csv` files, so that
+//! double-clicking one starts Fisha with its path as the first argument.
+//!
+//! Run once with `fisha --register-file-types`, or from Settings. Linux gets a
+//! desktop entry and MIME type under `~/.local/share`; Windows gets per-user
+//! registry keys. macOS takes document types from the app bundle's
+//! `Info.plist` instead, so there is nothing to do at runtime.
+
+pub const REGISTER_FLAG: &str = "--register-file-types";
+
+/// Register the running executable for `.fiche` and `.csv` files
+pub fn register() -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    register_for(&exe)
+}
+
+#[cfg(target_os = "linux")]
+fn register_for(exe: &std::path::Path) -> Result<String, String> {
+    use std::path::PathBuf;
+    use std::process::Command;
+    
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or("HOME is not set")?;
+    let applications = data_dir.join("applications");
+    let mime_packages = data_dir.join("mime/packages");
+    std::fs::create_dir_all(&applications).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&mime_packages).map_err(|e| e.to_string())?;
+    
+    let desktop_entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Fisha\n\
+         Comment=Hierarchical knowledge notes\n\
+         Exec=\"{}\" %f\n\
+         Terminal=false\n\
+         Categories=Office;\n\
+         MimeType=application/x-fiche;text/csv;\n",
+        exe.display()
+    );
+    std::fs::write(applications.join("fisha.desktop"), desktop_entry).map_err(|e| e.to_string())?;
+    
+    let mime_type = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+  <mime-type type="application/x-fiche">
+    <comment>Fisha notes</comment>
+    <glob pattern="*.fiche"/>
+  </mime-type>
+</mime-info>
+"#;
+    std::fs::write(mime_packages.join("fisha.xml"), mime_type).map_err(|e| e.to_string())?;
+    
+    // Refreshing the caches is best effort; the files are picked up on next login anyway
+    let _ = Command::new("update-mime-database").arg(data_dir.join("mime")).status();
+    let _ = Command::new("update-desktop-database").arg(&applications).status();
+    let _ = Command::new("xdg-mime").args(["default", "fisha.desktop", "application/x-fiche", "text/csv"]).status();
+    
+    Ok(format!("Registered {} for .fiche and .csv files", exe.display()))
+}
+
+#[cfg(target_os = "windows")]
+fn register_for(exe: &std::path::Path) -> Result<String, String> {
+    use std::process::Command;
+    
+    let open_command = format!("\"{}\" \"%1\"", exe.display());
+    let keys: [(&str, &str); 5] = [
+        (r"HKCU\Software\Classes\.fiche", "Fisha.Fiche"),
+        (r"HKCU\Software\Classes\Fisha.Fiche", "Fisha notes"),
+        (r"HKCU\Software\Classes\Fisha.Fiche\shell\open\command", &open_command),
+        (r"HKCU\Software\Classes\Applications\fisha.exe\shell\open\command", &open_command),
+        (r"HKCU\Software\Classes\.csv\OpenWithProgids", ""),
+    ];
+    for (key, value) in keys {
+        let mut reg = Command::new("reg");
+        reg.args(["add", key, "/f"]);
+        if key.ends_with("OpenWithProgids") {
+            // Offer Fisha in "Open with" for CSV files without taking over the default
+            reg.args(["/v", "Fisha.Fiche", "/d", value]);
+        } else {
+            reg.args(["/ve", "/d", value]);
+        }
+        let status = reg.status().map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("reg add {} failed", key));
+        }
+    }
+    Ok(format!("Registered {} for .fiche and .csv files", exe.display()))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn register_for(_exe: &std::path::Path) -> Result<String, String> {
+    Err("File types are declared in the app bundle's Info.plist on this platform".to_string())
+}
@@ -0,0 +1,118 @@
+//! Noticing when another program changes the open file, such as a sync
+//! client (Dropbox, Syncthing) or Fisha in another window, by checking its
+//! modification time every few seconds. The fiche as last read or written is
+//! kept as the base for a three-way merge of both sides' changes.
+
+use crate::{FicheRow, Microfiche, Note, note_key};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the file's modification time is checked
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct Watch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: Instant,
+    /// The fiche the file held when it was last read or written
+    pub base: Arc<Microfiche>,
+}
+
+impl Watch {
+    pub fn new(path: &str, base: Arc<Microfiche>) -> Watch {
+        let path = PathBuf::from(path);
+        Watch {
+            modified: modified(&path),
+            path,
+            checked: Instant::now(),
+            base,
+        }
+    }
+
+    /// Whether the file was written by someone else since the last call.
+    /// A file that is briefly missing, as while a sync client replaces it,
+    /// doesn't count as changed.
+    pub fn changed(&mut self) -> bool {
+        if self.checked.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.checked = Instant::now();
+        let Some(modified) = modified(&self.path) else {
+            return false;
+        };
+        if Some(modified) == self.modified {
+            return false;
+        }
+        self.modified = Some(modified);
+        true
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Notes the merge took from the other side
+#[derive(Default)]
+pub struct Merged {
+    pub added: usize,
+    pub removed: usize,
+    pub updated: usize,
+}
+
+/// Bring the note changes made in `theirs` since `base` into `mine`, keeping
+/// the changes made here. Notes are matched by their place and text, so an
+/// edited note counts as one removed and one added; when both sides edited
+/// the same note, both versions are kept. Ratings, tags and other details
+/// changed on only one side win over the unchanged side. Categories not
+/// loaded here, and the file's settings, are left alone.
+pub fn merge(base: &Microfiche, mine: &mut Microfiche, theirs: &Microfiche) -> Merged {
+    let by_key = |fiche: &Microfiche| -> HashMap<String, Note> {
+        fiche.iter_notes()
+            .map(|(cat, sub, con, note)| (note_key(cat, sub, con, &note.text), note.clone()))
+            .collect()
+    };
+    let before = by_key(base);
+    let after = by_key(theirs);
+
+    let mut merged = Merged::default();
+    let ours: Vec<(String, String, String, Note)> = mine.iter_notes()
+        .map(|(cat, sub, con, note)| (cat.to_string(), sub.to_string(), con.to_string(), note.clone()))
+        .collect();
+    let mut kept = HashSet::new();
+    for (cat, sub, con, note) in ours {
+        let key = note_key(&cat, &sub, &con, &note.text);
+        kept.insert(key.clone());
+        // Notes added here stay
+        let Some(original) = before.get(&key) else {
+            continue;
+        };
+        match after.get(&key) {
+            None => {
+                mine.delete_note(&cat, &sub, &con, &note.text);
+                merged.removed += 1;
+            },
+            Some(changed) if note == *original && changed != original => {
+                if let Some(existing) = mine.find_note_mut(&cat, &sub, &con, &note.text) {
+                    *existing = changed.clone();
+                }
+                if changed.definition != original.definition {
+                    mine.set_definition(&cat, &sub, &con, &note.text, changed.definition);
+                }
+                merged.updated += 1;
+            },
+            Some(_) => {},
+        }
+    }
+    for (cat, sub, con, note) in theirs.iter_notes() {
+        let key = note_key(cat, sub, con, &note.text);
+        if mine.unloaded.contains_key(cat) || before.contains_key(&key) || kept.contains(&key) {
+            continue;
+        }
+        mine.add_row(FicheRow::from_note(cat, sub, con, note));
+        merged.added += 1;
+    }
+    merged
+}
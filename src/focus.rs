@@ -0,0 +1,54 @@
+//! A Pomodoro-style focus timer: a stretch of work, then a short break.
+//! When the work ends the app asks what was learned, so capturing notes
+//! follows the rhythm of studying instead of interrupting it.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Focus,
+    Break,
+}
+
+pub struct Timer {
+    pub phase: Phase,
+    ends: Instant,
+    // Time left when paused; the end moves on resuming
+    paused: Option<Duration>,
+}
+
+impl Timer {
+    pub fn start(phase: Phase, minutes: u32) -> Self {
+        Timer {
+            phase,
+            ends: Instant::now() + Duration::from_secs(u64::from(minutes) * 60),
+            paused: None,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.paused.unwrap_or_else(|| self.ends.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        match self.paused.take() {
+            Some(left) => self.ends = Instant::now() + left,
+            None => self.paused = Some(self.remaining()),
+        }
+    }
+
+    /// Time left as "mm:ss", rounded up so 0:00 only shows when it's over
+    pub fn countdown(&self) -> String {
+        let left = self.remaining();
+        let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
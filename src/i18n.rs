@@ -0,0 +1,104 @@
+//! UI strings, looked up by key through Fluent.
+//!
+//! English is built in (`locales/en.ftl`). Translations are `<lang>.ftl` files
+//! in a `locales` folder next to the executable or in the working directory;
+//! keys they leave out fall back to English.
+
+use fluent::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use std::cell::RefCell;
+use std::path::PathBuf;
+use unic_langid::LanguageIdentifier;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+const ENGLISH: &str = include_str!("../locales/en.ftl");
+
+struct Localizer {
+    // None when the language is English or its file failed to load
+    bundle: Option<FluentBundle<FluentResource>>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+thread_local! {
+    static LOCALIZER: RefCell<Localizer> = RefCell::new(Localizer {
+        bundle: None,
+        fallback: make_bundle(DEFAULT_LANGUAGE, ENGLISH.to_string())
+            .expect("built-in English strings are valid Fluent"),
+    });
+}
+
+fn make_bundle(language: &str, source: String) -> Option<FluentBundle<FluentResource>> {
+    let langid: LanguageIdentifier = language.parse().ok()?;
+    // Keep messages parsed even if a few entries have errors
+    let resource = FluentResource::try_new(source).unwrap_or_else(|(resource, _)| resource);
+    let mut bundle = FluentBundle::new(vec![langid]);
+    // egui has no glyphs for the Unicode isolation marks Fluent adds around arguments
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).ok()?;
+    Some(bundle)
+}
+
+fn locale_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("locales")];
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(|p| p.to_path_buf())) {
+        dirs.push(exe_dir.join("locales"));
+    }
+    dirs
+}
+
+/// Language codes with a translation file, English first
+pub fn available_languages() -> Vec<String> {
+    let mut languages = vec![DEFAULT_LANGUAGE.to_string()];
+    for dir in locale_dirs() {
+        let Ok(entries) = std::fs::read_dir(dir) else { continue };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().is_some_and(|ext| ext == "ftl")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+                && !languages.iter().any(|l| l == stem)
+            {
+                languages.push(stem.to_string());
+            }
+        }
+    }
+    languages[1..].sort();
+    languages
+}
+
+/// Switch the UI language, falling back to English if it can't be loaded
+pub fn set_language(language: &str) {
+    let bundle = if language == DEFAULT_LANGUAGE {
+        None
+    } else {
+        locale_dirs().into_iter()
+            .find_map(|dir| std::fs::read_to_string(dir.join(format!("{}.ftl", language))).ok())
+            .and_then(|source| make_bundle(language, source))
+    };
+    LOCALIZER.with(|localizer| localizer.borrow_mut().bundle = bundle);
+}
+
+fn format(key: &str, args: Option<&FluentArgs>) -> String {
+    LOCALIZER.with(|localizer| {
+        let localizer = localizer.borrow();
+        for bundle in localizer.bundle.iter().chain(std::iter::once(&localizer.fallback)) {
+            if let Some(pattern) = bundle.get_message(key).and_then(|m| m.value()) {
+                let mut errors = Vec::new();
+                return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+            }
+        }
+        key.to_string()
+    })
+}
+
+/// The UI string for `key` in the current language
+pub fn tr(key: &str) -> String {
+    format(key, None)
+}
+
+/// The UI string for `key`, filling in `{ $name }` placeholders
+pub fn tr_args(key: &str, args: &[(&str, FluentValue)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    format(key, Some(&fluent_args))
+}
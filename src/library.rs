@@ -0,0 +1,94 @@
+//! The fiche files registered in the library: searched by Search all files
+//! and listed in the Library view. A file is read the first time it's needed
+//! and again only once it has changed on disk.
+
+use crate::{FileInfo, Microfiche, Note, SearchFilter, absolute_path};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+struct Cached {
+    summary: FileSummary,
+    fiche: Microfiche,
+}
+
+/// A library file's size, when it last changed on disk and its File Info
+pub struct FileSummary {
+    pub modified: SystemTime,
+    pub info: FileInfo,
+    pub categories: usize,
+    pub concepts: usize,
+    pub notes: usize,
+}
+
+/// A match in a library file
+pub struct LibraryResult {
+    pub file: String,
+    pub category: String,
+    pub subcategory: String,
+    pub concept: String,
+    pub note: Note,
+}
+
+#[derive(Default)]
+pub struct Library {
+    cache: HashMap<String, Cached>,
+}
+
+impl Library {
+    /// Search every file in `files` except `skip` (the one already open),
+    /// returning the matches and a message for each file that couldn't be read
+    pub fn search(&mut self, files: &[String], skip: Option<&str>, query: &str, filter: &SearchFilter) -> (Vec<LibraryResult>, Vec<String>) {
+        let skip = skip.map(absolute_path);
+        let mut results = Vec::new();
+        let mut errors = Vec::new();
+        for file in files {
+            if skip.as_deref() == Some(absolute_path(file).as_str()) {
+                continue;
+            }
+            let fiche = match self.load(file) {
+                Ok(cached) => &cached.fiche,
+                Err(e) => {
+                    errors.push(format!("{}: {}", file, e));
+                    continue;
+                },
+            };
+            results.extend(fiche.search(query, filter).into_iter().map(|(category, subcategory, concept, note)| LibraryResult {
+                file: file.clone(),
+                category,
+                subcategory,
+                concept,
+                note,
+            }));
+        }
+        self.retain(files);
+        (results, errors)
+    }
+    
+    /// Counts and modification time of `file`, reading it if it changed
+    pub fn summary(&mut self, file: &str) -> Result<&FileSummary, String> {
+        self.load(file).map(|cached| &cached.summary)
+    }
+    
+    /// Drop cached files that are no longer in the library
+    pub fn retain(&mut self, files: &[String]) {
+        self.cache.retain(|path, _| files.contains(path));
+    }
+    
+    fn load(&mut self, file: &str) -> Result<&Cached, String> {
+        let modified = std::fs::metadata(file).and_then(|m| m.modified()).map_err(|e| e.to_string())?;
+        let fresh = self.cache.get(file).is_some_and(|cached| cached.summary.modified == modified);
+        if !fresh {
+            let fiche = Microfiche::open(file).map_err(|e| e.to_string())?;
+            let subcategories = fiche.categories.values().flat_map(|c| &c.subcategories);
+            let summary = FileSummary {
+                modified,
+                info: fiche.meta.info.clone(),
+                categories: fiche.categories.len(),
+                concepts: subcategories.map(|s| s.concepts.len()).sum(),
+                notes: fiche.note_count(),
+            };
+            self.cache.insert(file.to_string(), Cached { summary, fiche });
+        }
+        Ok(&self.cache[file])
+    }
+}
@@ -0,0 +1,148 @@
+//! A note editor that completes `[[` wiki links: typing `[[` opens a list of
+//! the concepts whose names match what follows, and picking one (click,
+//! Enter or Tab; arrows move, Esc closes) writes the whole `[[Concept]]`.
+
+use crate::{collate, fold_text};
+use eframe::egui;
+use egui::text::{CCursor, CCursorRange};
+
+/// Most concepts listed at once
+const SHOWN: usize = 8;
+
+/// What the list remembers between frames
+#[derive(Clone, Default)]
+struct Completion {
+    open: bool,
+    query: String,
+    selected: usize,
+    // Where the list was drawn, so clicking it doesn't count as leaving the editor
+    rect: Option<egui::Rect>,
+    // Char index of the `[[` closed with Esc, which stays closed
+    dismissed: Option<usize>,
+}
+
+/// The `[[` before `cursor` (a char index) that hasn't been closed yet, as
+/// its char index and the text typed after it
+fn open_link(text: &str, cursor: usize) -> Option<(usize, String)> {
+    let before: String = text.chars().take(cursor).collect();
+    let at = before.rfind("[[")?;
+    let query = &before[at + 2..];
+    if query.contains(']') || query.contains('[') || query.contains('\n') || query.chars().count() > 80 {
+        return None;
+    }
+    Some((before[..at].chars().count(), query.to_string()))
+}
+
+/// Names containing `query`, those starting with it first
+fn matching(mut names: Vec<String>, query: &str) -> Vec<String> {
+    let query = fold_text(query.trim());
+    names.sort_by(|a, b| collate(a, b));
+    names.dedup();
+    let mut starting = Vec::new();
+    let mut containing = Vec::new();
+    for name in names {
+        let folded = fold_text(&name);
+        if folded.starts_with(&query) {
+            starting.push(name);
+        } else if folded.contains(&query) {
+            containing.push(name);
+        }
+    }
+    starting.extend(containing);
+    starting.truncate(SHOWN);
+    starting
+}
+
+/// A multiline editor filling the width; `names` gives the concept names,
+/// and is only called while a link is being typed
+pub fn multiline(ui: &mut egui::Ui, id_salt: &str, text: &mut String, rows: usize, names: impl FnOnce() -> Vec<String>) -> egui::Response {
+    let id = ui.make_persistent_id(id_salt);
+    let completion_id = id.with("link_completion");
+    let mut completion: Completion = ui.data(|data| data.get_temp(completion_id)).unwrap_or_default();
+    
+    // While the list is open its keys don't reach the editor
+    let (mut up, mut down, mut accept, mut escape) = (false, false, false, false);
+    if completion.open && ui.memory(|memory| memory.has_focus(id)) {
+        ui.input_mut(|input| {
+            up = input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp);
+            down = input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown);
+            accept = input.consume_key(egui::Modifiers::NONE, egui::Key::Enter)
+                || input.consume_key(egui::Modifiers::NONE, egui::Key::Tab);
+            escape = input.consume_key(egui::Modifiers::NONE, egui::Key::Escape);
+        });
+    }
+    
+    let mut output = egui::TextEdit::multiline(text)
+        .id(id)
+        .desired_width(f32::INFINITY)
+        .desired_rows(rows)
+        .show(ui);
+    let pointer_on_list = completion.rect.is_some_and(|rect| ui.rect_contains_pointer(rect));
+    let cursor = output.state.cursor.char_range().map(|range| range.primary.index);
+    let link = cursor
+        .filter(|_| output.response.has_focus() || completion.open && pointer_on_list)
+        .and_then(|cursor| open_link(text, cursor).map(|(start, query)| (cursor, start, query)));
+    let Some((cursor, start, query)) = link else {
+        ui.data_mut(|data| data.insert_temp(completion_id, Completion::default()));
+        return output.response;
+    };
+    if escape {
+        completion.dismissed = Some(start);
+    }
+    let matches = if completion.dismissed == Some(start) { Vec::new() } else { matching(names(), &query) };
+    if matches.is_empty() {
+        completion.open = false;
+        completion.rect = None;
+        ui.data_mut(|data| data.insert_temp(completion_id, completion));
+        return output.response;
+    }
+    
+    if !completion.open || completion.query != query {
+        completion.selected = 0;
+    }
+    completion.open = true;
+    completion.query = query;
+    if up {
+        completion.selected = completion.selected.checked_sub(1).unwrap_or(matches.len() - 1);
+    }
+    if down {
+        completion.selected = (completion.selected + 1) % matches.len();
+    }
+    completion.selected = completion.selected.min(matches.len() - 1);
+    
+    let at = output.galley_pos + output.galley.pos_from_cursor(CCursor::new(cursor)).left_bottom().to_vec2();
+    let mut picked = accept.then_some(completion.selected);
+    let list = egui::Area::new(completion_id)
+        .order(egui::Order::Foreground)
+        .fixed_pos(at)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for (i, name) in matches.iter().enumerate() {
+                    if ui.selectable_label(i == completion.selected, name).clicked() {
+                        picked = Some(i);
+                    }
+                }
+            });
+        });
+    completion.rect = Some(list.response.rect);
+    
+    if let Some(i) = picked {
+        // Replace `[[` and the typed part, and a `]]` already after the cursor
+        let chars: Vec<char> = text.chars().collect();
+        let cursor = cursor.min(chars.len());
+        let end = if chars[cursor..].starts_with(&[']', ']']) { cursor + 2 } else { cursor };
+        let link = format!("[[{}]]", matches[i]);
+        let after = start + link.chars().count();
+        let mut linked: String = chars[..start].iter().collect();
+        linked.push_str(&link);
+        linked.extend(&chars[end..]);
+        *text = linked;
+        output.state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(after))));
+        output.state.store(ui.ctx(), id);
+        ui.memory_mut(|memory| memory.request_focus(id));
+        output.response.mark_changed();
+        completion = Completion::default();
+    }
+    ui.data_mut(|data| data.insert_temp(completion_id, completion));
+    output.response
+}
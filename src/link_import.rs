@@ -0,0 +1,112 @@
+//! Turning a list of links, such as the open tabs copied from a browser, into
+//! notes: one per link, shown as the page's title when titles are fetched.
+//! Pages are fetched by `curl`, as webhooks are, on a thread that's checked
+//! on later frames.
+
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+
+/// Seconds curl waits for each page
+const TIMEOUT: &str = "10";
+/// Pages fetched at the same time
+const PARALLEL: usize = 8;
+
+/// The http(s) links in `text`, the first on each line, in order and
+/// without repeats
+pub fn links(text: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let Some(link) = line.split_whitespace()
+            .map(|word| word.trim_start_matches(['<', '(', '"']))
+            .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+        else {
+            continue;
+        };
+        let link = link.trim_end_matches(['>', ')', '"', ',']).to_string();
+        if !links.contains(&link) {
+            links.push(link);
+        }
+    }
+    links
+}
+
+/// A note for `link`: a Markdown link with the page's title, or the bare link
+pub fn note(link: &str, title: Option<&str>) -> String {
+    match title {
+        Some(title) => format!("[{}]({})", title.replace('[', "\\[").replace(']', "\\]"), link),
+        None => link.to_string(),
+    }
+}
+
+/// Page titles being fetched
+pub struct TitleFetch {
+    pub total: usize,
+    done: Arc<AtomicUsize>,
+    handle: Option<JoinHandle<Vec<Option<String>>>>,
+}
+
+impl TitleFetch {
+    pub fn start(links: Vec<String>) -> TitleFetch {
+        let done = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&done);
+        let total = links.len();
+        let handle = std::thread::spawn(move || {
+            let mut titles = Vec::with_capacity(links.len());
+            for chunk in links.chunks(PARALLEL) {
+                std::thread::scope(|scope| {
+                    let fetches: Vec<_> = chunk.iter().map(|link| scope.spawn(|| fetch_title(link))).collect();
+                    for fetch in fetches {
+                        titles.push(fetch.join().unwrap_or(None));
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+            titles
+        });
+        TitleFetch { total, done, handle: Some(handle) }
+    }
+
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// None while pages are still being fetched, then each link's title
+    /// (None where the page couldn't be read or has none, or for every link
+    /// if the fetching thread panicked)
+    pub fn poll(&mut self) -> Option<Vec<Option<String>>> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        Some(self.handle.take()?.join().unwrap_or_else(|_| vec![None; self.total]))
+    }
+}
+
+fn fetch_title(link: &str) -> Option<String> {
+    let output = Command::new("curl")
+        .args(["--silent", "--fail", "--location", "--max-time", TIMEOUT, "--max-filesize", "5000000"])
+        .arg("--url")
+        .arg(link)
+        .output()
+        .ok()?;
+    title_of(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The text of an HTML page's `<title>`, with common entities decoded and
+/// whitespace collapsed
+fn title_of(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = html[start..end].split_whitespace().collect::<Vec<_>>().join(" ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    (!title.is_empty()).then_some(title)
+}
@@ -1,842 +1,11110 @@
 #![windows_subsystem = "windows"]
 
 use eframe::egui;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use serde::{Serialize, Deserialize};
+use indexmap::IndexMap;
 use csv::{Reader, Writer, StringRecord};
 use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct Concept {
-    name: String,
-    notes: Vec<String>,
+mod calendar;
+mod cli;
+mod compressed;
+mod external_editor;
+mod feed;
+mod fiche_file;
+mod file_types;
+mod file_watch;
+mod focus;
+mod i18n;
+mod library;
+mod link_complete;
+mod link_import;
+mod markdown;
+mod markdown_export;
+mod pack;
+mod publish;
+mod report;
+mod sample;
+mod search_index;
+mod share;
+mod snapshots;
+mod sqlite_store;
+mod text_encoding;
+mod validate;
+mod vault;
+mod webhook;
+use i18n::{tr, tr_args};
+
+/// Columns every fiche CSV must have; anything else is kept as a custom field.
+const CORE_COLUMNS: [&str; 4] = ["Category", "Subcategory", "Concept", "Note"];
+/// Built-in columns that older files may lack.
+const OPTIONAL_COLUMNS: [&str; 10] = ["Rating", "NeedsReview", "Created", "Modified", "Display", "Definition", "Flags", "Tags", "Author", "Comments"];
+
+fn is_reserved_column(name: &str) -> bool {
+    CORE_COLUMNS.contains(&name) || OPTIONAL_COLUMNS.contains(&name)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct Subcategory {
-    name: String,
-    concepts: Vec<Concept>,
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+struct Note {
+    text: String,
+    // 1-5 stars, None when unrated
+    #[serde(default, deserialize_with = "deserialize_rating")]
+    rating: Option<u8>,
+    // Set on imported notes until someone approves them in the Review view
+    needs_review: bool,
+    // RFC 3339 local time the note was first written; None for older notes
+    created: Option<String>,
+    // RFC 3339 local time the text last changed; None if never edited
+    #[serde(default)]
+    modified: Option<String>,
+    // Extra CSV columns (e.g. "Hours", "Mood"), keyed by header name
+    fields: BTreeMap<String, String>,
+    #[serde(default)]
+    display: NoteDisplay,
+    // The concept's pinned explanation, kept as its first note; at most one
+    // per concept
+    #[serde(default)]
+    definition: bool,
+    #[serde(default)]
+    flags: BTreeSet<NoteFlag>,
+    // Labels that cut across the hierarchy (e.g. "exam", "onboarding")
+    #[serde(default)]
+    tags: BTreeSet<String>,
+    // Who wrote it, so a fiche merged from several people's keeps track;
+    // empty when unknown
+    #[serde(default)]
+    author: String,
+    // Reviewers' discussion of the note, oldest first; never part of the text
+    #[serde(default)]
+    comments: Vec<NoteComment>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct Category {
-    name: String,
-    subcategories: Vec<Subcategory>,
+/// Read the `Tags` column or the Create form's tag field: comma-separated,
+/// lowercased, a leading `#` dropped
+fn parse_tags(value: &str) -> BTreeSet<String> {
+    value.split(',')
+        .map(|tag| tag.trim().trim_start_matches('#').trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
 }
 
-#[derive(Serialize, Deserialize)]
-struct Microfiche {
-    categories: HashMap<String, Category>,
+fn tags_column(tags: &BTreeSet<String>) -> String {
+    tags.iter().map(String::as_str).collect::<Vec<_>>().join(", ")
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct FicheRow {
-    #[serde(rename = "Category")]
-    category: String,
-    #[serde(rename = "Subcategory")]
-    subcategory: String,
-    #[serde(rename = "Concept")]
-    concept: String,
-    #[serde(rename = "Note")]
-    note: String,
+/// A remark on a note by someone reviewing a shared fiche
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NoteComment {
+    author: String,
+    // RFC 3339 local time it was posted
+    posted: String,
+    text: String,
 }
 
-impl Microfiche {
-    fn new() -> Self {
-        Microfiche {
-            categories: HashMap::new(),
-        }
+/// Read the `Comments` column: a JSON list, empty when there are none
+fn parse_comments(value: &str) -> Vec<NoteComment> {
+    match value.trim() {
+        "" => Vec::new(),
+        json => serde_json::from_str(json).unwrap_or_default(),
     }
+}
+
+fn comments_column(comments: &[NoteComment]) -> String {
+    if comments.is_empty() {
+        return String::new();
+    }
+    serde_json::to_string(comments).unwrap_or_default()
+}
+
+/// Quick triage marks, toggled with one click on a note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum NoteFlag {
+    Todo,
+    Verify,
+    Outdated,
+    Gold,
+}
+
+impl NoteFlag {
+    const ALL: [NoteFlag; 4] = [NoteFlag::Todo, NoteFlag::Verify, NoteFlag::Outdated, NoteFlag::Gold];
     
-    fn from_csv(path: &str) -> Result<Self, Box<dyn Error>> {
-        let mut fiche = Microfiche::new();
-        let mut rdr = Reader::from_path(path)?;
-        
-        for result in rdr.deserialize() {
-            let row: FicheRow = result?;
-            fiche.add_row(row);
+    /// Name in the `Flags` column
+    fn name(&self) -> &'static str {
+        match self {
+            NoteFlag::Todo => "todo",
+            NoteFlag::Verify => "verify",
+            NoteFlag::Outdated => "outdated",
+            NoteFlag::Gold => "gold",
         }
-        
-        Ok(fiche)
     }
     
-    fn to_csv(&self, path: &str) -> Result<(), Box<dyn Error>> {
-        let mut wtr = Writer::from_path(path)?;
-        wtr.write_record(&["Category", "Subcategory", "Concept", "Note"])?;
-        
-        for (cat_name, category) in &self.categories {
-            for subcat in &category.subcategories {
-                for concept in &subcat.concepts {
-                    for note in &concept.notes {
-                        wtr.write_record(&[
-                            &cat_name,
-                            &subcat.name,
-                            &concept.name,
-                            note,
-                        ])?;
-                    }
-                }
-            }
+    fn icon(&self) -> &'static str {
+        match self {
+            NoteFlag::Todo => "☑",
+            NoteFlag::Verify => "❓",
+            NoteFlag::Outdated => "⌛",
+            NoteFlag::Gold => "⭐",
         }
-        
-        wtr.flush()?;
-        Ok(())
     }
     
-    fn add_row(&mut self, row: FicheRow) {
-        let category = self.categories.entry(row.category.clone())
-            .or_insert_with(|| Category {
-                name: row.category.clone(),
-                subcategories: Vec::new(),
-            });
-        
-        if !category.subcategories.iter().any(|s| s.name == row.subcategory) {
-            category.subcategories.push(Subcategory {
-                name: row.subcategory.clone(),
-                concepts: Vec::new(),
-            });
-        }
-        let subcat = category.subcategories.iter_mut()
-            .find(|s| s.name == row.subcategory)
-            .unwrap();
-        
-        if !subcat.concepts.iter().any(|c| c.name == row.concept) {
-            subcat.concepts.push(Concept {
-                name: row.concept.clone(),
-                notes: Vec::new(),
-            });
+    fn label(&self) -> String {
+        match self {
+            NoteFlag::Todo => tr("flag-todo"),
+            NoteFlag::Verify => tr("flag-verify"),
+            NoteFlag::Outdated => tr("flag-outdated"),
+            NoteFlag::Gold => tr("flag-gold"),
         }
-        let concept = subcat.concepts.iter_mut()
-            .find(|c| c.name == row.concept)
-            .unwrap();
-        
-        concept.notes.push(row.note);
     }
     
-    fn search(&self, query: &str) -> Vec<(String, String, String, String)> {
-        let mut results = Vec::new();
-        let query_lower = query.to_lowercase();
-        
-        if query_lower.is_empty() {
-            return results;
-        }
-        
-        for (cat_name, category) in &self.categories {
-            for subcat in &category.subcategories {
-                for concept in &subcat.concepts {
-                    for note in &concept.notes {
-                        let full_text = format!("{} {} {} {}", 
-                            cat_name, subcat.name, concept.name, note)
-                            .to_lowercase();
-                        
-                        if full_text.contains(&query_lower) {
-                            results.push((
-                                cat_name.clone(),
-                                subcat.name.clone(),
-                                concept.name.clone(),
-                                note.clone(),
-                            ));
-                        }
-                    }
-                }
-            }
+    fn color(&self, visuals: &egui::Visuals) -> egui::Color32 {
+        match self {
+            NoteFlag::Todo => visuals.hyperlink_color,
+            NoteFlag::Verify => visuals.warn_fg_color,
+            NoteFlag::Outdated => visuals.error_fg_color,
+            NoteFlag::Gold => egui::Color32::GOLD,
         }
-        
-        results
     }
     
-    fn delete_note(&mut self, cat: &str, sub: &str, con: &str, note_content: &str) -> bool {
-        if let Some(category) = self.categories.get_mut(cat) {
-            if let Some(subcat) = category.subcategories.iter_mut().find(|s| s.name == sub) {
-                if let Some(concept) = subcat.concepts.iter_mut().find(|c| c.name == con) {
-                    if let Some(pos) = concept.notes.iter().position(|n| n == note_content) {
-                        concept.notes.remove(pos);
-                        
-                        // Cleanup empty structures
-                        if concept.notes.is_empty() {
-                            subcat.concepts.retain(|c| !c.notes.is_empty());
-                        }
-                        if subcat.concepts.is_empty() {
-                            category.subcategories.retain(|s| !s.concepts.is_empty());
-                        }
-                        if category.subcategories.is_empty() {
-                            self.categories.remove(cat);
-                        }
-                        
-                        return true;
+    /// Read the `Flags` column: space-separated flag names
+    fn parse(value: &str) -> BTreeSet<NoteFlag> {
+        value.split(|c: char| c.is_whitespace() || c == ',')
+            .filter_map(|name| NoteFlag::ALL.into_iter().find(|flag| flag.name().eq_ignore_ascii_case(name)))
+            .collect()
+    }
+    
+    fn to_column(flags: &BTreeSet<NoteFlag>) -> String {
+        flags.iter().map(|flag| flag.name()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// How a note's text is shown, chosen per note
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+struct NoteDisplay {
+    monospace: bool,
+    no_wrap: bool,
+    // Keep leading spaces and tabs at the start of each line
+    preserve_indent: bool,
+    // Tint of the note's card, to make it stand out
+    highlight: Option<Highlight>,
+}
+
+impl NoteDisplay {
+    /// Read the `Display` column: space-separated `mono`, `nowrap`, `indent`
+    /// and a highlight color
+    fn parse(value: &str) -> Self {
+        let mut display = NoteDisplay::default();
+        for flag in value.split(|c: char| c.is_whitespace() || c == ',') {
+            match flag.to_lowercase().as_str() {
+                "mono" | "monospace" => display.monospace = true,
+                "nowrap" => display.no_wrap = true,
+                "indent" => display.preserve_indent = true,
+                color => {
+                    if let Some(highlight) = Highlight::ALL.into_iter().find(|h| h.name() == color) {
+                        display.highlight = Some(highlight);
                     }
-                }
+                },
             }
         }
-        false
+        display
     }
     
-    fn stats(&self) -> HashMap<String, usize> {
-        let mut stats = HashMap::new();
-        let mut total_subcats = 0;
-        let mut total_concepts = 0;
-        let mut total_notes = 0;
-        
-        stats.insert("categories".to_string(), self.categories.len());
-        
-        for (_, category) in &self.categories {
-            total_subcats += category.subcategories.len();
-            for subcat in &category.subcategories {
-                total_concepts += subcat.concepts.len();
-                for concept in &subcat.concepts {
-                    total_notes += concept.notes.len();
-                }
-            }
-        }
-        
-        stats.insert("subcategories".to_string(), total_subcats);
-        stats.insert("concepts".to_string(), total_concepts);
-        stats.insert("total_notes".to_string(), total_notes);
-        
-        stats
+    fn to_column(self) -> String {
+        let flags = [(self.monospace, "mono"), (self.no_wrap, "nowrap"), (self.preserve_indent, "indent")];
+        flags.iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, flag)| *flag)
+            .chain(self.highlight.map(|h| h.name()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+    
+    /// Whether the text is drawn as written, so it can be rendered as Markdown
+    fn is_plain_text(&self) -> bool {
+        !self.monospace && !self.no_wrap && !self.preserve_indent
     }
 }
 
-struct MicroficheApp {
-    microfiche: Microfiche,
-    current_file: Option<String>,
-    
-    // UI State
-    search_query: String,
-    search_results: Vec<(String, String, String, String)>,
-    
-    // Create form
-    new_category: String,
-    new_subcategory: String,
-    new_concept: String,
-    new_note: String,
+/// Card colors a note can be highlighted with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum Highlight {
+    Yellow,
+    Green,
+    Blue,
+    Pink,
+    Orange,
+    Purple,
+}
+
+impl Highlight {
+    const ALL: [Highlight; 6] = [Highlight::Yellow, Highlight::Green, Highlight::Blue, Highlight::Pink, Highlight::Orange, Highlight::Purple];
     
-    // Selected for viewing
-    selected_category: Option<String>,
-    selected_subcategory: Option<String>,
-    selected_concept: Option<String>,
+    /// Name in the `Display` column
+    fn name(&self) -> &'static str {
+        match self {
+            Highlight::Yellow => "yellow",
+            Highlight::Green => "green",
+            Highlight::Blue => "blue",
+            Highlight::Pink => "pink",
+            Highlight::Orange => "orange",
+            Highlight::Purple => "purple",
+        }
+    }
     
-    // Messages
-    status_message: String,
+    fn label(&self) -> String {
+        match self {
+            Highlight::Yellow => tr("highlight-yellow"),
+            Highlight::Green => tr("highlight-green"),
+            Highlight::Blue => tr("highlight-blue"),
+            Highlight::Pink => tr("highlight-pink"),
+            Highlight::Orange => tr("highlight-orange"),
+            Highlight::Purple => tr("highlight-purple"),
+        }
+    }
     
-    // View mode
-    view_mode: ViewMode,
+    fn color(&self) -> egui::Color32 {
+        match self {
+            Highlight::Yellow => egui::Color32::from_rgb(240, 200, 40),
+            Highlight::Green => egui::Color32::from_rgb(80, 190, 100),
+            Highlight::Blue => egui::Color32::from_rgb(70, 140, 230),
+            Highlight::Pink => egui::Color32::from_rgb(230, 100, 170),
+            Highlight::Orange => egui::Color32::from_rgb(240, 140, 50),
+            Highlight::Purple => egui::Color32::from_rgb(150, 100, 220),
+        }
+    }
     
-    // Theme
-    current_theme: Theme,
-    show_theme_selector: bool,
-
-    // Pagination
-    cooccurrence_page: usize,
-    category_page: usize,
+    /// The card background: the color faded into the panel so text stays readable
+    fn tint(&self, visuals: &egui::Visuals) -> egui::Color32 {
+        let strength = if visuals.dark_mode { 0.22 } else { 0.3 };
+        visuals.panel_fill.lerp_to_gamma(self.color(), strength)
+    }
 }
 
-#[derive(PartialEq, Clone, Copy)]
-enum Theme {
-    Monokai,
-    TomorrowBlueHour,
-    DarkPlus,
+/// A move of a category, subcategory or concept within its list
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    Up,
+    Down,
 }
 
-impl Theme {
-    fn name(&self) -> &str {
+impl Step {
+    /// The place next to `index` in a list of `len`, if there is one
+    fn from(self, index: usize, len: usize) -> Option<usize> {
         match self {
-            Theme::Monokai => "Monokai",
-            Theme::TomorrowBlueHour => "Tomorrow (Blue Hour)",
-            Theme::DarkPlus => "Dark+",
+            Step::Up => index.checked_sub(1),
+            Step::Down => (index + 1 < len).then_some(index + 1),
         }
     }
     
-    fn apply(&self, ctx: &egui::Context) {
-        let mut visuals = egui::Visuals::dark();
-        
+    fn label(self) -> String {
         match self {
-            Theme::Monokai => {
-                // Monokai - warm dark theme with purple/pink accents
-                visuals.window_fill = egui::Color32::from_rgb(39, 40, 34);
-                visuals.panel_fill = egui::Color32::from_rgb(39, 40, 34);
-                visuals.faint_bg_color = egui::Color32::from_rgb(49, 50, 44);
-                
-                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(49, 50, 44);
-                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(60, 61, 54);
-                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(75, 76, 68);
-                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(90, 91, 82);
-                
-                visuals.selection.bg_fill = egui::Color32::from_rgb(73, 72, 62);
-                visuals.selection.stroke.color = egui::Color32::from_rgb(249, 38, 114);
-                
-                visuals.override_text_color = Some(egui::Color32::from_rgb(248, 248, 242));
-                visuals.hyperlink_color = egui::Color32::from_rgb(102, 217, 239);
-                visuals.warn_fg_color = egui::Color32::from_rgb(230, 219, 116);
-                visuals.error_fg_color = egui::Color32::from_rgb(249, 38, 114);
-            },
-            Theme::TomorrowBlueHour => {
-                // Tomorrow Night Blue - cool blue theme
-                visuals.window_fill = egui::Color32::from_rgb(0, 29, 51);
-                visuals.panel_fill = egui::Color32::from_rgb(0, 29, 51);
-                visuals.faint_bg_color = egui::Color32::from_rgb(0, 43, 71);
-                
-                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(0, 43, 71);
-                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(0, 56, 92);
-                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(7, 70, 115);
-                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(17, 85, 135);
-                
-                visuals.selection.bg_fill = egui::Color32::from_rgb(0, 72, 119);
-                visuals.selection.stroke.color = egui::Color32::from_rgb(125, 174, 198);
-                
-                visuals.override_text_color = Some(egui::Color32::from_rgb(231, 232, 235));
-                visuals.hyperlink_color = egui::Color32::from_rgb(125, 174, 198);
-                visuals.warn_fg_color = egui::Color32::from_rgb(255, 204, 102);
-                visuals.error_fg_color = egui::Color32::from_rgb(255, 102, 102);
-            },
-            Theme::DarkPlus => {
-                // Dark+ - VS Code default dark theme
-                visuals.window_fill = egui::Color32::from_rgb(30, 30, 30);
-                visuals.panel_fill = egui::Color32::from_rgb(30, 30, 30);
-                visuals.faint_bg_color = egui::Color32::from_rgb(37, 37, 38);
-                
-                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(45, 45, 45);
-                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(60, 60, 60);
-                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(75, 75, 75);
-                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(90, 90, 90);
-                
-                visuals.selection.bg_fill = egui::Color32::from_rgb(38, 79, 120);
-                visuals.selection.stroke.color = egui::Color32::from_rgb(14, 99, 156);
-                
-                visuals.override_text_color = Some(egui::Color32::from_rgb(212, 212, 212));
-                visuals.hyperlink_color = egui::Color32::from_rgb(78, 162, 230);
-                visuals.warn_fg_color = egui::Color32::from_rgb(206, 145, 120);
-                visuals.error_fg_color = egui::Color32::from_rgb(244, 71, 71);
-            },
+            Step::Up => tr("move-up"),
+            Step::Down => tr("move-down"),
         }
-        
-        ctx.set_visuals(visuals);
     }
 }
 
-#[derive(PartialEq)]
-enum ViewMode {
-    Browse,
-    Search,
-    Create,
-    Stats,
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Concept {
+    name: String,
+    notes: Vec<Note>,
 }
 
-impl Default for MicroficheApp {
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Subcategory {
+    name: String,
+    concepts: Vec<Concept>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Category {
+    name: String,
+    subcategories: Vec<Subcategory>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Microfiche {
+    // In the order shown in Browse and saved to the file
+    categories: IndexMap<String, Category>,
+    #[serde(skip)]
+    meta: FicheMeta,
+    // Files bundled with a .fiche container, by name
+    #[serde(skip)]
+    attachments: BTreeMap<String, Vec<u8>>,
+    // Categories left in the file by a selective open, with their note counts
+    #[serde(skip)]
+    unloaded: BTreeMap<String, usize>,
+    // Encoding a CSV file was converted from when it wasn't UTF-8
+    #[serde(skip)]
+    source_encoding: Option<&'static str>,
+    #[serde(skip)]
+    index: search_index::Cache,
+}
+
+/// Presentation data that doesn't fit the CSV columns, stored in a
+/// `<name>.meta.json` sidecar next to the fiche.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct FicheMeta {
+    #[serde(default)]
+    categories: BTreeMap<String, CategoryMeta>,
+    #[serde(default)]
+    cross_listings: Vec<CrossListing>,
+    #[serde(default)]
+    concept_aliases: Vec<ConceptAliases>,
+    #[serde(default)]
+    study: StudyProgress,
+    #[serde(default)]
+    aging: AgingState,
+    #[serde(default)]
+    goals: CaptureGoals,
+    #[serde(default)]
+    note_templates: Vec<NoteTemplate>,
+    #[serde(default)]
+    info: FileInfo,
+}
+
+/// Describes the file as a whole, so a shared fiche says what it is
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct FileInfo {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    description: String,
+    // The fiche's own layout version, kept by its author (e.g. "2.1")
+    #[serde(default)]
+    schema_version: String,
+}
+
+impl FileInfo {
+    /// Author, description and schema version on separate lines, for a tooltip
+    fn details(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.author.trim().is_empty() {
+            lines.push(tr_args("file-info-by", &[("author", self.author.trim().to_string().into())]));
+        }
+        if !self.description.trim().is_empty() {
+            lines.push(self.description.trim().to_string());
+        }
+        if !self.schema_version.trim().is_empty() {
+            lines.push(tr_args("file-info-schema", &[("version", self.schema_version.trim().to_string().into())]));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Text a new note starts with when added under a concept, or anywhere in a
+/// subcategory when `concept` is empty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NoteTemplate {
+    category: String,
+    subcategory: String,
+    #[serde(default)]
+    concept: String,
+    text: String,
+}
+
+/// Capture habit goals, measured against the activity log
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct CaptureGoals {
+    // 0 = no daily goal
+    #[serde(default)]
+    notes_per_day: usize,
+    #[serde(default)]
+    touch_categories_monthly: bool,
+}
+
+/// Periodic re-validation: how long before a note counts as stale, and which
+/// stale notes were confirmed, snoozed or archived
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AgingState {
+    #[serde(default = "default_stale_days")]
+    stale_days: usize,
+    // Also list notes in categories without recent activity
+    #[serde(default)]
+    all_categories: bool,
+    // note_key -> date confirmed still valid (YYYY-MM-DD)
+    #[serde(default)]
+    confirmed: BTreeMap<String, String>,
+    // note_key -> date it comes back (YYYY-MM-DD)
+    #[serde(default)]
+    snoozed: BTreeMap<String, String>,
+    // Notes kept as a historical record, never listed again
+    #[serde(default)]
+    archived: BTreeSet<String>,
+}
+
+fn default_stale_days() -> usize {
+    180
+}
+
+impl Default for AgingState {
     fn default() -> Self {
-        let microfiche = Microfiche::from_csv("microfiche.csv")
-            .unwrap_or_else(|_| Microfiche::new());
-        
-        let mut app = MicroficheApp {
-            microfiche,
-            current_file: Some("microfiche.csv".to_string()),
-            search_query: String::new(),
-            search_results: Vec::new(),
-            new_category: String::new(),
-            new_subcategory: String::new(),
-            new_concept: String::new(),
-            new_note: String::new(),
-            selected_category: None,
-            selected_subcategory: None,
-            selected_concept: None,
-            status_message: String::new(),
-            view_mode: ViewMode::Browse,
-            current_theme: Theme::Monokai,
-            show_theme_selector: false,
-            cooccurrence_page: 0,
-            category_page: 0,
-        };
-        
-        app
+        AgingState {
+            stale_days: default_stale_days(),
+            all_categories: false,
+            confirmed: BTreeMap::new(),
+            snoozed: BTreeMap::new(),
+            archived: BTreeSet::new(),
+        }
     }
 }
 
-impl MicroficheApp {
-    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self::default()
+impl AgingState {
+    fn forget(&mut self, key: &str) {
+        self.confirmed.remove(key);
+        self.snoozed.remove(key);
+        self.archived.remove(key);
+    }
+}
+
+/// A note due for re-validation, with the day it was last created, edited or
+/// confirmed (None when it carries no dates at all)
+struct StaleNote {
+    note: NoteTarget,
+    last_touched: Option<String>,
+}
+
+/// Daily study sessions: which notes were studied when, and per-day counts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StudyProgress {
+    #[serde(default = "default_daily_goal")]
+    daily_goal: usize,
+    // Empty means all categories
+    #[serde(default)]
+    categories: BTreeSet<String>,
+    // note_key -> date last studied (YYYY-MM-DD)
+    #[serde(default)]
+    studied: BTreeMap<String, String>,
+    // date -> notes studied that day
+    #[serde(default)]
+    days: BTreeMap<String, usize>,
+}
+
+fn default_daily_goal() -> usize {
+    10
+}
+
+impl Default for StudyProgress {
+    fn default() -> Self {
+        StudyProgress {
+            daily_goal: default_daily_goal(),
+            categories: BTreeSet::new(),
+            studied: BTreeMap::new(),
+            days: BTreeMap::new(),
+        }
+    }
+}
+
+impl StudyProgress {
+    fn studied_on(&self, day: &str) -> usize {
+        self.days.get(day).copied().unwrap_or(0)
     }
     
-    fn save_file(&mut self) {
-        if let Some(ref path) = self.current_file {
-            match self.microfiche.to_csv(path) {
-                Ok(_) => self.status_message = format!("Saved to {}", path),
-                Err(e) => self.status_message = format!("Error saving: {}", e),
-            }
-        } else {
-            self.save_file_as();
+    /// Consecutive days (ending today, or yesterday if today isn't done yet)
+    /// on which the daily goal was met
+    fn streak(&self, today: chrono::NaiveDate) -> usize {
+        let goal = self.daily_goal.max(1);
+        let met = |day: chrono::NaiveDate| self.studied_on(&day.format("%Y-%m-%d").to_string()) >= goal;
+        
+        let mut day = if met(today) { today } else { today - chrono::Duration::days(1) };
+        let mut streak = 0;
+        while met(day) {
+            streak += 1;
+            day -= chrono::Duration::days(1);
         }
+        streak
     }
     
-    fn save_file_as(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("CSV", &["csv"])
-            .save_file()
-        {
-            let path_str = path.to_string_lossy().to_string();
-            match self.microfiche.to_csv(&path_str) {
-                Ok(_) => {
-                    self.current_file = Some(path_str.clone());
-                    self.status_message = format!("Saved to {}", path_str);
-                },
-                Err(e) => self.status_message = format!("Error saving: {}", e),
-            }
+    fn record(&mut self, key: String, today: &str) {
+        self.studied.insert(key, today.to_string());
+        *self.days.entry(today.to_string()).or_insert(0) += 1;
+    }
+}
+
+/// Alternative names a concept is also known by (e.g. after a merge)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ConceptAliases {
+    concept: ConceptRef,
+    names: Vec<String>,
+}
+
+/// Shows a concept under another subcategory while its notes stay in one place
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CrossListing {
+    category: String,
+    subcategory: String,
+    source: ConceptRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ConceptRef {
+    category: String,
+    subcategory: String,
+    concept: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct CategoryMeta {
+    #[serde(default)]
+    color: Option<[u8; 3]>,
+    #[serde(default)]
+    icon: String,
+    // Overview shown when the category is opened in Browse
+    #[serde(default)]
+    description: String,
+    // Subcategory name -> overview text
+    #[serde(default)]
+    subcategories: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum ActivityAction {
+    Created,
+    Edited,
+    Deleted,
+    Rated,
+    Split,
+    Appended,
+    Merged,
+    MergeUndone,
+    CrossListed,
+    Unlinked,
+    Described,
+    Imported,
+    Reviewed,
+    FlaggedForReview,
+    Purged,
+    DisplayChanged,
+    DefinitionChanged,
+    FlagsChanged,
+    Confirmed,
+    Snoozed,
+    Archived,
+    Undone,
+    Redone,
+    Commented,
+    Restored,
+    Reordered,
+}
+
+impl ActivityAction {
+    fn label(&self) -> String {
+        match self {
+            ActivityAction::Created => tr("activity-created"),
+            ActivityAction::Edited => tr("activity-edited"),
+            ActivityAction::Deleted => tr("activity-deleted"),
+            ActivityAction::Rated => tr("activity-rated"),
+            ActivityAction::Split => tr("activity-split"),
+            ActivityAction::Appended => tr("activity-appended"),
+            ActivityAction::Merged => tr("activity-merged"),
+            ActivityAction::MergeUndone => tr("activity-merge-undone"),
+            ActivityAction::CrossListed => tr("activity-cross-listed"),
+            ActivityAction::Unlinked => tr("activity-unlinked"),
+            ActivityAction::Described => tr("activity-described"),
+            ActivityAction::Imported => tr("activity-imported"),
+            ActivityAction::Reviewed => tr("activity-reviewed"),
+            ActivityAction::FlaggedForReview => tr("activity-flagged-for-review"),
+            ActivityAction::Purged => tr("activity-purged"),
+            ActivityAction::DisplayChanged => tr("activity-display-changed"),
+            ActivityAction::DefinitionChanged => tr("activity-definition-changed"),
+            ActivityAction::FlagsChanged => tr("activity-flags-changed"),
+            ActivityAction::Confirmed => tr("activity-confirmed"),
+            ActivityAction::Snoozed => tr("activity-snoozed"),
+            ActivityAction::Archived => tr("activity-archived"),
+            ActivityAction::Undone => tr("activity-undone"),
+            ActivityAction::Redone => tr("activity-redone"),
+            ActivityAction::Commented => tr("activity-commented"),
+            ActivityAction::Restored => tr("activity-restored"),
+            ActivityAction::Reordered => tr("activity-reordered"),
         }
     }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ActivityEntry {
+    // RFC 3339, local time
+    timestamp: String,
+    action: ActivityAction,
+    path: String,
+    #[serde(default)]
+    detail: String,
+    #[serde(default)]
+    session_note: String,
+}
+
+impl ActivityEntry {
+    fn day(&self) -> &str {
+        self.timestamp.get(..10).unwrap_or(&self.timestamp)
+    }
     
-    fn open_file(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("CSV", &["csv"])
-            .pick_file()
-        {
-            let path_str = path.to_string_lossy().to_string();
-            match Microfiche::from_csv(&path_str) {
-                Ok(fiche) => {
-                    self.microfiche = fiche;
-                    self.current_file = Some(path_str.clone());
-                    self.status_message = format!("Loaded {}", path_str);
-                },
-                Err(e) => self.status_message = format!("Error loading: {}", e),
-            }
+    fn category(&self) -> &str {
+        self.path.split(" > ").next().unwrap_or(&self.path)
+    }
+}
+
+/// Append-only record of changes, kept in a `<name>.activity.jsonl` file next
+/// to the fiche. Entries are written out when the fiche is saved.
+#[derive(Default)]
+struct ActivityLog {
+    entries: Vec<ActivityEntry>,
+    // Trailing entries not yet written to disk
+    unsaved: usize,
+    // Entries were removed, so the whole file is written on the next flush
+    rewrite: bool,
+}
+
+impl ActivityLog {
+    fn log_path(fiche_path: &str) -> std::path::PathBuf {
+        Path::new(fiche_path).with_extension("activity.jsonl")
+    }
+    
+    fn load(fiche_path: &str) -> Self {
+        let entries = std::fs::read_to_string(Self::log_path(fiche_path))
+            .map(|contents| {
+                contents.lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        ActivityLog { entries, unsaved: 0, rewrite: false }
+    }
+    
+    fn record(&mut self, action: ActivityAction, path: String, detail: String, session_note: &str) {
+        self.entries.push(ActivityEntry {
+            timestamp: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            action,
+            path,
+            detail,
+            session_note: session_note.trim().to_string(),
+        });
+        self.unsaved += 1;
+    }
+    
+    /// Notes created per day (YYYY-MM-DD)
+    fn captures_by_day(&self) -> BTreeMap<&str, usize> {
+        let mut days = BTreeMap::new();
+        for entry in self.entries.iter().filter(|e| e.action == ActivityAction::Created) {
+            *days.entry(entry.day()).or_insert(0) += 1;
         }
+        days
     }
     
-    fn render_top_bar(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
-        ui.horizontal(|ui| {
-            ui.menu_button("File", |ui| {
-                if ui.button("Open...").clicked() {
-                    self.open_file();
-                    ui.close_menu();
-                }
-                if ui.button("Save").clicked() {
-                    self.save_file();
-                    ui.close_menu();
-                }
-                if ui.button("Save As...").clicked() {
-                    self.save_file_as();
-                    ui.close_menu();
-                }
-            });
-            
-            ui.separator();
-            
-            if ui.selectable_label(self.view_mode == ViewMode::Browse, "Browse").clicked() {
-                self.view_mode = ViewMode::Browse;
-            }
-            if ui.selectable_label(self.view_mode == ViewMode::Search, "Search").clicked() {
-                self.view_mode = ViewMode::Search;
+    /// Categories with any change between two days, inclusive
+    fn categories_touched(&self, from: &str, to: &str) -> BTreeMap<&str, usize> {
+        let mut touched = BTreeMap::new();
+        for entry in self.entries.iter().filter(|e| e.action != ActivityAction::Imported) {
+            let day = entry.day();
+            if day >= from && day <= to {
+                *touched.entry(entry.category()).or_insert(0) += 1;
             }
-            if ui.selectable_label(self.view_mode == ViewMode::Create, "Create").clicked() {
-                self.view_mode = ViewMode::Create;
+        }
+        touched
+    }
+    
+    /// Drop the entries under `path` that quote `text`, returning how many
+    fn purge(&mut self, path: &str, text: &str) -> usize {
+        let first_unsaved = self.entries.len() - self.unsaved;
+        let before = self.entries.len();
+        let mut index = 0;
+        let mut unsaved_removed = 0;
+        self.entries.retain(|entry| {
+            let keep = entry.path != path || !quotes_note(&entry.detail, text);
+            if !keep && index >= first_unsaved {
+                unsaved_removed += 1;
             }
-            if ui.selectable_label(self.view_mode == ViewMode::Stats, "Stats").clicked() {
-                self.view_mode = ViewMode::Stats;
+            index += 1;
+            keep
+        });
+        self.unsaved -= unsaved_removed;
+        let removed = before - self.entries.len();
+        self.rewrite |= removed > unsaved_removed;
+        removed
+    }
+    
+    /// Edits and appends logged for the note at `path` quoting `text`
+    fn revisions(&self, path: &str, text: &str) -> usize {
+        self.entries.iter()
+            .filter(|entry| matches!(entry.action, ActivityAction::Edited | ActivityAction::Appended))
+            .filter(|entry| entry.path == path && quotes_note(&entry.detail, text))
+            .count()
+    }
+    
+    fn flush(&mut self, fiche_path: &str) -> Result<(), Box<dyn Error>> {
+        use std::io::Write;
+        
+        if self.rewrite {
+            let mut contents = String::new();
+            for entry in &self.entries {
+                contents.push_str(&serde_json::to_string(entry)?);
+                contents.push('\n');
             }
-            
-            ui.separator();
-            
-            if ui.button("Theme").clicked() {
-                self.show_theme_selector = !self.show_theme_selector;
+            std::fs::write(Self::log_path(fiche_path), contents)?;
+            self.rewrite = false;
+            self.unsaved = 0;
+            return Ok(());
+        }
+        if self.unsaved == 0 {
+            return Ok(());
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::log_path(fiche_path))?;
+        for entry in &self.entries[self.entries.len() - self.unsaved..] {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        self.unsaved = 0;
+        Ok(())
+    }
+}
+
+impl CategoryMeta {
+    fn is_empty(&self) -> bool {
+        *self == CategoryMeta::default()
+    }
+}
+
+impl FicheMeta {
+    fn sidecar_path(fiche_path: &str) -> std::path::PathBuf {
+        Path::new(fiche_path).with_extension("meta.json")
+    }
+    
+    fn load(fiche_path: &str) -> Result<Self, Box<dyn Error>> {
+        let path = Self::sidecar_path(fiche_path);
+        if !path.exists() {
+            return Ok(FicheMeta::default());
+        }
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+    
+    fn save(&self, fiche_path: &str) -> Result<(), Box<dyn Error>> {
+        let path = Self::sidecar_path(fiche_path);
+        if self.is_empty() {
+            // Don't leave a stale sidecar behind once everything is reset
+            if path.exists() {
+                std::fs::remove_file(path)?;
             }
-            
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                ui.label(&self.status_message);
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        write_replacing(&path.to_string_lossy(), |partial| Ok(std::fs::write(partial, json)?))
+    }
+    
+    fn is_empty(&self) -> bool {
+        self.categories.values().all(|c| c.is_empty())
+            && self.cross_listings.is_empty()
+            && self.concept_aliases.is_empty()
+            && self.study == StudyProgress::default()
+            && self.aging == AgingState::default()
+            && self.goals == CaptureGoals::default()
+            && self.note_templates.is_empty()
+            && self.info == FileInfo::default()
+    }
+    
+    fn category_color(&self, cat: &str) -> Option<egui::Color32> {
+        self.categories.get(cat)
+            .and_then(|m| m.color)
+            .map(|[r, g, b]| egui::Color32::from_rgb(r, g, b))
+    }
+    
+    /// Category name prefixed with its icon, if one is set
+    fn category_label(&self, cat: &str) -> String {
+        match self.categories.get(cat) {
+            Some(m) if !m.icon.is_empty() => format!("{} {}", m.icon, cat),
+            _ => cat.to_string(),
+        }
+    }
+    
+    /// Drop entries for categories that no longer exist
+    fn prune(&mut self, existing: &IndexMap<String, Category>) {
+        self.categories.retain(|name, m| {
+            let Some(category) = existing.get(name) else {
+                return false;
+            };
+            m.subcategories.retain(|sub, text| {
+                !text.trim().is_empty() && category.subcategories.iter().any(|s| &s.name == sub)
             });
+            !m.is_empty()
         });
         
-        // Theme selector window
-        if self.show_theme_selector {
-            egui::Window::new("Theme Selection")
-                .collapsible(false)
-                .resizable(false)
-                .show(ctx, |ui| {
-                    ui.vertical(|ui| {
-                        if ui.selectable_label(
-                            self.current_theme == Theme::Monokai, 
-                            "Monokai"
-                        ).clicked() {
-                            self.current_theme = Theme::Monokai;
-                            self.current_theme.apply(ctx);
-                            self.show_theme_selector = false;
-                        }
-                        
-                        if ui.selectable_label(
-                            self.current_theme == Theme::TomorrowBlueHour, 
-                            "Tomorrow (Blue Hour)"
-                        ).clicked() {
-                            self.current_theme = Theme::TomorrowBlueHour;
-                            self.current_theme.apply(ctx);
-                            self.show_theme_selector = false;
-                        }
-                        
-                        if ui.selectable_label(
-                            self.current_theme == Theme::DarkPlus, 
-                            "Dark+"
-                        ).clicked() {
-                            self.current_theme = Theme::DarkPlus;
-                            self.current_theme.apply(ctx);
-                            self.show_theme_selector = false;
-                        }
-                    });
-                    
-                    ui.separator();
-                    
-                    if ui.button("Close").clicked() {
-                        self.show_theme_selector = false;
-                    }
-                });
+        let has_subcategory = |cat: &str, sub: &str| {
+            existing.get(cat).is_some_and(|c| c.subcategories.iter().any(|s| s.name == sub))
+        };
+        let has_concept = |concept: &ConceptRef| {
+            existing.get(&concept.category)
+                .and_then(|c| c.subcategories.iter().find(|s| s.name == concept.subcategory))
+                .is_some_and(|s| s.concepts.iter().any(|c| c.name == concept.concept))
+        };
+        self.cross_listings.retain(|link| {
+            has_subcategory(&link.category, &link.subcategory) && has_concept(&link.source)
+        });
+        self.concept_aliases.retain(|a| !a.names.is_empty() && has_concept(&a.concept));
+        self.note_templates.retain(|t| {
+            has_subcategory(&t.category, &t.subcategory)
+                && (t.concept.is_empty() || has_concept(&ConceptRef {
+                    category: t.category.clone(),
+                    subcategory: t.subcategory.clone(),
+                    concept: t.concept.clone(),
+                }))
+        });
+    }
+    
+    fn aliases(&self, concept: &ConceptRef) -> &[String] {
+        self.concept_aliases.iter()
+            .find(|a| &a.concept == concept)
+            .map(|a| a.names.as_slice())
+            .unwrap_or_default()
+    }
+    
+    fn add_alias(&mut self, concept: &ConceptRef, name: &str) {
+        match self.concept_aliases.iter_mut().find(|a| &a.concept == concept) {
+            Some(entry) => {
+                if !entry.names.iter().any(|n| n == name) {
+                    entry.names.push(name.to_string());
+                }
+            },
+            None => self.concept_aliases.push(ConceptAliases {
+                concept: concept.clone(),
+                names: vec![name.to_string()],
+            }),
         }
     }
     
-    fn render_browse_view(&mut self, ui: &mut egui::Ui) {
-        egui::SidePanel::left("categories_panel")
-            .resizable(true)
-            .default_width(200.0)
-            .show_inside(ui, |ui| {
-                ui.heading("Categories");
-                ui.separator();
-                
-                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
-                    let mut categories: Vec<_> = self.microfiche.categories.keys().collect();
-                    categories.sort();
-                    
-                    for cat_name in categories {
-                        let is_selected = self.selected_category.as_ref() == Some(cat_name);
-                        if ui.selectable_label(is_selected, cat_name).clicked() {
-                            self.selected_category = Some(cat_name.clone());
-                            self.selected_subcategory = None;
-                            self.selected_concept = None;
-                        }
-                    }
-                });
-            });
-        
-        if let Some(ref cat_name) = self.selected_category.clone() {
-            if let Some(category) = self.microfiche.categories.get(cat_name) {
-                egui::SidePanel::left("subcategories_panel")
-                    .resizable(true)
-                    .default_width(200.0)
-                    .show_inside(ui, |ui| {
-                        ui.heading("Subcategories");
-                        ui.separator();
-                        
-                        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
-                            for subcat in &category.subcategories {
-                                let is_selected = self.selected_subcategory.as_ref() == Some(&subcat.name);
-                                if ui.selectable_label(is_selected, &subcat.name).clicked() {
-                                    self.selected_subcategory = Some(subcat.name.clone());
-                                    self.selected_concept = None;
-                                }
-                            }
-                        });
-                    });
+    /// Point everything that referenced `from` at `to` instead (used after merges)
+    fn redirect_concept(&mut self, from: &ConceptRef, to: &ConceptRef) {
+        for link in &mut self.cross_listings {
+            if &link.source == from {
+                link.source = to.clone();
+            }
+        }
+        self.cross_listings.retain(|link| {
+            !(link.source == *to && link.category == to.category && link.subcategory == to.subcategory)
+        });
+        let mut seen = Vec::new();
+        self.cross_listings.retain(|link| {
+            let duplicate = seen.contains(link);
+            seen.push(link.clone());
+            !duplicate
+        });
+        
+        if let Some(pos) = self.concept_aliases.iter().position(|a| &a.concept == from) {
+            let moved = self.concept_aliases.remove(pos);
+            for name in moved.names {
+                self.add_alias(to, &name);
+            }
+        }
+    }
+    
+    fn add_cross_listing(&mut self, category: &str, subcategory: &str, source: ConceptRef) -> bool {
+        if source.category == category && source.subcategory == subcategory {
+            return false;
+        }
+        let link = CrossListing {
+            category: category.to_string(),
+            subcategory: subcategory.to_string(),
+            source,
+        };
+        if self.cross_listings.contains(&link) {
+            return false;
+        }
+        self.cross_listings.push(link);
+        true
+    }
+    
+    fn remove_cross_listing(&mut self, category: &str, subcategory: &str, source: &ConceptRef) {
+        self.cross_listings.retain(|link| {
+            !(link.category == category && link.subcategory == subcategory && &link.source == source)
+        });
+    }
+    
+    fn description(&self, cat: &str, sub: Option<&str>) -> &str {
+        let Some(meta) = self.categories.get(cat) else {
+            return "";
+        };
+        match sub {
+            Some(sub) => meta.subcategories.get(sub).map(|d| d.as_str()).unwrap_or(""),
+            None => &meta.description,
+        }
+    }
+    
+    fn set_description(&mut self, cat: &str, sub: Option<&str>, text: String) {
+        let meta = self.categories.entry(cat.to_string()).or_default();
+        match sub {
+            Some(sub) => {
+                meta.subcategories.insert(sub.to_string(), text);
+            },
+            None => meta.description = text,
+        }
+    }
+    
+    /// The template set on exactly this concept (or subcategory, for "")
+    fn own_note_template(&self, cat: &str, sub: &str, con: &str) -> Option<&str> {
+        self.note_templates.iter()
+            .find(|t| t.category == cat && t.subcategory == sub && t.concept == con)
+            .map(|t| t.text.as_str())
+    }
+    
+    /// Text for a new note under a concept: its own template, else its subcategory's
+    fn note_template(&self, cat: &str, sub: &str, con: &str) -> Option<&str> {
+        self.own_note_template(cat, sub, con).or_else(|| self.own_note_template(cat, sub, ""))
+    }
+    
+    /// Set or (with empty text) remove the template on a concept, or on a
+    /// subcategory when `con` is empty
+    fn set_note_template(&mut self, cat: &str, sub: &str, con: &str, text: String) {
+        self.note_templates.retain(|t| !(t.category == cat && t.subcategory == sub && t.concept == con));
+        if !text.trim().is_empty() {
+            self.note_templates.push(NoteTemplate {
+                category: cat.to_string(),
+                subcategory: sub.to_string(),
+                concept: con.to_string(),
+                text,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FicheRow {
+    #[serde(rename = "Category")]
+    category: String,
+    #[serde(rename = "Subcategory")]
+    subcategory: String,
+    #[serde(rename = "Concept")]
+    concept: String,
+    #[serde(rename = "Note")]
+    note: String,
+    #[serde(rename = "Rating")]
+    rating: Option<u8>,
+    #[serde(rename = "NeedsReview")]
+    needs_review: bool,
+    #[serde(rename = "Created")]
+    created: Option<String>,
+    #[serde(skip)]
+    modified: Option<String>,
+    #[serde(skip)]
+    fields: BTreeMap<String, String>,
+    #[serde(skip)]
+    display: NoteDisplay,
+    #[serde(skip)]
+    definition: bool,
+    #[serde(skip)]
+    flags: BTreeSet<NoteFlag>,
+    #[serde(skip)]
+    tags: BTreeSet<String>,
+    #[serde(skip)]
+    author: String,
+    #[serde(skip)]
+    comments: Vec<NoteComment>,
+}
+
+impl FicheRow {
+    fn from_note(cat: &str, sub: &str, con: &str, note: &Note) -> Self {
+        FicheRow {
+            category: cat.to_string(),
+            subcategory: sub.to_string(),
+            concept: con.to_string(),
+            note: note.text.clone(),
+            rating: note.rating,
+            needs_review: note.needs_review,
+            created: note.created.clone(),
+            modified: note.modified.clone(),
+            fields: note.fields.clone(),
+            display: note.display,
+            definition: note.definition,
+            flags: note.flags.clone(),
+            tags: note.tags.clone(),
+            author: note.author.clone(),
+            comments: note.comments.clone(),
+        }
+    }
+    
+    fn from_record(headers: &StringRecord, record: &StringRecord) -> Result<Self, Box<dyn Error>> {
+        let column = |name: &str| -> Result<String, Box<dyn Error>> {
+            let idx = headers.iter().position(|h| h == name)
+                .ok_or_else(|| format!("Missing required column '{}'", name))?;
+            Ok(record.get(idx).unwrap_or("").to_string())
+        };
+        let optional = |name: &str| -> &str {
+            headers.iter().position(|h| h == name)
+                .and_then(|idx| record.get(idx))
+                .unwrap_or("")
+        };
+        
+        let mut fields = BTreeMap::new();
+        for (header, value) in headers.iter().zip(record.iter()) {
+            if !is_reserved_column(header) && !value.is_empty() {
+                fields.insert(header.to_string(), value.to_string());
+            }
+        }
+        
+        Ok(FicheRow {
+            category: column("Category")?,
+            subcategory: column("Subcategory")?,
+            concept: column("Concept")?,
+            note: column("Note")?,
+            rating: parse_rating(optional("Rating")),
+            needs_review: parse_flag(optional("NeedsReview")),
+            created: Some(optional("Created").trim()).filter(|c| !c.is_empty()).map(str::to_string),
+            modified: Some(optional("Modified").trim()).filter(|m| !m.is_empty()).map(str::to_string),
+            fields,
+            display: NoteDisplay::parse(optional("Display")),
+            definition: parse_flag(optional("Definition")),
+            flags: NoteFlag::parse(optional("Flags")),
+            tags: parse_tags(optional("Tags")),
+            author: optional("Author").trim().to_string(),
+            comments: parse_comments(optional("Comments")),
+        })
+    }
+}
+
+/// Narrows search results beyond the text query
+#[derive(Default)]
+struct SearchFilter {
+    // 0 means any rating
+    min_rating: u8,
+    // Only notes in this category
+    category: Option<String>,
+    // Only notes with this flag
+    flag: Option<NoteFlag>,
+    // Only notes with all of these tags
+    tags: BTreeSet<String>,
+    // Only notes by this author; Some("") for notes without one
+    author: Option<String>,
+    // Only notes created in this range: YYYY, YYYY-MM or YYYY-MM-DD, both
+    // ends included; empty for no limit
+    created_from: String,
+    created_to: String,
+    order: NoteOrder,
+}
+
+impl SearchFilter {
+    fn is_active(&self) -> bool {
+        self.min_rating > 0
+            || self.category.is_some()
+            || self.flag.is_some()
+            || !self.tags.is_empty()
+            || self.author.is_some()
+            || self.has_date_range()
+    }
+    
+    fn has_date_range(&self) -> bool {
+        !self.created_from.trim().is_empty() || !self.created_to.trim().is_empty()
+    }
+    
+    /// Notes without a creation date are left out once a range is set
+    fn in_date_range(&self, note: &Note) -> bool {
+        if !self.has_date_range() {
+            return true;
+        }
+        let Some(day) = note.created.as_deref().map(|c| c.get(..10).unwrap_or(c)) else {
+            return false;
+        };
+        let (from, to) = (self.created_from.trim(), self.created_to.trim());
+        (from.is_empty() || day >= from) && (to.is_empty() || day <= to || day.starts_with(to))
+    }
+    
+    fn matches(&self, note: &Note) -> bool {
+        (self.min_rating == 0 || note.rating.is_some_and(|r| r >= self.min_rating))
+            && self.flag.is_none_or(|flag| note.flags.contains(&flag))
+            && self.tags.is_subset(&note.tags)
+            && self.author.as_ref().is_none_or(|author| *author == note.author)
+            && self.in_date_range(note)
+    }
+}
+
+impl Microfiche {
+    fn new() -> Self {
+        Microfiche {
+            categories: IndexMap::new(),
+            meta: FicheMeta::default(),
+            attachments: BTreeMap::new(),
+            unloaded: BTreeMap::new(),
+            source_encoding: None,
+            index: search_index::Cache::default(),
+        }
+    }
+    
+    /// Note count per category of a CSV file, reading just the Category column
+    fn scan_categories(path: &str) -> Result<BTreeMap<String, usize>, Box<dyn Error>> {
+        compressed::read_with(path, |input| {
+            let (input, _) = text_encoding::to_utf8(input)?;
+            let mut rdr = Reader::from_reader(input);
+            let category_column = rdr.headers()?.iter().position(|h| h == "Category")
+                .ok_or("Missing required column 'Category'")?;
+            
+            let mut counts = BTreeMap::new();
+            let mut record = csv::ByteRecord::new();
+            while rdr.read_byte_record(&mut record)? {
+                let category = String::from_utf8_lossy(record.get(category_column).unwrap_or(b""));
+                if let Some(count) = counts.get_mut(category.as_ref()) {
+                    *count += 1;
+                } else {
+                    counts.insert(category.into_owned(), 1);
+                }
+            }
+            Ok(counts)
+        })
+    }
+    
+    /// Read the unloaded categories named by `wanted` from `path`, merging
+    /// them with any notes already added under the same names
+    fn load_categories(&mut self, path: &str, wanted: &BTreeSet<String>) -> Result<usize, Box<dyn Error>> {
+        let loaded = Self::from_csv_where(path, |cat| wanted.contains(cat))?;
+        for cat in wanted {
+            self.unloaded.remove(cat);
+        }
+        Ok(self.import(&loaded, false, DuplicateCheck::Off).0)
+    }
+    
+    /// Run `cleanup` over every note, returning how many changed
+    fn clean_notes(&mut self, cleanup: &NoteCleanup) -> usize {
+        let mut changed = 0;
+        for category in self.categories.values_mut() {
+            for subcat in &mut category.subcategories {
+                for concept in &mut subcat.concepts {
+                    for note in &mut concept.notes {
+                        let cleaned = cleanup.apply(&note.text);
+                        // A note that is nothing but markup keeps its text
+                        if cleaned != note.text && !cleaned.is_empty() {
+                            note.text = cleaned;
+                            changed += 1;
+                        }
+                    }
+                }
+            }
+        }
+        if changed > 0 {
+            self.index.clear();
+        }
+        changed
+    }
+    
+    /// Build a fiche from the repaired rows of a validation report
+    fn from_validated(report: &validate::Report) -> Result<Self, Box<dyn Error>> {
+        let mut fiche = Microfiche::new();
+        for record in &report.fixed {
+            fiche.add_row(FicheRow::from_record(&report.headers, record)?);
+        }
+        fiche.meta = FicheMeta::load(&report.path)?;
+        Ok(fiche)
+    }
+    
+    /// Load a `.fiche` container, a SQLite database (`.db`), or a CSV file
+    /// for any other extension
+    fn open(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut fiche = if fiche_file::is_fiche_path(path) {
+            fiche_file::read(path)?
+        } else if sqlite_store::is_db_path(path) {
+            Self::open_sqlite(path)?
+        } else {
+            Self::from_csv(path)?
+        };
+        if let Some(index) = search_index::SearchIndex::restore(path, &fiche) {
+            fiche.index.set(index);
+        }
+        Ok(fiche)
+    }
+    
+    /// Load a SQLite database written by `save`
+    fn open_sqlite(path: &str) -> Result<Self, Box<dyn Error>> {
+        sqlite_store::read(path)
+    }
+    
+    /// Save as a `.fiche` container, into a SQLite database (in place, only
+    /// the concepts that changed), or as CSV for any other extension. The
+    /// previous version is kept as `<file>.bak`.
+    fn save(&self, path: &str, progress: &JobProgress) -> Result<(), Box<dyn Error>> {
+        self.save_keeping(path, true, progress)
+    }
+    
+    /// Save, copying the file being replaced to `<file>.bak` when
+    /// `keep_previous` is set. Without it an old `.bak` is removed too, for a
+    /// purge whose text mustn't stay on disk.
+    fn save_keeping(&self, path: &str, keep_previous: bool, progress: &JobProgress) -> Result<(), Box<dyn Error>> {
+        progress.start(self.note_count());
+        if keep_previous {
+            keep_backup(path)?;
+        } else {
+            drop_backup(path)?;
+        }
+        if fiche_file::is_fiche_path(path) {
+            write_replacing(path, |partial| fiche_file::write(self, partial, progress))
+        } else if sqlite_store::is_db_path(path) {
+            sqlite_store::write(self, path, progress)
+        } else {
+            write_replacing(path, |partial| self.to_csv(partial, path, progress))?;
+            let mut meta = self.meta.clone();
+            meta.prune(&self.categories);
+            meta.save(path)
+        }
+    }
+    
+    fn note_count(&self) -> usize {
+        self.categories.values()
+            .flat_map(|c| &c.subcategories)
+            .flat_map(|s| &s.concepts)
+            .map(|c| c.notes.len())
+            .sum()
+    }
+    
+    /// Read a CSV file, gzipped (`.csv.gz`) or zipped (`.zip`) CSV files included
+    fn from_csv(path: &str) -> Result<Self, Box<dyn Error>> {
+        Self::from_csv_where(path, |_| true)
+    }
+    
+    /// Read only the rows whose category passes `keep`
+    fn from_csv_where(path: &str, keep: impl Fn(&str) -> bool) -> Result<Self, Box<dyn Error>> {
+        let mut fiche = Microfiche::new();
+        compressed::read_with(path, |input| {
+            let (input, encoding) = text_encoding::to_utf8(input)?;
+            fiche.source_encoding = encoding;
+            let mut rdr = Reader::from_reader(input);
+            let headers = rdr.headers()?.clone();
+            let category_column = headers.iter().position(|h| h == "Category")
+                .ok_or("Missing required column 'Category'")?;
+            
+            for result in rdr.records() {
+                let record = result?;
+                if keep(record.get(category_column).unwrap_or("")) {
+                    fiche.add_row(FicheRow::from_record(&headers, &record)?);
+                }
+            }
+            Ok(())
+        })?;
+        fiche.meta = FicheMeta::load(path)?;
+        
+        Ok(fiche)
+    }
+    
+    /// Write CSV to `file`, compressed when `target` (the path it will be
+    /// renamed to) ends in `.gz` or `.zip`. Rows are streamed field by field,
+    /// counting one step of `progress` per note.
+    fn to_csv(&self, file: &str, target: &str, progress: &JobProgress) -> Result<(), Box<dyn Error>> {
+        let field_names = self.field_names();
+        compressed::write_with(file, target, |output| {
+            let mut wtr = Writer::from_writer(output);
+            let mut header: Vec<&str> = CORE_COLUMNS.to_vec();
+            header.extend(OPTIONAL_COLUMNS);
+            header.extend(field_names.iter().map(|f| f.as_str()));
+            wtr.write_record(&header)?;
+            
+            for (cat_name, category) in &self.categories {
+                for subcat in &category.subcategories {
+                    for concept in &subcat.concepts {
+                        for note in &concept.notes {
+                            progress.advance(1)?;
+                            // Ratings are 1-5, a single digit
+                            let rating = note.rating.map(|r| [b'0' + r]);
+                            wtr.write_field(cat_name)?;
+                            wtr.write_field(&subcat.name)?;
+                            wtr.write_field(&concept.name)?;
+                            wtr.write_field(&note.text)?;
+                            wtr.write_field(rating.as_ref().map_or(&b""[..], |r| &r[..]))?;
+                            wtr.write_field(if note.needs_review { "yes" } else { "" })?;
+                            wtr.write_field(note.created.as_deref().unwrap_or(""))?;
+                            wtr.write_field(note.modified.as_deref().unwrap_or(""))?;
+                            wtr.write_field(note.display.to_column())?;
+                            wtr.write_field(if note.definition { "yes" } else { "" })?;
+                            wtr.write_field(NoteFlag::to_column(&note.flags))?;
+                            wtr.write_field(tags_column(&note.tags))?;
+                            wtr.write_field(&note.author)?;
+                            wtr.write_field(comments_column(&note.comments))?;
+                            for field in &field_names {
+                                wtr.write_field(note.fields.get(field).map_or("", String::as_str))?;
+                            }
+                            wtr.write_record(None::<&[u8]>)?;
+                        }
+                    }
+                }
+            }
+            
+            wtr.flush()?;
+            Ok(())
+        })
+    }
+    
+    fn add_row(&mut self, row: FicheRow) {
+        if let Some(index) = self.index.get_mut() {
+            index.insert(&row.category, &row.subcategory, &row.concept, &row.note);
+        }
+        let category = self.categories.entry(row.category.clone())
+            .or_insert_with(|| Category {
+                name: row.category.clone(),
+                subcategories: Vec::new(),
+            });
+        
+        if !category.subcategories.iter().any(|s| s.name == row.subcategory) {
+            category.subcategories.push(Subcategory {
+                name: row.subcategory.clone(),
+                concepts: Vec::new(),
+            });
+        }
+        let subcat = category.subcategories.iter_mut()
+            .find(|s| s.name == row.subcategory)
+            .unwrap();
+        
+        if !subcat.concepts.iter().any(|c| c.name == row.concept) {
+            subcat.concepts.push(Concept {
+                name: row.concept.clone(),
+                notes: Vec::new(),
+            });
+        }
+        let concept = subcat.concepts.iter_mut()
+            .find(|c| c.name == row.concept)
+            .unwrap();
+        
+        let note = Note {
+            text: row.note,
+            rating: row.rating,
+            needs_review: row.needs_review,
+            created: row.created,
+            modified: row.modified,
+            fields: row.fields,
+            display: row.display,
+            definition: row.definition,
+            flags: row.flags,
+            tags: row.tags,
+            author: row.author,
+            comments: row.comments,
+        };
+        if note.definition {
+            for other in &mut concept.notes {
+                other.definition = false;
+            }
+            concept.notes.insert(0, note);
+        } else {
+            concept.notes.push(note);
+        }
+    }
+    
+    fn concept(&self, cat: &str, sub: &str, con: &str) -> Option<&Concept> {
+        self.categories.get(cat)?
+            .subcategories.iter().find(|s| s.name == sub)?
+            .concepts.iter().find(|c| c.name == con)
+    }
+    
+    /// Swap a category with the one above or below it; false when it's
+    /// already first or last
+    fn move_category(&mut self, cat: &str, step: Step) -> bool {
+        let Some(index) = self.categories.get_index_of(cat) else {
+            return false;
+        };
+        let Some(other) = step.from(index, self.categories.len()) else {
+            return false;
+        };
+        self.categories.swap_indices(index, other);
+        true
+    }
+    
+    fn move_subcategory(&mut self, cat: &str, sub: &str, step: Step) -> bool {
+        let Some(category) = self.categories.get_mut(cat) else {
+            return false;
+        };
+        let Some(index) = category.subcategories.iter().position(|s| s.name == sub) else {
+            return false;
+        };
+        let Some(other) = step.from(index, category.subcategories.len()) else {
+            return false;
+        };
+        category.subcategories.swap(index, other);
+        true
+    }
+    
+    fn move_concept(&mut self, cat: &str, sub: &str, con: &str, step: Step) -> bool {
+        let Some(subcat) = self.categories.get_mut(cat)
+            .and_then(|c| c.subcategories.iter_mut().find(|s| s.name == sub))
+        else {
+            return false;
+        };
+        let Some(index) = subcat.concepts.iter().position(|c| c.name == con) else {
+            return false;
+        };
+        let Some(other) = step.from(index, subcat.concepts.len()) else {
+            return false;
+        };
+        subcat.concepts.swap(index, other);
+        true
+    }
+    
+    /// Every concept with its path, in the fiche's order
+    fn iter_concepts(&self) -> impl Iterator<Item = (ConceptRef, &Concept)> {
+        self.categories.iter().flat_map(move |(cat, category)| {
+            category.subcategories.iter().flat_map(move |sub| {
+                sub.concepts.iter().map(move |con| (ConceptRef {
+                    category: cat.clone(),
+                    subcategory: sub.name.clone(),
+                    concept: con.name.clone(),
+                }, con))
+            })
+        })
+    }
+    
+    /// Concepts connected to `center` by wiki links, shared tags or similar
+    /// terms, strongest first. Links made of term similarity alone are capped
+    /// at `max_similar`.
+    fn local_links(&self, center: &ConceptRef, max_similar: usize) -> Vec<ConceptLink> {
+        struct Profile {
+            names: Vec<String>,
+            links: HashSet<String>,
+            tags: BTreeSet<String>,
+            terms: HashSet<String>,
+        }
+        let profile = |concept_ref: &ConceptRef, concept: &Concept| {
+            let mut names = vec![concept.name.to_lowercase()];
+            names.extend(self.meta.aliases(concept_ref).iter().map(|a| a.to_lowercase()));
+            let mut profile = Profile {
+                names,
+                links: HashSet::new(),
+                tags: BTreeSet::new(),
+                terms: concept_terms(concept),
+            };
+            for note in &concept.notes {
+                profile.links.extend(wiki_links(&note.text));
+                profile.tags.extend(note_tags(&note.text));
+                profile.tags.extend(note.tags.iter().cloned());
+            }
+            profile
+        };
+        
+        let Some(center_concept) = self.concept(&center.category, &center.subcategory, &center.concept) else {
+            return Vec::new();
+        };
+        let center_profile = profile(center, center_concept);
+        
+        let mut links: Vec<ConceptLink> = self.iter_concepts()
+            .filter(|(concept_ref, _)| concept_ref != center)
+            .filter_map(|(concept_ref, concept)| {
+                let other = profile(&concept_ref, concept);
+                let wiki = other.names.iter().any(|n| center_profile.links.contains(n))
+                    || center_profile.names.iter().any(|n| other.links.contains(n));
+                let shared_tags: Vec<String> = center_profile.tags.intersection(&other.tags).cloned().collect();
+                let similarity = jaccard(&center_profile.terms, &other.terms);
+                (wiki || !shared_tags.is_empty() || similarity >= 0.1)
+                    .then_some(ConceptLink { target: concept_ref, wiki, shared_tags, similarity })
+            })
+            .collect();
+        links.sort_by(|a, b| b.strength().total_cmp(&a.strength()));
+        
+        let mut similar_only = 0;
+        links.retain(|link| {
+            if link.wiki || !link.shared_tags.is_empty() {
+                return true;
+            }
+            similar_only += 1;
+            similar_only <= max_similar
+        });
+        links
+    }
+    
+    /// Word frequencies (concept names and notes) and how often each pair of
+    /// words appears in the same note
+    fn term_network(&self) -> TermNetwork {
+        let mut network = TermNetwork::default();
+        for (_, concept) in self.iter_concepts() {
+            for word in extract_words(&concept.name) {
+                *network.frequencies.entry(word).or_insert(0) += 1;
+            }
+            for note in &concept.notes {
+                let words = extract_words(&note.text);
+                for word in &words {
+                    *network.frequencies.entry(word.clone()).or_insert(0) += 1;
+                }
+                for i in 0..words.len() {
+                    for j in (i + 1)..words.len() {
+                        if words[i] != words[j] {
+                            let pair = if words[i] < words[j] {
+                                (words[i].clone(), words[j].clone())
+                            } else {
+                                (words[j].clone(), words[i].clone())
+                            };
+                            *network.pairs.entry(pair).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+        }
+        network
+    }
+    
+    /// Notes mentioning each term per month, from the first to the last month
+    /// any note was created. Notes without a creation date are left out.
+    fn term_trends(&self, terms: &[String]) -> TermTrends {
+        let mut by_month: BTreeMap<&str, (usize, Vec<usize>)> = BTreeMap::new();
+        let mut undated = 0;
+        for (_, _, con, note) in self.iter_notes() {
+            let Some(month) = note.created.as_deref().and_then(|c| c.get(..7)) else {
+                undated += 1;
+                continue;
+            };
+            let words: HashSet<String> = extract_words(&note.text).into_iter().chain(extract_words(con)).collect();
+            let entry = by_month.entry(month).or_insert_with(|| (0, vec![0; terms.len()]));
+            entry.0 += 1;
+            for (i, term) in terms.iter().enumerate() {
+                if words.contains(term) {
+                    entry.1[i] += 1;
+                }
+            }
+        }
+        
+        let mut trends = TermTrends { undated, ..TermTrends::default() };
+        let (Some(first), Some(last)) = (by_month.keys().next(), by_month.keys().next_back()) else {
+            return trends;
+        };
+        let parse = |month: &str| chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok();
+        let (Some(mut month), Some(last)) = (parse(first), parse(last)) else {
+            return trends;
+        };
+        while month <= last {
+            let key = month.format("%Y-%m").to_string();
+            let (total, counts) = by_month.get(key.as_str()).cloned().unwrap_or_else(|| (0, vec![0; terms.len()]));
+            trends.months.push(key);
+            trends.totals.push(total);
+            trends.counts.push(counts);
+            month = month + chrono::Months::new(1);
+        }
+        trends
+    }
+    
+    /// A copy holding only one category, or one subcategory of it; `None`
+    /// when no scope is given or it no longer exists
+    fn subtree(&self, cat: &Option<String>, sub: &Option<String>) -> Option<Microfiche> {
+        let cat = cat.as_ref()?;
+        let mut category = self.categories.get(cat)?.clone();
+        if let Some(sub) = sub {
+            category.subcategories.retain(|s| &s.name == sub);
+        }
+        
+        let mut scoped = Microfiche::new();
+        scoped.categories.insert(cat.clone(), category);
+        scoped.meta = self.meta.clone();
+        Some(scoped)
+    }
+    
+    fn category_profile(&self, cat: &str) -> Option<CategoryProfile> {
+        let category = self.categories.get(cat)?;
+        let mut profile = CategoryProfile {
+            subcategories: category.subcategories.len(),
+            concepts: 0,
+            notes: 0,
+            average_length: 0.0,
+            terms: HashMap::new(),
+        };
+        let mut total_length = 0;
+        for subcat in &category.subcategories {
+            profile.concepts += subcat.concepts.len();
+            for concept in &subcat.concepts {
+                for term in extract_words(&concept.name) {
+                    *profile.terms.entry(term).or_insert(0) += 1;
+                }
+                for note in &concept.notes {
+                    profile.notes += 1;
+                    total_length += note.text.chars().count();
+                    for term in extract_words(&note.text) {
+                        *profile.terms.entry(term).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        if profile.notes > 0 {
+            profile.average_length = total_length as f32 / profile.notes as f32;
+        }
+        Some(profile)
+    }
+    
+    /// Terms found in at least `min_categories` categories, ranked by the
+    /// entropy of their spread: evenly shared terms rank highest
+    fn bridge_terms(&self, min_categories: usize) -> Vec<BridgeTerm> {
+        let mut term_categories: HashMap<String, BTreeMap<String, usize>> = HashMap::new();
+        for (cat, _, con, note) in self.iter_notes() {
+            let mut terms: HashSet<String> = extract_words(&note.text).into_iter().collect();
+            terms.extend(extract_words(con));
+            for term in terms {
+                *term_categories.entry(term).or_default().entry(cat.to_string()).or_insert(0) += 1;
+            }
+        }
+        
+        let mut bridges: Vec<BridgeTerm> = term_categories.into_iter()
+            .filter(|(_, categories)| categories.len() >= min_categories)
+            .map(|(term, categories)| {
+                let total: usize = categories.values().sum();
+                let entropy = categories.values()
+                    .map(|&count| {
+                        let p = count as f32 / total as f32;
+                        -p * p.log2()
+                    })
+                    .sum();
+                BridgeTerm { term, entropy, categories }
+            })
+            .collect();
+        bridges.sort_by(|a, b| b.entropy.total_cmp(&a.entropy).then_with(|| a.term.cmp(&b.term)));
+        bridges
+    }
+    
+    /// Groups concepts with similar terms by label propagation on the graph
+    /// of concept pairs at least `min_similarity` alike. Concepts without
+    /// such a neighbour are left out; clusters come largest first.
+    fn cluster_concepts(&self, min_similarity: f32) -> Vec<ConceptCluster> {
+        let concepts: Vec<(ConceptRef, HashSet<String>)> = self.iter_concepts()
+            .map(|(concept_ref, concept)| (concept_ref, concept_terms(concept)))
+            .collect();
+        let n = concepts.len();
+        
+        let mut neighbours: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let similarity = jaccard(&concepts[i].1, &concepts[j].1);
+                if similarity >= min_similarity {
+                    neighbours[i].push((j, similarity));
+                    neighbours[j].push((i, similarity));
+                }
+            }
+        }
+        
+        // Each concept takes the label carrying the most similarity among its
+        // neighbours until nothing changes (ties go to the lowest label)
+        let mut labels: Vec<usize> = (0..n).collect();
+        for _ in 0..30 {
+            let mut changed = false;
+            for i in 0..n {
+                if neighbours[i].is_empty() {
+                    continue;
+                }
+                let mut weights: BTreeMap<usize, f32> = BTreeMap::new();
+                for &(j, similarity) in &neighbours[i] {
+                    *weights.entry(labels[j]).or_insert(0.0) += similarity;
+                }
+                let best = weights.iter()
+                    .fold((labels[i], f32::MIN), |best, (&label, &weight)| {
+                        if weight > best.1 { (label, weight) } else { best }
+                    })
+                    .0;
+                if best != labels[i] {
+                    labels[i] = best;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        
+        let mut document_freq: HashMap<&str, usize> = HashMap::new();
+        for (_, terms) in &concepts {
+            for term in terms {
+                *document_freq.entry(term.as_str()).or_insert(0) += 1;
+            }
+        }
+        
+        let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (i, label) in labels.iter().enumerate() {
+            if !neighbours[i].is_empty() {
+                members.entry(*label).or_default().push(i);
+            }
+        }
+        
+        let mut clusters: Vec<ConceptCluster> = members.into_values()
+            .filter(|indices| indices.len() > 1)
+            .map(|indices| {
+                // Name the cluster after terms common inside it but rare elsewhere
+                let mut cluster_freq: HashMap<&str, usize> = HashMap::new();
+                let mut categories: BTreeMap<String, usize> = BTreeMap::new();
+                for &i in &indices {
+                    for term in &concepts[i].1 {
+                        *cluster_freq.entry(term.as_str()).or_insert(0) += 1;
+                    }
+                    *categories.entry(concepts[i].0.category.clone()).or_insert(0) += 1;
+                }
+                let mut scored: Vec<(&str, f32)> = cluster_freq.into_iter()
+                    .filter(|(_, count)| *count > 1)
+                    .map(|(term, count)| (term, count as f32 * (n as f32 / document_freq[term] as f32).ln()))
+                    .collect();
+                scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+                
+                ConceptCluster {
+                    concepts: indices.iter().map(|&i| concepts[i].0.clone()).collect(),
+                    top_terms: scored.into_iter().take(3).map(|(term, _)| term.to_string()).collect(),
+                    categories,
+                }
+            })
+            .collect();
+        clusters.sort_by_key(|c| std::cmp::Reverse(c.concepts.len()));
+        clusters
+    }
+    
+    fn find_note_mut(&mut self, cat: &str, sub: &str, con: &str, note_content: &str) -> Option<&mut Note> {
+        self.categories.get_mut(cat)?
+            .subcategories.iter_mut().find(|s| s.name == sub)?
+            .concepts.iter_mut().find(|c| c.name == con)?
+            .notes.iter_mut().find(|n| n.text == note_content)
+    }
+    
+    fn set_rating(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, rating: Option<u8>) -> bool {
+        match self.find_note_mut(cat, sub, con, note_content) {
+            Some(note) => {
+                note.rating = rating;
+                true
+            },
+            None => false,
+        }
+    }
+    
+    fn set_needs_review(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, needs_review: bool) -> bool {
+        match self.find_note_mut(cat, sub, con, note_content) {
+            Some(note) => {
+                note.needs_review = needs_review;
+                true
+            },
+            None => false,
+        }
+    }
+    
+    fn set_display(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, display: NoteDisplay) -> bool {
+        match self.find_note_mut(cat, sub, con, note_content) {
+            Some(note) => {
+                note.display = display;
+                true
+            },
+            None => false,
+        }
+    }
+    
+    /// Pin a note as its concept's definition, moving it first and unpinning
+    /// any other, or unpin it
+    fn set_definition(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, definition: bool) -> bool {
+        let Some(concept) = self.categories.get_mut(cat)
+            .and_then(|c| c.subcategories.iter_mut().find(|s| s.name == sub))
+            .and_then(|s| s.concepts.iter_mut().find(|c| c.name == con))
+        else {
+            return false;
+        };
+        let Some(index) = concept.notes.iter().position(|n| n.text == note_content) else {
+            return false;
+        };
+        if definition {
+            for note in &mut concept.notes {
+                note.definition = false;
+            }
+            let note = concept.notes.remove(index);
+            concept.notes.insert(0, note);
+        }
+        concept.notes[if definition { 0 } else { index }].definition = definition;
+        true
+    }
+    
+    fn set_flags(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, flags: BTreeSet<NoteFlag>) -> bool {
+        match self.find_note_mut(cat, sub, con, note_content) {
+            Some(note) => {
+                note.flags = flags;
+                true
+            },
+            None => false,
+        }
+    }
+    
+    fn add_comment(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, comment: NoteComment) -> bool {
+        match self.find_note_mut(cat, sub, con, note_content) {
+            Some(note) => {
+                note.comments.push(comment);
+                true
+            },
+            None => false,
+        }
+    }
+    
+    fn remove_comment(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, index: usize) -> Option<NoteComment> {
+        let note = self.find_note_mut(cat, sub, con, note_content)?;
+        (index < note.comments.len()).then(|| note.comments.remove(index))
+    }
+    
+    /// Notes carrying each tag, for tags in use
+    fn tag_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for (_, _, _, note) in self.iter_notes() {
+            for tag in &note.tags {
+                *counts.entry(tag.clone()).or_default() += 1;
+            }
+        }
+        counts
+    }
+    
+    /// Notes by each author, with "" for notes without one
+    fn author_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for (_, _, _, note) in self.iter_notes() {
+            *counts.entry(note.author.clone()).or_default() += 1;
+        }
+        counts
+    }
+    
+    /// Notes carrying each flag, for flags in use
+    fn flag_counts(&self) -> BTreeMap<NoteFlag, usize> {
+        let mut counts = BTreeMap::new();
+        for (_, _, _, note) in self.iter_notes() {
+            for flag in &note.flags {
+                *counts.entry(*flag).or_default() += 1;
+            }
+        }
+        counts
+    }
+    
+    fn review_count(&self) -> usize {
+        self.categories.values()
+            .flat_map(|c| &c.subcategories)
+            .flat_map(|s| &s.concepts)
+            .flat_map(|c| &c.notes)
+            .filter(|n| n.needs_review)
+            .count()
+    }
+    
+    /// Every note with its path, in the fiche's order
+    fn iter_notes(&self) -> impl Iterator<Item = (&str, &str, &str, &Note)> {
+        self.categories.iter().flat_map(move |(cat, category)| {
+            category.subcategories.iter().flat_map(move |sub| {
+                sub.concepts.iter().flat_map(move |con| {
+                    con.notes.iter().map(move |note| (cat.as_str(), sub.name.as_str(), con.name.as_str(), note))
+                })
+            })
+        })
+    }
+    
+    /// Notes flagged for review, in hierarchy order
+    fn review_queue(&self) -> Vec<(String, String, String, Note)> {
+        self.iter_notes()
+            .filter(|(_, _, _, note)| note.needs_review)
+            .map(|(cat, sub, con, note)| (cat.to_string(), sub.to_string(), con.to_string(), note.clone()))
+            .collect()
+    }
+    
+    /// Notes to study next: never-studied notes first, then the longest unseen.
+    /// Notes already studied `today` are left out.
+    fn study_queue(&self, today: &str) -> Vec<(String, String, String, Note)> {
+        let study = &self.meta.study;
+        let mut queue: Vec<_> = self.iter_notes()
+            .filter(|(cat, _, _, _)| study.categories.is_empty() || study.categories.contains(*cat))
+            .map(|(cat, sub, con, note)| (study.studied.get(&note_key(cat, sub, con, &note.text)), cat, sub, con, note))
+            .filter(|(last, ..)| last.is_none_or(|d| d != today))
+            .collect();
+        // Stable sort keeps hierarchy order within the same date
+        queue.sort_by_key(|(last, ..)| *last);
+        queue.into_iter()
+            .map(|(_, cat, sub, con, note)| (cat.to_string(), sub.to_string(), con.to_string(), note.clone()))
+            .collect()
+    }
+    
+    /// Notes not created, edited or confirmed in the last `aging.stale_days`,
+    /// undated ones first and then oldest first. Snoozed and archived notes are
+    /// left out, and so are categories without activity in that time unless
+    /// `aging.all_categories` is set (or there's no activity log to go by)
+    fn stale_notes(&self, activity: &[ActivityEntry], today: chrono::NaiveDate) -> Vec<StaleNote> {
+        let aging = &self.meta.aging;
+        let day = |date: chrono::NaiveDate| date.format("%Y-%m-%d").to_string();
+        let today_str = day(today);
+        let cutoff = day(today - chrono::Duration::days(aging.stale_days as i64));
+        let active: HashSet<&str> = activity.iter()
+            .filter(|e| e.day() >= cutoff.as_str())
+            .map(|e| e.category())
+            .collect();
+        let every_category = aging.all_categories || activity.is_empty();
+        
+        let mut stale: Vec<StaleNote> = self.iter_notes()
+            .filter(|(cat, _, _, _)| every_category || active.contains(cat))
+            .filter_map(|(cat, sub, con, note)| {
+                let key = note_key(cat, sub, con, &note.text);
+                if aging.archived.contains(&key) || aging.snoozed.get(&key).is_some_and(|until| until.as_str() > today_str.as_str()) {
+                    return None;
+                }
+                let last_touched = [note.created.as_deref(), note.modified.as_deref(), aging.confirmed.get(&key).map(String::as_str)]
+                    .into_iter()
+                    .flatten()
+                    .map(|date| date.get(..10).unwrap_or(date))
+                    .max()
+                    .map(str::to_string);
+                if last_touched.as_ref().is_some_and(|d| d.as_str() >= cutoff.as_str()) {
+                    return None;
+                }
+                Some(StaleNote {
+                    note: (cat.to_string(), sub.to_string(), con.to_string(), note.text.clone()),
+                    last_touched,
+                })
+            })
+            .collect();
+        stale.sort_by(|a, b| a.last_touched.cmp(&b.last_touched));
+        stale
+    }
+    
+    /// Per category: (notes studied at least once, total notes)
+    fn study_coverage(&self) -> BTreeMap<String, (usize, usize)> {
+        let mut coverage: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+        for (cat, sub, con, note) in self.iter_notes() {
+            let entry = coverage.entry(cat.to_string()).or_insert((0, 0));
+            entry.1 += 1;
+            if self.meta.study.studied.contains_key(&note_key(cat, sub, con, &note.text)) {
+                entry.0 += 1;
+            }
+        }
+        coverage
+    }
+    
+    /// Add every note of `other`, returning how many were added and how many
+    /// were skipped as duplicates of notes already here
+    fn import(&mut self, other: &Microfiche, flag_for_review: bool, duplicates: DuplicateCheck) -> (usize, usize) {
+        let mut seen: HashSet<String> = self.iter_notes()
+            .filter_map(|(cat, sub, con, note)| duplicates.key(cat, sub, con, &note.text))
+            .collect();
+        let mut count = 0;
+        let mut skipped = 0;
+        for (cat_name, category) in &other.categories {
+            for subcat in &category.subcategories {
+                for concept in &subcat.concepts {
+                    for note in &concept.notes {
+                        if let Some(key) = duplicates.key(cat_name, &subcat.name, &concept.name, &note.text)
+                            && !seen.insert(key)
+                        {
+                            skipped += 1;
+                            continue;
+                        }
+                        let mut row = FicheRow::from_note(cat_name, &subcat.name, &concept.name, note);
+                        row.needs_review |= flag_for_review;
+                        self.add_row(row);
+                        count += 1;
+                    }
+                }
+            }
+        }
+        (count, skipped)
+    }
+    
+    /// Average rating per category as (average, rated note count), skipping unrated notes
+    fn rating_stats(&self) -> BTreeMap<String, (f64, usize)> {
+        let mut stats = BTreeMap::new();
+        for (cat_name, category) in &self.categories {
+            let ratings: Vec<u8> = category.subcategories.iter()
+                .flat_map(|s| &s.concepts)
+                .flat_map(|c| &c.notes)
+                .filter_map(|n| n.rating)
+                .collect();
+            if !ratings.is_empty() {
+                let total: u32 = ratings.iter().map(|&r| r as u32).sum();
+                stats.insert(cat_name.clone(), (total as f64 / ratings.len() as f64, ratings.len()));
+            }
+        }
+        stats
+    }
+    
+    /// All custom field names used by any note, in column order
+    fn field_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        for category in self.categories.values() {
+            for subcat in &category.subcategories {
+                for concept in &subcat.concepts {
+                    for note in &concept.notes {
+                        names.extend(note.fields.keys().cloned());
+                    }
+                }
+            }
+        }
+        names
+    }
+    
+    /// Find the custom fields where every non-empty value parses as a number and
+    /// aggregate them per category as (sum, count).
+    fn numeric_field_stats(&self) -> BTreeMap<String, BTreeMap<String, (f64, usize)>> {
+        let mut stats: BTreeMap<String, BTreeMap<String, (f64, usize)>> = BTreeMap::new();
+        let mut non_numeric: BTreeSet<String> = BTreeSet::new();
+        
+        for (cat_name, category) in &self.categories {
+            for subcat in &category.subcategories {
+                for concept in &subcat.concepts {
+                    for note in &concept.notes {
+                        for (field, value) in &note.fields {
+                            if non_numeric.contains(field) {
+                                continue;
+                            }
+                            match value.trim().parse::<f64>() {
+                                Ok(number) if number.is_finite() => {
+                                    let entry = stats.entry(field.clone())
+                                        .or_default()
+                                        .entry(cat_name.clone())
+                                        .or_insert((0.0, 0));
+                                    entry.0 += number;
+                                    entry.1 += 1;
+                                },
+                                _ => {
+                                    non_numeric.insert(field.clone());
+                                    stats.remove(field);
+                                },
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        stats
+    }
+    
+    /// Notes matching every word and `"quoted phrase"` of `query` in their
+    /// text, path or concept aliases, best match first, then `filter`'s order
+    fn search(&self, query: &str, filter: &SearchFilter) -> Vec<(String, String, String, Note)> {
+        let mut results = Vec::new();
+        let clauses = search_index::parse(query);
+        
+        if clauses.is_empty() {
+            if !filter.is_active() {
+                return results;
+            }
+            for (cat_name, category) in &self.categories {
+                if filter.category.as_ref().is_some_and(|c| c != cat_name) {
+                    continue;
+                }
+                for subcat in &category.subcategories {
+                    for concept in &subcat.concepts {
+                        for note in concept.notes.iter().filter(|note| filter.matches(note)) {
+                            results.push((cat_name.clone(), subcat.name.clone(), concept.name.clone(), note.clone()));
+                        }
+                    }
+                }
+            }
+        } else {
+            let hits = self.index.get_or_build(self).search(&clauses, |cat, sub, con| {
+                let aliases = self.meta.aliases(&ConceptRef {
+                    category: cat.to_string(),
+                    subcategory: sub.to_string(),
+                    concept: con.to_string(),
+                }).join(" ");
+                fold_text(&format!("{} {} {} {}", cat, sub, con, aliases))
+            });
+            for hit in hits {
+                if filter.category.as_ref().is_some_and(|c| c != hit.category) {
+                    continue;
+                }
+                let note = self.concept(hit.category, hit.subcategory, hit.concept)
+                    .and_then(|c| c.notes.iter().find(|n| n.text == hit.text));
+                if let Some(note) = note && filter.matches(note) {
+                    results.push((hit.category.to_string(), hit.subcategory.to_string(), hit.concept.to_string(), note.clone()));
+                }
+            }
+        }
+        
+        filter.order.sort(&mut results, |(_, _, _, note)| note);
+        results
+    }
+    
+    fn delete_note(&mut self, cat: &str, sub: &str, con: &str, note_content: &str) -> Option<Note> {
+        if let Some(category) = self.categories.get_mut(cat) {
+            if let Some(subcat) = category.subcategories.iter_mut().find(|s| s.name == sub) {
+                if let Some(concept) = subcat.concepts.iter_mut().find(|c| c.name == con) {
+                    if let Some(pos) = concept.notes.iter().position(|n| n.text == note_content) {
+                        let removed = concept.notes.remove(pos);
+                        if let Some(index) = self.index.get_mut() {
+                            index.remove(cat, sub, con, note_content);
+                        }
+                        
+                        // Cleanup empty structures
+                        if concept.notes.is_empty() {
+                            subcat.concepts.retain(|c| !c.notes.is_empty());
+                        }
+                        if subcat.concepts.is_empty() {
+                            category.subcategories.retain(|s| !s.concepts.is_empty());
+                        }
+                        if category.subcategories.is_empty() {
+                            self.categories.shift_remove(cat);
+                        }
+                        
+                        return Some(removed);
+                    }
+                }
+            }
+        }
+        None
+    }
+    
+    /// Replace a note with several notes in its place, each keeping the
+    /// original's rating and fields
+    fn split_note(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, parts: Vec<String>) -> bool {
+        let Some(concept) = self.categories.get_mut(cat)
+            .and_then(|c| c.subcategories.iter_mut().find(|s| s.name == sub))
+            .and_then(|s| s.concepts.iter_mut().find(|c| c.name == con))
+        else {
+            return false;
+        };
+        let Some(pos) = concept.notes.iter().position(|n| n.text == note_content) else {
+            return false;
+        };
+        
+        let original = concept.notes.remove(pos);
+        let modified = Some(timestamp_now());
+        let new_notes: Vec<Note> = parts.into_iter()
+            .map(|text| Note { text, modified: modified.clone(), ..original.clone() })
+            .collect();
+        if let Some(index) = self.index.get_mut() {
+            index.remove(cat, sub, con, note_content);
+            for note in &new_notes {
+                index.insert(cat, sub, con, &note.text);
+            }
+        }
+        concept.notes.splice(pos..pos, new_notes);
+        true
+    }
+    
+    /// Add a paragraph to the end (or start) of a note
+    fn extend_note(&mut self, cat: &str, sub: &str, con: &str, note_content: &str, addition: &str, prepend: bool) -> bool {
+        let Some(note) = self.find_note_mut(cat, sub, con, note_content) else {
+            return false;
+        };
+        note.text = if prepend {
+            format!("{}\n\n{}", addition, note.text)
+        } else {
+            format!("{}\n\n{}", note.text, addition)
+        };
+        note.modified = Some(timestamp_now());
+        let text = note.text.clone();
+        self.reindex_note(cat, sub, con, note_content, &text);
+        true
+    }
+    
+    /// Update the search index for a note whose text changed from `old` to `new`
+    fn reindex_note(&mut self, cat: &str, sub: &str, con: &str, old: &str, new: &str) {
+        if let Some(index) = self.index.get_mut() {
+            index.remove(cat, sub, con, old);
+            index.insert(cat, sub, con, new);
+        }
+    }
+    
+    /// Remove a whole concept, cleaning up any subcategory/category left empty
+    fn remove_concept(&mut self, cat: &str, sub: &str, con: &str) -> Option<Concept> {
+        let category = self.categories.get_mut(cat)?;
+        let subcat = category.subcategories.iter_mut().find(|s| s.name == sub)?;
+        let pos = subcat.concepts.iter().position(|c| c.name == con)?;
+        let removed = subcat.concepts.remove(pos);
+        if let Some(index) = self.index.get_mut() {
+            for note in &removed.notes {
+                index.remove(cat, sub, con, &note.text);
+            }
+        }
+        
+        if subcat.concepts.is_empty() {
+            category.subcategories.retain(|s| !s.concepts.is_empty());
+        }
+        if category.subcategories.is_empty() {
+            self.categories.shift_remove(cat);
+        }
+        
+        Some(removed)
+    }
+    
+    /// Notes of `source` that `target` doesn't already contain (identical text is a duplicate)
+    fn merge_preview(&self, source: &ConceptRef, target: &ConceptRef) -> Option<(Vec<Note>, usize)> {
+        let from = self.concept(&source.category, &source.subcategory, &source.concept)?;
+        let into = self.concept(&target.category, &target.subcategory, &target.concept)?;
+        let mut new_notes: Vec<Note> = Vec::new();
+        for note in &from.notes {
+            let duplicate = into.notes.iter().chain(new_notes.iter()).any(|n| n.text == note.text);
+            if !duplicate {
+                new_notes.push(note.clone());
+            }
+        }
+        let duplicates = from.notes.len() - new_notes.len();
+        Some((new_notes, duplicates))
+    }
+    
+    /// Move the notes of `source` into `target` and remove `source`.
+    /// Returns the number of notes moved and duplicates dropped.
+    fn merge_concepts(&mut self, source: &ConceptRef, target: &ConceptRef, keep_alias: bool) -> Option<(usize, usize)> {
+        if source == target {
+            return None;
+        }
+        let (new_notes, duplicates) = self.merge_preview(source, target)?;
+        let moved = new_notes.len();
+        
+        self.remove_concept(&source.category, &source.subcategory, &source.concept)?;
+        if let Some(index) = self.index.get_mut() {
+            for note in &new_notes {
+                index.insert(&target.category, &target.subcategory, &target.concept, &note.text);
+            }
+        }
+        let subcat = self.categories.get_mut(&target.category)?
+            .subcategories.iter_mut().find(|s| s.name == target.subcategory)?;
+        subcat.concepts.iter_mut()
+            .find(|c| c.name == target.concept)?
+            .notes.extend(new_notes);
+        
+        self.meta.redirect_concept(source, target);
+        if keep_alias && source.concept != target.concept {
+            self.meta.add_alias(target, &source.concept);
+        }
+        
+        Some((moved, duplicates))
+    }
+    
+    /// Put a concept back exactly as it was before a merge
+    fn restore_concept(&mut self, at: &ConceptRef, concept: Concept) {
+        let category = self.categories.entry(at.category.clone())
+            .or_insert_with(|| Category {
+                name: at.category.clone(),
+                subcategories: Vec::new(),
+            });
+        if !category.subcategories.iter().any(|s| s.name == at.subcategory) {
+            category.subcategories.push(Subcategory {
+                name: at.subcategory.clone(),
+                concepts: Vec::new(),
+            });
+        }
+        let subcat = category.subcategories.iter_mut()
+            .find(|s| s.name == at.subcategory)
+            .unwrap();
+        if let Some(index) = self.index.get_mut() {
+            for old in subcat.concepts.iter().filter(|c| c.name == concept.name) {
+                for note in &old.notes {
+                    index.remove(&at.category, &at.subcategory, &concept.name, &note.text);
+                }
+            }
+            for note in &concept.notes {
+                index.insert(&at.category, &at.subcategory, &concept.name, &note.text);
+            }
+        }
+        subcat.concepts.retain(|c| c.name != concept.name);
+        subcat.concepts.push(concept);
+    }
+    
+    fn stats(&self) -> HashMap<String, usize> {
+        let mut stats = HashMap::new();
+        let mut total_subcats = 0;
+        let mut total_concepts = 0;
+        let mut total_notes = 0;
+        
+        stats.insert("categories".to_string(), self.categories.len());
+        
+        for (_, category) in &self.categories {
+            total_subcats += category.subcategories.len();
+            for subcat in &category.subcategories {
+                total_concepts += subcat.concepts.len();
+                for concept in &subcat.concepts {
+                    total_notes += concept.notes.len();
+                }
+            }
+        }
+        
+        stats.insert("subcategories".to_string(), total_subcats);
+        stats.insert("concepts".to_string(), total_concepts);
+        stats.insert("total_notes".to_string(), total_notes);
+        
+        stats
+    }
+    
+    /// Every category, subcategory or concept with its note count, largest first
+    fn ranked_by_size(&self, level: HierarchyLevel) -> Vec<(Vec<String>, usize)> {
+        let mut ranked = Vec::new();
+        for (cat_name, category) in &self.categories {
+            let mut cat_total = 0;
+            for subcat in &category.subcategories {
+                let mut sub_total = 0;
+                for concept in &subcat.concepts {
+                    sub_total += concept.notes.len();
+                    if level == HierarchyLevel::Concept {
+                        ranked.push((vec![cat_name.clone(), subcat.name.clone(), concept.name.clone()], concept.notes.len()));
+                    }
+                }
+                cat_total += sub_total;
+                if level == HierarchyLevel::Subcategory {
+                    ranked.push((vec![cat_name.clone(), subcat.name.clone()], sub_total));
+                }
+            }
+            if level == HierarchyLevel::Category {
+                ranked.push((vec![cat_name.clone()], cat_total));
+            }
+        }
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| collate(&a.0.join(" > "), &b.0.join(" > "))));
+        ranked
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HierarchyLevel {
+    Category,
+    Subcategory,
+    Concept,
+}
+
+/// Categories, subcategories or concepts ranked by note count, from the Stats
+/// Overview; paths are 1-3 names long
+struct SizeRanking {
+    level: HierarchyLevel,
+    entries: Vec<(Vec<String>, usize)>,
+}
+
+/// A concept as rendered in the Browse central panel. Cross-listed concepts
+/// keep the path of the subcategory that owns their notes.
+struct ConceptView {
+    category: String,
+    subcategory: String,
+    name: String,
+    // Only the notes shown so far; `note_count` is the full number
+    notes: Vec<Note>,
+    note_count: usize,
+    // Notes before the concept's quick filter
+    unfiltered_count: usize,
+    cross_listed: bool,
+}
+
+/// Term co-occurrence network behind the Stats panels and the graph export
+#[derive(Default)]
+struct TermNetwork {
+    frequencies: HashMap<String, usize>,
+    // (a, b) with a < b -> notes containing both
+    pairs: HashMap<(String, String), usize>,
+}
+
+impl TermNetwork {
+    /// Terms that take part in at least one pair, sorted
+    fn nodes(&self) -> Vec<&str> {
+        let nodes: BTreeSet<&str> = self.pairs.keys()
+            .flat_map(|(a, b)| [a.as_str(), b.as_str()])
+            .collect();
+        nodes.into_iter().collect()
+    }
+    
+    fn sorted_edges(&self) -> Vec<(&str, &str, usize)> {
+        let mut edges: Vec<(&str, &str, usize)> = self.pairs.iter()
+            .map(|((a, b), weight)| (a.as_str(), b.as_str(), *weight))
+            .collect();
+        edges.sort();
+        edges
+    }
+    
+    /// GEXF 1.3, as read by Gephi
+    fn write_gexf(&self, out: &mut impl std::io::Write, progress: &JobProgress) -> std::io::Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+        writeln!(out, r#"  <meta><creator>Fisha</creator><description>Term co-occurrence network</description></meta>"#)?;
+        writeln!(out, r#"  <graph defaultedgetype="undirected">"#)?;
+        writeln!(out, r#"    <attributes class="node"><attribute id="0" title="frequency" type="integer"/></attributes>"#)?;
+        writeln!(out, "    <nodes>")?;
+        for term in self.nodes() {
+            progress.advance(1)?;
+            let frequency = self.frequencies.get(term).copied().unwrap_or(0);
+            writeln!(out, r#"      <node id="{0}" label="{0}"><attvalues><attvalue for="0" value="{1}"/></attvalues></node>"#, xml_escape(term), frequency)?;
+        }
+        writeln!(out, "    </nodes>")?;
+        writeln!(out, "    <edges>")?;
+        for (i, (a, b, weight)) in self.sorted_edges().into_iter().enumerate() {
+            progress.advance(1)?;
+            writeln!(out, r#"      <edge id="{}" source="{}" target="{}" weight="{}"/>"#, i, xml_escape(a), xml_escape(b), weight)?;
+        }
+        writeln!(out, "    </edges>")?;
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</gexf>")
+    }
+    
+    fn write_graphml(&self, out: &mut impl std::io::Write, progress: &JobProgress) -> std::io::Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(out, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+        writeln!(out, r#"  <key id="frequency" for="node" attr.name="frequency" attr.type="int"/>"#)?;
+        writeln!(out, r#"  <key id="weight" for="edge" attr.name="weight" attr.type="int"/>"#)?;
+        writeln!(out, r#"  <graph id="terms" edgedefault="undirected">"#)?;
+        for term in self.nodes() {
+            progress.advance(1)?;
+            let frequency = self.frequencies.get(term).copied().unwrap_or(0);
+            writeln!(out, r#"    <node id="{}"><data key="frequency">{}</data></node>"#, xml_escape(term), frequency)?;
+        }
+        for (a, b, weight) in self.sorted_edges() {
+            progress.advance(1)?;
+            writeln!(out, r#"    <edge source="{}" target="{}"><data key="weight">{}</data></edge>"#, xml_escape(a), xml_escape(b), weight)?;
+        }
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</graphml>")
+    }
+}
+
+/// Month-by-month mentions of chosen terms
+#[derive(Default)]
+struct TermTrends {
+    // YYYY-MM, consecutive
+    months: Vec<String>,
+    // Dated notes per month
+    totals: Vec<usize>,
+    // Per month, notes mentioning each term
+    counts: Vec<Vec<usize>>,
+    undated: usize,
+}
+
+/// Size and vocabulary of one category, for side-by-side comparison
+struct CategoryProfile {
+    subcategories: usize,
+    concepts: usize,
+    notes: usize,
+    // Characters per note
+    average_length: f32,
+    terms: HashMap<String, usize>,
+}
+
+impl CategoryProfile {
+    /// Most frequent terms that `other` never uses
+    fn exclusive_terms(&self, other: &CategoryProfile, limit: usize) -> Vec<(&str, usize)> {
+        let mut terms: Vec<(&str, usize)> = self.terms.iter()
+            .filter(|(term, _)| !other.terms.contains_key(*term))
+            .map(|(term, count)| (term.as_str(), *count))
+            .collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        terms.truncate(limit);
+        terms
+    }
+    
+    /// Terms used by both, with counts in each, by the smaller count
+    fn shared_terms<'a>(&'a self, other: &CategoryProfile, limit: usize) -> Vec<(&'a str, usize, usize)> {
+        let mut terms: Vec<(&str, usize, usize)> = self.terms.iter()
+            .filter_map(|(term, count)| other.terms.get(term).map(|other_count| (term.as_str(), *count, *other_count)))
+            .collect();
+        terms.sort_by(|a, b| b.1.min(b.2).cmp(&a.1.min(a.2)).then(a.0.cmp(b.0)));
+        terms.truncate(limit);
+        terms
+    }
+    
+    /// Share of distinct terms the two categories have in common
+    fn vocabulary_overlap(&self, other: &CategoryProfile) -> f32 {
+        let shared = self.terms.keys().filter(|term| other.terms.contains_key(*term)).count();
+        let union = self.terms.len() + other.terms.len() - shared;
+        if union == 0 { 0.0 } else { shared as f32 / union as f32 }
+    }
+}
+
+/// A term shared across categories, with notes per category
+struct BridgeTerm {
+    term: String,
+    // Shannon entropy (bits) of the term's distribution over categories
+    entropy: f32,
+    categories: BTreeMap<String, usize>,
+}
+
+/// Concepts grouped by term similarity in the Stats cluster analysis
+struct ConceptCluster {
+    concepts: Vec<ConceptRef>,
+    top_terms: Vec<String>,
+    // Category -> number of member concepts
+    categories: BTreeMap<String, usize>,
+}
+
+impl ConceptCluster {
+    fn name(&self) -> String {
+        if self.top_terms.is_empty() {
+            "(unnamed)".to_string()
+        } else {
+            self.top_terms.join(" · ")
+        }
+    }
+    
+    /// The category most members belong to, if the cluster spans several
+    fn majority_category(&self) -> Option<&str> {
+        if self.categories.len() < 2 {
+            return None;
+        }
+        self.categories.iter().max_by_key(|(_, count)| **count).map(|(cat, _)| cat.as_str())
+    }
+}
+
+/// Why a concept shows up in another concept's local graph
+struct ConceptLink {
+    target: ConceptRef,
+    wiki: bool,
+    shared_tags: Vec<String>,
+    // Jaccard similarity of the two concepts' terms
+    similarity: f32,
+}
+
+impl ConceptLink {
+    fn strength(&self) -> f32 {
+        (if self.wiki { 1.0 } else { 0.0 }) + 0.5 * self.shared_tags.len() as f32 + self.similarity
+    }
+}
+
+struct MicroficheApp {
+    microfiche: Microfiche,
+    current_file: Option<String>,
+    // Changed since it was loaded or last saved, and when that was
+    dirty: bool,
+    last_saved: std::time::Instant,
+    // Exit or open asked for with unsaved changes, waiting on the prompt, or
+    // on the save picked there
+    confirm_discard: Option<PendingDiscard>,
+    after_save: Option<PendingDiscard>,
+    
+    // UI State
+    search_query: String,
+    search_results: Vec<(String, String, String, Note)>,
+    // Search the library files as well, and what they turned up
+    search_all_files: bool,
+    library: library::Library,
+    library_results: Vec<library::LibraryResult>,
+    search_filter: SearchFilter,
+    
+    // Text jotted in the scratchpad panel before it's ready to be filed
+    scratchpad: String,
+    show_scratchpad: bool,
+    
+    // Create form
+    new_category: String,
+    new_subcategory: String,
+    new_concept: String,
+    new_note: String,
+    new_fields: Vec<(String, String)>,
+    new_rating: Option<u8>,
+    // Creation time carried over when an existing note is edited
+    new_created: Option<String>,
+    // Display options carried over when an existing note is edited
+    new_display: NoteDisplay,
+    // Pin the new note as its concept's definition
+    new_definition: bool,
+    // Flags carried over when an existing note is edited
+    new_flags: BTreeSet<NoteFlag>,
+    // Comma-separated tags
+    new_tags: String,
+    // Comments on the note being edited, kept as they were
+    new_comments: Vec<NoteComment>,
+    // Empty signs the note with the user's name
+    new_author: String,
+    // Headings and labels of the note picked with Template, and its field names
+    template_skeleton: Option<(String, Vec<String>)>,
+    // Category, subcategory and concept the note was last pre-filled for, and the text
+    note_prefill: Option<((String, String, String), String)>,
+    // Notes like the one being written, looked up once typing pauses: the
+    // text as of its last change, when that was if not looked up yet, and
+    // the notes found with how alike they are
+    similar_text: String,
+    similar_pending: Option<std::time::Instant>,
+    similar_notes: Vec<(NoteTarget, f32)>,
+    
+    // Selected for viewing
+    selected_category: Option<String>,
+    selected_subcategory: Option<String>,
+    selected_concept: Option<String>,
+    
+    // Messages
+    status_message: String,
+    
+    // View mode
+    view_mode: ViewMode,
+    
+    // Theme
+    current_theme: Theme,
+    show_theme_selector: bool,
+    // The theme being edited, previewed while the editor is open: which
+    // custom theme it replaces (None for a new one) and the draft
+    theme_editor: Option<(Option<usize>, CustomTheme)>,
+
+    // User preferences, kept by eframe between runs
+    settings: Settings,
+    show_settings: bool,
+    // The Keyboard Shortcuts window, and the command whose next key press is
+    // being recorded as a shortcut
+    show_shortcuts: bool,
+    recording_shortcut: Option<Command>,
+    // First-run welcome, and the guided tour's current step with the tab
+    // buttons it points at
+    show_welcome: bool,
+    // Last title sent to the window
+    shown_title: String,
+    tour_step: Option<usize>,
+    tour_targets: [egui::Rect; TOUR_STEPS.len()],
+    vim: VimState,
+    
+    // Save or export running in the background
+    job: Option<BackgroundJob>,
+    
+    selective_open: Option<SelectiveOpen>,
+    show_secret_report: bool,
+    show_aging: bool,
+    // File > Validate results awaiting review
+    validation: Option<validate::Report>,
+    // Banner noting a file was converted from another text encoding
+    encoding_notice: Option<String>,
+    // The open file's modification time, checked for changes made elsewhere
+    file_watch: Option<file_watch::Watch>,
+    // Banner offering to reload or merge a file changed by another program
+    external_change: bool,
+    import_wizard: Option<ImportWizard>,
+    link_import: Option<LinkImport>,
+    
+    // Concepts folded in Browse, by path (remembered between runs)
+    collapsed_concepts: BTreeSet<String>,
+    // Notes listed per concept in Browse once "Show more" was clicked, by path
+    notes_shown: HashMap<String, usize>,
+    // Text typed into a concept's quick filter in Browse, by path
+    concept_filters: HashMap<String, String>,
+    // Browse lists only notes with all of these tags
+    browse_tags: BTreeSet<String>,
+    browse_order: NoteOrder,
+    
+    // Pagination (remembered between runs), and rows loaded in infinite scroll
+    cooccurrence_page: usize,
+    category_page: usize,
+    cooccurrence_shown: usize,
+    category_shown: usize,
+    
+    // Stats scope: category and optional subcategory, or the whole fiche
+    stats_scope: (Option<String>, Option<String>),
+    // Ranked list opened by clicking an Overview count
+    size_ranking: Option<SizeRanking>,
+    
+    // Whose settings, workspaces and Create draft are in use, every known
+    // profile, and a switch requested from the UI (done in update(), which
+    // has the storage)
+    profile: String,
+    profiles: Vec<String>,
+    switch_profile: Option<String>,
+    choosing_profile: bool,
+    new_profile_name: Option<String>,
+    
+    // Saved workspaces by name (remembered between runs), the one in use,
+    // and the name being typed for a new one
+    workspaces: BTreeMap<String, Workspace>,
+    active_workspace: Option<String>,
+    new_workspace_name: Option<String>,
+    
+    // Term trends: comma-separated terms, and whether to plot share of notes
+    trend_terms: String,
+    trend_relative: bool,
+    
+    // Category comparison in Stats
+    compare_categories: (String, String),
+    
+    // Bridge terms: minimum number of categories a term must span
+    bridge_min_categories: usize,
+    
+    // Concept cluster analysis: similarity threshold and last results
+    cluster_threshold: f32,
+    clusters: Option<Vec<ConceptCluster>>,
+    
+    // Numeric field table sorting
+    numeric_sort_field: Option<String>,
+    numeric_sort_desc: bool,
+    
+    // Category/subcategory overview being edited, with its draft text
+    editing_description: Option<(String, Option<String>)>,
+    description_draft: String,
+    
+    // Concept (or subcategory, with an empty concept) whose note template is being edited
+    editing_note_template: Option<(String, String, String)>,
+    note_template_draft: String,
+    
+    // File > File Info window, with the fields as edited so far
+    file_info_draft: Option<FileInfo>,
+    
+    // File > Restore from Backup window: the open file's snapshots
+    restore_backups: Option<Vec<BackupEntry>>,
+    
+    // Info window of a subcategory in Browse
+    subcategory_info: Option<SubcategoryInfo>,
+    
+    // Note whose comment thread has a reply being written, and the reply
+    comment_draft: Option<(NoteTarget, String)>,
+    
+    // Notes shown as their raw text instead of rendered Markdown, by note_key
+    raw_notes: HashSet<String>,
+    // Compact cards opened to the whole note, by note_key
+    expanded_cards: HashSet<String>,
+    // The card under the pointer, which shows its action buttons when
+    // they're hidden until hover; kept while one of its menus is open
+    hovered_card: Option<String>,
+    
+    // File > Export Pack window, and a pack picked with File > Import Pack
+    pack_export: Option<PackExport>,
+    pack_import: Option<PackImport>,
+    // File > Export RSS Feed window
+    feed_export: Option<FeedExport>,
+    calendar_export: Option<CalendarExport>,
+    // File > Export as Markdown window
+    markdown_export: Option<MarkdownExport>,
+    
+    // Concept being cross-listed and the chosen destination
+    cross_list_source: Option<ConceptRef>,
+    cross_list_target: (String, String),
+    
+    // Merge tool
+    merge_source: Option<ConceptRef>,
+    merge_target: (String, String, String),
+    merge_keep_alias: bool,
+    merge_undo: Option<MergeUndo>,
+    history: History,
+    
+    // Local graph window, centered on this concept
+    graph_center: Option<ConceptRef>,
+    
+    // Split tool: note being split and the text with split markers
+    split_note: Option<(String, String, String, String)>,
+    split_draft: String,
+    
+    // Append tool
+    append_note: Option<(String, String, String, String)>,
+    append_draft: String,
+    append_prepend: bool,
+    append_timestamp: bool,
+    
+    // Note awaiting confirmation to be purged
+    purge_note: Option<NoteTarget>,
+    // Note shown in the Details window
+    details_note: Option<NoteTarget>,
+    // Text to put on the clipboard at the end of the frame
+    to_clipboard: Option<String>,
+    qr_popup: Option<QrPopup>,
+    // Notes open in an external editor, by their text when it was last synced
+    external_edits: Vec<(NoteTarget, external_editor::Session)>,
+    // Webhook requests still going, and whether each is a Send test
+    webhook_deliveries: Vec<(webhook::Delivery, bool)>,
+    // Concept whose Publish window is open, and its upload while one runs
+    publish_concept: Option<ConceptRef>,
+    upload: Option<publish::Upload>,
+    
+    // Focus timer in the top bar, and the note asked for when a session ends
+    focus: Option<focus::Timer>,
+    focus_capture: Option<FocusCapture>,
+    
+    // Activity log
+    activity: ActivityLog,
+    session_note: String,
+    activity_filter: String,
+    // Note loaded into the Create form by Edit, so saving it logs an edit
+    editing_note: Option<String>,
+    
+    // Review queue: notes handled this session and how many were skipped
+    review_done: usize,
+    review_skip: usize,
+    
+    // Study session
+    study_skip: usize,
+    study_revealed: bool,
+}
+
+/// Everything needed to reverse the most recent concept merge
+struct MergeUndo {
+    source: ConceptRef,
+    source_concept: Concept,
+    target: ConceptRef,
+    target_notes: Vec<Note>,
+    meta: FicheMeta,
+}
+
+/// What unsaved changes would be dropped for
+enum PendingDiscard {
+    Exit,
+    Open(String),
+}
+
+/// Most undo steps kept
+const HISTORY_LIMIT: usize = 50;
+
+/// Category, subcategory and concept names
+type ConceptPath = (String, String, String);
+
+/// The names of a fiche's categories, subcategories and concepts, in order
+type Layout = Vec<(String, Vec<(String, Vec<String>)>)>;
+
+fn layout(categories: &IndexMap<String, Category>) -> Layout {
+    categories.iter()
+        .map(|(cat, category)| (cat.clone(), category.subcategories.iter()
+            .map(|sub| (sub.name.clone(), sub.concepts.iter().map(|con| con.name.clone()).collect()))
+            .collect()))
+        .collect()
+}
+
+/// The parts of the fiche a change touched, as they were on one side of it.
+/// Applying a change swaps those parts in and returns the change that swaps
+/// them back, so an undo step costs what it changed rather than the fiche.
+#[derive(Clone, Default)]
+struct Change {
+    // Notes by concept path; None where the concept didn't exist
+    concepts: Vec<(ConceptPath, Option<Vec<Note>>)>,
+    // Set when categories, subcategories or concepts were added, removed,
+    // renamed or moved
+    layout: Option<Layout>,
+    meta: Option<FicheMeta>,
+    attachments: Vec<(String, Option<Vec<u8>>)>,
+    unloaded: Option<BTreeMap<String, usize>>,
+}
+
+impl Change {
+    /// What differs between `before` and `after`: the change back to
+    /// `before` and the change forward to `after`. None when nothing does.
+    fn between(before: &Microfiche, after: &Microfiche) -> Option<(Change, Change)> {
+        fn by_path(fiche: &Microfiche) -> HashMap<ConceptPath, &Vec<Note>> {
+            fiche.iter_concepts()
+                .map(|(at, concept)| ((at.category, at.subcategory, at.concept), &concept.notes))
+                .collect()
+        }
+        let old = by_path(before);
+        let new = by_path(after);
+        
+        let mut back = Change::default();
+        let mut forward = Change::default();
+        for (path, notes) in &old {
+            match new.get(path) {
+                Some(changed) if changed == notes => {},
+                changed => {
+                    back.concepts.push((path.clone(), Some((*notes).clone())));
+                    forward.concepts.push((path.clone(), changed.map(|notes| (*notes).clone())));
+                },
+            }
+        }
+        for (path, notes) in &new {
+            if !old.contains_key(path) {
+                back.concepts.push((path.clone(), None));
+                forward.concepts.push((path.clone(), Some((*notes).clone())));
+            }
+        }
+        let (old_layout, new_layout) = (layout(&before.categories), layout(&after.categories));
+        if old_layout != new_layout {
+            back.layout = Some(old_layout);
+            forward.layout = Some(new_layout);
+        }
+        if before.meta != after.meta {
+            back.meta = Some(before.meta.clone());
+            forward.meta = Some(after.meta.clone());
+        }
+        let names: BTreeSet<&String> = before.attachments.keys().chain(after.attachments.keys()).collect();
+        for name in names {
+            let (old, new) = (before.attachments.get(name), after.attachments.get(name));
+            if old != new {
+                back.attachments.push((name.clone(), old.cloned()));
+                forward.attachments.push((name.clone(), new.cloned()));
+            }
+        }
+        if before.unloaded != after.unloaded {
+            back.unloaded = Some(before.unloaded.clone());
+            forward.unloaded = Some(after.unloaded.clone());
+        }
+        
+        let unchanged = back.concepts.is_empty() && back.layout.is_none() && back.meta.is_none()
+            && back.attachments.is_empty() && back.unloaded.is_none();
+        (!unchanged).then_some((back, forward))
+    }
+    
+    /// Put this side of the change into `fiche`, returning the other side
+    fn apply(self, fiche: &mut Microfiche) -> Change {
+        let mut other = Change {
+            layout: self.layout.as_ref().map(|_| layout(&fiche.categories)),
+            ..Change::default()
+        };
+        let shape = self.layout.unwrap_or_else(|| layout(&fiche.categories));
+        let mut pool: HashMap<ConceptPath, Vec<Note>> = HashMap::new();
+        for (cat, category) in std::mem::take(&mut fiche.categories) {
+            for sub in category.subcategories {
+                for con in sub.concepts {
+                    pool.insert((cat.clone(), sub.name.clone(), con.name), con.notes);
+                }
+            }
+        }
+        for (path, notes) in self.concepts {
+            let (cat, sub, con) = &path;
+            if let Some(index) = fiche.index.get_mut() {
+                for note in pool.get(&path).into_iter().flatten() {
+                    index.remove(cat, sub, con, &note.text);
+                }
+                for note in notes.iter().flatten() {
+                    index.insert(cat, sub, con, &note.text);
+                }
+            }
+            let replaced = match notes {
+                Some(notes) => pool.insert(path.clone(), notes),
+                None => pool.remove(&path),
+            };
+            other.concepts.push((path, replaced));
+        }
+        fiche.categories = shape.into_iter()
+            .map(|(cat, subs)| {
+                let subcategories = subs.into_iter()
+                    .map(|(sub, cons)| Subcategory {
+                        concepts: cons.into_iter()
+                            .map(|con| Concept {
+                                notes: pool.remove(&(cat.clone(), sub.clone(), con.clone())).unwrap_or_default(),
+                                name: con,
+                            })
+                            .collect(),
+                        name: sub,
+                    })
+                    .collect();
+                (cat.clone(), Category { name: cat, subcategories })
+            })
+            .collect();
+        
+        if let Some(meta) = self.meta {
+            other.meta = Some(std::mem::replace(&mut fiche.meta, meta));
+        }
+        for (name, bytes) in self.attachments {
+            let replaced = match bytes {
+                Some(bytes) => fiche.attachments.insert(name.clone(), bytes),
+                None => fiche.attachments.remove(&name),
+            };
+            other.attachments.push((name, replaced));
+        }
+        if let Some(unloaded) = self.unloaded {
+            other.unloaded = Some(std::mem::replace(&mut fiche.unloaded, unloaded));
+        }
+        other
+    }
+}
+
+/// Undo and redo for changes to the fiche. Every change is recorded in the
+/// activity log, so a frame in which the log grew ends one undo step, holding
+/// what it changed as it was before.
+#[derive(Default)]
+struct History {
+    // (change back, what the change was), newest last
+    undo: Vec<(Change, String)>,
+    redo: Vec<(Change, String)>,
+    // The fiche as of the last step, to find what the next one changed;
+    // None until the first frame after a load
+    current: Option<Microfiche>,
+    // Activity entries already accounted for
+    seen: usize,
+}
+
+impl History {
+    /// Called at the end of every frame; true when the fiche changed
+    fn track(&mut self, fiche: &Microfiche, activity: &ActivityLog) -> bool {
+        let mut changed = false;
+        if self.current.is_none() {
+            self.current = Some(fiche.clone());
+        } else if activity.entries.len() > self.seen
+            && let Some(current) = &mut self.current
+        {
+            changed = true;
+            if let Some((back, forward)) = Change::between(current, fiche) {
+                forward.apply(current);
+                let label = activity.entries.last()
+                    .map(|entry| format!("{} {}", entry.action.label(), entry.path).trim().to_string())
+                    .unwrap_or_default();
+                self.undo.push((back, label));
+                if self.undo.len() > HISTORY_LIMIT {
+                    self.undo.remove(0);
+                }
+                self.redo.clear();
+            }
+        }
+        self.seen = activity.entries.len();
+        changed
+    }
+    
+    /// Take `meta` into the state the next step starts from, for a change
+    /// that isn't an undo step of its own
+    fn keep_meta(&mut self, meta: &FicheMeta) {
+        if let Some(current) = &mut self.current {
+            current.meta = meta.clone();
+        }
+    }
+    
+    /// Return `fiche` to the state before the last change, returning what
+    /// the change was
+    fn undo(&mut self, fiche: &mut Microfiche) -> Option<String> {
+        let (change, label) = self.undo.pop()?;
+        if let Some(current) = &mut self.current {
+            change.clone().apply(current);
+        }
+        self.redo.push((change.apply(fiche), label.clone()));
+        Some(label)
+    }
+    
+    fn redo(&mut self, fiche: &mut Microfiche) -> Option<String> {
+        let (change, label) = self.redo.pop()?;
+        if let Some(current) = &mut self.current {
+            change.clone().apply(current);
+        }
+        self.undo.push((change.apply(fiche), label.clone()));
+        Some(label)
+    }
+}
+
+/// Preferences set in the Settings window
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    // Rows per page in the Stats co-occurrence and category panels
+    items_per_page: usize,
+    // Load more rows while scrolling instead of paging
+    infinite_scroll: bool,
+    // UI language code, e.g. "en" or "de"
+    language: String,
+    // Extra font file used for glyphs the built-in and system fonts lack
+    custom_font: Option<String>,
+    // Font file and size for code notes
+    monospace_font: Option<String>,
+    monospace_size: f32,
+    // Where the Stats counters, term tags and charts get their colors
+    stats_palette: StatsPalette,
+    // Primary, secondary, highlight and alert colors for StatsPalette::Custom
+    custom_stats_colors: [[u8; 3]; 4],
+    // Modal j/k, /, dd and : keys
+    vim_mode: bool,
+    // Mask note bodies for screen sharing, except in these categories
+    privacy_mode: bool,
+    privacy_exceptions: BTreeSet<String>,
+    // Which imported notes count as already present and are skipped
+    import_duplicates: DuplicateCheck,
+    // Transforms applied to imported notes
+    import_cleanup: NoteCleanup,
+    // Command for Edit in external editor; empty uses $VISUAL, $EDITOR or the system default
+    external_editor: String,
+    // What opens on startup and when switching to this profile
+    startup: StartupAction,
+    // The fixed file for StartupAction::FixedFile, and the fallback when
+    // there's no last file; empty uses DEFAULT_FILE
+    default_file: String,
+    // The file open when the profile was last saved, as an absolute path
+    last_file: Option<String>,
+    // Files opened or saved lately, newest first, and the favorites kept at
+    // the top of File > Open Recent; absolute paths
+    recent_files: Vec<String>,
+    pinned_files: Vec<String>,
+    // Themes made in the theme editor
+    custom_themes: Vec<CustomTheme>,
+    // The first-run welcome has been answered
+    welcomed: bool,
+    // Other fiche files searched by Search all files
+    library: Vec<String>,
+    // Window title and PNG icon replacing the built-in ones
+    window_title: String,
+    custom_icon: Option<String>,
+    // Save unsaved changes to the open file this often; 0 turns it off
+    autosave_minutes: u32,
+    // Name new notes and comments are signed with; empty uses the profile name
+    author_name: String,
+    // Draw note text as Markdown in Browse, Search, Review and Study
+    render_markdown: bool,
+    // URLs posted to when notes are created, edited or deleted
+    webhooks: Vec<webhook::Webhook>,
+    // Lengths of a focus session and the break after it
+    focus_minutes: u32,
+    break_minutes: u32,
+    // Keyboard shortcuts; a command can have several
+    shortcuts: Vec<(Command, egui::KeyboardShortcut)>,
+    // Snapshots kept in .fisha_backups, one taken on every save; 0 turns them off
+    snapshots_kept: usize,
+    // Note cards in Browse and Search: one line each until clicked, action
+    // buttons only while the pointer is over the card, and the gap between cards
+    compact_cards: bool,
+    card_actions_on_hover: bool,
+    card_spacing: f32,
+    // Columns of note cards in Browse; 0 fits as many as the width allows
+    browse_columns: usize,
+    // Where Publish uploads a concept, and the GitHub token or Pastebin key
+    publish_service: publish::Service,
+    publish_token: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            items_per_page: 10,
+            infinite_scroll: false,
+            language: i18n::DEFAULT_LANGUAGE.to_string(),
+            custom_font: None,
+            monospace_font: None,
+            monospace_size: 12.0,
+            stats_palette: StatsPalette::Theme,
+            custom_stats_colors: OKABE_ITO_STATS,
+            vim_mode: false,
+            privacy_mode: false,
+            privacy_exceptions: BTreeSet::new(),
+            import_duplicates: DuplicateCheck::SamePath,
+            import_cleanup: NoteCleanup::default(),
+            external_editor: String::new(),
+            startup: StartupAction::LastFile,
+            default_file: String::new(),
+            last_file: None,
+            recent_files: Vec::new(),
+            pinned_files: Vec::new(),
+            custom_themes: Vec::new(),
+            welcomed: false,
+            library: Vec::new(),
+            window_title: String::new(),
+            custom_icon: None,
+            autosave_minutes: 0,
+            author_name: String::new(),
+            render_markdown: true,
+            webhooks: Vec::new(),
+            focus_minutes: 25,
+            break_minutes: 5,
+            shortcuts: default_shortcuts(),
+            snapshots_kept: 10,
+            compact_cards: false,
+            card_actions_on_hover: false,
+            card_spacing: 5.0,
+            browse_columns: 1,
+            publish_service: publish::Service::Gist,
+            publish_token: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Whether notes in `category` are masked by privacy mode
+    fn masks(&self, category: &str) -> bool {
+        self.privacy_mode && !self.privacy_exceptions.contains(category)
+    }
+    
+    /// Primary, secondary, highlight and alert colors for the Stats tab
+    fn stats_colors(&self, visuals: &egui::Visuals) -> [egui::Color32; 4] {
+        match self.stats_palette {
+            StatsPalette::Theme => [
+                visuals.hyperlink_color,
+                visuals.selection.stroke.color,
+                visuals.warn_fg_color,
+                visuals.error_fg_color,
+            ],
+            StatsPalette::ColorBlindSafe => OKABE_ITO_STATS.map(|[r, g, b]| egui::Color32::from_rgb(r, g, b)),
+            StatsPalette::Custom => self.custom_stats_colors.map(|[r, g, b]| egui::Color32::from_rgb(r, g, b)),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum StatsPalette {
+    Theme,
+    ColorBlindSafe,
+    Custom,
+}
+
+impl StatsPalette {
+    const ALL: [StatsPalette; 3] = [StatsPalette::Theme, StatsPalette::ColorBlindSafe, StatsPalette::Custom];
+    
+    fn label(&self) -> String {
+        match self {
+            StatsPalette::Theme => tr("stats-palette-theme"),
+            StatsPalette::ColorBlindSafe => tr("stats-palette-color-blind-safe"),
+            StatsPalette::Custom => tr("stats-palette-custom"),
+        }
+    }
+}
+
+/// What the app opens on startup
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum StartupAction {
+    LastFile,
+    FixedFile,
+    Empty,
+}
+
+impl StartupAction {
+    const ALL: [StartupAction; 3] = [StartupAction::LastFile, StartupAction::FixedFile, StartupAction::Empty];
+    
+    fn label(&self) -> String {
+        match self {
+            StartupAction::LastFile => tr("startup-last-file"),
+            StartupAction::FixedFile => tr("startup-fixed-file"),
+            StartupAction::Empty => tr("startup-empty"),
+        }
+    }
+}
+
+/// Order notes are listed in, within each concept in Browse and across the
+/// results in Search. Notes without the date sort last
+#[derive(PartialEq, Clone, Copy, Default)]
+enum NoteOrder {
+    // As stored in the file
+    #[default]
+    Stored,
+    NewestFirst,
+    OldestFirst,
+    // By last edit, or creation for notes never edited
+    RecentlyEdited,
+}
+
+impl NoteOrder {
+    const ALL: [NoteOrder; 4] = [NoteOrder::Stored, NoteOrder::NewestFirst, NoteOrder::OldestFirst, NoteOrder::RecentlyEdited];
+    
+    fn label(&self) -> String {
+        match self {
+            NoteOrder::Stored => tr("order-stored"),
+            NoteOrder::NewestFirst => tr("order-newest"),
+            NoteOrder::OldestFirst => tr("order-oldest"),
+            NoteOrder::RecentlyEdited => tr("order-recently-edited"),
+        }
+    }
+    
+    /// Stable sort of `items`, each holding a note
+    fn sort<T>(self, items: &mut [T], note: impl Fn(&T) -> &Note) {
+        match self {
+            NoteOrder::Stored => {},
+            NoteOrder::NewestFirst => items.sort_by_cached_key(|item| std::cmp::Reverse(note(item).created.clone())),
+            NoteOrder::OldestFirst => items.sort_by_cached_key(|item| {
+                let created = note(item).created.clone();
+                (created.is_none(), created)
+            }),
+            NoteOrder::RecentlyEdited => items.sort_by_cached_key(|item| {
+                let note = note(item);
+                std::cmp::Reverse(note.modified.clone().or_else(|| note.created.clone()))
+            }),
+        }
+    }
+}
+
+/// How File > Import recognizes a note that is already in the fiche
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum DuplicateCheck {
+    Off,
+    // Same category, subcategory, concept and text
+    SamePath,
+    // Same text anywhere, ignoring case, accents and spacing
+    SameText,
+}
+
+impl DuplicateCheck {
+    const ALL: [DuplicateCheck; 3] = [DuplicateCheck::Off, DuplicateCheck::SamePath, DuplicateCheck::SameText];
+    
+    fn label(&self) -> String {
+        match self {
+            DuplicateCheck::Off => tr("duplicates-off"),
+            DuplicateCheck::SamePath => tr("duplicates-same-path"),
+            DuplicateCheck::SameText => tr("duplicates-same-text"),
+        }
+    }
+    
+    /// What two notes must share to be duplicates, or None when not checking
+    fn key(&self, cat: &str, sub: &str, con: &str, text: &str) -> Option<String> {
+        match self {
+            DuplicateCheck::Off => None,
+            DuplicateCheck::SamePath => Some(note_key(cat, sub, con, text)),
+            DuplicateCheck::SameText => {
+                let normalized = fold_text(text).split_whitespace().collect::<Vec<_>>().join(" ");
+                Some(note_key("", "", "", &normalized))
+            },
+        }
+    }
+}
+
+/// Tidying applied to notes on their way in through File > Import
+#[derive(PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct NoteCleanup {
+    // Trailing spaces on each line, and blank space around the note
+    trim: bool,
+    // Runs of blank lines become a single one
+    collapse_blank_lines: bool,
+    // Tags removed and common entities decoded
+    strip_html: bool,
+    // Curly quotes and apostrophes become straight ones
+    plain_quotes: bool,
+}
+
+impl NoteCleanup {
+    fn apply(&self, text: &str) -> String {
+        let mut text = if self.strip_html { strip_html(text) } else { text.to_string() };
+        if self.plain_quotes {
+            text = text.chars()
+                .map(|c| match c {
+                    '‘' | '’' | '‚' | '‛' | '′' => '\'',
+                    '“' | '”' | '„' | '‟' | '″' => '"',
+                    c => c,
+                })
+                .collect();
+        }
+        if self.trim {
+            text = text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string();
+        }
+        if self.collapse_blank_lines {
+            let mut lines: Vec<&str> = Vec::new();
+            for line in text.lines() {
+                let blank = line.trim().is_empty();
+                if !(blank && lines.last().is_some_and(|last| last.trim().is_empty())) {
+                    lines.push(if blank { "" } else { line });
+                }
+            }
+            text = lines.join("\n");
+        }
+        text
+    }
+}
+
+/// A note shown as a QR code, holding either its text or just its path
+struct QrPopup {
+    note: NoteTarget,
+    path_only: bool,
+    // None when the text is more than a QR code can hold
+    code: Option<qrcodegen::QrCode>,
+}
+
+impl QrPopup {
+    fn new(note: NoteTarget, path_only: bool) -> Self {
+        let (cat, sub, con, text) = &note;
+        let content = if path_only { note_path(cat, sub, con) } else { text.clone() };
+        // Medium error correction when it fits, otherwise trade it for capacity
+        let code = qrcodegen::QrCode::encode_text(&content, qrcodegen::QrCodeEcc::Medium)
+            .or_else(|_| qrcodegen::QrCode::encode_text(&content, qrcodegen::QrCodeEcc::Low))
+            .ok();
+        QrPopup { note, path_only, code }
+    }
+}
+
+/// File > Import after the file is read, while its options are chosen
+struct ImportWizard {
+    path: String,
+    fiche: Microfiche,
+    // Options the preview count was worked out for, and the count
+    preview: Option<(NoteCleanup, usize)>,
+    // How the files were placed, when importing a folder of Markdown files
+    vault: Option<vault::Mapping>,
+}
+
+/// File > Import Links: links pasted from the clipboard, filed as notes
+/// under one concept
+struct LinkImport {
+    text: String,
+    category: String,
+    subcategory: String,
+    concept: String,
+    fetch_titles: bool,
+    // The clipboard was pasted in when the window opened
+    pasted: bool,
+    // Links whose titles are being fetched
+    fetch: Option<(Vec<String>, link_import::TitleFetch)>,
+}
+
+/// What goes into a pack being exported and the File Info it carries
+struct PackExport {
+    selection: pack::Selection,
+    info: FileInfo,
+    attachments: bool,
+}
+
+/// Choices in the File > Export RSS Feed window
+struct FeedExport {
+    // Only this category; None for the whole fiche
+    category: Option<String>,
+    // A feed file for each category, written into one folder
+    per_category: bool,
+    limit: usize,
+    link: String,
+}
+
+/// The "what did you learn?" note asked for at the end of a focus session;
+/// the concept is kept for the next one
+#[derive(Default)]
+struct FocusCapture {
+    // The window is showing
+    asking: bool,
+    category: String,
+    subcategory: String,
+    concept: String,
+    text: String,
+}
+
+/// Choices in the File > Export Calendar window
+struct CalendarExport {
+    // Only this category; None for the whole fiche
+    category: Option<String>,
+    // Custom fields holding dates, and which of them become events
+    date_fields: BTreeSet<String>,
+    fields: BTreeSet<String>,
+}
+
+/// Choices in the File > Export as Markdown window
+struct MarkdownExport {
+    // Only this category, and within it only this subcategory; None for all
+    category: Option<String>,
+    subcategory: Option<String>,
+    // A file for each category, written into one folder
+    per_category: bool,
+}
+
+/// A pack being imported, with where its categories go and how concepts
+/// already in the fiche are handled
+struct PackImport {
+    path: String,
+    pack: Microfiche,
+    namespace: String,
+    conflict: pack::Conflict,
+    flag_for_review: bool,
+}
+
+/// Progress of a background save or export, shared with the window showing it
+#[derive(Default)]
+struct JobProgress {
+    done: AtomicUsize,
+    // 0 until the job knows how much there is to do
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl JobProgress {
+    fn start(&self, total: usize) {
+        self.done.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+    }
+    
+    /// Count finished work, failing once the user has cancelled
+    fn advance(&self, amount: usize) -> std::io::Result<()> {
+        if self.is_cancelled() {
+            return Err(std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled"));
+        }
+        self.done.fetch_add(amount, Ordering::Relaxed);
+        Ok(())
+    }
+    
+    fn fraction(&self) -> Option<f32> {
+        let total = self.total.load(Ordering::Relaxed);
+        (total > 0).then(|| self.done.load(Ordering::Relaxed) as f32 / total as f32)
+    }
+    
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+enum JobKind {
+    // The fiche as written, which the file holds once the save is done
+    Save { path: String, fiche: Arc<Microfiche> },
+    Export,
+}
+
+struct BackgroundJob {
+    kind: JobKind,
+    label: String,
+    progress: Arc<JobProgress>,
+    handle: std::thread::JoinHandle<Result<String, String>>,
+}
+
+/// Write to a `~`-prefixed file beside `path` and move it into place once
+/// complete and flushed to disk, so a failed or cancelled write, or a crash,
+/// leaves the existing file alone
+fn write_replacing(path: &str, write: impl FnOnce(&str) -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+    let target = Path::new(path);
+    let file_name = target.file_name().ok_or_else(|| format!("{} is not a file path", path))?;
+    let partial = target.with_file_name(format!("~{}", file_name.to_string_lossy()));
+    let partial = partial.to_string_lossy();
+    if let Err(e) = write(&partial).and_then(|()| Ok(std::fs::OpenOptions::new().write(true).open(&*partial)?.sync_all()?)) {
+        let _ = std::fs::remove_file(&*partial);
+        return Err(e);
+    }
+    std::fs::rename(&*partial, target)?;
+    // Make the rename itself durable; directories can't be opened on Windows
+    if let Some(dir) = target.parent().filter(|dir| !dir.as_os_str().is_empty())
+        && let Ok(dir) = std::fs::File::open(dir)
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Copy the file at `path`, if there is one, to `path.bak` before it's saved
+/// over, so the previous version can be got back
+fn keep_backup(path: &str) -> std::io::Result<()> {
+    if Path::new(path).is_file() {
+        std::fs::copy(path, format!("{}.bak", path))?;
+    }
+    Ok(())
+}
+
+/// Remove `path.bak`, if there is one
+fn drop_backup(path: &str) -> std::io::Result<()> {
+    match std::fs::remove_file(format!("{}.bak", path)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// What was clicked in the Library view
+enum LibraryAction {
+    Open(String),
+    Merge(String),
+    Export(String),
+    Remove(usize),
+}
+
+/// File > Open Selectively: the picked file, and each category with its note
+/// count and whether to load it
+struct SelectiveOpen {
+    path: String,
+    categories: Vec<(String, usize, bool)>,
+}
+
+/// A note addressed by category, subcategory, concept and text
+type NoteTarget = (String, String, String, String);
+
+/// Subcategory > Info: a summary taken when the window opens
+struct SubcategoryInfo {
+    category: String,
+    subcategory: String,
+    concepts: usize,
+    notes: usize,
+    // Most frequent words with their counts
+    top_terms: Vec<(String, usize)>,
+    // Excerpt and creation date of the newest and oldest dated notes
+    newest: Option<(String, String)>,
+    oldest: Option<(String, String)>,
+    // Activity log entries per day, oldest day first
+    activity: Vec<usize>,
+}
+
+/// A snapshot with its category and note counts, or why it can't be read
+type BackupEntry = (snapshots::Snapshot, Result<(usize, usize), String>);
+
+/// Cursor and pending keys for Vim keybindings
+#[derive(Default)]
+struct VimState {
+    // Index of the highlighted note in the Browse or Search list
+    cursor: usize,
+    // Notes in the list drawn last frame, and the one under the cursor
+    list_len: usize,
+    target: Option<NoteTarget>,
+    // Scroll the cursor into view on the next draw
+    scroll: bool,
+    // First "d" of "dd" was typed
+    pending_delete: bool,
+    confirm_delete: Option<NoteTarget>,
+    palette_open: bool,
+    palette_query: String,
+    focus_search: bool,
+}
+
+impl VimState {
+    /// Outline the note card under the cursor and remember it for "dd"
+    fn mark(&mut self, ui: &egui::Ui, rect: egui::Rect, note: NoteTarget) {
+        ui.painter().rect_stroke(rect, 4.0, ui.visuals().selection.stroke, egui::StrokeKind::Outside);
+        if std::mem::take(&mut self.scroll) {
+            ui.scroll_to_rect(rect, None);
+        }
+        self.target = Some(note);
+    }
+}
+
+/// Actions offered by the ":" command palette, and bound to keyboard shortcuts
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Command {
+    View(ViewMode),
+    Open,
+    Import,
+    Save,
+    SaveAs,
+    ExportTermNetwork,
+    Undo,
+    Redo,
+    Theme,
+    Settings,
+    // Switch to Search with the query box focused
+    Find,
+    // Close the theme window, or clear the search
+    Dismiss,
+    Shortcuts,
+}
+
+impl Command {
+    const ALL: [Command; 20] = [
+        Command::View(ViewMode::Browse),
+        Command::View(ViewMode::Search),
+        Command::View(ViewMode::Create),
+        Command::View(ViewMode::Stats),
+        Command::View(ViewMode::Study),
+        Command::View(ViewMode::Activity),
+        Command::View(ViewMode::Review),
+        Command::View(ViewMode::Library),
+        Command::Open,
+        Command::Import,
+        Command::Save,
+        Command::SaveAs,
+        Command::ExportTermNetwork,
+        Command::Undo,
+        Command::Redo,
+        Command::Theme,
+        Command::Settings,
+        Command::Find,
+        Command::Dismiss,
+        Command::Shortcuts,
+    ];
+    
+    fn label(&self) -> String {
+        match self {
+            Command::View(ViewMode::Browse) => tr("browse"),
+            Command::View(ViewMode::Search) => tr("search"),
+            Command::View(ViewMode::Create) => tr("create"),
+            Command::View(ViewMode::Stats) => tr("stats"),
+            Command::View(ViewMode::Study) => tr("study"),
+            Command::View(ViewMode::Activity) => tr("activity"),
+            Command::View(ViewMode::Review) => tr("review"),
+            Command::View(ViewMode::Library) => tr("library"),
+            Command::Open => tr("open"),
+            Command::Import => tr("import"),
+            Command::Save => tr("save"),
+            Command::SaveAs => tr("save-as"),
+            Command::ExportTermNetwork => tr("export-term-network"),
+            Command::Undo => tr("undo"),
+            Command::Redo => tr("redo"),
+            Command::Theme => tr("theme"),
+            Command::Settings => tr("settings"),
+            Command::Find => tr("command-find"),
+            Command::Dismiss => tr("command-dismiss"),
+            Command::Shortcuts => tr("keyboard-shortcuts"),
+        }
+    }
+}
+
+/// The keymap Settings starts with; Ctrl is Cmd on macOS
+fn default_shortcuts() -> Vec<(Command, egui::KeyboardShortcut)> {
+    use egui::{Key, KeyboardShortcut, Modifiers};
+    
+    vec![
+        (Command::Save, KeyboardShortcut::new(Modifiers::COMMAND, Key::S)),
+        (Command::Open, KeyboardShortcut::new(Modifiers::COMMAND, Key::O)),
+        (Command::Find, KeyboardShortcut::new(Modifiers::COMMAND, Key::F)),
+        (Command::View(ViewMode::Create), KeyboardShortcut::new(Modifiers::COMMAND, Key::N)),
+        (Command::Undo, KeyboardShortcut::new(Modifiers::COMMAND, Key::Z)),
+        (Command::Redo, KeyboardShortcut::new(Modifiers::COMMAND, Key::Y)),
+        (Command::Redo, KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z)),
+        (Command::Dismiss, KeyboardShortcut::new(Modifiers::NONE, Key::Escape)),
+        (Command::Shortcuts, KeyboardShortcut::new(Modifiers::NONE, Key::F1)),
+    ]
+}
+
+/// Blue, orange, sky blue and vermillion from the Okabe-Ito palette, which
+/// stay apart for deuteranopia and protanopia (no red/green pairs)
+const OKABE_ITO_STATS: [[u8; 3]; 4] = [[0, 114, 178], [230, 159, 0], [86, 180, 233], [213, 94, 0]];
+
+/// Opened on startup when no file is given on the command line
+const DEFAULT_FILE: &str = "microfiche.csv";
+
+const SETTINGS_KEY: &str = "settings";
+/// Window title unless Settings gives another; also names the app's storage
+const APP_TITLE: &str = "Fisha GUI";
+const PROFILES_KEY: &str = "profiles";
+const DRAFT_KEY: &str = "create_draft";
+const DEFAULT_PROFILE: &str = "Default";
+const STATS_PAGES_KEY: &str = "stats_pages";
+const COLLAPSED_CONCEPTS_KEY: &str = "collapsed_concepts";
+const WORKSPACES_KEY: &str = "workspaces";
+const SCRATCHPAD_KEY: &str = "scratchpad";
+/// The theme and tab in use, restored on the next start
+const VIEW_KEY: &str = "view";
+
+/// Files listed in File > Open Recent besides the pinned ones
+const RECENT_FILES_KEPT: usize = 10;
+
+/// Height of the concept name pinned to the top of Browse while scrolling
+const STICKY_HEADER_HEIGHT: f32 = 24.0;
+
+/// The tabs the guided tour points out, with what it says about each
+const TOUR_STEPS: [(ViewMode, &str); 4] = [
+    (ViewMode::Browse, "tour-browse"),
+    (ViewMode::Search, "tour-search"),
+    (ViewMode::Create, "tour-create"),
+    (ViewMode::Stats, "tour-stats"),
+];
+
+/// Stale notes listed at once in the Aging window
+const AGING_LISTED: usize = 100;
+const AGING_SNOOZE_DAYS: i64 = 30;
+
+/// Narrowest a Browse column gets when the column count fits the width
+const BROWSE_COLUMN_WIDTH: f32 = 380.0;
+
+/// Notes a Browse concept needs before it gets a quick filter
+const CONCEPT_FILTER_MIN_NOTES: usize = 10;
+
+/// Words listed in a subcategory's Info window
+const SUBCATEGORY_INFO_TERMS: usize = 10;
+
+/// Days of activity in a subcategory's Info sparkline
+const SUBCATEGORY_INFO_DAYS: usize = 30;
+
+/// Notes a Browse concept lists at first, and how many more "Show more" adds
+const NOTES_SHOWN_STEP: usize = 50;
+
+/// Average silent reading speed, for a note's reading time
+const READING_WORDS_PER_MINUTE: usize = 200;
+
+/// How long typing in the Create form pauses before similar notes are looked
+/// up, how alike they must be, and how many are listed
+const SIMILAR_DELAY: std::time::Duration = std::time::Duration::from_millis(400);
+const SIMILAR_THRESHOLD: f32 = 0.3;
+const SIMILAR_SHOWN: usize = 5;
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Theme {
+    Monokai,
+    TomorrowBlueHour,
+    DarkPlus,
+    HighContrast,
+    ColorBlindSafe,
+    SolarizedLight,
+    GithubLight,
+    // Made in the theme editor, by its place in Settings' custom themes
+    Custom(usize),
+}
+
+impl Theme {
+    const PRESETS: [Theme; 7] = [
+        Theme::Monokai,
+        Theme::TomorrowBlueHour,
+        Theme::DarkPlus,
+        Theme::HighContrast,
+        Theme::ColorBlindSafe,
+        Theme::SolarizedLight,
+        Theme::GithubLight,
+    ];
+    
+    fn label(&self, custom: &[CustomTheme]) -> String {
+        match self {
+            Theme::Monokai => "Monokai".to_string(),
+            Theme::TomorrowBlueHour => "Tomorrow (Blue Hour)".to_string(),
+            Theme::DarkPlus => "Dark+".to_string(),
+            Theme::HighContrast => tr("theme-high-contrast"),
+            Theme::ColorBlindSafe => tr("theme-color-blind-safe"),
+            Theme::SolarizedLight => "Solarized Light".to_string(),
+            Theme::GithubLight => "GitHub Light".to_string(),
+            Theme::Custom(index) => custom.get(*index).map_or_else(|| "Monokai".to_string(), |theme| theme.name.clone()),
+        }
+    }
+    
+    fn is_light(&self) -> bool {
+        matches!(self, Theme::SolarizedLight | Theme::GithubLight)
+    }
+    
+    fn apply(&self, ctx: &egui::Context, custom: &[CustomTheme]) {
+        ctx.set_visuals(self.visuals(custom));
+    }
+    
+    fn visuals(&self, custom: &[CustomTheme]) -> egui::Visuals {
+        let mut visuals = if self.is_light() { egui::Visuals::light() } else { egui::Visuals::dark() };
+        
+        match self {
+            Theme::Monokai => {
+                // Monokai - warm dark theme with purple/pink accents
+                visuals.window_fill = egui::Color32::from_rgb(39, 40, 34);
+                visuals.panel_fill = egui::Color32::from_rgb(39, 40, 34);
+                visuals.faint_bg_color = egui::Color32::from_rgb(49, 50, 44);
+                
+                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(49, 50, 44);
+                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(60, 61, 54);
+                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(75, 76, 68);
+                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(90, 91, 82);
+                
+                visuals.selection.bg_fill = egui::Color32::from_rgb(73, 72, 62);
+                visuals.selection.stroke.color = egui::Color32::from_rgb(249, 38, 114);
+                
+                visuals.override_text_color = Some(egui::Color32::from_rgb(248, 248, 242));
+                visuals.hyperlink_color = egui::Color32::from_rgb(102, 217, 239);
+                visuals.warn_fg_color = egui::Color32::from_rgb(230, 219, 116);
+                visuals.error_fg_color = egui::Color32::from_rgb(249, 38, 114);
+            },
+            Theme::TomorrowBlueHour => {
+                // Tomorrow Night Blue - cool blue theme
+                visuals.window_fill = egui::Color32::from_rgb(0, 29, 51);
+                visuals.panel_fill = egui::Color32::from_rgb(0, 29, 51);
+                visuals.faint_bg_color = egui::Color32::from_rgb(0, 43, 71);
+                
+                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(0, 43, 71);
+                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(0, 56, 92);
+                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(7, 70, 115);
+                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(17, 85, 135);
+                
+                visuals.selection.bg_fill = egui::Color32::from_rgb(0, 72, 119);
+                visuals.selection.stroke.color = egui::Color32::from_rgb(125, 174, 198);
+                
+                visuals.override_text_color = Some(egui::Color32::from_rgb(231, 232, 235));
+                visuals.hyperlink_color = egui::Color32::from_rgb(125, 174, 198);
+                visuals.warn_fg_color = egui::Color32::from_rgb(255, 204, 102);
+                visuals.error_fg_color = egui::Color32::from_rgb(255, 102, 102);
+            },
+            Theme::DarkPlus => {
+                // Dark+ - VS Code default dark theme
+                visuals.window_fill = egui::Color32::from_rgb(30, 30, 30);
+                visuals.panel_fill = egui::Color32::from_rgb(30, 30, 30);
+                visuals.faint_bg_color = egui::Color32::from_rgb(37, 37, 38);
+                
+                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(45, 45, 45);
+                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(60, 60, 60);
+                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(75, 75, 75);
+                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(90, 90, 90);
+                
+                visuals.selection.bg_fill = egui::Color32::from_rgb(38, 79, 120);
+                visuals.selection.stroke.color = egui::Color32::from_rgb(14, 99, 156);
+                
+                visuals.override_text_color = Some(egui::Color32::from_rgb(212, 212, 212));
+                visuals.hyperlink_color = egui::Color32::from_rgb(78, 162, 230);
+                visuals.warn_fg_color = egui::Color32::from_rgb(206, 145, 120);
+                visuals.error_fg_color = egui::Color32::from_rgb(244, 71, 71);
+            },
+            Theme::HighContrast => {
+                // High Contrast - pure black and white with yellow focus, outlined widgets
+                let white = egui::Color32::WHITE;
+                let yellow = egui::Color32::from_rgb(255, 230, 0);
+                visuals.window_fill = egui::Color32::BLACK;
+                visuals.panel_fill = egui::Color32::BLACK;
+                visuals.faint_bg_color = egui::Color32::from_rgb(24, 24, 24);
+                visuals.extreme_bg_color = egui::Color32::BLACK;
+                visuals.window_stroke = egui::Stroke::new(2.0, white);
+                
+                visuals.widgets.noninteractive.bg_fill = egui::Color32::BLACK;
+                visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(1.0, white);
+                visuals.widgets.inactive.bg_fill = egui::Color32::BLACK;
+                visuals.widgets.inactive.weak_bg_fill = egui::Color32::BLACK;
+                visuals.widgets.inactive.bg_stroke = egui::Stroke::new(1.0, white);
+                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(40, 40, 0);
+                visuals.widgets.hovered.weak_bg_fill = egui::Color32::from_rgb(40, 40, 0);
+                visuals.widgets.hovered.bg_stroke = egui::Stroke::new(2.0, yellow);
+                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(70, 70, 0);
+                visuals.widgets.active.weak_bg_fill = egui::Color32::from_rgb(70, 70, 0);
+                visuals.widgets.active.bg_stroke = egui::Stroke::new(2.0, yellow);
+                
+                visuals.selection.bg_fill = egui::Color32::from_rgb(0, 70, 160);
+                visuals.selection.stroke = egui::Stroke::new(2.0, yellow);
+                
+                visuals.override_text_color = Some(white);
+                visuals.hyperlink_color = yellow;
+                visuals.warn_fg_color = egui::Color32::from_rgb(255, 190, 0);
+                visuals.error_fg_color = egui::Color32::from_rgb(255, 100, 100);
+            },
+            Theme::ColorBlindSafe => {
+                // Color-blind Safe - Okabe-Ito accents, readable with deuteranopia/protanopia
+                visuals.window_fill = egui::Color32::from_rgb(28, 30, 36);
+                visuals.panel_fill = egui::Color32::from_rgb(28, 30, 36);
+                visuals.faint_bg_color = egui::Color32::from_rgb(36, 39, 46);
+                
+                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(40, 43, 51);
+                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(52, 56, 66);
+                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(66, 71, 84);
+                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(80, 86, 102);
+                
+                visuals.selection.bg_fill = egui::Color32::from_rgb(0, 84, 132);
+                visuals.selection.stroke.color = egui::Color32::from_rgb(230, 159, 0);
+                
+                visuals.override_text_color = Some(egui::Color32::from_rgb(230, 230, 230));
+                visuals.hyperlink_color = egui::Color32::from_rgb(86, 180, 233);
+                visuals.warn_fg_color = egui::Color32::from_rgb(240, 228, 66);
+                visuals.error_fg_color = egui::Color32::from_rgb(213, 94, 0);
+            },
+            Theme::SolarizedLight => {
+                // Solarized Light - warm cream background with muted blue-gray text
+                visuals.window_fill = egui::Color32::from_rgb(253, 246, 227);
+                visuals.panel_fill = egui::Color32::from_rgb(253, 246, 227);
+                visuals.faint_bg_color = egui::Color32::from_rgb(238, 232, 213);
+                visuals.extreme_bg_color = egui::Color32::from_rgb(255, 251, 240);
+                
+                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(238, 232, 213);
+                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(230, 223, 200);
+                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(220, 212, 188);
+                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(205, 197, 172);
+                
+                visuals.selection.bg_fill = egui::Color32::from_rgb(190, 215, 230);
+                visuals.selection.stroke.color = egui::Color32::from_rgb(38, 139, 210);
+                
+                visuals.override_text_color = Some(egui::Color32::from_rgb(88, 110, 117));
+                visuals.hyperlink_color = egui::Color32::from_rgb(38, 139, 210);
+                visuals.warn_fg_color = egui::Color32::from_rgb(181, 137, 0);
+                visuals.error_fg_color = egui::Color32::from_rgb(220, 50, 47);
+            },
+            Theme::GithubLight => {
+                // GitHub Light - white with cool gray widgets and blue accents
+                visuals.window_fill = egui::Color32::WHITE;
+                visuals.panel_fill = egui::Color32::WHITE;
+                visuals.faint_bg_color = egui::Color32::from_rgb(246, 248, 250);
+                visuals.extreme_bg_color = egui::Color32::WHITE;
+                
+                visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(246, 248, 250);
+                visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(234, 238, 242);
+                visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(208, 215, 222);
+                visuals.widgets.active.bg_fill = egui::Color32::from_rgb(175, 184, 193);
+                
+                visuals.selection.bg_fill = egui::Color32::from_rgb(221, 244, 255);
+                visuals.selection.stroke.color = egui::Color32::from_rgb(9, 105, 218);
+                
+                visuals.override_text_color = Some(egui::Color32::from_rgb(31, 35, 40));
+                visuals.hyperlink_color = egui::Color32::from_rgb(9, 105, 218);
+                visuals.warn_fg_color = egui::Color32::from_rgb(154, 103, 0);
+                visuals.error_fg_color = egui::Color32::from_rgb(209, 36, 47);
+            },
+            // A theme since deleted falls back to the default
+            Theme::Custom(index) => return custom.get(*index).map_or_else(|| Theme::Monokai.visuals(custom), CustomTheme::visuals),
+        }
+        
+        visuals
+    }
+}
+
+/// A theme made in the theme editor: a light or dark base with its key
+/// colors replaced
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct CustomTheme {
+    name: String,
+    dark: bool,
+    // Window and panel background
+    background: [u8; 3],
+    // Striped rows, cards and labels' backgrounds
+    faint_background: [u8; 3],
+    // Buttons and other widgets at rest, under the pointer, and pressed
+    widget: [u8; 3],
+    hovered: [u8; 3],
+    pressed: [u8; 3],
+    selection: [u8; 3],
+    // Focus outlines and the selection's edge
+    accent: [u8; 3],
+    text: [u8; 3],
+    link: [u8; 3],
+    warning: [u8; 3],
+    error: [u8; 3],
+}
+
+impl CustomTheme {
+    /// A theme with the colors of `visuals`, to start editing from
+    fn from_visuals(name: String, visuals: &egui::Visuals) -> Self {
+        let rgb = |color: egui::Color32| [color.r(), color.g(), color.b()];
+        CustomTheme {
+            name,
+            dark: visuals.dark_mode,
+            background: rgb(visuals.panel_fill),
+            faint_background: rgb(visuals.faint_bg_color),
+            widget: rgb(visuals.widgets.inactive.bg_fill),
+            hovered: rgb(visuals.widgets.hovered.bg_fill),
+            pressed: rgb(visuals.widgets.active.bg_fill),
+            selection: rgb(visuals.selection.bg_fill),
+            accent: rgb(visuals.selection.stroke.color),
+            text: rgb(visuals.text_color()),
+            link: rgb(visuals.hyperlink_color),
+            warning: rgb(visuals.warn_fg_color),
+            error: rgb(visuals.error_fg_color),
+        }
+    }
+    
+    fn visuals(&self) -> egui::Visuals {
+        let color = |[r, g, b]: [u8; 3]| egui::Color32::from_rgb(r, g, b);
+        let mut visuals = if self.dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+        visuals.window_fill = color(self.background);
+        visuals.panel_fill = color(self.background);
+        visuals.faint_bg_color = color(self.faint_background);
+        visuals.widgets.noninteractive.bg_fill = color(self.faint_background);
+        for (widget, fill) in [
+            (&mut visuals.widgets.inactive, self.widget),
+            (&mut visuals.widgets.hovered, self.hovered),
+            (&mut visuals.widgets.active, self.pressed),
+        ] {
+            widget.bg_fill = color(fill);
+            widget.weak_bg_fill = color(fill);
+        }
+        visuals.selection.bg_fill = color(self.selection);
+        visuals.selection.stroke.color = color(self.accent);
+        visuals.override_text_color = Some(color(self.text));
+        visuals.hyperlink_color = color(self.link);
+        visuals.warn_fg_color = color(self.warning);
+        visuals.error_fg_color = color(self.error);
+        visuals
+    }
+    
+    /// The colors the editor offers, with their labels
+    fn colors_mut(&mut self) -> [(String, &mut [u8; 3]); 11] {
+        [
+            (tr("theme-background"), &mut self.background),
+            (tr("theme-faint-background"), &mut self.faint_background),
+            (tr("theme-widget"), &mut self.widget),
+            (tr("theme-hovered"), &mut self.hovered),
+            (tr("theme-pressed"), &mut self.pressed),
+            (tr("theme-selected"), &mut self.selection),
+            (tr("theme-accent"), &mut self.accent),
+            (tr("theme-text"), &mut self.text),
+            (tr("theme-link"), &mut self.link),
+            (tr("theme-warning"), &mut self.warning),
+            (tr("theme-error"), &mut self.error),
+        ]
+    }
+}
+
+/// Less frequent note actions, offered from the "⋯" menu on note cards
+#[derive(PartialEq, Clone, Copy)]
+enum NoteTool {
+    Split,
+    Append,
+    ToggleReview,
+    ToggleDefinition,
+    ToggleMonospace,
+    ToggleWrap,
+    ToggleIndent,
+    CopyAsQuote,
+    ShareByEmail,
+    ShowQr,
+    Details,
+    EditExternally,
+    Comment,
+    Purge,
+    // Picked from the Highlight submenu rather than listed in ALL
+    Highlight(Option<Highlight>),
+}
+
+impl NoteTool {
+    const ALL: [NoteTool; 14] = [
+        NoteTool::Split,
+        NoteTool::Append,
+        NoteTool::ToggleReview,
+        NoteTool::ToggleDefinition,
+        NoteTool::ToggleMonospace,
+        NoteTool::ToggleWrap,
+        NoteTool::ToggleIndent,
+        NoteTool::CopyAsQuote,
+        NoteTool::ShareByEmail,
+        NoteTool::ShowQr,
+        NoteTool::Details,
+        NoteTool::EditExternally,
+        NoteTool::Comment,
+        NoteTool::Purge,
+    ];
+    
+    fn label(&self, note: &Note) -> String {
+        match self {
+            NoteTool::Split => tr("note-tool-split"),
+            NoteTool::Append => tr("note-tool-append"),
+            NoteTool::ToggleReview if note.needs_review => tr("note-tool-mark-reviewed"),
+            NoteTool::ToggleReview => tr("note-tool-flag-for-review"),
+            NoteTool::ToggleDefinition if note.definition => tr("note-tool-unpin-definition"),
+            NoteTool::ToggleDefinition => tr("note-tool-pin-definition"),
+            NoteTool::ToggleMonospace if note.display.monospace => tr("note-tool-proportional"),
+            NoteTool::ToggleMonospace => tr("note-tool-monospace"),
+            NoteTool::ToggleWrap if note.display.no_wrap => tr("note-tool-wrap"),
+            NoteTool::ToggleWrap => tr("note-tool-no-wrap"),
+            NoteTool::ToggleIndent if note.display.preserve_indent => tr("note-tool-no-indent"),
+            NoteTool::ToggleIndent => tr("note-tool-indent"),
+            NoteTool::CopyAsQuote => tr("note-tool-copy-as-quote"),
+            NoteTool::ShareByEmail => tr("note-tool-share-by-email"),
+            NoteTool::ShowQr => tr("note-tool-show-qr"),
+            NoteTool::Details => tr("note-tool-details"),
+            NoteTool::EditExternally => tr("note-tool-edit-externally"),
+            NoteTool::Comment => tr("note-tool-comment"),
+            NoteTool::Purge => tr("note-tool-purge"),
+            NoteTool::Highlight(_) => tr("note-tool-highlight"),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum ViewMode {
+    Browse,
+    Search,
+    Create,
+    Stats,
+    Activity,
+    Review,
+    Study,
+    Library,
+}
+
+/// A named context to come back to: the open file, where Browse was, the
+/// current tab, and the search and Stats filters in effect
+#[derive(Clone, Serialize, Deserialize)]
+struct Workspace {
+    file: Option<String>,
+    view_mode: ViewMode,
+    selected_category: Option<String>,
+    selected_subcategory: Option<String>,
+    search_query: String,
+    min_rating: u8,
+    search_category: Option<String>,
+    #[serde(default)]
+    search_flag: Option<NoteFlag>,
+    #[serde(default)]
+    search_tags: BTreeSet<String>,
+    #[serde(default)]
+    search_author: Option<String>,
+    stats_scope: (Option<String>, Option<String>),
+}
+
+impl Default for MicroficheApp {
+    fn default() -> Self {
+        let mut app = MicroficheApp {
+            microfiche: Microfiche::new(),
+            current_file: Some(DEFAULT_FILE.to_string()),
+            dirty: false,
+            last_saved: std::time::Instant::now(),
+            confirm_discard: None,
+            after_save: None,
+            search_query: String::new(),
+            search_results: Vec::new(),
+            search_all_files: false,
+            library: library::Library::default(),
+            library_results: Vec::new(),
+            search_filter: SearchFilter::default(),
+            new_category: String::new(),
+            new_subcategory: String::new(),
+            new_concept: String::new(),
+            new_note: String::new(),
+            scratchpad: String::new(),
+            show_scratchpad: false,
+            new_fields: Vec::new(),
+            new_rating: None,
+            new_created: None,
+            new_display: NoteDisplay::default(),
+            new_definition: false,
+            new_flags: BTreeSet::new(),
+            new_tags: String::new(),
+            new_comments: Vec::new(),
+            new_author: String::new(),
+            template_skeleton: None,
+            note_prefill: None,
+            similar_text: String::new(),
+            similar_pending: None,
+            similar_notes: Vec::new(),
+            selected_category: None,
+            selected_subcategory: None,
+            selected_concept: None,
+            status_message: String::new(),
+            view_mode: ViewMode::Browse,
+            current_theme: Theme::Monokai,
+            show_theme_selector: false,
+            theme_editor: None,
+            settings: Settings::default(),
+            show_settings: false,
+            show_shortcuts: false,
+            recording_shortcut: None,
+            show_welcome: false,
+            shown_title: String::new(),
+            tour_step: None,
+            tour_targets: [egui::Rect::NOTHING; TOUR_STEPS.len()],
+            vim: VimState::default(),
+            job: None,
+            selective_open: None,
+            show_secret_report: false,
+            show_aging: false,
+            validation: None,
+            encoding_notice: None,
+            file_watch: None,
+            external_change: false,
+            import_wizard: None,
+            link_import: None,
+            collapsed_concepts: BTreeSet::new(),
+            notes_shown: HashMap::new(),
+            concept_filters: HashMap::new(),
+            browse_tags: BTreeSet::new(),
+            browse_order: NoteOrder::default(),
+            cooccurrence_page: 0,
+            category_page: 0,
+            cooccurrence_shown: 0,
+            category_shown: 0,
+            stats_scope: (None, None),
+            size_ranking: None,
+            profile: DEFAULT_PROFILE.to_string(),
+            profiles: vec![DEFAULT_PROFILE.to_string()],
+            switch_profile: None,
+            choosing_profile: false,
+            new_profile_name: None,
+            workspaces: BTreeMap::new(),
+            active_workspace: None,
+            new_workspace_name: None,
+            trend_terms: String::new(),
+            trend_relative: false,
+            compare_categories: (String::new(), String::new()),
+            bridge_min_categories: 3,
+            cluster_threshold: 0.15,
+            clusters: None,
+            numeric_sort_field: None,
+            numeric_sort_desc: true,
+            editing_description: None,
+            editing_note_template: None,
+            note_template_draft: String::new(),
+            file_info_draft: None,
+            restore_backups: None,
+            subcategory_info: None,
+            comment_draft: None,
+            raw_notes: HashSet::new(),
+            expanded_cards: HashSet::new(),
+            hovered_card: None,
+            pack_export: None,
+            feed_export: None,
+            calendar_export: None,
+            markdown_export: None,
+            pack_import: None,
+            description_draft: String::new(),
+            cross_list_source: None,
+            cross_list_target: (String::new(), String::new()),
+            merge_source: None,
+            merge_target: (String::new(), String::new(), String::new()),
+            merge_keep_alias: true,
+            merge_undo: None,
+            history: History::default(),
+            graph_center: None,
+            split_note: None,
+            split_draft: String::new(),
+            append_note: None,
+            append_draft: String::new(),
+            append_prepend: false,
+            append_timestamp: true,
+            purge_note: None,
+            details_note: None,
+            to_clipboard: None,
+            qr_popup: None,
+            external_edits: Vec::new(),
+            webhook_deliveries: Vec::new(),
+            publish_concept: None,
+            upload: None,
+            activity: ActivityLog::default(),
+            focus: None,
+            focus_capture: None,
+            session_note: String::new(),
+            activity_filter: String::new(),
+            editing_note: None,
+            review_done: 0,
+            review_skip: 0,
+            study_skip: 0,
+            study_revealed: false,
+        };
+        
+        app
+    }
+}
+
+impl MicroficheApp {
+    /// Open `startup_file` if given (e.g. by the OS when a data file is
+    /// double-clicked), otherwise whatever the startup setting says
+    fn new(cc: &eframe::CreationContext<'_>, startup_file: Option<String>, profile: Option<String>) -> Self {
+        let mut app = Self::default();
+        let mut last_profile = None;
+        if let Some(storage) = cc.storage
+            && let Some((profiles, last)) = eframe::get_value::<(Vec<String>, String)>(storage, PROFILES_KEY)
+            && !profiles.is_empty()
+        {
+            app.profiles = profiles;
+            last_profile = Some(last);
+        }
+        // Ask who's using the app unless a profile or a file to open (by
+        // whoever double-clicked it, in the last profile) was given
+        app.choosing_profile = profile.is_none() && startup_file.is_none() && app.profiles.len() > 1;
+        let profile = profile.or(last_profile).unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        if !app.profiles.contains(&profile) {
+            app.profiles.push(profile.clone());
+        }
+        app.load_profile(cc.storage, &cc.egui_ctx, profile);
+        
+        match startup_file {
+            Some(path) => app.load_file(path),
+            None => app.open_default_file(),
+        }
+        app
+    }
+    
+    /// Read a profile's settings, pages, workspaces, Create draft, scratchpad, theme and tab
+    fn load_profile(&mut self, storage: Option<&dyn eframe::Storage>, ctx: &egui::Context, profile: String) {
+        let key = |name: &str| profile_key(&profile, name);
+        
+        self.settings = stored_value(storage, &key(SETTINGS_KEY)).unwrap_or_default();
+        i18n::set_language(&self.settings.language);
+        apply_fonts(ctx, &self.settings);
+        if let Err(e) = apply_branding(ctx, &self.settings) {
+            self.status_message = e;
+        }
+        (self.cooccurrence_page, self.category_page) = stored_value(storage, &key(STATS_PAGES_KEY)).unwrap_or_default();
+        self.collapsed_concepts = stored_value(storage, &key(COLLAPSED_CONCEPTS_KEY)).unwrap_or_default();
+        (self.workspaces, self.active_workspace) = stored_value(storage, &key(WORKSPACES_KEY)).unwrap_or_default();
+        (self.new_category, self.new_subcategory, self.new_concept, self.new_note) = stored_value(storage, &key(DRAFT_KEY)).unwrap_or_default();
+        (self.scratchpad, self.show_scratchpad) = stored_value(storage, &key(SCRATCHPAD_KEY)).unwrap_or_default();
+        (self.current_theme, self.view_mode) = stored_value(storage, &key(VIEW_KEY)).unwrap_or((Theme::Monokai, ViewMode::Browse));
+        self.profile = profile;
+    }
+    
+    fn save_profile(&mut self, storage: &mut dyn eframe::Storage) {
+        let key = |name: &str| profile_key(&self.profile, name);
+        self.settings.last_file = self.current_file.as_deref().map(absolute_path);
+        eframe::set_value(storage, &key(SETTINGS_KEY), &self.settings);
+        eframe::set_value(storage, &key(STATS_PAGES_KEY), &(self.cooccurrence_page, self.category_page));
+        eframe::set_value(storage, &key(COLLAPSED_CONCEPTS_KEY), &self.collapsed_concepts);
+        // Keep the workspace in use up to date, so it reopens as it was left
+        if let Some(name) = self.active_workspace.clone() {
+            self.workspaces.insert(name, self.capture_workspace());
+        }
+        eframe::set_value(storage, &key(WORKSPACES_KEY), &(&self.workspaces, &self.active_workspace));
+        // An edit in progress belongs to the file it came from, so only new notes are kept
+        let draft = if self.editing_note.is_some() {
+            Default::default()
+        } else {
+            (self.new_category.clone(), self.new_subcategory.clone(), self.new_concept.clone(), self.new_note.clone())
+        };
+        eframe::set_value(storage, &key(DRAFT_KEY), &draft);
+        eframe::set_value(storage, &key(SCRATCHPAD_KEY), &(&self.scratchpad, self.show_scratchpad));
+        eframe::set_value(storage, &key(VIEW_KEY), &(self.current_theme, self.view_mode));
+        eframe::set_value(storage, PROFILES_KEY, &(&self.profiles, &self.profile));
+    }
+    
+    /// Save the current profile, load `profile` (new or existing) and open its data file
+    fn change_profile(&mut self, frame: &mut eframe::Frame, ctx: &egui::Context, profile: String) {
+        if let Some(storage) = frame.storage_mut() {
+            self.save_profile(storage);
+        }
+        if !self.profiles.contains(&profile) {
+            self.profiles.push(profile.clone());
+        }
+        self.load_profile(frame.storage(), ctx, profile);
+        self.editing_note = None;
+        self.selected_category = None;
+        self.selected_subcategory = None;
+        self.selected_concept = None;
+        self.open_default_file();
+    }
+    
+    /// Open the last file, the fixed data file or nothing, as set in Settings
+    fn open_default_file(&mut self) {
+        let fixed_file = match self.settings.default_file.trim() {
+            "" => DEFAULT_FILE.to_string(),
+            path => path.to_string(),
+        };
+        let path = match self.settings.startup {
+            StartupAction::LastFile => Some(self.settings.last_file.clone().unwrap_or(fixed_file)),
+            StartupAction::FixedFile => Some(fixed_file),
+            StartupAction::Empty => None,
+        };
+        let exists = path.as_ref().is_some_and(|path| Path::new(path).exists());
+        if !exists {
+            // Nothing to show yet on a first run; offer the sample fiche instead
+            if !self.settings.welcomed {
+                self.show_welcome = true;
+            } else if let Some(path) = &path {
+                let shown = std::path::absolute(path).map_or_else(|_| path.clone(), |p| p.display().to_string());
+                self.status_message = tr_args("data-file-not-found", &[("path", shown.into())]);
+            }
+        }
+        let opened = path.and_then(|path| match Microfiche::open(&path) {
+            Ok(fiche) => Some((path, fiche)),
+            // Created by the first save
+            Err(_) if !exists => Some((path, Microfiche::new())),
+            // Left closed, so a save can't write an empty fiche over it
+            Err(e) => {
+                self.status_message = tr_args("error-loading", &[("error", e.to_string().into())]);
+                None
+            },
+        });
+        let Some((path, fiche)) = opened else {
+            self.microfiche = Microfiche::new();
+            self.activity = ActivityLog::default();
+            self.history = History::default();
+            self.current_file = None;
+            self.file_watch = None;
+            self.mark_clean();
+            return;
+        };
+        self.microfiche = fiche;
+        self.activity = ActivityLog::load(&path);
+        self.history = History::default();
+        self.watch_file(&path, Arc::new(self.microfiche.clone()));
+        if exists {
+            self.remember_file(&path);
+        }
+        self.current_file = Some(path);
+        self.mark_clean();
+    }
+    
+    fn render_welcome_window(&mut self, ctx: &egui::Context) {
+        if !self.show_welcome {
+            return;
+        }
+        let mut choice = None;
+        egui::Window::new(tr("welcome-to-fisha"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.set_max_width(380.0);
+                ui.label(tr("welcome-text"));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button(tr("create-sample-fiche")).on_hover_text(tr("create-sample-fiche-hint")).clicked() {
+                        choice = Some(0);
+                    }
+                    if ui.button(tr("start-empty")).clicked() {
+                        choice = Some(1);
+                    }
+                    if ui.button(tr("open")).clicked() {
+                        choice = Some(2);
+                    }
+                });
+            });
+        
+        let Some(choice) = choice else {
+            return;
+        };
+        self.show_welcome = false;
+        self.settings.welcomed = true;
+        match choice {
+            0 => {
+                self.microfiche = sample::fiche();
+                self.history = History::default();
+                self.mark_clean();
+                self.status_message = tr("sample-fiche-created");
+                self.tour_step = Some(0);
+            },
+            1 => self.tour_step = Some(0),
+            _ => self.open_file(),
+        }
+    }
+    
+    /// One step of the guided tour: the tab it's about is opened and outlined,
+    /// with an explanation underneath
+    fn render_tour(&mut self, ctx: &egui::Context) {
+        let Some(step) = self.tour_step else {
+            return;
+        };
+        let (view_mode, text) = TOUR_STEPS[step];
+        self.view_mode = view_mode;
+        let target = self.tour_targets[step];
+        
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("tour_highlight")));
+        let stroke = egui::Stroke::new(2.5, ctx.style().visuals.selection.stroke.color);
+        painter.rect_stroke(target.expand(3.0), 4.0, stroke, egui::StrokeKind::Outside);
+        
+        let mut next_step = Some(step);
+        egui::Window::new(tr("guided-tour"))
+            .id(egui::Id::new("tour_window"))
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .fixed_pos(target.left_bottom() + egui::vec2(0.0, 10.0))
+            .show(ctx, |ui| {
+                ui.set_max_width(320.0);
+                ui.label(egui::RichText::new(tr_args("tour-step", &[
+                    ("step", (step + 1).to_string().into()),
+                    ("count", TOUR_STEPS.len().to_string().into()),
+                ])).size(11.0).color(egui::Color32::GRAY));
+                ui.label(tr(text));
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if step > 0 && ui.button(tr("back")).clicked() {
+                        next_step = Some(step - 1);
+                    }
+                    let last = step + 1 == TOUR_STEPS.len();
+                    if ui.button(if last { tr("finish") } else { tr("next") }).clicked() {
+                        next_step = (!last).then_some(step + 1);
+                    }
+                    if !last && ui.button(tr("skip-tour")).clicked() {
+                        next_step = None;
+                    }
+                });
+            });
+        if next_step.is_none() {
+            self.view_mode = ViewMode::Browse;
+        }
+        self.tour_step = next_step;
+    }
+    
+    fn save_file(&mut self) {
+        if let Some(path) = self.current_file.clone() {
+            self.save_to(path);
+        } else {
+            self.save_file_as();
+        }
+    }
+    
+    fn save_to(&mut self, path: String) {
+        self.start_save(path, None);
+    }
+    
+    /// Save a snapshot of the fiche on a worker thread; `finish_job` records
+    /// the outcome. Saving after purging the note `purged` keeps no `.bak`
+    /// and deletes the backups still holding its text.
+    fn start_save(&mut self, path: String, purged: Option<String>) {
+        if self.job_running() {
+            return;
+        }
+        // A selectively opened file must be complete before it is written back
+        if !self.microfiche.unloaded.is_empty() {
+            let wanted = self.microfiche.unloaded.keys().cloned().collect();
+            self.load_unloaded(wanted);
+            if !self.microfiche.unloaded.is_empty() {
+                return;
+            }
+        }
+        let fiche = Arc::new(self.microfiche.clone());
+        let index = self.microfiche.index.shared();
+        let label = tr_args("saving", &[("path", path.to_string().into())]);
+        let target = path.clone();
+        let snapshots_kept = self.settings.snapshots_kept;
+        // Set again if the save fails, or by changes made while it runs
+        self.mark_clean();
+        self.start_job(JobKind::Save { path, fiche: Arc::clone(&fiche) }, label, move |progress| {
+            fiche.save_keeping(&target, purged.is_none(), progress)?;
+            // Only a cache: without it the next open builds the index again
+            if let Some(index) = index {
+                let _ = index.persist(&target, &fiche);
+            }
+            // A backup that can't be read can't be shown to be free of the text
+            if let Some(text) = &purged
+                && let Err(e) = snapshots::remove_where(&target, |snapshot| {
+                    Microfiche::open(&snapshot.to_string_lossy())
+                        .map_or(true, |old| old.iter_notes().any(|(_, _, _, note)| note.text == *text))
+                })
+            {
+                return Ok(e.to_string());
+            }
+            // The file is saved either way; a failed snapshot is only reported
+            if snapshots_kept > 0
+                && let Err(e) = snapshots::take(&target, snapshots_kept)
+            {
+                return Ok(e.to_string());
+            }
+            Ok(String::new())
+        });
+    }
+    
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.last_saved = std::time::Instant::now();
+    }
+    
+    /// Save to the open file once the autosave interval has passed since the
+    /// last save, if anything changed
+    fn autosave(&mut self, ctx: &egui::Context) {
+        let minutes = self.settings.autosave_minutes;
+        // Saving a selectively opened file would load all of it first
+        if minutes == 0 || !self.dirty || self.job.is_some() || !self.microfiche.unloaded.is_empty() {
+            return;
+        }
+        let Some(path) = self.current_file.clone() else { return };
+        let interval = std::time::Duration::from_secs(minutes as u64 * 60);
+        let elapsed = self.last_saved.elapsed();
+        if elapsed >= interval {
+            self.save_to(path);
+        } else {
+            ctx.request_repaint_after(interval - elapsed);
+        }
+    }
+    
+    /// End the focus session or break once its time is up: a session asks
+    /// what was learned and starts the break, a break just ends
+    fn tick_focus(&mut self, ctx: &egui::Context) {
+        let Some(timer) = &self.focus else {
+            return;
+        };
+        if timer.is_paused() {
+            return;
+        }
+        if !timer.is_over() {
+            // Repaint on the next second so the countdown keeps moving
+            ctx.request_repaint_after(std::time::Duration::from_millis(u64::from(timer.remaining().subsec_millis()) + 1));
+            return;
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Informational));
+        match timer.phase {
+            focus::Phase::Focus => {
+                let capture = self.focus_capture.get_or_insert_with(FocusCapture::default);
+                // Start from the concept being browsed the first time
+                if capture.concept.is_empty()
+                    && let (Some(cat), Some(sub), Some(con)) = (&self.selected_category, &self.selected_subcategory, &self.selected_concept)
+                {
+                    capture.category = cat.clone();
+                    capture.subcategory = sub.clone();
+                    capture.concept = con.clone();
+                }
+                capture.asking = true;
+                self.focus = Some(focus::Timer::start(focus::Phase::Break, self.settings.break_minutes));
+                self.status_message = tr("focus-session-over");
+            },
+            focus::Phase::Break => {
+                self.focus = None;
+                self.status_message = tr("focus-break-over");
+            },
+        }
+    }
+    
+    /// The timer's start button, or its countdown with pause and stop
+    fn render_focus_timer(&mut self, ui: &mut egui::Ui) {
+        let Some(timer) = &mut self.focus else {
+            let start = accessible(ui.button("⏱"), tr("focus-start"))
+                .on_hover_text(tr_args("focus-start-hint", &[("minutes", self.settings.focus_minutes.to_string().into())]));
+            if start.clicked() {
+                self.focus = Some(focus::Timer::start(focus::Phase::Focus, self.settings.focus_minutes));
+            }
+            return;
+        };
+        let (icon, hint) = match timer.phase {
+            focus::Phase::Focus => ("🍅", tr("focus-running-hint")),
+            focus::Phase::Break => ("☕", tr("focus-break-hint")),
+        };
+        ui.label(egui::RichText::new(format!("{} {}", icon, timer.countdown())).monospace())
+            .on_hover_text(hint);
+        let pause = if timer.is_paused() { ("▶", tr("focus-resume")) } else { ("⏸", tr("focus-pause")) };
+        if accessible(ui.small_button(pause.0), pause.1.clone()).on_hover_text(pause.1).clicked() {
+            timer.toggle_pause();
+        }
+        if accessible(ui.small_button("⏹"), tr("focus-stop")).on_hover_text(tr("focus-stop")).clicked() {
+            self.focus = None;
+        }
+    }
+    
+    /// Ask what was learned in the session that just ended and file it as a
+    /// note under the chosen concept
+    fn render_focus_capture_window(&mut self, ctx: &egui::Context) {
+        let Some(capture) = self.focus_capture.as_mut().filter(|capture| capture.asking) else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut file = false;
+        let mut skip = false;
+        egui::Window::new(tr("focus-capture-title"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("focus_capture").num_columns(2).show(ui, |ui| {
+                    for (label, value) in [
+                        (tr("category-label"), &mut capture.category),
+                        (tr("subcategory-label"), &mut capture.subcategory),
+                        (tr("concept-label"), &mut capture.concept),
+                    ] {
+                        let label = ui.label(label);
+                        ui.add(egui::TextEdit::singleline(value).desired_width(260.0)).labelled_by(label.id);
+                        ui.end_row();
+                    }
+                });
+                let text = ui.add(egui::TextEdit::multiline(&mut capture.text)
+                    .hint_text(tr("focus-capture-hint"))
+                    .desired_rows(5)
+                    .desired_width(360.0));
+                if ui.memory(|memory| memory.focused().is_none()) {
+                    text.request_focus();
+                }
+                let ready = [&capture.category, &capture.subcategory, &capture.concept, &capture.text]
+                    .iter()
+                    .all(|value| !value.trim().is_empty());
+                ui.horizontal(|ui| {
+                    file = ui.add_enabled(ready, egui::Button::new(tr("focus-file-note"))).clicked();
+                    skip = ui.button(tr("focus-skip")).clicked();
+                });
+            });
+        
+        if file {
+            self.file_focus_note();
+        } else if !open || skip {
+            capture.asking = false;
+            capture.text.clear();
+        }
+    }
+    
+    fn file_focus_note(&mut self) {
+        let Some(capture) = &mut self.focus_capture else {
+            return;
+        };
+        capture.asking = false;
+        let (cat, sub, con) = (capture.category.trim().to_string(), capture.subcategory.trim().to_string(), capture.concept.trim().to_string());
+        let text = std::mem::take(&mut capture.text).trim().to_string();
+        let author = self.user_name();
+        self.microfiche.add_row(FicheRow {
+            category: cat.clone(),
+            subcategory: sub.clone(),
+            concept: con.clone(),
+            note: text.clone(),
+            rating: None,
+            needs_review: false,
+            created: Some(timestamp_now()),
+            modified: None,
+            fields: BTreeMap::new(),
+            display: NoteDisplay::default(),
+            definition: false,
+            flags: BTreeSet::new(),
+            tags: BTreeSet::new(),
+            author,
+            comments: Vec::new(),
+        });
+        self.log_activity(ActivityAction::Created, note_path(&cat, &sub, &con), &text);
+        if let Some(added) = self.microfiche.concept(&cat, &sub, &con)
+            .and_then(|c| c.notes.iter().find(|n| n.text == text))
+            .cloned()
+        {
+            self.notify(webhook::Event::Created, (&cat, &sub, &con), &added, None);
+        }
+        self.status_message = tr_args("focus-note-filed", &[("path", note_path(&cat, &sub, &con).into())]);
+    }
+    
+    /// Look up notes like the Create form's once its text has stayed the same
+    /// for `SIMILAR_DELAY`
+    fn update_similar_notes(&mut self, ctx: &egui::Context) {
+        if self.new_note != self.similar_text {
+            self.similar_text = self.new_note.clone();
+            self.similar_pending = Some(std::time::Instant::now());
+        }
+        let Some(changed) = self.similar_pending else {
+            return;
+        };
+        let waited = changed.elapsed();
+        if waited < SIMILAR_DELAY {
+            ctx.request_repaint_after(SIMILAR_DELAY - waited);
+            return;
+        }
+        self.similar_pending = None;
+        self.similar_notes.clear();
+        // A few words are alike too often to mean anything
+        if search_index::terms(&self.new_note).iter().collect::<HashSet<_>>().len() < 3 {
+            return;
+        }
+        // The note being edited is like itself
+        let editing = self.editing_note.as_ref().map(|text| (
+            self.new_category.as_str(),
+            self.new_subcategory.as_str(),
+            self.new_concept.as_str(),
+            text.as_str(),
+        ));
+        self.similar_notes = self.microfiche.index.get_or_build(&self.microfiche)
+            .similar(&self.new_note, SIMILAR_THRESHOLD, SIMILAR_SHOWN + 1)
+            .into_iter()
+            .filter(|(hit, _)| editing != Some((hit.category, hit.subcategory, hit.concept, hit.text)))
+            .take(SIMILAR_SHOWN)
+            .map(|(hit, similarity)| ((
+                hit.category.to_string(),
+                hit.subcategory.to_string(),
+                hit.concept.to_string(),
+                hit.text.to_string(),
+            ), similarity))
+            .collect();
+    }
+    
+    /// Exit, or open another file, without saving
+    fn discard_and(&mut self, ctx: &egui::Context, pending: PendingDiscard) {
+        self.dirty = false;
+        match pending {
+            PendingDiscard::Exit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            PendingDiscard::Open(path) => self.load_file(path),
+        }
+    }
+    
+    /// Save, Don't Save or Cancel, before unsaved changes are dropped
+    fn render_unsaved_prompt(&mut self, ctx: &egui::Context) {
+        if self.confirm_discard.is_none() {
+            return;
+        }
+        let file = self.current_file.as_deref().map_or_else(|| tr("untitled"), file_name);
+        
+        let mut save = false;
+        let mut discard = false;
+        let mut cancel = ctx.input(|input| input.key_pressed(egui::Key::Escape));
+        egui::Window::new(tr("unsaved-changes"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(tr_args("unsaved-changes-prompt", &[("file", file.into())]));
+                ui.separator();
+                ui.horizontal(|ui| {
+                    save = ui.button(tr("save")).clicked();
+                    discard = ui.button(tr("dont-save")).clicked();
+                    cancel |= ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if save {
+            let pending = self.confirm_discard.take();
+            self.save_file();
+            // Carried out once the save has gone through
+            if self.job.is_some() {
+                self.after_save = pending;
+            }
+        } else if discard && let Some(pending) = self.confirm_discard.take() {
+            self.discard_and(ctx, pending);
+        } else if cancel {
+            self.confirm_discard = None;
+        }
+    }
+    
+    /// True (and says so in the status bar) while a save or export is running
+    fn job_running(&mut self) -> bool {
+        if self.job.is_some() {
+            self.status_message = tr("wait-for-the-current-save-or-export");
+        }
+        self.job.is_some()
+    }
+    
+    fn start_job(
+        &mut self,
+        kind: JobKind,
+        label: String,
+        work: impl FnOnce(&JobProgress) -> Result<String, Box<dyn Error>> + Send + 'static,
+    ) {
+        let progress = Arc::new(JobProgress::default());
+        let worker_progress = Arc::clone(&progress);
+        let handle = std::thread::spawn(move || work(&worker_progress).map_err(|e| e.to_string()));
+        self.job = Some(BackgroundJob { kind, label, progress, handle });
+    }
+    
+    fn finish_job(&mut self) {
+        let Some(job) = self.job.take_if(|job| job.handle.is_finished()) else { return };
+        let result = job.handle.join().unwrap_or_else(|_| Err("the worker thread panicked".to_string()));
+        if job.progress.is_cancelled() {
+            self.status_message = tr("cancelled");
+            self.dirty |= matches!(job.kind, JobKind::Save { .. });
+            return;
+        }
+        match (job.kind, result) {
+            (JobKind::Save { path, fiche }, Ok(snapshot_error)) => {
+                self.status_message = tr_args("saved-to", &[("path", path.to_string().into())]);
+                if !snapshot_error.is_empty() {
+                    self.status_message = tr_args("saved-but-the-snapshot-failed", &[("error", snapshot_error.into())]);
+                }
+                if let Err(e) = self.activity.flush(&path) {
+                    self.status_message = tr_args("saved-but-the-activity-log-failed", &[("error", e.to_string().into())]);
+                }
+                self.watch_file(&path, fiche);
+                self.remember_file(&path);
+                self.current_file = Some(path);
+            },
+            (JobKind::Save { .. }, Err(e)) => {
+                self.status_message = tr_args("error-saving", &[("error", e.into())]);
+                self.dirty = true;
+            },
+            (JobKind::Export, Ok(message)) => self.status_message = message,
+            (JobKind::Export, Err(e)) => self.status_message = tr_args("error-exporting", &[("error", e.into())]),
+        }
+    }
+    
+    /// Start watching `path` for changes made elsewhere; `base` is the fiche
+    /// it holds now
+    fn watch_file(&mut self, path: &str, base: Arc<Microfiche>) {
+        self.file_watch = Some(file_watch::Watch::new(path, base));
+        self.external_change = false;
+    }
+    
+    /// Check whether another program changed the open file, e.g. a sync
+    /// client bringing in edits made on another computer
+    fn check_file_changes(&mut self, ctx: &egui::Context) {
+        // The file is changed by our own save while it runs
+        if self.file_watch.is_none() || self.job.is_some() {
+            return;
+        }
+        ctx.request_repaint_after(file_watch::POLL_INTERVAL);
+        if self.file_watch.as_mut().is_some_and(|watch| watch.changed()) {
+            self.external_change = true;
+        }
+    }
+    
+    fn render_external_change_banner(&mut self, ctx: &egui::Context) {
+        if !self.external_change {
+            return;
+        }
+        let Some(path) = self.current_file.clone() else {
+            self.external_change = false;
+            return;
+        };
+        let mut choice = None;
+        egui::TopBottomPanel::top("external_change").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                let message = tr_args("file-changed-on-disk", &[("file", file_name(&path).into())]);
+                ui.label(egui::RichText::new(format!("⚠ {}", message)).color(ui.visuals().warn_fg_color));
+                if ui.button(tr("reload")).on_hover_text(tr("reload-hint")).clicked() {
+                    choice = Some(0);
+                }
+                if ui.button(tr("keep-mine")).on_hover_text(tr("keep-mine-hint")).clicked() {
+                    choice = Some(1);
+                }
+                if ui.button(tr("merge-changes")).on_hover_text(tr("merge-changes-hint")).clicked() {
+                    choice = Some(2);
+                }
+            });
+        });
+        
+        match choice {
+            Some(0) => {
+                let fiche = Microfiche::open(&path);
+                self.set_fiche(path, fiche);
+            },
+            Some(1) => {
+                // Saving again writes this version over theirs
+                self.external_change = false;
+                self.dirty = true;
+            },
+            Some(_) => match Microfiche::open(&path) {
+                Ok(theirs) => {
+                    let theirs = Arc::new(theirs);
+                    let Some(watch) = &mut self.file_watch else { return };
+                    let base = std::mem::replace(&mut watch.base, Arc::clone(&theirs));
+                    let merged = file_watch::merge(&base, &mut self.microfiche, &theirs);
+                    self.external_change = false;
+                    self.merge_undo = None;
+                    self.clusters = None;
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                    self.log_activity(ActivityAction::Merged, path, &format!(
+                        "changes on disk: {} added, {} removed, {} updated", merged.added, merged.removed, merged.updated
+                    ));
+                    self.status_message = tr_args("merged-changes-from-disk", &[
+                        ("added", merged.added.into()),
+                        ("removed", merged.removed.into()),
+                        ("updated", merged.updated.into()),
+                    ]);
+                },
+                Err(e) => self.status_message = tr_args("error-loading", &[("error", e.to_string().into())]),
+            },
+            None => {},
+        }
+    }
+    
+    fn render_job_window(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.job else { return };
+        
+        egui::Window::new(&job.label)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -40.0])
+            .show(ctx, |ui| {
+                let bar = match job.progress.fraction() {
+                    Some(fraction) => egui::ProgressBar::new(fraction).show_percentage(),
+                    None => egui::ProgressBar::new(0.0).animate(true),
+                };
+                ui.add(bar.desired_width(300.0));
+                if job.progress.is_cancelled() {
+                    ui.label(tr("cancelling"));
+                } else if ui.button(tr("cancel")).clicked() {
+                    job.progress.cancel();
+                }
+            });
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+    
+    fn save_file_as(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Fisha", &[fiche_file::EXTENSION])
+            .add_filter("SQLite", &sqlite_store::EXTENSIONS)
+            .add_filter("CSV", &["csv"])
+            .add_filter(&tr("compressed-csv"), &["gz", "zip"])
+            .save_file()
+        {
+            self.save_to(path.to_string_lossy().to_string());
+        }
+    }
+    
+    fn open_file(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter(&tr("fisha-or-csv"), &[fiche_file::EXTENSION, "db", "sqlite", "csv", "gz", "zip"])
+            .pick_file()
+        {
+            self.load_file(path.to_string_lossy().to_string());
+        }
+    }
+    
+    /// Open `path_str` in place of the current fiche, asking first when
+    /// that would drop unsaved changes
+    fn load_file(&mut self, path_str: String) {
+        if self.dirty {
+            self.confirm_discard = Some(PendingDiscard::Open(path_str));
+            return;
+        }
+        let fiche = Microfiche::open(&path_str);
+        self.set_fiche(path_str, fiche);
+    }
+    
+    fn set_fiche(&mut self, path_str: String, fiche: Result<Microfiche, Box<dyn Error>>) {
+        match fiche {
+            Ok(fiche) => {
+                self.encoding_notice = fiche.source_encoding.map(|encoding| {
+                    tr_args("converted-from-encoding", &[("path", path_str.to_string().into()), ("encoding", encoding.into())])
+                });
+                self.microfiche = fiche;
+                self.merge_undo = None;
+                self.history = History::default();
+                self.mark_clean();
+                self.clusters = None;
+                self.stats_scope = (None, None);
+                self.activity = ActivityLog::load(&path_str);
+                self.watch_file(&path_str, Arc::new(self.microfiche.clone()));
+                self.remember_file(&path_str);
+                self.current_file = Some(path_str.clone());
+                self.status_message = tr_args("loaded", &[("path_str", path_str.to_string().into())]);
+            },
+            Err(e) => {
+                self.status_message = tr_args("error-loading", &[("error", e.to_string().into())]);
+                // Show what is wrong with a CSV that doesn't load, and offer to repair it
+                if !fiche_file::is_fiche_path(&path_str)
+                    && !sqlite_store::is_db_path(&path_str)
+                    && Path::new(&path_str).exists()
+                    && let Ok(report) = validate::check(&path_str)
+                    && !report.issues.is_empty()
+                {
+                    self.validation = Some(report);
+                }
+            },
+        }
+    }
+    
+    /// Check the current CSV file (or a picked one) for structural problems
+    fn validate_file(&mut self) {
+        let path_str = match &self.current_file {
+            Some(path) if Path::new(path).exists() => path.clone(),
+            _ => match rfd::FileDialog::new().add_filter("CSV", &["csv", "gz", "zip"]).pick_file() {
+                Some(path) => path.to_string_lossy().to_string(),
+                None => return,
+            },
+        };
+        if fiche_file::is_fiche_path(&path_str) || sqlite_store::is_db_path(&path_str) {
+            self.status_message = tr("only-csv-files-can-be-validated");
+            return;
+        }
+        match validate::check(&path_str) {
+            Ok(report) if report.issues.is_empty() => {
+                self.status_message = tr_args("no-problems-found-in-rows", &[("count", report.rows.to_string().into())]);
+            },
+            Ok(report) => self.validation = Some(report),
+            Err(e) => self.status_message = tr_args("error-validating", &[("error", e.to_string().into())]),
+        }
+    }
+    
+    fn render_validation_window(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.validation else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new(tr("validation-report"))
+            .open(&mut open)
+            .default_size([550.0, 400.0])
+            .show(ctx, |ui| {
+                ui.strong(&report.path);
+                ui.label(tr_args("rows-checked-problems-found", &[("rows", report.rows.to_string().into()), ("count", report.issues.len().to_string().into())]));
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("validation_grid").striped(true).num_columns(3).show(ui, |ui| {
+                        for issue in &report.issues {
+                            ui.label(tr_args("line", &[("line", issue.line.to_string().into())]));
+                            ui.label(issue.kind.describe());
+                            match issue.kind.fix() {
+                                Some(fix) => ui.label(egui::RichText::new(fix).color(egui::Color32::GRAY)),
+                                None => ui.label(egui::RichText::new(tr("cannot-be-fixed")).color(ui.visuals().error_fg_color)),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    apply = ui.add_enabled(report.fixable(), egui::Button::new(tr("open-with-fixes")))
+                        .on_disabled_hover_text(tr("add-the-missing-columns-first"))
+                        .clicked();
+                    ui.label(egui::RichText::new(tr("the-file-is-unchanged-until-you-save")).size(11.0).color(egui::Color32::GRAY));
+                });
+            });
+        
+        if apply {
+            let report = self.validation.take().unwrap();
+            let fixed = Microfiche::from_validated(&report);
+            self.set_fiche(report.path.clone(), fixed);
+            if self.current_file.as_ref() == Some(&report.path) {
+                self.status_message = tr_args("opened-with-fixes", &[("count", report.issues.len().to_string().into())]);
+            }
+        } else if !open {
+            self.validation = None;
+        }
+    }
+    
+    /// Pick a CSV file and list its categories, to load only some of them
+    fn open_selectively(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv", "gz", "zip"])
+            .pick_file()
+        else {
+            return;
+        };
+        let path_str = path.to_string_lossy().to_string();
+        if fiche_file::is_fiche_path(&path_str) {
+            self.load_file(path_str);
+            return;
+        }
+        match Microfiche::scan_categories(&path_str) {
+            Ok(counts) => {
+                let mut categories: Vec<(String, usize, bool)> = counts.into_iter()
+                    .map(|(name, count)| (name, count, false))
+                    .collect();
+                categories.sort_by(|a, b| collate(&a.0, &b.0));
+                self.selective_open = Some(SelectiveOpen { path: path_str, categories });
+            },
+            Err(e) => self.status_message = tr_args("error-loading", &[("error", e.to_string().into())]),
+        }
+    }
+    
+    fn render_selective_open_window(&mut self, ctx: &egui::Context) {
+        let Some(SelectiveOpen { path, categories }) = &mut self.selective_open else { return };
+        
+        let mut open = true;
+        let mut load = false;
+        egui::Window::new(tr("open-selectively"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                ui.label(tr("pick-the-categories-to-load"));
+                ui.horizontal(|ui| {
+                    if ui.small_button(tr("select-all")).clicked() {
+                        categories.iter_mut().for_each(|c| c.2 = true);
+                    }
+                    if ui.small_button(tr("select-none")).clicked() {
+                        categories.iter_mut().for_each(|c| c.2 = false);
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (name, count, selected) in categories.iter_mut() {
+                        ui.checkbox(selected, format!("{} ({})", name, count));
+                    }
+                });
+                ui.separator();
+                let chosen: usize = categories.iter().filter(|c| c.2).map(|c| c.1).sum();
+                ui.label(tr_args("notes-to-load", &[("count", chosen.to_string().into())]));
+                load = ui.button(tr("load")).clicked();
+            });
+        
+        if load {
+            let path = path.clone();
+            let wanted: BTreeSet<String> = categories.iter().filter(|c| c.2).map(|c| c.0.clone()).collect();
+            let unloaded: BTreeMap<String, usize> = categories.iter()
+                .filter(|c| !c.2)
+                .map(|c| (c.0.clone(), c.1))
+                .collect();
+            let fiche = Microfiche::from_csv_where(&path, |cat| wanted.contains(cat)).map(|mut fiche| {
+                fiche.unloaded = unloaded;
+                fiche
+            });
+            self.selective_open = None;
+            self.set_fiche(path, fiche);
+        } else if !open {
+            self.selective_open = None;
+        }
+    }
+    
+    /// Load categories left out by a selective open from the current file
+    fn load_unloaded(&mut self, wanted: BTreeSet<String>) {
+        let Some(path) = self.current_file.clone() else { return };
+        match self.microfiche.load_categories(&path, &wanted) {
+            Ok(count) => self.status_message = tr_args("loaded-notes", &[("count", count.to_string().into())]),
+            Err(e) => self.status_message = tr_args("error-loading", &[("error", e.to_string().into())]),
+        }
+        self.clusters = None;
+    }
+    
+    /// Merge another CSV into the current fiche, flagging its notes for review
+    fn import_file(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter(&tr("fisha-or-csv"), &[fiche_file::EXTENSION, "db", "sqlite", "csv", "gz", "zip"])
+            .pick_file()
+        {
+            self.import_path(path.to_string_lossy().to_string());
+        }
+    }
+    
+    /// Open the import wizard on `path_str`
+    fn import_path(&mut self, path_str: String) {
+        match Microfiche::open(&path_str) {
+            Ok(fiche) => self.import_wizard = Some(ImportWizard { path: path_str, fiche, preview: None, vault: None }),
+            Err(e) => self.status_message = tr_args("error-importing", &[("error", e.to_string().into())]),
+        }
+    }
+    
+    /// Pick a folder of Markdown files, such as an Obsidian vault, and open
+    /// the import wizard on it
+    fn import_vault(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let Some(dir) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+        let mapping = vault::Mapping::Files;
+        match vault::read(&dir, mapping) {
+            Ok(fiche) if fiche.categories.is_empty() => self.status_message = tr("vault-no-notes"),
+            Ok(fiche) => {
+                let path = dir.to_string_lossy().to_string();
+                self.import_wizard = Some(ImportWizard { path, fiche, preview: None, vault: Some(mapping) });
+            },
+            Err(e) => self.status_message = tr_args("error-importing", &[("error", e.to_string().into())]),
+        }
+    }
+    
+    fn render_link_import_window(&mut self, ctx: &egui::Context) {
+        let Some(import) = &mut self.link_import else {
+            return;
+        };
+        
+        let links = link_import::links(&import.text);
+        let filed: Vec<&str> = self.microfiche.concept(import.category.trim(), import.subcategory.trim(), import.concept.trim())
+            .map(|concept| concept.notes.iter().map(|note| note.text.as_str()).collect())
+            .unwrap_or_default();
+        // Links already in the concept, bare or titled, are left out
+        let new: Vec<String> = links.iter()
+            .filter(|link| !filed.iter().any(|text| text.contains(link.as_str())))
+            .cloned()
+            .collect();
+        let fetching = import.fetch.is_some();
+        let mut open = true;
+        let mut start = false;
+        egui::Window::new(tr("import-links"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("import-links-hint")).size(11.0).color(egui::Color32::GRAY));
+                ui.add_enabled_ui(!fetching, |ui| {
+                    egui::Grid::new("link_import").num_columns(2).show(ui, |ui| {
+                        for (label, value) in [
+                            (tr("category-label"), &mut import.category),
+                            (tr("subcategory-label"), &mut import.subcategory),
+                            (tr("concept-label"), &mut import.concept),
+                        ] {
+                            let label = ui.label(label);
+                            ui.add(egui::TextEdit::singleline(value).desired_width(260.0)).labelled_by(label.id);
+                            ui.end_row();
+                        }
+                    });
+                    let text = ui.add(egui::TextEdit::multiline(&mut import.text)
+                        .hint_text(tr("paste-links-one-per-line"))
+                        .desired_rows(8)
+                        .desired_width(400.0));
+                    // Bring in what's on the clipboard once the box has focus
+                    if !import.pasted {
+                        if text.has_focus() {
+                            ui.ctx().send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+                            import.pasted = true;
+                        } else {
+                            text.request_focus();
+                        }
+                    }
+                    ui.checkbox(&mut import.fetch_titles, tr("fetch-page-titles"))
+                        .on_hover_text(tr("fetch-page-titles-hint"));
+                });
+                ui.separator();
+                ui.label(tr_args("links-found", &[("count", links.len().into())]));
+                if new.len() < links.len() {
+                    ui.label(egui::RichText::new(tr_args("links-already-filed", &[("count", (links.len() - new.len()).into())]))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY));
+                }
+                if let Some((_, fetch)) = &import.fetch {
+                    ui.add(egui::ProgressBar::new(fetch.done() as f32 / fetch.total.max(1) as f32)
+                        .text(tr_args("fetching-titles", &[("done", fetch.done().into()), ("total", fetch.total.into())])));
+                }
+                let ready = !fetching
+                    && !new.is_empty()
+                    && [&import.category, &import.subcategory, &import.concept].iter().all(|value| !value.trim().is_empty());
+                start = ui.add_enabled(ready, egui::Button::new(tr_args("import-links-button", &[("count", new.len().into())]))).clicked();
+            });
+        
+        if !open {
+            self.link_import = None;
+            return;
+        }
+        if start {
+            if import.fetch_titles {
+                import.fetch = Some((new.clone(), link_import::TitleFetch::start(new)));
+            } else {
+                let titles = vec![None; new.len()];
+                self.file_links(new, titles);
+                return;
+            }
+        }
+        let Some((links, fetch)) = &mut import.fetch else {
+            return;
+        };
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        if let Some(titles) = fetch.poll() {
+            let links = std::mem::take(links);
+            self.file_links(links, titles);
+        }
+    }
+    
+    /// Add a note for each link under the Import Links window's concept
+    fn file_links(&mut self, links: Vec<String>, titles: Vec<Option<String>>) {
+        let Some(import) = self.link_import.take() else {
+            return;
+        };
+        let (cat, sub, con) = (import.category.trim().to_string(), import.subcategory.trim().to_string(), import.concept.trim().to_string());
+        let author = self.user_name();
+        let count = links.len();
+        for (link, title) in links.iter().zip(titles.into_iter().chain(std::iter::repeat(None))) {
+            self.microfiche.add_row(FicheRow {
+                category: cat.clone(),
+                subcategory: sub.clone(),
+                concept: con.clone(),
+                note: link_import::note(link, title.as_deref()),
+                rating: None,
+                needs_review: false,
+                created: Some(timestamp_now()),
+                modified: None,
+                fields: BTreeMap::new(),
+                display: NoteDisplay::default(),
+                definition: false,
+                flags: BTreeSet::new(),
+                tags: BTreeSet::new(),
+                author: author.clone(),
+                comments: Vec::new(),
+            });
+        }
+        self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        self.log_activity(ActivityAction::Imported, note_path(&cat, &sub, &con), &format!("{} links", count));
+        self.status_message = tr_args("imported-links", &[("count", count.into()), ("path", note_path(&cat, &sub, &con).into())]);
+    }
+    
+    fn render_import_wizard(&mut self, ctx: &egui::Context) {
+        let Some(wizard) = &mut self.import_wizard else {
+            return;
+        };
+        
+        let cleanup = &mut self.settings.import_cleanup;
+        let mut open = true;
+        let mut import = false;
+        let mut cancel = false;
+        egui::Window::new(tr("import-notes"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong(&wizard.path);
+                if let Some(mapping) = &mut wizard.vault {
+                    let before = *mapping;
+                    let label = ui.label(tr("vault-mapping-label"));
+                    egui::ComboBox::from_id_salt("vault_mapping")
+                        .selected_text(mapping.label())
+                        .show_ui(ui, |ui| {
+                            for option in vault::Mapping::ALL {
+                                ui.selectable_value(mapping, option, option.label());
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                    if *mapping != before {
+                        match vault::read(Path::new(&wizard.path), *mapping) {
+                            Ok(fiche) => {
+                                wizard.fiche = fiche;
+                                wizard.preview = None;
+                            },
+                            Err(e) => {
+                                *mapping = before;
+                                self.status_message = tr_args("error-importing", &[("error", e.to_string().into())]);
+                            },
+                        }
+                    }
+                }
+                ui.label(tr_args("notes", &[("count", wizard.fiche.iter_notes().count().to_string().into())]));
+                egui::CollapsingHeader::new(tr("import-contents")).show(ui, |ui| {
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for (concept, notes) in wizard.fiche.iter_concepts() {
+                            ui.label(format!(
+                                "{} ({})",
+                                note_path(&concept.category, &concept.subcategory, &concept.concept),
+                                notes.notes.len(),
+                            ));
+                        }
+                    });
+                });
+                ui.separator();
+                
+                ui.label(tr("clean-up-notes"));
+                ui.checkbox(&mut cleanup.trim, tr("cleanup-trim"));
+                ui.checkbox(&mut cleanup.collapse_blank_lines, tr("cleanup-collapse-blank-lines"));
+                ui.checkbox(&mut cleanup.strip_html, tr("cleanup-strip-html"));
+                ui.checkbox(&mut cleanup.plain_quotes, tr("cleanup-plain-quotes"));
+                let changed = match wizard.preview {
+                    Some((options, changed)) if options == *cleanup => changed,
+                    _ => {
+                        let mut copy = wizard.fiche.clone();
+                        let changed = copy.clean_notes(cleanup);
+                        wizard.preview = Some((*cleanup, changed));
+                        changed
+                    },
+                };
+                ui.label(egui::RichText::new(tr_args("notes-will-be-changed", &[("count", changed.to_string().into())]))
+                    .size(11.0)
+                    .color(egui::Color32::GRAY));
+                ui.separator();
+                
+                let label = ui.label(tr("skip-on-import"));
+                egui::ComboBox::from_id_salt("import_duplicates")
+                    .selected_text(self.settings.import_duplicates.label())
+                    .show_ui(ui, |ui| {
+                        for check in DuplicateCheck::ALL {
+                            ui.selectable_value(&mut self.settings.import_duplicates, check, check.label());
+                        }
+                    })
+                    .response
+                    .labelled_by(label.id);
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    import = ui.button(tr("import-notes")).clicked();
+                    cancel = ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if import {
+            let mut wizard = self.import_wizard.take().unwrap();
+            wizard.fiche.clean_notes(&self.settings.import_cleanup);
+            self.finish_import(wizard.path, wizard.fiche);
+        } else if !open || cancel {
+            self.import_wizard = None;
+        }
+    }
+    
+    fn finish_import(&mut self, path_str: String, other: Microfiche) {
+        let (count, skipped) = self.microfiche.import(&other, true, self.settings.import_duplicates);
+        if let Some(encoding) = other.source_encoding {
+            self.encoding_notice = Some(tr_args("converted-from-encoding", &[("path", path_str.to_string().into()), ("encoding", encoding.into())]));
+        }
+        self.log_activity(ActivityAction::Imported, path_str.clone(), &format!("{} notes", count));
+        self.status_message = tr_args("imported-notes-from-flagged-for-review", &[("count", count.to_string().into()), ("path_str", path_str.to_string().into())]);
+        if skipped > 0 {
+            self.status_message.push_str(&tr_args("skipped-duplicate-notes", &[("count", skipped.to_string().into())]));
+        }
+        let with_secrets = other.iter_notes().filter(|(_, _, _, note)| !scan_secrets(&note.text).is_empty()).count();
+        if with_secrets > 0 {
+            self.status_message.push_str(&tr_args("imported-notes-with-possible-secrets", &[("count", with_secrets.to_string().into())]));
+            self.show_secret_report = true;
+        }
+    }
+    
+    /// Pick a fiche pack and open the window for importing it
+    fn import_pack(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(&tr("fiche-pack"), &[pack::EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+        let path_str = path.to_string_lossy().to_string();
+        match fiche_file::read(&path_str) {
+            Ok(pack) => {
+                self.pack_import = Some(PackImport {
+                    path: path_str,
+                    pack,
+                    namespace: String::new(),
+                    conflict: pack::Conflict::Merge,
+                    flag_for_review: true,
+                });
+            },
+            Err(e) => self.status_message = tr_args("error-importing", &[("error", e.to_string().into())]),
+        }
+    }
+    
+    fn render_pack_import_window(&mut self, ctx: &egui::Context) {
+        let Some(import) = &mut self.pack_import else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        egui::Window::new(tr("import-pack"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let info = &import.pack.meta.info;
+                match info.title.trim() {
+                    "" => ui.strong(file_name(&import.path)),
+                    title => ui.strong(title),
+                };
+                let details = info.details();
+                if !details.is_empty() {
+                    ui.label(egui::RichText::new(details).size(11.0).color(egui::Color32::GRAY));
+                }
+                ui.label(tr_args("pack-contents", &[
+                    ("categories", import.pack.categories.len().to_string().into()),
+                    ("concepts", import.pack.iter_concepts().count().to_string().into()),
+                    ("notes", import.pack.note_count().to_string().into()),
+                    ("attachments", import.pack.attachments.len().to_string().into()),
+                ]));
+                ui.separator();
+                
+                let label = ui.label(tr("pack-namespace"));
+                ui.add(egui::TextEdit::singleline(&mut import.namespace).hint_text(tr("pack-namespace-hint")).desired_width(250.0))
+                    .labelled_by(label.id);
+                if let Some(cat) = import.pack.categories.keys().min_by(|a, b| collate(a, b)) {
+                    ui.label(egui::RichText::new(tr_args("pack-lands-as", &[("category", pack::namespaced(&import.namespace, cat).into())]))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY));
+                }
+                ui.separator();
+                
+                let conflicts = pack::conflicts(&self.microfiche, &import.pack, &import.namespace);
+                if conflicts == 0 {
+                    ui.label(tr("pack-no-conflicts"));
+                } else {
+                    let label = ui.label(tr_args("pack-conflicts", &[("count", conflicts.to_string().into())]));
+                    egui::ComboBox::from_id_salt("pack_conflict")
+                        .selected_text(import.conflict.label())
+                        .show_ui(ui, |ui| {
+                            for conflict in pack::Conflict::ALL {
+                                ui.selectable_value(&mut import.conflict, conflict, conflict.label());
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                }
+                ui.checkbox(&mut import.flag_for_review, tr("pack-flag-for-review"));
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    confirm = ui.button(tr("import-pack")).clicked();
+                    cancel = ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if confirm && let Some(import) = self.pack_import.take() {
+            let outcome = pack::apply(&mut self.microfiche, &import.pack, &import.namespace, import.conflict, import.flag_for_review);
+            let name = match import.pack.meta.info.title.trim() {
+                "" => file_name(&import.path),
+                title => title.to_string(),
+            };
+            self.log_activity(ActivityAction::Imported, import.path.clone(), &format!("{} notes from pack {}", outcome.notes, name));
+            self.status_message = tr_args("pack-imported", &[
+                ("count", outcome.notes.to_string().into()),
+                ("name", name.into()),
+                ("attachments", outcome.attachments.to_string().into()),
+            ]);
+            if outcome.merged + outcome.renamed + outcome.skipped + outcome.replaced > 0 {
+                self.status_message.push_str(&tr_args("pack-imported-conflicts", &[
+                    ("merged", outcome.merged.to_string().into()),
+                    ("renamed", outcome.renamed.to_string().into()),
+                    ("skipped", outcome.skipped.to_string().into()),
+                    ("replaced", outcome.replaced.to_string().into()),
+                ]));
+            }
+            if !outcome.renamed_attachments.is_empty() {
+                let names: Vec<String> = outcome.renamed_attachments.iter()
+                    .map(|(old, new)| format!("{} → {}", old, new))
+                    .collect();
+                self.status_message.push(' ');
+                self.status_message.push_str(&tr_args("pack-attachments-renamed", &[("names", names.join(", ").into())]));
+            }
+            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+            self.clusters = None;
+        } else if !open || cancel {
+            self.pack_import = None;
+        }
+    }
+    
+    fn render_pack_export_window(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.pack_export else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        let fiche = &self.microfiche;
+        egui::Window::new(tr("export-pack"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("export-pack-hint")).size(11.0).color(egui::Color32::GRAY));
+                egui::Grid::new("pack_info").num_columns(2).show(ui, |ui| {
+                    let label = ui.label(tr("file-info-title"));
+                    ui.add(egui::TextEdit::singleline(&mut export.info.title).hint_text(tr("pack-title-hint")).desired_width(300.0))
+                        .labelled_by(label.id);
+                    ui.end_row();
+                    let label = ui.label(tr("file-info-author"));
+                    ui.add(egui::TextEdit::singleline(&mut export.info.author).desired_width(300.0)).labelled_by(label.id);
+                    ui.end_row();
+                    let label = ui.label(tr("file-info-description"));
+                    ui.add(egui::TextEdit::multiline(&mut export.info.description).desired_width(300.0).desired_rows(3))
+                        .labelled_by(label.id);
+                    ui.end_row();
+                });
+                ui.separator();
+                
+                let mut cat_names: Vec<&String> = fiche.categories.keys().collect();
+                cat_names.sort_by(|a, b| collate(a, b));
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for cat in cat_names {
+                        let subcategories = &fiche.categories[cat].subcategories;
+                        let pairs: Vec<(String, String)> = subcategories.iter().map(|s| (cat.clone(), s.name.clone())).collect();
+                        let mut all = pairs.iter().all(|pair| export.selection.contains(pair));
+                        let partly = !all && pairs.iter().any(|pair| export.selection.contains(pair));
+                        if ui.add(egui::Checkbox::new(&mut all, fiche.meta.category_label(cat)).indeterminate(partly)).changed() {
+                            for pair in pairs {
+                                if all {
+                                    export.selection.insert(pair);
+                                } else {
+                                    export.selection.remove(&pair);
+                                }
+                            }
+                        }
+                        ui.indent(cat, |ui| {
+                            for subcat in subcategories {
+                                let pair = (cat.clone(), subcat.name.clone());
+                                let mut checked = export.selection.contains(&pair);
+                                if ui.checkbox(&mut checked, &subcat.name).changed() {
+                                    if checked {
+                                        export.selection.insert(pair);
+                                    } else {
+                                        export.selection.remove(&pair);
+                                    }
+                                }
+                            }
+                        });
+                    }
+                });
+                ui.separator();
+                
+                let notes: usize = fiche.categories.values()
+                    .flat_map(|c| c.subcategories.iter().map(move |s| (&c.name, s)))
+                    .filter(|(cat, s)| export.selection.contains(&((*cat).clone(), s.name.clone())))
+                    .flat_map(|(_, s)| &s.concepts)
+                    .map(|c| c.notes.len())
+                    .sum();
+                ui.label(tr_args("notes", &[("count", notes.to_string().into())]));
+                if !fiche.attachments.is_empty() {
+                    ui.checkbox(&mut export.attachments, tr_args("pack-include-attachments", &[("count", fiche.attachments.len().to_string().into())]));
+                }
+                ui.horizontal(|ui| {
+                    confirm = ui.add_enabled(notes > 0, egui::Button::new(tr("export-pack"))).clicked();
+                    cancel = ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if confirm {
+            self.export_pack();
+        } else if !open || cancel {
+            self.pack_export = None;
+        }
+    }
+    
+    /// Write the chosen part of the fiche as a pack, in the background
+    fn export_pack(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let Some(export) = &self.pack_export else {
+            return;
+        };
+        let name = match export.info.title.trim() {
+            "" => "pack".to_string(),
+            title => title.to_string(),
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(&tr("fiche-pack"), &[pack::EXTENSION])
+            .set_file_name(&format!("{}.{}", name, pack::EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        let export = self.pack_export.take().unwrap();
+        let fiche = pack::extract(&self.microfiche, &export.selection, export.info, export.attachments);
+        let target = path.to_string_lossy().to_string();
+        let label = tr_args("exporting", &[("path", target.clone().into())]);
+        let done = tr_args("pack-exported", &[("count", fiche.note_count().to_string().into()), ("path", target.clone().into())]);
+        self.start_job(JobKind::Export, label, move |progress| {
+            write_replacing(&target, |partial| fiche_file::write(&fiche, partial, progress))?;
+            Ok(done)
+        });
+    }
+    
+    fn render_feed_export_window(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.feed_export else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        let fiche = &self.microfiche;
+        egui::Window::new(tr("export-feed"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("export-feed-hint")).size(11.0).color(egui::Color32::GRAY));
+                let mut cat_names: Vec<&String> = fiche.categories.keys().collect();
+                cat_names.sort_by(|a, b| collate(a, b));
+                egui::Grid::new("feed_export").num_columns(2).show(ui, |ui| {
+                    let label = ui.label(tr("category-label"));
+                    ui.add_enabled_ui(!export.per_category, |ui| {
+                        let selected = export.category.as_deref().map_or_else(|| tr("all-categories"), |cat| fiche.meta.category_label(cat));
+                        egui::ComboBox::from_id_salt("feed_category")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut export.category, None, tr("all-categories"));
+                                for cat in &cat_names {
+                                    ui.selectable_value(&mut export.category, Some((*cat).clone()), fiche.meta.category_label(cat));
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id);
+                    });
+                    ui.end_row();
+                    ui.label("");
+                    ui.checkbox(&mut export.per_category, tr("feed-per-category"))
+                        .on_hover_text(tr("feed-per-category-hint"));
+                    ui.end_row();
+                    let label = ui.label(tr("feed-limit-label"));
+                    ui.add(egui::DragValue::new(&mut export.limit).range(1..=1000)).labelled_by(label.id);
+                    ui.end_row();
+                    let label = ui.label(tr("feed-link-label"));
+                    ui.add(egui::TextEdit::singleline(&mut export.link).hint_text("https://example.com/notes/").desired_width(300.0))
+                        .labelled_by(label.id)
+                        .on_hover_text(tr("feed-link-hint"));
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    confirm = ui.add_enabled(!fiche.categories.is_empty(), egui::Button::new(tr("export-feed"))).clicked();
+                    cancel = ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if confirm {
+            self.export_feed();
+        } else if !open || cancel {
+            self.feed_export = None;
+        }
+    }
+    
+    /// Write the RSS feed, or one per category into a folder
+    fn export_feed(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let Some(export) = &self.feed_export else {
+            return;
+        };
+        let target = if export.per_category {
+            rfd::FileDialog::new().pick_folder()
+        } else {
+            let name = export.category.as_deref().map_or_else(|| "feed.xml".to_string(), |cat| category_file_name(cat, "feed", "xml"));
+            rfd::FileDialog::new()
+                .add_filter("RSS", &["xml", "rss"])
+                .set_file_name(&name)
+                .save_file()
+        };
+        let Some(target) = target else {
+            return;
+        };
+        let export = self.feed_export.take().unwrap();
+        let fiche = self.microfiche.clone();
+        let info = &fiche.meta.info;
+        let name = match info.title.trim() {
+            "" => self.current_file.as_deref().map_or_else(|| APP_TITLE.to_string(), file_name),
+            title => title.to_string(),
+        };
+        let description = info.description.clone();
+        let categories: Vec<Option<String>> = if export.per_category {
+            fiche.categories.keys().cloned().map(Some).collect()
+        } else {
+            vec![export.category.clone()]
+        };
+        let label = tr_args("exporting", &[("path", target.display().to_string().into())]);
+        let done = tr_args("feed-exported", &[("count", categories.len().to_string().into()), ("path", target.display().to_string().into())]);
+        self.start_job(JobKind::Export, label, move |progress| {
+            progress.start(categories.len());
+            for category in categories {
+                let title = match &category {
+                    Some(cat) => format!("{} – {}", name, cat),
+                    None => name.clone(),
+                };
+                let xml = feed::rss(&fiche, &feed::Options {
+                    title: &title,
+                    description: &description,
+                    link: export.link.trim(),
+                    category: category.as_deref(),
+                    limit: export.limit,
+                });
+                let path = match (&category, export.per_category) {
+                    (Some(cat), true) => target.join(category_file_name(cat, "feed", "xml")),
+                    _ => target.clone(),
+                };
+                write_replacing(&path.to_string_lossy(), |partial| Ok(std::fs::write(partial, &xml)?))?;
+                progress.advance(1)?;
+            }
+            Ok(done)
+        });
+    }
+    
+    fn render_calendar_export_window(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.calendar_export else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        let fiche = &self.microfiche;
+        egui::Window::new(tr("export-calendar"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("export-calendar-hint")).size(11.0).color(egui::Color32::GRAY));
+                if export.date_fields.is_empty() {
+                    ui.label(tr("calendar-no-date-fields"));
+                } else {
+                    let mut cat_names: Vec<&String> = fiche.categories.keys().collect();
+                    cat_names.sort_by(|a, b| collate(a, b));
+                    egui::Grid::new("calendar_export").num_columns(2).show(ui, |ui| {
+                        let label = ui.label(tr("category-label"));
+                        let selected = export.category.as_deref().map_or_else(|| tr("all-categories"), |cat| fiche.meta.category_label(cat));
+                        egui::ComboBox::from_id_salt("calendar_category")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut export.category, None, tr("all-categories"));
+                                for cat in &cat_names {
+                                    ui.selectable_value(&mut export.category, Some((*cat).clone()), fiche.meta.category_label(cat));
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id);
+                        ui.end_row();
+                        ui.label(tr("calendar-fields-label"));
+                        ui.vertical(|ui| {
+                            for field in &export.date_fields {
+                                let mut checked = export.fields.contains(field);
+                                if ui.checkbox(&mut checked, field).changed() {
+                                    if checked {
+                                        export.fields.insert(field.clone());
+                                    } else {
+                                        export.fields.remove(field);
+                                    }
+                                }
+                            }
+                        });
+                        ui.end_row();
+                    });
+                }
+                ui.horizontal(|ui| {
+                    confirm = ui.add_enabled(!export.fields.is_empty(), egui::Button::new(tr("export-calendar"))).clicked();
+                    cancel = ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if confirm {
+            self.export_calendar();
+        } else if !open || cancel {
+            self.calendar_export = None;
+        }
+    }
+    
+    /// Write the chosen date fields' events as an iCalendar file
+    fn export_calendar(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("iCalendar", &["ics"])
+            .set_file_name("fisha.ics")
+            .save_file()
+        else {
+            return;
+        };
+        let export = self.calendar_export.take().unwrap();
+        let name = match self.microfiche.meta.info.title.trim() {
+            "" => self.current_file.as_deref().map_or_else(|| APP_TITLE.to_string(), file_name),
+            title => title.to_string(),
+        };
+        let (ics, count) = calendar::ics(&self.microfiche, &calendar::Options {
+            name: &name,
+            fields: &export.fields,
+            category: export.category.as_deref(),
+        });
+        let label = tr_args("exporting", &[("path", path.display().to_string().into())]);
+        let done = tr_args("calendar-exported", &[("count", count.to_string().into()), ("path", path.display().to_string().into())]);
+        self.start_job(JobKind::Export, label, move |progress| {
+            progress.start(1);
+            write_replacing(&path.to_string_lossy(), |partial| Ok(std::fs::write(partial, &ics)?))?;
+            progress.advance(1)?;
+            Ok(done)
+        });
+    }
+    
+    fn render_markdown_export_window(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.markdown_export else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        let fiche = &self.microfiche;
+        egui::Window::new(tr("export-markdown"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("export-markdown-hint")).size(11.0).color(egui::Color32::GRAY));
+                let mut cat_names: Vec<&String> = fiche.categories.keys().collect();
+                cat_names.sort_by(|a, b| collate(a, b));
+                egui::Grid::new("markdown_export").num_columns(2).show(ui, |ui| {
+                    let label = ui.label(tr("category-label"));
+                    ui.add_enabled_ui(!export.per_category, |ui| {
+                        let selected = export.category.as_deref().map_or_else(|| tr("all-categories"), |cat| fiche.meta.category_label(cat));
+                        let before = export.category.clone();
+                        egui::ComboBox::from_id_salt("markdown_category")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut export.category, None, tr("all-categories"));
+                                for cat in &cat_names {
+                                    ui.selectable_value(&mut export.category, Some((*cat).clone()), fiche.meta.category_label(cat));
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id);
+                        if export.category != before {
+                            export.subcategory = None;
+                        }
+                    });
+                    ui.end_row();
+                    let label = ui.label(tr("subcategory-label"));
+                    let subcategories = export.category.as_ref()
+                        .filter(|_| !export.per_category)
+                        .and_then(|cat| fiche.categories.get(cat));
+                    ui.add_enabled_ui(subcategories.is_some(), |ui| {
+                        egui::ComboBox::from_id_salt("markdown_subcategory")
+                            .selected_text(export.subcategory.clone().unwrap_or_else(|| tr("all-subcategories")))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut export.subcategory, None, tr("all-subcategories"));
+                                for subcat in subcategories.into_iter().flat_map(|c| &c.subcategories) {
+                                    ui.selectable_value(&mut export.subcategory, Some(subcat.name.clone()), &subcat.name);
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id);
+                    });
+                    ui.end_row();
+                    ui.label("");
+                    ui.checkbox(&mut export.per_category, tr("markdown-per-category"))
+                        .on_hover_text(tr("markdown-per-category-hint"));
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    confirm = ui.add_enabled(!fiche.categories.is_empty(), egui::Button::new(tr("export-markdown"))).clicked();
+                    cancel = ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if confirm {
+            self.export_markdown();
+        } else if !open || cancel {
+            self.markdown_export = None;
+        }
+    }
+    
+    /// Write the chosen part of the fiche as a Markdown file, or every
+    /// category as its own file in a folder
+    fn export_markdown(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let Some(export) = &self.markdown_export else {
+            return;
+        };
+        let target = if export.per_category {
+            rfd::FileDialog::new().pick_folder()
+        } else {
+            let name = match (&export.category, &export.subcategory) {
+                (Some(_), Some(sub)) => category_file_name(sub, "notes", "md"),
+                (Some(cat), None) => category_file_name(cat, "notes", "md"),
+                _ => self.current_file.as_deref()
+                    .and_then(|file| Path::new(file).file_stem())
+                    .map_or_else(|| "notes.md".to_string(), |stem| format!("{}.md", stem.to_string_lossy())),
+            };
+            rfd::FileDialog::new()
+                .add_filter("Markdown", &["md"])
+                .set_file_name(&name)
+                .save_file()
+        };
+        let Some(target) = target else {
+            return;
+        };
+        let export = self.markdown_export.take().unwrap();
+        let fiche = &self.microfiche;
+        // (category, subcategory, file) for each file written
+        let scopes: Vec<(Option<String>, Option<String>, std::path::PathBuf)> = if export.per_category {
+            fiche.categories.keys()
+                .map(|cat| (Some(cat.clone()), None, target.join(category_file_name(cat, "notes", "md"))))
+                .collect()
+        } else {
+            vec![(export.category.clone(), export.subcategory.clone(), target.clone())]
+        };
+        let mut count = 0;
+        let files: Vec<(std::path::PathBuf, String)> = scopes.into_iter()
+            .map(|(category, subcategory, path)| {
+                let (text, notes) = markdown_export::document(fiche, &markdown_export::Options {
+                    category: category.as_deref(),
+                    subcategory: subcategory.as_deref(),
+                });
+                count += notes;
+                (path, text)
+            })
+            .collect();
+        let label = tr_args("exporting", &[("path", target.display().to_string().into())]);
+        let done = tr_args("markdown-exported", &[
+            ("count", count.to_string().into()),
+            ("files", files.len().to_string().into()),
+            ("path", target.display().to_string().into()),
+        ]);
+        self.start_job(JobKind::Export, label, move |progress| {
+            progress.start(files.len());
+            for (path, text) in &files {
+                write_replacing(&path.to_string_lossy(), |partial| Ok(std::fs::write(partial, text)?))?;
+                progress.advance(1)?;
+            }
+            Ok(done)
+        });
+    }
+    
+    /// Write the term co-occurrence network as GEXF or GraphML, by extension
+    fn export_term_network(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("GEXF", &["gexf"])
+            .add_filter("GraphML", &["graphml"])
+            .set_file_name("terms.gexf")
+            .save_file()
+        else {
+            return;
+        };
+        
+        
+        let network = self.microfiche.term_network();
+        let label = tr_args("exporting", &[("path", path.display().to_string().into())]);
+        let done = tr_args("term-network-exported", &[
+            ("terms", network.nodes().len().to_string().into()),
+            ("links", network.pairs.len().to_string().into()),
+            ("path", path.display().to_string().into()),
+        ]);
+        self.start_job(JobKind::Export, label, move |progress| {
+            progress.start(network.nodes().len() + network.pairs.len());
+            let path_str = path.to_string_lossy().to_string();
+            write_replacing(&path_str, |partial| {
+                let mut out = std::io::BufWriter::new(std::fs::File::create(partial)?);
+                if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("graphml")) {
+                    network.write_graphml(&mut out, progress)?;
+                } else {
+                    network.write_gexf(&mut out, progress)?;
+                }
+                Ok(std::io::Write::flush(&mut out)?)
+            })?;
+            Ok(done)
+        });
+    }
+    
+    fn capture_workspace(&self) -> Workspace {
+        Workspace {
+            file: self.current_file.clone(),
+            view_mode: self.view_mode,
+            selected_category: self.selected_category.clone(),
+            selected_subcategory: self.selected_subcategory.clone(),
+            search_query: self.search_query.clone(),
+            min_rating: self.search_filter.min_rating,
+            search_category: self.search_filter.category.clone(),
+            search_flag: self.search_filter.flag,
+            search_tags: self.search_filter.tags.clone(),
+            search_author: self.search_filter.author.clone(),
+            stats_scope: self.stats_scope.clone(),
+        }
+    }
+    
+    /// Remember where the current workspace was left, then restore `name`,
+    /// opening its file if another one is open
+    fn switch_workspace(&mut self, name: &str) {
+        let Some(workspace) = self.workspaces.get(name).cloned() else {
+            return;
+        };
+        if self.job_running() {
+            return;
+        }
+        if let Some(current) = self.active_workspace.clone() {
+            self.workspaces.insert(current, self.capture_workspace());
+        }
+        
+        if let Some(file) = &workspace.file
+            && self.current_file.as_ref() != Some(file)
+        {
+            self.load_file(file.clone());
+            if self.current_file.as_ref() != Some(file) {
+                // Couldn't open it; the status bar says why
+                return;
+            }
+        }
+        self.view_mode = workspace.view_mode;
+        self.selected_category = workspace.selected_category;
+        self.selected_subcategory = workspace.selected_subcategory;
+        self.selected_concept = None;
+        self.search_query = workspace.search_query;
+        self.search_filter.min_rating = workspace.min_rating;
+        self.search_filter.category = workspace.search_category;
+        self.search_filter.flag = workspace.search_flag;
+        self.search_filter.tags = workspace.search_tags;
+        self.search_filter.author = workspace.search_author;
+        self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        self.stats_scope = workspace.stats_scope;
+        self.active_workspace = Some(name.to_string());
+        self.status_message = tr_args("switched-to-workspace", &[("name", name.to_string().into())]);
+    }
+    
+    /// The startup "who's using Fisha" picker, and naming a new profile
+    fn render_profile_windows(&mut self, ctx: &egui::Context) {
+        if self.choosing_profile {
+            let mut chosen = None;
+            egui::Window::new(tr("choose-profile"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    for profile in &self.profiles {
+                        if ui.add_sized([200.0, 28.0], egui::Button::new(profile)).clicked() {
+                            chosen = Some(profile.clone());
+                        }
+                    }
+                });
+            if let Some(profile) = chosen {
+                self.choosing_profile = false;
+                if profile != self.profile {
+                    self.switch_profile = Some(profile);
+                }
+            }
+        }
+        
+        let Some(name) = &mut self.new_profile_name else {
+            return;
+        };
+        let mut open = true;
+        let mut create = false;
+        egui::Window::new(tr("new-profile"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(tr("profile-keeps-its-own-settings-workspaces-draft"));
+                let response = ui.horizontal(|ui| {
+                    let label = ui.label(tr("name-label"));
+                    ui.text_edit_singleline(name).labelled_by(label.id)
+                }).inner;
+                let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let valid = !name.trim().is_empty() && !self.profiles.iter().any(|p| p == name.trim());
+                if self.activity.unsaved > 0 {
+                    ui.label(egui::RichText::new(tr("switching-profile-closes-the-file-unsaved"))
+                        .size(11.0)
+                        .color(ui.visuals().warn_fg_color));
+                }
+                if (ui.add_enabled(valid, egui::Button::new(tr("create-profile"))).clicked() || entered) && valid {
+                    create = true;
+                }
+            });
+        
+        if create {
+            self.switch_profile = Some(name.trim().to_string());
+            open = false;
+        }
+        if !open {
+            self.new_profile_name = None;
+        }
+    }
+    
+    fn render_new_workspace_window(&mut self, ctx: &egui::Context) {
+        let Some(name) = &mut self.new_workspace_name else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut save = false;
+        egui::Window::new(tr("save-workspace"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(tr("workspace-saves-the-open-file-selections-and"));
+                let response = ui.horizontal(|ui| {
+                    let label = ui.label(tr("name-label"));
+                    ui.text_edit_singleline(name).labelled_by(label.id)
+                }).inner;
+                let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if self.workspaces.contains_key(name.trim()) {
+                    ui.label(egui::RichText::new(tr("replaces-the-workspace-with-this-name"))
+                        .size(11.0)
+                        .color(ui.visuals().warn_fg_color));
+                }
+                let valid = !name.trim().is_empty();
+                if (ui.add_enabled(valid, egui::Button::new(tr("save"))).clicked() || entered) && valid {
+                    save = true;
+                }
+            });
+        
+        if save {
+            let name = name.trim().to_string();
+            self.workspaces.insert(name.clone(), self.capture_workspace());
+            self.status_message = tr_args("saved-workspace", &[("name", name.clone().into())]);
+            self.active_workspace = Some(name);
+            open = false;
+        }
+        if !open {
+            self.new_workspace_name = None;
+        }
+    }
+    
+    /// Overview, top terms, recent activity and health warnings as Markdown or PDF
+    fn export_report(&mut self) {
+        if self.job_running() {
+            return;
+        }
+        let month = chrono::Local::now().format("%Y-%m");
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Markdown", &["md"])
+            .add_filter("PDF", &["pdf"])
+            .set_file_name(&format!("report-{}.md", month))
+            .save_file()
+        else {
+            return;
+        };
+        
+        let fiche = self.microfiche.clone();
+        let activity = self.activity.entries.clone();
+        let title = match &self.current_file {
+            Some(file) => tr_args("report-title", &[("file", file_name(file).into())]),
+            None => tr("report-title-default"),
+        };
+        let label = tr_args("exporting", &[("path", path.display().to_string().into())]);
+        let done = tr_args("report-exported", &[("path", path.display().to_string().into())]);
+        self.start_job(JobKind::Export, label, move |progress| {
+            let markdown = report::markdown(&fiche, &activity, &title, progress)?;
+            let path_str = path.to_string_lossy().to_string();
+            write_replacing(&path_str, |partial| {
+                if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf")) {
+                    std::fs::write(partial, report::pdf(&markdown))?;
+                } else {
+                    std::fs::write(partial, &markdown)?;
+                }
+                Ok(())
+            })?;
+            Ok(done)
+        });
+    }
+    
+    /// Every note that looks like it contains credentials, with a way to jump to it
+    fn render_secret_report(&mut self, ctx: &egui::Context) {
+        if !self.show_secret_report {
+            return;
+        }
+        
+        let flagged: Vec<(String, String, String, Vec<&'static str>)> = self.microfiche.iter_notes()
+            .filter_map(|(cat, sub, con, note)| {
+                let kinds = scan_secrets(&note.text);
+                (!kinds.is_empty()).then(|| (cat.to_string(), sub.to_string(), con.to_string(), kinds))
+            })
+            .collect();
+        let mut open = true;
+        let mut go_to = None;
+        egui::Window::new(tr("secret-scan-report"))
+            .open(&mut open)
+            .default_size([500.0, 400.0])
+            .show(ctx, |ui| {
+                if flagged.is_empty() {
+                    ui.label(tr("no-notes-look-like-they-contain-secrets"));
+                    return;
+                }
+                ui.label(tr_args("notes-may-contain-secrets", &[("count", flagged.len().to_string().into())]));
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (cat, sub, con, kinds) in &flagged {
+                        ui.horizontal(|ui| {
+                            if ui.small_button(tr("show")).clicked() {
+                                go_to = Some((cat.clone(), sub.clone(), con.clone()));
+                            }
+                            ui.label(note_path(cat, sub, con));
+                            ui.label(egui::RichText::new(kinds.join(", ")).color(ui.visuals().error_fg_color));
+                        });
+                    }
+                });
+            });
+        
+        if let Some((cat, sub, con)) = go_to {
+            self.view_mode = ViewMode::Browse;
+            self.selected_category = Some(cat);
+            self.selected_subcategory = Some(sub);
+            self.selected_concept = Some(con);
+        }
+        if !open {
+            self.show_secret_report = false;
+        }
+    }
+    
+    fn render_aging_window(&mut self, ctx: &egui::Context) {
+        if !self.show_aging {
+            return;
+        }
+        
+        let today = chrono::Local::now().date_naive();
+        let stale = self.microfiche.stale_notes(&self.activity.entries, today);
+        let mut open = true;
+        let mut go_to = None;
+        let mut action: Option<(ActivityAction, NoteTarget)> = None;
+        let mut settings_changed = false;
+        egui::Window::new(tr("aging"))
+            .open(&mut open)
+            .default_size([560.0, 450.0])
+            .show(ctx, |ui| {
+                let aging = &mut self.microfiche.meta.aging;
+                ui.horizontal(|ui| {
+                    ui.label(tr("aging-stale-after"));
+                    settings_changed |= ui.add(egui::DragValue::new(&mut aging.stale_days).range(7..=3650)).changed();
+                    ui.label(tr("aging-days"));
+                    settings_changed |= ui.checkbox(&mut aging.all_categories, tr("aging-all-categories"))
+                        .on_hover_text(tr("aging-all-categories-hint"))
+                        .changed();
+                });
+                ui.separator();
+                if stale.is_empty() {
+                    ui.label(tr("aging-nothing-stale"));
+                    return;
+                }
+                ui.label(tr_args("aging-stale-notes", &[("count", stale.len().to_string().into())]));
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for entry in stale.iter().take(AGING_LISTED) {
+                        let (cat, sub, con, text) = &entry.note;
+                        ui.group(|ui| {
+                            ui.set_width(ui.available_width());
+                            ui.horizontal(|ui| {
+                                ui.strong(note_path(cat, sub, con));
+                                let age = entry.last_touched.clone().unwrap_or_else(|| tr("aging-undated"));
+                                ui.label(egui::RichText::new(age).size(11.0).color(egui::Color32::GRAY));
+                            });
+                            let shown = if self.settings.masks(cat) { masked_text(text) } else { excerpt(text, 160) };
+                            ui.label(shown);
+                            ui.horizontal(|ui| {
+                                if ui.small_button(tr("aging-still-valid")).clicked() {
+                                    action = Some((ActivityAction::Confirmed, entry.note.clone()));
+                                }
+                                if ui.small_button(tr_args("aging-snooze", &[("days", AGING_SNOOZE_DAYS.to_string().into())])).clicked() {
+                                    action = Some((ActivityAction::Snoozed, entry.note.clone()));
+                                }
+                                if ui.small_button(tr("aging-archive")).on_hover_text(tr("aging-archive-hint")).clicked() {
+                                    action = Some((ActivityAction::Archived, entry.note.clone()));
+                                }
+                                if ui.small_button(tr("show")).clicked() {
+                                    go_to = Some((cat.clone(), sub.clone(), con.clone()));
+                                }
+                            });
+                        });
+                    }
+                    if stale.len() > AGING_LISTED {
+                        ui.label(egui::RichText::new(tr_args("aging-more", &[("count", (stale.len() - AGING_LISTED).to_string().into())]))
+                            .color(egui::Color32::GRAY));
+                    }
+                });
+            });
+        
+        if let Some((kind, (cat, sub, con, text))) = action {
+            let key = note_key(&cat, &sub, &con, &text);
+            let aging = &mut self.microfiche.meta.aging;
+            let day = |date: chrono::NaiveDate| date.format("%Y-%m-%d").to_string();
+            match kind {
+                ActivityAction::Confirmed => {
+                    aging.confirmed.insert(key, day(today));
+                },
+                ActivityAction::Snoozed => {
+                    aging.snoozed.insert(key, day(today + chrono::Duration::days(AGING_SNOOZE_DAYS)));
+                },
+                _ => {
+                    aging.archived.insert(key);
+                },
+            }
+            self.log_activity(kind, note_path(&cat, &sub, &con), &text);
+        }
+        if settings_changed {
+            self.meta_changed();
+        }
+        if let Some((cat, sub, con)) = go_to {
+            self.view_mode = ViewMode::Browse;
+            self.selected_category = Some(cat);
+            self.selected_subcategory = Some(sub);
+            self.selected_concept = Some(con);
+        }
+        if !open {
+            self.show_aging = false;
+        }
+    }
+    
+    /// Vim mode keys, read while no text field has focus
+    fn handle_vim_keys(&mut self, ctx: &egui::Context) {
+        // The list views report their length and cursor note again while drawing
+        let list_len = std::mem::take(&mut self.vim.list_len);
+        let target = self.vim.target.take();
+        self.vim.cursor = self.vim.cursor.min(list_len.saturating_sub(1));
+        
+        if !self.settings.vim_mode
+            || ctx.wants_keyboard_input()
+            || self.vim.palette_open
+            || self.vim.confirm_delete.is_some()
+        {
+            self.vim.pending_delete = false;
+            return;
+        }
+        
+        let typed: String = ctx.input_mut(|input| {
+            let mut typed = String::new();
+            input.events.retain(|event| match event {
+                egui::Event::Text(text) => {
+                    typed.push_str(text);
+                    false
+                }
+                _ => true,
+            });
+            typed
+        });
+        if ctx.input(|input| input.key_pressed(egui::Key::Escape)) {
+            self.vim.pending_delete = false;
+        }
+        
+        for key in typed.chars() {
+            let pending_delete = std::mem::take(&mut self.vim.pending_delete);
+            match key {
+                'j' if list_len > 0 => {
+                    self.vim.cursor = (self.vim.cursor + 1).min(list_len - 1);
+                    self.vim.scroll = true;
+                }
+                'k' => {
+                    self.vim.cursor = self.vim.cursor.saturating_sub(1);
+                    self.vim.scroll = true;
+                }
+                'd' if pending_delete => self.vim.confirm_delete = target.clone(),
+                'd' => self.vim.pending_delete = true,
+                '/' => {
+                    self.view_mode = ViewMode::Search;
+                    self.vim.focus_search = true;
+                }
+                ':' => {
+                    self.vim.palette_query.clear();
+                    self.vim.palette_open = true;
+                }
+                'u' => self.undo(),
+                _ => {}
+            }
+        }
+    }
+    
+    /// The open file's title, when File Info gives one, then the app's
+    fn update_title(&mut self, ctx: &egui::Context) {
+        let mut title = match self.microfiche.meta.info.title.trim() {
+            "" => app_title(&self.settings).to_string(),
+            file_title => format!("{} — {}", file_title, app_title(&self.settings)),
+        };
+        if self.dirty {
+            title.insert_str(0, "• ");
+        }
+        if title != self.shown_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.shown_title = title;
+        }
+    }
+    
+    /// Run the command bound to a pressed shortcut, or bind the shortcut
+    /// being recorded in the Keyboard Shortcuts window. Undo and redo are left
+    /// to a focused text field, which has its own
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if let Some(command) = self.recording_shortcut {
+            let pressed = ctx.input_mut(|input| {
+                let at = input.events.iter().position(|event| matches!(event, egui::Event::Key { pressed: true, repeat: false, .. }))?;
+                match input.events.remove(at) {
+                    egui::Event::Key { key, mut modifiers, .. } => {
+                        // Stored as Ctrl on Windows and Linux and Cmd on macOS, like the defaults
+                        if modifiers.command {
+                            modifiers.ctrl = false;
+                            modifiers.mac_cmd = false;
+                        }
+                        Some(egui::KeyboardShortcut::new(modifiers, key))
+                    },
+                    _ => None,
+                }
+            });
+            match pressed {
+                Some(shortcut) if shortcut.logical_key == egui::Key::Escape && shortcut.modifiers.is_none() => {
+                    self.recording_shortcut = None;
+                },
+                Some(shortcut) => {
+                    // A shortcut runs one command
+                    self.settings.shortcuts.retain(|(_, bound)| *bound != shortcut);
+                    self.settings.shortcuts.push((command, shortcut));
+                    self.recording_shortcut = None;
+                },
+                None => {},
+            }
+            return;
+        }
+        if self.vim.palette_open || self.vim.confirm_delete.is_some() {
+            return;
+        }
+        
+        let typing = ctx.wants_keyboard_input();
+        let mut bindings = self.settings.shortcuts.clone();
+        // Ctrl+Shift+Z first, since Ctrl+Z would match it too
+        bindings.sort_by_key(|(_, shortcut)| {
+            let modifiers = shortcut.modifiers;
+            std::cmp::Reverse([modifiers.alt, modifiers.ctrl, modifiers.shift, modifiers.mac_cmd, modifiers.command].iter().filter(|&&held| held).count())
+        });
+        for (command, shortcut) in bindings {
+            let applies = match command {
+                Command::Undo | Command::Redo => !typing,
+                Command::Dismiss => self.show_theme_selector || self.view_mode == ViewMode::Search && !self.search_query.is_empty(),
+                _ => true,
+            };
+            if applies && ctx.input_mut(|input| input.consume_shortcut(&shortcut)) {
+                self.run_command(command);
+                break;
+            }
+        }
+    }
+    
+    /// Every command with its shortcuts, which can be removed, added by
+    /// pressing them, or reset
+    fn render_shortcuts_window(&mut self, ctx: &egui::Context) {
+        if !self.show_shortcuts {
+            return;
+        }
+        
+        let mut open = true;
+        let mut reset = false;
+        egui::Window::new(tr("keyboard-shortcuts"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("keyboard-shortcuts-hint")).size(11.0).color(egui::Color32::GRAY));
+                egui::Grid::new("shortcuts_grid").num_columns(2).striped(true).show(ui, |ui| {
+                    for command in Command::ALL {
+                        ui.label(command.label());
+                        ui.horizontal(|ui| {
+                            let mut unbind = None;
+                            for (bound, shortcut) in &self.settings.shortcuts {
+                                if *bound == command {
+                                    let chip = ui.small_button(format!("{} ✖", ctx.format_shortcut(shortcut)));
+                                    if accessible(chip, tr("remove-shortcut")).on_hover_text(tr("remove-shortcut")).clicked() {
+                                        unbind = Some(*shortcut);
+                                    }
+                                }
+                            }
+                            if let Some(shortcut) = unbind {
+                                self.settings.shortcuts.retain(|binding| *binding != (command, shortcut));
+                            }
+                            if self.recording_shortcut == Some(command) {
+                                ui.label(egui::RichText::new(tr("press-a-shortcut")).italics());
+                            } else if accessible(ui.small_button("+"), tr("add-shortcut")).on_hover_text(tr("add-shortcut")).clicked() {
+                                self.recording_shortcut = Some(command);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                reset = ui.button(tr("reset-shortcuts")).clicked();
+            });
+        
+        if reset {
+            self.settings.shortcuts = default_shortcuts();
+            self.recording_shortcut = None;
+        }
+        if !open {
+            self.show_shortcuts = false;
+            self.recording_shortcut = None;
+        }
+    }
+    
+    fn undo(&mut self) {
+        match self.history.undo(&mut self.microfiche) {
+            Some(change) => self.history_stepped(ActivityAction::Undone, change),
+            None => self.status_message = tr("nothing-to-undo"),
+        }
+    }
+    
+    fn redo(&mut self) {
+        match self.history.redo(&mut self.microfiche) {
+            Some(change) => self.history_stepped(ActivityAction::Redone, change),
+            None => self.status_message = tr("nothing-to-redo"),
+        }
+    }
+    
+    /// Refresh what was computed from the fiche and log the undo or redo,
+    /// without it becoming an undo step of its own
+    fn history_stepped(&mut self, action: ActivityAction, change: String) {
+        self.merge_undo = None;
+        self.clusters = None;
+        self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        self.status_message = match action {
+            ActivityAction::Redone => tr_args("redone", &[("change", change.clone().into())]),
+            _ => tr_args("undone", &[("change", change.clone().into())]),
+        };
+        self.activity.record(action, String::new(), change, &self.session_note);
+        self.history.seen = self.activity.entries.len();
+        self.dirty = true;
+    }
+    
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::View(view_mode) => self.view_mode = view_mode,
+            Command::Open => self.open_file(),
+            Command::Import => self.import_file(),
+            Command::Save => self.save_file(),
+            Command::SaveAs => self.save_file_as(),
+            Command::ExportTermNetwork => self.export_term_network(),
+            Command::Undo => self.undo(),
+            Command::Redo => self.redo(),
+            Command::Theme => self.show_theme_selector = true,
+            Command::Settings => self.show_settings = true,
+            Command::Find => {
+                self.view_mode = ViewMode::Search;
+                self.vim.focus_search = true;
+            },
+            Command::Dismiss => {
+                if self.show_theme_selector {
+                    self.show_theme_selector = false;
+                } else {
+                    self.search_query.clear();
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                }
+            },
+            Command::Shortcuts => self.show_shortcuts = true,
+        }
+    }
+    
+    /// The first shortcut bound to `command`, written for this platform
+    fn shortcut_text(&self, ctx: &egui::Context, command: Command) -> String {
+        self.settings.shortcuts.iter()
+            .find(|(bound, _)| *bound == command)
+            .map(|(_, shortcut)| ctx.format_shortcut(shortcut))
+            .unwrap_or_default()
+    }
+    
+    fn render_command_palette(&mut self, ctx: &egui::Context) {
+        if !self.vim.palette_open {
+            return;
+        }
+        
+        let query = fold_text(&self.vim.palette_query);
+        let matches: Vec<Command> = Command::ALL.into_iter()
+            .filter(|command| fold_text(&command.label()).contains(&query))
+            .collect();
+        let mut picked = None;
+        let mut close = ctx.input(|input| input.key_pressed(egui::Key::Escape));
+        
+        egui::Window::new(tr("command-palette"))
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(":");
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.vim.palette_query)
+                        .hint_text(tr("type-a-command"))
+                        .desired_width(300.0));
+                    response.request_focus();
+                    if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                        picked = matches.first().copied();
+                    }
+                });
+                ui.separator();
+                for command in &matches {
+                    let shortcut = self.shortcut_text(ctx, *command);
+                    if ui.add(egui::Button::new(command.label()).shortcut_text(shortcut).frame(false)).clicked() {
+                        picked = Some(*command);
+                    }
+                }
+                if matches.is_empty() {
+                    ui.label(egui::RichText::new(tr("no-matching-commands")).color(egui::Color32::GRAY));
+                }
+            });
+        
+        if let Some(command) = picked {
+            self.run_command(command);
+            close = true;
+        }
+        if close {
+            self.vim.palette_open = false;
+        }
+    }
+    
+    fn render_vim_delete_confirm(&mut self, ctx: &egui::Context) {
+        let Some((cat, sub, con, note)) = self.vim.confirm_delete.clone() else { return };
+        
+        let mut confirmed = ctx.input(|input| input.key_pressed(egui::Key::Y) || input.key_pressed(egui::Key::Enter));
+        let mut cancelled = ctx.input(|input| input.key_pressed(egui::Key::N) || input.key_pressed(egui::Key::Escape));
+        
+        egui::Window::new(tr("delete-note"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.strong(note_path(&cat, &sub, &con));
+                let preview: String = note.chars().take(200).collect();
+                ui.label(egui::RichText::new(preview).italics());
+                ui.separator();
+                ui.horizontal(|ui| {
+                    confirmed |= ui.button(tr("delete-y")).clicked();
+                    cancelled |= ui.button(tr("cancel-n")).clicked();
+                });
+            });
+        
+        if confirmed {
+            if let Some(removed) = self.microfiche.delete_note(&cat, &sub, &con, &note) {
+                self.log_activity(ActivityAction::Deleted, note_path(&cat, &sub, &con), &note);
+                self.notify(webhook::Event::Deleted, (&cat, &sub, &con), &removed, None);
+                self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                self.status_message = tr("entry-deleted");
+            }
+            self.vim.confirm_delete = None;
+        } else if cancelled {
+            self.vim.confirm_delete = None;
+        }
+    }
+    
+    /// File > Open Recent: the favorites, then the other recent files
+    fn render_recent_files_menu(&mut self, ui: &mut egui::Ui) {
+        let pinned = self.settings.pinned_files.clone();
+        let recent: Vec<String> = self.settings.recent_files.iter()
+            .filter(|file| !pinned.contains(file))
+            .cloned()
+            .collect();
+        if pinned.is_empty() && recent.is_empty() {
+            ui.label(tr("no-recent-files"));
+            return;
+        }
+        
+        let mut to_open = None;
+        let mut to_toggle = None;
+        for file in &pinned {
+            let (open, toggle) = recent_file_row(ui, file, true);
+            if open {
+                to_open = Some(file.clone());
+            }
+            if toggle {
+                to_toggle = Some(file.clone());
+            }
+        }
+        if !pinned.is_empty() && !recent.is_empty() {
+            ui.separator();
+        }
+        for file in &recent {
+            let (open, toggle) = recent_file_row(ui, file, false);
+            if open {
+                to_open = Some(file.clone());
+            }
+            if toggle {
+                to_toggle = Some(file.clone());
+            }
+        }
+        if !recent.is_empty() {
+            ui.separator();
+            if ui.button(tr("clear-recent-files")).on_hover_text(tr("clear-recent-files-hint")).clicked() {
+                self.settings.recent_files.clear();
+            }
+        }
+        
+        if let Some(file) = to_toggle {
+            if pinned.contains(&file) {
+                self.settings.pinned_files.retain(|pinned| *pinned != file);
+            } else {
+                self.settings.pinned_files.push(file);
+            }
+        }
+        if let Some(file) = to_open {
+            self.load_file(file);
+            ui.close();
+        }
+    }
+    
+    /// Put `path` at the top of File > Open Recent
+    fn remember_file(&mut self, path: &str) {
+        let path = absolute_path(path);
+        self.settings.recent_files.retain(|file| *file != path);
+        self.settings.recent_files.insert(0, path);
+        self.settings.recent_files.truncate(RECENT_FILES_KEPT);
+    }
+    
+    fn render_top_bar(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.menu_button(tr("file-menu"), |ui| {
+                if ui.add(egui::Button::new(tr("open")).shortcut_text(self.shortcut_text(ctx, Command::Open))).clicked() {
+                    self.open_file();
+                    ui.close_menu();
+                }
+                ui.menu_button(tr("open-recent"), |ui| self.render_recent_files_menu(ui));
+                if ui.button(tr("open-selectively")).clicked() {
+                    self.open_selectively();
+                    ui.close();
+                }
+                if ui.button(tr("validate-file")).clicked() {
+                    self.validate_file();
+                    ui.close();
+                }
+                if ui.button(tr("import")).clicked() {
+                    self.import_file();
+                    ui.close();
+                }
+                if ui.button(tr("import-pack-menu")).clicked() {
+                    self.import_pack();
+                    ui.close();
+                }
+                if ui.button(tr("import-vault-menu")).clicked() {
+                    self.import_vault();
+                    ui.close();
+                }
+                if ui.button(tr("import-links-menu")).clicked() {
+                    self.link_import = Some(LinkImport {
+                        text: String::new(),
+                        category: self.selected_category.clone().unwrap_or_default(),
+                        subcategory: self.selected_subcategory.clone().unwrap_or_default(),
+                        concept: self.selected_concept.clone().unwrap_or_default(),
+                        fetch_titles: true,
+                        pasted: false,
+                        fetch: None,
+                    });
+                    ui.close();
+                }
+                if ui.add(egui::Button::new(tr("save")).shortcut_text(self.shortcut_text(ctx, Command::Save))).clicked() {
+                    self.save_file();
+                    ui.close_menu();
+                }
+                if ui.button(tr("save-as")).clicked() {
+                    self.save_file_as();
+                    ui.close_menu();
+                }
+                if ui.add_enabled(self.current_file.is_some(), egui::Button::new(tr("restore-backup-menu"))).clicked() {
+                    self.open_restore_backup();
+                    ui.close();
+                }
+                if ui.button(tr("file-info-menu")).clicked() {
+                    self.file_info_draft = Some(self.microfiche.meta.info.clone());
+                    ui.close();
+                }
+                ui.separator();
+                if ui.button(tr("export-term-network")).clicked() {
+                    self.export_term_network();
+                    ui.close();
+                }
+                if ui.button(tr("export-report")).clicked() {
+                    self.export_report();
+                    ui.close();
+                }
+                if ui.button(tr("export-feed-menu")).clicked() {
+                    self.feed_export = Some(FeedExport {
+                        category: self.selected_category.clone(),
+                        per_category: false,
+                        limit: 50,
+                        link: String::new(),
+                    });
+                    ui.close();
+                }
+                if ui.button(tr("export-calendar-menu")).clicked() {
+                    let date_fields = calendar::date_fields(&self.microfiche);
+                    self.calendar_export = Some(CalendarExport {
+                        category: self.selected_category.clone(),
+                        fields: date_fields.clone(),
+                        date_fields,
+                    });
+                    ui.close();
+                }
+                if ui.button(tr("export-markdown-menu")).clicked() {
+                    self.markdown_export = Some(MarkdownExport {
+                        category: self.selected_category.clone(),
+                        subcategory: self.selected_subcategory.clone().filter(|_| self.selected_category.is_some()),
+                        per_category: false,
+                    });
+                    ui.close();
+                }
+                if ui.button(tr("export-pack-menu")).clicked() {
+                    let selection = self.microfiche.categories.values()
+                        .filter(|c| self.selected_category.as_ref().is_some_and(|selected| *selected == c.name))
+                        .flat_map(|c| c.subcategories.iter()
+                            .filter(|s| self.selected_subcategory.as_ref().is_none_or(|selected| *selected == s.name))
+                            .map(|s| (c.name.clone(), s.name.clone())))
+                        .collect();
+                    let info = FileInfo {
+                        author: self.microfiche.meta.info.author.clone(),
+                        ..FileInfo::default()
+                    };
+                    self.pack_export = Some(PackExport { selection, info, attachments: true });
+                    ui.close();
+                }
+                ui.separator();
+                ui.menu_button(tr_args("profile-menu", &[("name", self.profile.clone().into())]), |ui| {
+                    for profile in &self.profiles {
+                        if ui.selectable_label(*profile == self.profile, profile).clicked() && *profile != self.profile {
+                            self.switch_profile = Some(profile.clone());
+                            ui.close();
+                        }
+                    }
+                    ui.separator();
+                    if ui.button(tr("new-profile")).clicked() {
+                        self.new_profile_name = Some(String::new());
+                        ui.close();
+                    }
+                });
+                ui.menu_button(tr("workspaces"), |ui| {
+                    let mut to_switch = None;
+                    let mut to_delete = None;
+                    for name in self.workspaces.keys() {
+                        ui.horizontal(|ui| {
+                            let active = self.active_workspace.as_ref() == Some(name);
+                            if ui.selectable_label(active, name).clicked() {
+                                to_switch = Some(name.clone());
+                            }
+                            if accessible(ui.small_button("🗑"), tr("delete")).clicked() {
+                                to_delete = Some(name.clone());
+                            }
+                        });
+                    }
+                    if !self.workspaces.is_empty() {
+                        ui.separator();
+                    }
+                    if ui.button(tr("save-workspace-as")).clicked() {
+                        self.new_workspace_name = Some(self.active_workspace.clone().unwrap_or_default());
+                        ui.close();
+                    }
+                    if let Some(name) = to_switch {
+                        self.switch_workspace(&name);
+                        ui.close();
+                    }
+                    if let Some(name) = to_delete {
+                        self.workspaces.remove(&name);
+                        if self.active_workspace.as_ref() == Some(&name) {
+                            self.active_workspace = None;
+                        }
+                    }
+                });
+                ui.menu_button(tr("library"), |ui| {
+                    let mut to_remove = None;
+                    for (index, file) in self.settings.library.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(file_name(file)).on_hover_text(file);
+                            if accessible(ui.small_button("🗑"), tr("remove-from-library")).on_hover_text(tr("remove-from-library")).clicked() {
+                                to_remove = Some(index);
+                            }
+                        });
+                    }
+                    if let Some(index) = to_remove {
+                        self.settings.library.remove(index);
+                    }
+                    if !self.settings.library.is_empty() {
+                        ui.separator();
+                    }
+                    let current = self.current_file.as_deref().map(absolute_path);
+                    if let Some(current) = current.filter(|file| !self.settings.library.contains(file))
+                        && ui.button(tr("add-current-file")).clicked()
+                    {
+                        self.settings.library.push(current);
+                        ui.close();
+                    }
+                    if ui.button(tr("add-files")).clicked() {
+                        self.pick_library_files();
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button(tr("manage-library")).clicked() {
+                        self.view_mode = ViewMode::Library;
+                        ui.close();
+                    }
+                });
+            });
+            
+            ui.separator();
+            
+            for (index, (view_mode, label)) in [
+                (ViewMode::Browse, tr("browse")),
+                (ViewMode::Search, tr("search")),
+                (ViewMode::Create, tr("create")),
+                (ViewMode::Stats, tr("stats")),
+            ].into_iter().enumerate() {
+                let tab = ui.selectable_label(self.view_mode == view_mode, label);
+                if tab.clicked() {
+                    self.view_mode = view_mode;
+                }
+                self.tour_targets[index] = tab.rect;
+            }
+            if ui.selectable_label(self.view_mode == ViewMode::Study, tr("study")).clicked() {
+                self.view_mode = ViewMode::Study;
+            }
+            if ui.selectable_label(self.view_mode == ViewMode::Activity, tr("activity")).clicked() {
+                self.view_mode = ViewMode::Activity;
+            }
+            let review_count = self.microfiche.review_count();
+            let review_label = if review_count > 0 { format!("Review ({})", review_count) } else { "Review".to_string() };
+            if ui.selectable_label(self.view_mode == ViewMode::Review, review_label).clicked() {
+                self.view_mode = ViewMode::Review;
+            }
+            if ui.selectable_label(self.view_mode == ViewMode::Library, tr("library")).clicked() {
+                self.view_mode = ViewMode::Library;
+            }
+            
+            ui.separator();
+            
+            if ui.button(tr("theme")).clicked() {
+                self.show_theme_selector = !self.show_theme_selector;
+            }
+            if ui.button(tr("settings")).clicked() {
+                self.show_settings = !self.show_settings;
+            }
+            ui.toggle_value(&mut self.settings.privacy_mode, tr("privacy"))
+                .on_hover_text(tr("privacy-hint"));
+            ui.toggle_value(&mut self.show_scratchpad, tr("scratchpad"))
+                .on_hover_text(tr("scratchpad-hint"));
+            self.render_focus_timer(ui);
+            
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let redo_hint = self.history.redo.last()
+                    .map_or_else(|| tr("redo"), |(_, change)| tr_args("redo-change", &[("change", change.clone().into())]));
+                let redo = ui.add_enabled(!self.history.redo.is_empty(), egui::Button::new("⟳").small());
+                if accessible(redo, tr("redo")).on_hover_text(redo_hint).clicked() {
+                    self.redo();
+                }
+                let undo_hint = self.history.undo.last()
+                    .map_or_else(|| tr("undo"), |(_, change)| tr_args("undo-change", &[("change", change.clone().into())]));
+                let undo = ui.add_enabled(!self.history.undo.is_empty(), egui::Button::new("⟲").small());
+                if accessible(undo, tr("undo")).on_hover_text(undo_hint).clicked() {
+                    self.undo();
+                }
+                if self.merge_undo.is_some() && ui.small_button(tr("undo-merge")).clicked() {
+                    self.undo_merge();
+                }
+                if self.dirty {
+                    let hint = match self.settings.autosave_minutes {
+                        0 => tr("unsaved-changes-hint"),
+                        minutes => tr_args("unsaved-changes-autosave-hint", &[("minutes", minutes.to_string().into())]),
+                    };
+                    ui.label(egui::RichText::new(tr("unsaved-changes-indicator")).color(ui.visuals().warn_fg_color))
+                        .on_hover_text(hint);
+                }
+                if !self.external_edits.is_empty() {
+                    let label = tr_args("done-editing-externally", &[("count", self.external_edits.len().to_string().into())]);
+                    if ui.small_button(label).on_hover_text(tr("stop-watching-the-editor-files")).clicked() {
+                        // Pick up a last save made just before clicking
+                        self.sync_external_edits(ctx);
+                        self.external_edits.clear();
+                    }
+                }
+                ui.label(&self.status_message);
+            });
+        });
+        
+        // Theme selector window
+        if self.show_theme_selector {
+            egui::Window::new(tr("theme-selection"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.vertical(|ui| {
+                        for theme in Theme::PRESETS {
+                            if ui.selectable_label(self.current_theme == theme, theme.label(&[])).clicked() {
+                                self.current_theme = theme;
+                                self.show_theme_selector = false;
+                            }
+                        }
+                        
+                        if !self.settings.custom_themes.is_empty() {
+                            ui.separator();
+                        }
+                        let mut edit = None;
+                        for (index, custom) in self.settings.custom_themes.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.selectable_label(self.current_theme == Theme::Custom(index), &custom.name).clicked() {
+                                    self.current_theme = Theme::Custom(index);
+                                    self.show_theme_selector = false;
+                                }
+                                let button = ui.small_button("✏").on_hover_text(tr("edit-theme"));
+                                if accessible(button, tr_args("edit-theme-named", &[("name", custom.name.clone().into())])).clicked() {
+                                    edit = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = edit {
+                            self.theme_editor = Some((Some(index), self.settings.custom_themes[index].clone()));
+                        }
+                    });
+                    
+                    ui.separator();
+                    
+                    if ui.button(tr("new-theme")).on_hover_text(tr("new-theme-hint")).clicked() {
+                        let name = tr_args("custom-theme-name", &[("number", (self.settings.custom_themes.len() + 1).into())]);
+                        let visuals = self.current_theme.visuals(&self.settings.custom_themes);
+                        self.theme_editor = Some((None, CustomTheme::from_visuals(name, &visuals)));
+                    }
+                    ui.separator();
+                    
+                    if ui.button(tr("close")).clicked() {
+                        self.show_theme_selector = false;
+                    }
+                });
+        }
+        
+        if self.show_settings {
+            egui::Window::new(tr("settings"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.strong(tr("stats-panels"));
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("items-per-page-label"));
+                        ui.add(egui::DragValue::new(&mut self.settings.items_per_page).range(5..=200)).labelled_by(label.id);
+                    });
+                    ui.checkbox(&mut self.settings.infinite_scroll, tr("infinite-scroll-instead-of-pages"));
+                    
+                    ui.separator();
+                    
+                    ui.checkbox(&mut self.settings.vim_mode, tr("vim-keybindings"))
+                        .on_hover_text(tr("vim-keybindings-hint"));
+                    if ui.button(tr("keyboard-shortcuts-menu")).clicked() {
+                        self.show_shortcuts = true;
+                    }
+                    
+                    ui.separator();
+                    
+                    if ui.button(tr("register-file-types"))
+                        .on_hover_text(tr("register-file-types-hint"))
+                        .clicked()
+                    {
+                        self.status_message = match file_types::register() {
+                            Ok(message) => message,
+                            Err(e) => tr_args("error-registering-file-types", &[("error", e.into())]),
+                        };
+                    }
+                    
+                    ui.separator();
+                    
+                    ui.horizontal(|ui| {
+                        ui.label(tr("language-label"));
+                        egui::ComboBox::from_id_salt("language_select")
+                            .selected_text(&self.settings.language)
+                            .show_ui(ui, |ui| {
+                                for language in i18n::available_languages() {
+                                    if ui.selectable_label(self.settings.language == language, &language).clicked() {
+                                        i18n::set_language(&language);
+                                        self.settings.language = language;
+                                    }
+                                }
+                            });
+                    });
+                    ui.label(egui::RichText::new(tr("translations-hint"))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY));
+                    
+                    ui.separator();
+                    
+                    ui.strong(tr("fonts"));
+                    let mut fonts_changed = false;
+                    egui::Grid::new("font_settings_grid").num_columns(3).show(ui, |ui| {
+                        for (label, font) in [
+                            (tr("fallback-font-label"), &mut self.settings.custom_font),
+                            (tr("code-font-label"), &mut self.settings.monospace_font),
+                        ] {
+                            ui.label(label);
+                            let name = font.as_deref()
+                                .and_then(|path| Path::new(path).file_name())
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_else(|| tr("font-default"));
+                            if ui.button(name).on_hover_text(tr("choose-font-file")).clicked()
+                                && let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("Fonts", &["ttf", "otf", "ttc"])
+                                    .pick_file()
+                            {
+                                *font = Some(path.to_string_lossy().to_string());
+                                fonts_changed = true;
+                            }
+                            if font.is_some() && accessible(ui.small_button("✖"), tr("font-reset")).on_hover_text(tr("font-reset")).clicked() {
+                                *font = None;
+                                fonts_changed = true;
+                            }
+                            ui.end_row();
+                        }
+                        ui.label(tr("code-font-size-label"));
+                        fonts_changed |= ui.add(egui::DragValue::new(&mut self.settings.monospace_size).range(8.0..=32.0)).changed();
+                        ui.end_row();
+                    });
+                    if fonts_changed {
+                        apply_fonts(ctx, &self.settings);
+                    }
+                    
+                    ui.separator();
+                    
+                    ui.strong(tr("branding"));
+                    let mut branding_changed = false;
+                    egui::Grid::new("branding_settings_grid").num_columns(3).show(ui, |ui| {
+                        let label = ui.label(tr("window-title-label"));
+                        branding_changed |= ui.add(
+                            egui::TextEdit::singleline(&mut self.settings.window_title)
+                                .hint_text(APP_TITLE)
+                                .desired_width(200.0)
+                        ).labelled_by(label.id).changed();
+                        ui.end_row();
+                        
+                        ui.label(tr("window-icon-label"));
+                        let icon = &mut self.settings.custom_icon;
+                        let name = icon.as_deref()
+                            .and_then(|path| Path::new(path).file_name())
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_else(|| tr("font-default"));
+                        if ui.button(name).on_hover_text(tr("choose-icon-file")).clicked()
+                            && let Some(path) = rfd::FileDialog::new()
+                                .add_filter("PNG", &["png"])
+                                .pick_file()
+                        {
+                            *icon = Some(path.to_string_lossy().to_string());
+                            branding_changed = true;
+                        }
+                        if icon.is_some() && accessible(ui.small_button("✖"), tr("font-reset")).on_hover_text(tr("font-reset")).clicked() {
+                            *icon = None;
+                            branding_changed = true;
+                        }
+                        ui.end_row();
+                    });
+                    if branding_changed && let Err(e) = apply_branding(ctx, &self.settings) {
+                        self.status_message = e;
+                    }
+                    
+                    ui.separator();
+                    
+                    ui.strong(tr("stats-colors"));
+                    egui::ComboBox::from_id_salt("stats_palette")
+                        .selected_text(self.settings.stats_palette.label())
+                        .show_ui(ui, |ui| {
+                            for palette in StatsPalette::ALL {
+                                ui.selectable_value(&mut self.settings.stats_palette, palette, palette.label());
+                            }
+                        });
+                    if self.settings.stats_palette == StatsPalette::Custom {
+                        egui::Grid::new("stats_colors_grid").num_columns(2).show(ui, |ui| {
+                            let names = [tr("stats-color-primary"), tr("stats-color-secondary"), tr("stats-color-highlight"), tr("stats-color-alert")];
+                            for (name, color) in names.into_iter().zip(self.settings.custom_stats_colors.iter_mut()) {
+                                let label = ui.label(name);
+                                ui.color_edit_button_srgb(color).labelled_by(label.id);
+                                ui.end_row();
+                            }
+                        });
+                        if ui.small_button(tr("reset-to-color-blind-safe")).clicked() {
+                            self.settings.custom_stats_colors = OKABE_ITO_STATS;
+                        }
+                    }
+                    let visuals = ui.visuals().clone();
+                    ui.horizontal(|ui| {
+                        for color in self.settings.stats_colors(&visuals) {
+                            ui.label(egui::RichText::new("■■").color(color));
+                        }
+                    });
+                    
+                    ui.separator();
+                    
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("external-editor"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.settings.external_editor)
+                                .hint_text(external_editor::default_command())
+                                .desired_width(200.0)
+                        ).labelled_by(label.id)
+                            .on_hover_text(tr("external-editor-hint"));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("on-startup-label"));
+                        egui::ComboBox::from_id_salt("startup_action")
+                            .selected_text(self.settings.startup.label())
+                            .show_ui(ui, |ui| {
+                                for action in StartupAction::ALL {
+                                    ui.selectable_value(&mut self.settings.startup, action, action.label());
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("data-file-label"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.settings.default_file)
+                                .hint_text(DEFAULT_FILE)
+                                .desired_width(200.0)
+                        ).labelled_by(label.id)
+                            .on_hover_text(tr("data-file-hint"));
+                        if ui.button(tr("browse-files")).clicked()
+                            && let Some(path) = rfd::FileDialog::new()
+                                .add_filter(&tr("fisha-or-csv"), &[fiche_file::EXTENSION, "db", "sqlite", "csv", "gz", "zip"])
+                                .pick_file()
+                        {
+                            self.settings.default_file = path.to_string_lossy().to_string();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("author-name-label"));
+                        ui.add(egui::TextEdit::singleline(&mut self.settings.author_name).hint_text(&self.profile).desired_width(200.0))
+                            .labelled_by(label.id)
+                            .on_hover_text(tr("author-name-hint"));
+                    });
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("autosave-label"));
+                        ui.add(egui::DragValue::new(&mut self.settings.autosave_minutes).range(0..=120).suffix(" min"))
+                            .labelled_by(label.id)
+                            .on_hover_text(tr("autosave-hint"));
+                    });
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("snapshots-kept-label"));
+                        ui.add(egui::DragValue::new(&mut self.settings.snapshots_kept).range(0..=100))
+                            .labelled_by(label.id)
+                            .on_hover_text(tr("snapshots-kept-hint"));
+                    });
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("focus-length-label"));
+                        ui.add(egui::DragValue::new(&mut self.settings.focus_minutes).range(1..=180).suffix(" min"))
+                            .labelled_by(label.id);
+                        let label = ui.label(tr("break-length-label"));
+                        ui.add(egui::DragValue::new(&mut self.settings.break_minutes).range(1..=60).suffix(" min"))
+                            .labelled_by(label.id);
+                    });
+                    ui.checkbox(&mut self.settings.render_markdown, tr("render-markdown"))
+                        .on_hover_text(tr("render-markdown-hint"));
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.settings.compact_cards, tr("compact-cards"))
+                            .on_hover_text(tr("compact-cards-hint"));
+                        ui.checkbox(&mut self.settings.card_actions_on_hover, tr("card-actions-on-hover"));
+                        let label = ui.label(tr("card-spacing-label"));
+                        ui.add(egui::DragValue::new(&mut self.settings.card_spacing).range(0.0..=40.0).suffix(" px"))
+                            .labelled_by(label.id);
+                    });
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("browse-columns-label"));
+                        let columns_label = |count: usize| match count {
+                            0 => tr("browse-columns-auto"),
+                            count => count.to_string(),
+                        };
+                        egui::ComboBox::from_id_salt("browse_columns")
+                            .selected_text(columns_label(self.settings.browse_columns))
+                            .show_ui(ui, |ui| {
+                                for count in 0..=4 {
+                                    ui.selectable_value(&mut self.settings.browse_columns, count, columns_label(count));
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id)
+                            .on_hover_text(tr("browse-columns-hint"));
+                    });
+                    self.render_webhook_settings(ui);
+                    self.render_publish_settings(ui);
+                    
+                    ui.separator();
+                    
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("show-guided-tour")).clicked() {
+                            self.tour_step = Some(0);
+                            self.show_settings = false;
+                        }
+                        if ui.button(tr("close")).clicked() {
+                            self.show_settings = false;
+                        }
+                    });
+                });
+        }
+    }
+    
+    /// A concept for the Browse central panel, with as many notes as are
+    /// shown: none when it's collapsed, else the first `NOTES_SHOWN_STEP`
+    /// until "Show more" is clicked
+    fn concept_view(&self, cat: &str, sub: &str, concept: &Concept, cross_listed: bool) -> ConceptView {
+        let path = note_path(cat, sub, &concept.name);
+        let shown = if self.collapsed_concepts.contains(&path) {
+            0
+        } else {
+            self.notes_shown.get(&path).copied().unwrap_or(NOTES_SHOWN_STEP)
+        };
+        let mut notes: Vec<&Note> = concept.notes.iter()
+            .filter(|note| self.browse_tags.is_subset(&note.tags))
+            .collect();
+        let unfiltered_count = notes.len();
+        if let Some(filter) = self.concept_filters.get(&path) {
+            let words: Vec<String> = filter.split_whitespace().map(fold_text).collect();
+            notes.retain(|note| {
+                let text = fold_text(&note.text);
+                words.iter().all(|word| text.contains(word.as_str()) || note.tags.iter().any(|tag| fold_text(tag).contains(word.as_str())))
+            });
+        }
+        if self.browse_order != NoteOrder::Stored {
+            self.browse_order.sort(&mut notes, |note| note);
+            // The definition stays on top
+            notes.sort_by_key(|note| !note.definition);
+        }
+        ConceptView {
+            category: cat.to_string(),
+            subcategory: sub.to_string(),
+            name: concept.name.clone(),
+            note_count: notes.len(),
+            notes: notes.into_iter().take(shown).cloned().collect(),
+            unfiltered_count,
+            cross_listed,
+        }
+    }
+    
+    fn render_browse_view(&mut self, ui: &mut egui::Ui) {
+        self.render_cross_list_window(&ui.ctx().clone());
+        self.render_merge_window(&ui.ctx().clone());
+        self.render_note_template_window(&ui.ctx().clone());
+        
+        egui::SidePanel::left("categories_panel")
+            .resizable(true)
+            .default_width(200.0)
+            .show_inside(ui, |ui| {
+                ui.heading(tr("categories"));
+                ui.separator();
+                
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    let categories: Vec<String> = self.microfiche.categories.keys().cloned().collect();
+                    
+                    for cat_name in categories {
+                        let is_selected = self.selected_category.as_ref() == Some(&cat_name);
+                        let mut label = egui::RichText::new(self.microfiche.meta.category_label(&cat_name));
+                        if let Some(color) = self.microfiche.meta.category_color(&cat_name) {
+                            label = label.color(color);
+                        }
+                        
+                        let response = ui.selectable_label(is_selected, label);
+                        if response.clicked() {
+                            self.selected_category = Some(cat_name.clone());
+                            self.selected_subcategory = None;
+                            self.selected_concept = None;
+                        }
+                        response.context_menu(|ui| {
+                            self.render_category_label_menu(ui, &cat_name);
+                        });
+                    }
+                    
+                    // Left out by a selective open; clicking loads the category
+                    let mut to_load = None;
+                    for (cat_name, count) in &self.microfiche.unloaded {
+                        let label = egui::RichText::new(format!("⬇ {} ({})", cat_name, count))
+                            .color(egui::Color32::GRAY);
+                        if ui.selectable_label(false, label).on_hover_text(tr("not-loaded-click-to-load")).clicked() {
+                            to_load = Some(cat_name.clone());
+                        }
+                    }
+                    if let Some(cat_name) = to_load {
+                        self.load_unloaded(BTreeSet::from([cat_name.clone()]));
+                        self.selected_category = Some(cat_name);
+                        self.selected_subcategory = None;
+                        self.selected_concept = None;
+                    }
+                });
+            });
+        
+        let mut info = None;
+        let mut moved = None;
+        if let Some(ref cat_name) = self.selected_category.clone() {
+            if let Some(category) = self.microfiche.categories.get(cat_name) {
+                egui::SidePanel::left("subcategories_panel")
+                    .resizable(true)
+                    .default_width(200.0)
+                    .show_inside(ui, |ui| {
+                        ui.heading(tr("subcategories"));
+                        ui.separator();
+                        
+                        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                            let count = category.subcategories.len();
+                            for (index, subcat) in category.subcategories.iter().enumerate() {
+                                let is_selected = self.selected_subcategory.as_ref() == Some(&subcat.name);
+                                let response = ui.selectable_label(is_selected, &subcat.name);
+                                if response.clicked() {
+                                    self.selected_subcategory = Some(subcat.name.clone());
+                                    self.selected_concept = None;
+                                }
+                                response.context_menu(|ui| {
+                                    if ui.button(tr("subcategory-info")).clicked() {
+                                        info = Some(subcat.name.clone());
+                                        ui.close();
+                                    }
+                                    ui.separator();
+                                    if let Some(step) = move_buttons(ui, index, count) {
+                                        moved = Some((subcat.name.clone(), step));
+                                    }
+                                });
+                            }
+                        });
+                    });
+            }
+            if let Some(sub_name) = info {
+                self.open_subcategory_info(cat_name, &sub_name);
+            }
+            if let Some((sub_name, step)) = moved {
+                self.reorder(cat_name, Some(&sub_name), None, step);
             }
         }
         
         // Collect data before rendering to avoid borrow issues
-        let display_data: Option<(String, String, Vec<(String, Vec<String>)>)> = 
+        let display_data: Option<(String, String, Vec<ConceptView>)> = 
             if let Some(ref cat_name) = self.selected_category {
                 if let Some(category) = self.microfiche.categories.get(cat_name) {
                     if let Some(ref sub_name) = self.selected_subcategory {
                         if let Some(subcat) = category.subcategories.iter().find(|s| &s.name == sub_name) {
-                            let concepts: Vec<_> = subcat.concepts.iter().map(|concept| {
-                                (concept.name.clone(), concept.notes.clone())
-                            }).collect();
+                            let mut concepts: Vec<_> = subcat.concepts.iter()
+                                .map(|concept| self.concept_view(cat_name, sub_name, concept, false))
+                                .collect();
+                            
+                            // Concepts listed here from elsewhere share the original's notes
+                            for link in &self.microfiche.meta.cross_listings {
+                                if &link.category != cat_name || &link.subcategory != sub_name {
+                                    continue;
+                                }
+                                if let Some(source) = self.microfiche.concept(
+                                    &link.source.category, &link.source.subcategory, &link.source.concept
+                                ) {
+                                    concepts.push(self.concept_view(&link.source.category, &link.source.subcategory, source, true));
+                                }
+                            }
+                            // With a tag filter on, concepts without a matching note are left out
+                            if !self.browse_tags.is_empty() {
+                                concepts.retain(|concept| concept.note_count > 0);
+                            }
                             Some((cat_name.clone(), sub_name.clone(), concepts))
                         } else {
-                            None
+                            None
+                        }
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+        
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            if let Some((cat_name, sub_name, concepts)) = display_data {
+                ui.heading(format!("{} > {}", cat_name, sub_name));
+                self.render_description(ui, &cat_name, Some(&sub_name));
+                let concept_paths: Vec<String> = concepts.iter()
+                    .map(|c| note_path(&c.category, &c.subcategory, &c.name))
+                    .collect();
+                ui.horizontal(|ui| {
+                    if ui.small_button(tr("note-template"))
+                        .on_hover_text(tr("text-new-notes-in-this-subcategory-start-with"))
+                        .clicked()
+                    {
+                        self.edit_note_template(&cat_name, &sub_name, "");
+                    }
+                    if ui.small_button(tr("expand-all")).clicked() {
+                        for path in &concept_paths {
+                            self.collapsed_concepts.remove(path);
+                        }
+                    }
+                    if ui.small_button(tr("collapse-all")).clicked() {
+                        self.collapsed_concepts.extend(concept_paths.iter().cloned());
+                    }
+                    if ui.small_button(tr("subcategory-info")).clicked() {
+                        self.open_subcategory_info(&cat_name, &sub_name);
+                    }
+                    ui.separator();
+                    note_order_combo(ui, "browse_order", &mut self.browse_order);
+                });
+                let tag_counts = self.microfiche.tag_counts();
+                if !tag_counts.is_empty() || !self.browse_tags.is_empty() {
+                    tag_chips(ui, &tag_counts, &mut self.browse_tags);
+                }
+                ui.separator();
+                
+                let mut to_delete: Option<(String, String, String, String)> = None;
+                let mut to_edit: Option<(String, String, String, String)> = None;
+                let mut to_template: Option<(String, String, String, Note)> = None;
+                let mut to_rate: Option<(String, String, String, String, Option<u8>)> = None;
+                let mut to_flag: Option<(NoteTarget, BTreeSet<NoteFlag>)> = None;
+                let mut to_comment: Option<(NoteTarget, CommentAction)> = None;
+                let mut to_filter_tag: Option<String> = None;
+                let mut to_toggle_raw: Option<String> = None;
+                let mut to_toggle_expanded: Option<String> = None;
+                let mut hovered_card: Option<String> = None;
+                
+                let mut to_cross_list: Option<ConceptRef> = None;
+                let mut to_unlink: Option<ConceptRef> = None;
+                let mut to_merge: Option<ConceptRef> = None;
+                let mut to_tool: Option<(NoteTool, (String, String, String, String))> = None;
+                let mut to_graph: Option<ConceptRef> = None;
+                let mut to_edit_template: Option<ConceptRef> = None;
+                let mut to_publish: Option<ConceptRef> = None;
+                let mut to_move: Option<(ConceptRef, Step)> = None;
+                let mut scrolled_to_concept = false;
+                let mut note_index = 0;
+                
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    let visible = ui.clip_rect();
+                    // The concept whose heading has scrolled off the top while its notes are still in view
+                    let mut pinned: Option<(String, egui::Rect)> = None;
+                    for concept in concepts {
+                        let ConceptView { category: cat_name, subcategory: sub_name, name: concept_name, notes, note_count, unfiltered_count, cross_listed } = concept;
+                        let concept_ref = ConceptRef {
+                            category: cat_name.clone(),
+                            subcategory: sub_name.clone(),
+                            concept: concept_name.clone(),
+                        };
+                        let path = note_path(&cat_name, &sub_name, &concept_name);
+                        // A concept picked from the graph is unfolded to show it
+                        if self.selected_concept.as_ref() == Some(&concept_name) {
+                            self.collapsed_concepts.remove(&path);
+                        }
+                        let collapsed = self.collapsed_concepts.contains(&path);
+                        let group = ui.group(|ui| {
+                            ui.horizontal(|ui| {
+                                let (arrow, hint) = if collapsed { ("⏵", "expand") } else { ("⏷", "collapse") };
+                                if ui.small_button(arrow).on_hover_text(tr(hint)).clicked() {
+                                    if collapsed {
+                                        self.collapsed_concepts.remove(&path);
+                                    } else {
+                                        self.collapsed_concepts.insert(path.clone());
+                                    }
+                                }
+                                let heading = ui.add(egui::Label::new(egui::RichText::new(&concept_name)
+                                    .strong()
+                                    .color(egui::Color32::from_rgb(100, 149, 237)))
+                                    .sense(egui::Sense::click()));
+                                // Listed here from elsewhere, it keeps its place there
+                                if !cross_listed {
+                                    heading.context_menu(|ui| {
+                                        let (index, len) = self.microfiche.categories.get(&cat_name)
+                                            .and_then(|c| c.subcategories.iter().find(|s| s.name == sub_name))
+                                            .and_then(|s| Some((s.concepts.iter().position(|c| c.name == concept_name)?, s.concepts.len())))
+                                            .unwrap_or_default();
+                                        if let Some(step) = move_buttons(ui, index, len) {
+                                            to_move = Some((concept_ref.clone(), step));
+                                        }
+                                    });
+                                }
+                                if collapsed {
+                                    ui.label(egui::RichText::new(tr_args("notes", &[("count", note_count.to_string().into())]))
+                                        .size(11.0)
+                                        .color(egui::Color32::GRAY));
+                                }
+                                if ui.small_button(tr("graph"))
+                                    .on_hover_text(tr("show-concepts-linked-to-this-one"))
+                                    .clicked()
+                                {
+                                    to_graph = Some(concept_ref.clone());
+                                }
+                                if cross_listed {
+                                    ui.label(egui::RichText::new(format!("↗ {} > {}", cat_name, sub_name))
+                                        .size(11.0)
+                                        .color(egui::Color32::GRAY));
+                                    if ui.small_button(tr("unlink")).on_hover_text(tr("remove-this-cross-listing")).clicked() {
+                                        to_unlink = Some(concept_ref.clone());
+                                    }
+                                } else {
+                                    if ui.small_button(tr("cross-list"))
+                                        .on_hover_text(tr("also-show-this-concept-under-another"))
+                                        .clicked()
+                                    {
+                                        to_cross_list = Some(concept_ref.clone());
+                                    }
+                                    if ui.small_button(tr("merge-into"))
+                                        .on_hover_text(tr("move-this-concept-s-notes-into"))
+                                        .clicked()
+                                    {
+                                        to_merge = Some(concept_ref.clone());
+                                    }
+                                }
+                                if ui.small_button(tr("note-template"))
+                                    .on_hover_text(tr("text-new-notes-under-this-concept-start-with"))
+                                    .clicked()
+                                {
+                                    to_edit_template = Some(concept_ref.clone());
+                                }
+                                if ui.small_button(tr("publish"))
+                                    .on_hover_text(tr("share-this-concept-s-notes-as-a-link"))
+                                    .clicked()
+                                {
+                                    to_publish = Some(concept_ref.clone());
+                                }
+                            });
+                            if collapsed {
+                                return;
+                            }
+                            let aliases = self.microfiche.meta.aliases(&concept_ref);
+                            if !aliases.is_empty() {
+                                ui.label(egui::RichText::new(tr_args("concept-aliases", &[("aliases", aliases.join(", ").into())]))
+                                    .size(11.0)
+                                    .italics()
+                                    .color(egui::Color32::GRAY));
+                            }
+                            if unfiltered_count >= CONCEPT_FILTER_MIN_NOTES || self.concept_filters.contains_key(&path) {
+                                ui.horizontal(|ui| {
+                                    let filter = self.concept_filters.entry(path.clone()).or_default();
+                                    let edit = ui.add(egui::TextEdit::singleline(filter)
+                                        .hint_text(tr("filter-notes-hint"))
+                                        .desired_width(220.0));
+                                    let edit = accessible(edit, tr("filter-notes"));
+                                    let mut changed = edit.changed();
+                                    if !filter.is_empty() {
+                                        ui.label(egui::RichText::new(tr_args("notes-match", &[
+                                            ("count", note_count.to_string().into()),
+                                            ("total", unfiltered_count.to_string().into()),
+                                        ])).size(11.0).color(egui::Color32::GRAY));
+                                        if accessible(ui.small_button("✖"), tr("clear-filter")).clicked() {
+                                            filter.clear();
+                                            changed = true;
+                                        }
+                                    }
+                                    if filter.trim().is_empty() {
+                                        self.concept_filters.remove(&path);
+                                    }
+                                    // The list is built before drawing, so it narrows on the next frame
+                                    if changed {
+                                        ui.ctx().request_repaint();
+                                    }
+                                });
+                            }
+                            ui.separator();
+                            
+                            let shown = notes.len();
+                            let column_count = match self.settings.browse_columns {
+                                0 => (ui.available_width() / BROWSE_COLUMN_WIDTH).floor().max(1.0) as usize,
+                                count => count,
+                            };
+                            ui.columns(column_count, |columns| {
+                                for note in notes {
+                                    // Each card goes under the shortest column so far
+                                    let shortest = (0..columns.len())
+                                        .min_by(|&a, &b| columns[a].cursor().top().total_cmp(&columns[b].cursor().top()))
+                                        .unwrap_or(0);
+                                    let ui = &mut columns[shortest];
+                                    let masked = self.settings.masks(&cat_name);
+                                    let key = note_key(&cat_name, &sub_name, &concept_name, &note.text);
+                                    let expanded = !self.settings.compact_cards || self.expanded_cards.contains(&key);
+                                    let show_actions = !self.settings.card_actions_on_hover || self.hovered_card.as_ref() == Some(&key);
+                                    let frame = note_card(ui, &note);
+                                    let card = frame.show(ui, |ui| {
+                                        ui.vertical(|ui| {
+                                            if !expanded {
+                                                if compact_note_line(ui, &note, masked) {
+                                                    to_toggle_expanded = Some(key.clone());
+                                                }
+                                                return;
+                                            }
+                                            if note.definition {
+                                                ui.label(egui::RichText::new(tr("definition"))
+                                                    .size(11.0)
+                                                    .strong()
+                                                    .color(ui.visuals().selection.stroke.color));
+                                            }
+                                            let markdown = self.settings.render_markdown && note.display.is_plain_text()
+                                                && markdown::has_markup(&note.text);
+                                            let raw = self.raw_notes.contains(&key);
+                                            note_label(ui, &note.text, note.display, masked, markdown && !raw);
+                                            if !note.fields.is_empty() && !masked {
+                                                ui.label(egui::RichText::new(format_fields(&note.fields))
+                                                    .size(11.0)
+                                                    .color(egui::Color32::GRAY));
+                                            }
+                                            note_byline(ui, &note);
+                                            if let Some(tag) = note_tag_links(ui, &note.tags) {
+                                                to_filter_tag = Some(tag);
+                                            }
+                                            ui.horizontal(|ui| {
+                                                if self.settings.compact_cards && ui.small_button("⏶").on_hover_text(tr("collapse")).clicked() {
+                                                    to_toggle_expanded = Some(key.clone());
+                                                }
+                                                if let Some(rating) = rating_widget(ui, note.rating) {
+                                                    to_rate = Some((
+                                                        cat_name.clone(),
+                                                        sub_name.clone(),
+                                                        concept_name.clone(),
+                                                        note.text.clone(),
+                                                        rating,
+                                                    ));
+                                                }
+                                                if let Some(flags) = flags_widget(ui, &note.flags) {
+                                                    to_flag = Some(((
+                                                        cat_name.clone(),
+                                                        sub_name.clone(),
+                                                        concept_name.clone(),
+                                                        note.text.clone(),
+                                                    ), flags));
+                                                }
+                                                ui.separator();
+                                                if note.needs_review {
+                                                    review_badge(ui);
+                                                }
+                                                secret_badge(ui, &note.text);
+                                                // Hidden rather than left out, so the card keeps its size
+                                                ui.scope_builder(card_actions(show_actions), |ui| {
+                                                    if markdown && !masked && raw_toggle(ui, raw) {
+                                                        to_toggle_raw = Some(key.clone());
+                                                    }
+                                                
+                                                    if ui.button(tr("template")).clicked() {
+                                                        to_template = Some((
+                                                            cat_name.clone(),
+                                                            sub_name.clone(),
+                                                            concept_name.clone(),
+                                                            note.clone(),
+                                                        ));
+                                                    }
+                                                
+                                                    if ui.button(tr("edit")).clicked() {
+                                                        to_edit = Some((
+                                                            cat_name.clone(),
+                                                            sub_name.clone(),
+                                                            concept_name.clone(),
+                                                            note.text.clone(),
+                                                        ));
+                                                    }
+                                                
+                                                    if ui.button(tr("delete")).clicked() {
+                                                        to_delete = Some((
+                                                            cat_name.clone(),
+                                                            sub_name.clone(),
+                                                            concept_name.clone(),
+                                                            note.text.clone(),
+                                                        ));
+                                                    }
+                                                
+                                                    if let Some(tool) = note_tools_menu(ui, &note) {
+                                                        to_tool = Some((tool, (
+                                                            cat_name.clone(),
+                                                            sub_name.clone(),
+                                                            concept_name.clone(),
+                                                            note.text.clone(),
+                                                        )));
+                                                    }
+                                                });
+                                            });
+                                            let target = (cat_name.clone(), sub_name.clone(), concept_name.clone(), note.text.clone());
+                                            let reply = self.comment_draft.as_mut()
+                                                .filter(|(draft_target, _)| *draft_target == target)
+                                                .map(|(_, reply)| reply);
+                                            if let Some(action) = comment_thread(ui, &target, &note.comments, reply) {
+                                                to_comment = Some((target, action));
+                                            }
+                                        });
+                                    });
+                                    if card.response.contains_pointer() {
+                                        hovered_card = Some(key.clone());
+                                    }
+                                    if self.settings.vim_mode && note_index == self.vim.cursor {
+                                        self.vim.mark(ui, card.response.rect, (
+                                            cat_name.clone(),
+                                            sub_name.clone(),
+                                            concept_name.clone(),
+                                            note.text.clone(),
+                                        ));
+                                    }
+                                    ui.add_space(self.settings.card_spacing - ui.spacing().item_spacing.y);
+                                    note_index += 1;
+                                }
+                            });
+                            if note_count > shown {
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new(tr_args("showing-of-notes", &[
+                                        ("shown", shown.to_string().into()),
+                                        ("count", note_count.to_string().into()),
+                                    ])).color(egui::Color32::GRAY));
+                                    if ui.button(tr_args("show-more", &[("count", NOTES_SHOWN_STEP.to_string().into())])).clicked() {
+                                        self.notes_shown.insert(path.clone(), shown + NOTES_SHOWN_STEP);
+                                    }
+                                    if ui.button(tr("show-all")).clicked() {
+                                        self.notes_shown.insert(path.clone(), note_count);
+                                    }
+                                });
+                            }
+                            ui.add_space(5.0);
+                        });
+                        if self.selected_concept.as_ref() == Some(&concept_name) {
+                            group.response.scroll_to_me(Some(egui::Align::TOP));
+                            scrolled_to_concept = true;
+                        }
+                        let rect = group.response.rect;
+                        if rect.top() < visible.top() && rect.bottom() > visible.top() + STICKY_HEADER_HEIGHT {
+                            pinned = Some((concept_name.clone(), rect));
+                        }
+                        ui.add_space(10.0);
+                    }
+                    
+                    if let Some((concept_name, rect)) = pinned {
+                        let header = egui::Rect::from_min_size(visible.min, egui::vec2(visible.width(), STICKY_HEADER_HEIGHT));
+                        // Drawn after the notes so it covers them, and takes their clicks
+                        let response = ui.interact(header, ui.id().with("sticky_concept_header"), egui::Sense::click())
+                            .on_hover_text(tr("scroll-to-the-start-of-this-concept"));
+                        if response.clicked() {
+                            ui.scroll_to_rect(rect, Some(egui::Align::TOP));
+                        }
+                        let painter = ui.painter();
+                        painter.rect_filled(header, 0.0, ui.visuals().panel_fill);
+                        painter.hline(header.x_range(), header.bottom(), ui.visuals().widgets.noninteractive.bg_stroke);
+                        painter.text(
+                            header.left_center() + egui::vec2(8.0, 0.0),
+                            egui::Align2::LEFT_CENTER,
+                            concept_name,
+                            egui::TextStyle::Body.resolve(ui.style()),
+                            egui::Color32::from_rgb(100, 149, 237),
+                        );
+                    }
+                });
+                
+                self.vim.list_len = note_index;
+                self.track_hovered_card(ui.ctx(), hovered_card);
+                
+                // Scroll to a concept picked from the graph only once
+                if scrolled_to_concept {
+                    self.selected_concept = None;
+                }
+                if let Some(concept) = to_edit_template {
+                    self.edit_note_template(&concept.category, &concept.subcategory, &concept.concept);
+                }
+                if to_publish.is_some() {
+                    self.publish_concept = to_publish;
+                }
+                if let Some((concept, step)) = to_move {
+                    self.reorder(&concept.category, Some(&concept.subcategory), Some(&concept.concept), step);
+                }
+                if to_graph.is_some() {
+                    self.graph_center = to_graph;
+                }
+                
+                // Handle actions after the scroll area
+                if let Some((cat, sub, con, note)) = to_delete {
+                    if let Some(removed) = self.microfiche.delete_note(&cat, &sub, &con, &note) {
+                        self.log_activity(ActivityAction::Deleted, note_path(&cat, &sub, &con), &note);
+                        self.notify(webhook::Event::Deleted, (&cat, &sub, &con), &removed, None);
+                        self.status_message = tr("entry-deleted");
+                    }
+                }
+                
+                if let Some((tool, note)) = to_tool {
+                    self.open_note_tool(tool, note);
+                }
+                
+                if let Some(source) = to_cross_list {
+                    self.cross_list_target = (String::new(), String::new());
+                    self.cross_list_source = Some(source);
+                }
+                
+                if let Some(source) = to_merge {
+                    self.merge_target = (source.category.clone(), source.subcategory.clone(), String::new());
+                    self.merge_source = Some(source);
+                }
+                
+                if let Some(source) = to_unlink {
+                    self.microfiche.meta.remove_cross_listing(&cat_name, &sub_name, &source);
+                    self.log_activity(
+                        ActivityAction::Unlinked,
+                        note_path(&source.category, &source.subcategory, &source.concept),
+                        &format!("from {} > {}", cat_name, sub_name),
+                    );
+                    self.status_message = tr_args("removed-cross-listing-of", &[("concept", source.concept.to_string().into())]);
+                }
+                
+                if let Some((cat, sub, con, note, rating)) = to_rate
+                    && self.microfiche.set_rating(&cat, &sub, &con, &note, rating)
+                {
+                    let stars = rating.map(|r| format!("{}★", r)).unwrap_or_else(|| "unrated".to_string());
+                    self.log_activity(ActivityAction::Rated, note_path(&cat, &sub, &con), &format!("{}: {}", stars, note));
+                }
+                
+                if let Some((note, flags)) = to_flag {
+                    self.set_note_flags(note, flags);
+                }
+                if let Some((note, action)) = to_comment {
+                    self.comment_action(note, action);
+                }
+                if let Some(tag) = to_filter_tag {
+                    self.browse_tags.insert(tag);
+                }
+                if let Some(key) = to_toggle_raw
+                    && !self.raw_notes.remove(&key)
+                {
+                    self.raw_notes.insert(key);
+                }
+                if let Some(key) = to_toggle_expanded
+                    && !self.expanded_cards.remove(&key)
+                {
+                    self.expanded_cards.insert(key);
+                }
+                
+                if let Some((cat, sub, con, note)) = to_edit {
+                    // Delete the old entry
+                    if let Some(old) = self.microfiche.delete_note(&cat, &sub, &con, &note) {
+                        self.editing_note = Some(note.clone());
+                        // Populate the create form with the old data
+                        self.new_category = cat;
+                        self.new_subcategory = sub;
+                        self.new_concept = con;
+                        self.new_note = old.text;
+                        self.template_skeleton = None;
+                    self.template_skeleton = None;
+                        self.new_fields = old.fields.into_iter().collect();
+                        self.new_rating = old.rating;
+                        self.new_created = old.created;
+                        self.new_display = old.display;
+                        self.new_definition = old.definition;
+                        self.new_flags = old.flags;
+                        self.new_tags = tags_column(&old.tags);
+                        self.new_comments = old.comments;
+                        self.new_author = old.author;
+                        
+                        // Switch to create view
+                        self.view_mode = ViewMode::Create;
+                        self.status_message = tr("entry-loaded-for-editing-modify-and");
+                    }
+                }
+                
+                if let Some((cat, sub, con, note)) = to_template {
+                    self.load_template(cat, sub, con, &note);
+                }
+            } else if let (Some(cat_name), None) = (self.selected_category.clone(), &self.selected_subcategory) {
+                ui.heading(self.microfiche.meta.category_label(&cat_name));
+                self.render_description(ui, &cat_name, None);
+                ui.separator();
+                ui.centered_and_justified(|ui| {
+                    ui.label(tr("select-a-subcategory-to-view-its"));
+                });
+            } else {
+                if self.microfiche.categories.is_empty() {
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(ui.available_height() / 2.0 - 50.0);
+                        ui.label(egui::RichText::new(tr("no-data-loaded")).size(14.0));
+                        ui.add_space(10.0);
+                        if ui.button(egui::RichText::new(tr("open-file")).size(12.0)).clicked() {
+                            self.open_file();
+                        }
+                    });
+                } else {
+                    ui.centered_and_justified(|ui| {
+                        ui.label(tr("select-a-category-from-the-left"));
+                    });
+                }
+            }
+        });
+    }
+    
+    fn render_cross_list_window(&mut self, ctx: &egui::Context) {
+        let Some(source) = self.cross_list_source.clone() else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(tr("cross-list-concept"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("Show \"{}\" ({} > {}) under:", source.concept, source.category, source.subcategory));
+                ui.add_space(5.0);
+                
+                let (target_cat, target_sub) = &mut self.cross_list_target;
+                hierarchy_picker(ui, "cross_list", &self.microfiche.categories, target_cat, target_sub, None);
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let ready = !target_cat.is_empty() && !target_sub.is_empty();
+                    if ui.add_enabled(ready, egui::Button::new(tr("cross-list-button"))).clicked() {
+                        if self.microfiche.meta.add_cross_listing(target_cat, target_sub, source.clone()) {
+                            self.status_message = tr_args("now-also-appears-in", &[("concept", source.concept.to_string().into()), ("target_cat", target_cat.to_string().into()), ("target_sub", target_sub.to_string().into())]);
+                            self.activity.record(
+                                ActivityAction::CrossListed,
+                                note_path(&source.category, &source.subcategory, &source.concept),
+                                format!("into {} > {}", target_cat, target_sub),
+                                &self.session_note,
+                            );
+                        } else {
+                            self.status_message = tr("concept-is-already-listed-there");
+                        }
+                        close = true;
+                    }
+                    if ui.button(tr("cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        
+        if !open || close {
+            self.cross_list_source = None;
+        }
+    }
+    
+    fn render_graph_window(&mut self, ctx: &egui::Context) {
+        let Some(center) = self.graph_center.clone() else {
+            return;
+        };
+        let links = self.microfiche.local_links(&center, 8);
+        
+        let wiki_color = egui::Color32::from_rgb(100, 149, 237);
+        let tag_color = egui::Color32::from_rgb(230, 160, 60);
+        let similar_color = egui::Color32::GRAY;
+        
+        let mut open = true;
+        let mut navigate: Option<ConceptRef> = None;
+        egui::Window::new(tr_args("local-graph", &[("concept", center.concept.to_string().into())]))
+            .open(&mut open)
+            .default_size([560.0, 460.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(tr("wiki-link")).color(wiki_color));
+                    ui.label(egui::RichText::new(tr("shared-tag")).color(tag_color));
+                    ui.label(egui::RichText::new(tr("similar-terms")).color(similar_color));
+                    ui.separator();
+                    ui.label(egui::RichText::new(tr("click-a-concept-to-open-it")).color(egui::Color32::GRAY));
+                });
+                ui.separator();
+                
+                if links.is_empty() {
+                    ui.label(tr("no-linked-concepts-add-concept-links"));
+                    return;
+                }
+                
+                let size = egui::vec2(ui.available_width(), ui.available_height().max(320.0));
+                let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                let center_pos = rect.center();
+                let radius = (rect.width().min(rect.height()) / 2.0 - 45.0).max(60.0);
+                let text_color = ui.visuals().text_color();
+                
+                let positions: Vec<egui::Pos2> = (0..links.len())
+                    .map(|i| {
+                        let angle = -std::f32::consts::FRAC_PI_2 + std::f32::consts::TAU * i as f32 / links.len() as f32;
+                        center_pos + radius * egui::vec2(angle.cos(), angle.sin())
+                    })
+                    .collect();
+                
+                for (link, pos) in links.iter().zip(&positions) {
+                    let color = if link.wiki {
+                        wiki_color
+                    } else if !link.shared_tags.is_empty() {
+                        tag_color
+                    } else {
+                        similar_color
+                    };
+                    let width = 1.0 + 3.0 * link.strength().min(1.5) / 1.5;
+                    painter.line_segment([center_pos, *pos], egui::Stroke::new(width, color));
+                }
+                
+                let center_color = self.microfiche.meta.category_color(&center.category).unwrap_or(wiki_color);
+                painter.circle_filled(center_pos, 14.0, center_color);
+                painter.text(center_pos + egui::vec2(0.0, 20.0), egui::Align2::CENTER_TOP, &center.concept,
+                    egui::FontId::proportional(14.0), text_color);
+                
+                for (i, (link, pos)) in links.iter().zip(&positions).enumerate() {
+                    let hit = egui::Rect::from_center_size(*pos, egui::vec2(24.0, 24.0));
+                    let response = ui.interact(hit, ui.id().with(("graph_node", i)), egui::Sense::click());
+                    let response = accessible(response, link.target.concept.clone());
+                    let color = self.microfiche.meta.category_color(&link.target.category).unwrap_or(similar_color);
+                    let node_radius = if response.hovered() { 11.0 } else { 8.0 };
+                    painter.circle_filled(*pos, node_radius, color);
+                    painter.text(*pos + egui::vec2(0.0, 12.0), egui::Align2::CENTER_TOP, &link.target.concept,
+                        egui::FontId::proportional(12.0), text_color);
+                    
+                    let mut reasons = vec![note_path(&link.target.category, &link.target.subcategory, &link.target.concept)];
+                    if link.wiki {
+                        reasons.push("Wiki link".to_string());
+                    }
+                    if !link.shared_tags.is_empty() {
+                        reasons.push(format!("Shared tags: #{}", link.shared_tags.join(" #")));
+                    }
+                    if link.similarity > 0.0 {
+                        reasons.push(format!("Term similarity: {:.0}%", link.similarity * 100.0));
+                    }
+                    if response.on_hover_text(reasons.join("\n")).clicked() {
+                        navigate = Some(link.target.clone());
+                    }
+                }
+            });
+        
+        if let Some(target) = navigate {
+            self.selected_category = Some(target.category.clone());
+            self.selected_subcategory = Some(target.subcategory.clone());
+            self.selected_concept = Some(target.concept.clone());
+            self.view_mode = ViewMode::Browse;
+            self.graph_center = Some(target);
+        } else if !open {
+            self.graph_center = None;
+        }
+    }
+    
+    fn render_merge_window(&mut self, ctx: &egui::Context) {
+        let Some(source) = self.merge_source.clone() else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(tr("merge-concept"))
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("Merge \"{}\" ({} > {}) into:", source.concept, source.category, source.subcategory));
+                ui.add_space(5.0);
+                
+                let (cat, sub, con) = &mut self.merge_target;
+                hierarchy_picker(ui, "merge", &self.microfiche.categories, cat, sub, Some(con));
+                let target = ConceptRef {
+                    category: cat.clone(),
+                    subcategory: sub.clone(),
+                    concept: con.clone(),
+                };
+                
+                ui.checkbox(&mut self.merge_keep_alias, format!("Keep \"{}\" as an alias", source.concept));
+                ui.separator();
+                
+                let preview = if target == source {
+                    None
+                } else {
+                    self.microfiche.merge_preview(&source, &target)
+                };
+                match &preview {
+                    Some((new_notes, duplicates)) => {
+                        ui.label(tr_args("notes-will-be-moved-identical-notes", &[("count", new_notes.len().to_string().into()), ("duplicates", duplicates.to_string().into())]));
+                        egui::ScrollArea::vertical()
+                            .id_salt("merge_preview_scroll")
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                for note in new_notes {
+                                    ui.add(egui::Label::new(format!("• {}", note.text)).truncate());
+                                }
+                            });
+                    },
+                    None if target == source => {
+                        ui.label(egui::RichText::new(tr("pick-a-different-concept")).color(egui::Color32::GRAY));
+                    },
+                    None => {
+                        ui.label(egui::RichText::new(tr("pick-a-target-concept-to-preview")).color(egui::Color32::GRAY));
+                    },
+                }
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(preview.is_some(), egui::Button::new(tr("merge"))).clicked() {
+                        let source_concept = self.microfiche.concept(&source.category, &source.subcategory, &source.concept).cloned();
+                        let target_notes = self.microfiche.concept(&target.category, &target.subcategory, &target.concept)
+                            .map(|c| c.notes.clone());
+                        let meta = self.microfiche.meta.clone();
+                        
+                        if let Some((moved, duplicates)) = self.microfiche.merge_concepts(&source, &target, self.merge_keep_alias) {
+                            if let (Some(source_concept), Some(target_notes)) = (source_concept, target_notes) {
+                                self.merge_undo = Some(MergeUndo {
+                                    source: source.clone(),
+                                    source_concept,
+                                    target: target.clone(),
+                                    target_notes,
+                                    meta,
+                                });
+                            }
+                            self.selected_category = Some(target.category.clone());
+                            self.selected_subcategory = Some(target.subcategory.clone());
+                            self.status_message = tr_args("merged-into-moved-duplicates-dropped", &[("concept", source.concept.to_string().into()), ("concept2", target.concept.to_string().into()), ("moved", moved.to_string().into()), ("duplicates", duplicates.to_string().into())]);
+                            self.log_activity(
+                                ActivityAction::Merged,
+                                note_path(&source.category, &source.subcategory, &source.concept),
+                                &format!("into {} ({} notes moved)", note_path(&target.category, &target.subcategory, &target.concept), moved),
+                            );
+                        }
+                        close = true;
+                    }
+                    if ui.button(tr("cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        
+        if !open || close {
+            self.merge_source = None;
+        }
+    }
+    
+    fn set_note_flags(&mut self, (cat, sub, con, text): NoteTarget, flags: BTreeSet<NoteFlag>) {
+        let names = if flags.is_empty() { "none".to_string() } else { NoteFlag::to_column(&flags) };
+        if self.microfiche.set_flags(&cat, &sub, &con, &text, flags) {
+            self.log_activity(ActivityAction::FlagsChanged, note_path(&cat, &sub, &con), &format!("{}: {}", names, text));
+            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        }
+    }
+    
+    /// Name new notes and comments are signed with: Your name from
+    /// Settings, or the profile name
+    fn user_name(&self) -> String {
+        match self.settings.author_name.trim() {
+            "" => self.profile.clone(),
+            name => name.to_string(),
+        }
+    }
+    
+    fn comment_action(&mut self, (cat, sub, con, text): NoteTarget, action: CommentAction) {
+        match action {
+            CommentAction::Reply => self.comment_draft = Some(((cat, sub, con, text), String::new())),
+            CommentAction::Cancel => self.comment_draft = None,
+            CommentAction::Post => {
+                let Some((_, reply)) = self.comment_draft.take() else { return };
+                let reply = reply.trim().to_string();
+                if reply.is_empty() {
+                    return;
+                }
+                let author = self.user_name();
+                let detail = format!("{}: {}", author, reply);
+                let comment = NoteComment { author, posted: timestamp_now(), text: reply };
+                if self.microfiche.add_comment(&cat, &sub, &con, &text, comment) {
+                    self.log_activity(ActivityAction::Commented, note_path(&cat, &sub, &con), &detail);
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                } else {
+                    self.status_message = tr("note-no-longer-exists");
+                }
+            },
+            CommentAction::Remove(index) => {
+                if let Some(comment) = self.microfiche.remove_comment(&cat, &sub, &con, &text, index) {
+                    let detail = format!("removed {}'s comment: {}", comment.author, comment.text);
+                    self.log_activity(ActivityAction::Commented, note_path(&cat, &sub, &con), &detail);
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                }
+            },
+        }
+    }
+    
+    /// Move a category, or a subcategory or concept in it, one place up or
+    /// down in Browse and in the saved file
+    fn reorder(&mut self, cat: &str, sub: Option<&str>, con: Option<&str>, step: Step) {
+        let moved = match (sub, con) {
+            (Some(sub), Some(con)) => self.microfiche.move_concept(cat, sub, con, step),
+            (Some(sub), None) => self.microfiche.move_subcategory(cat, sub, step),
+            _ => self.microfiche.move_category(cat, step),
+        };
+        if moved {
+            let path = [Some(cat), sub, con].into_iter().flatten().collect::<Vec<_>>().join(" > ");
+            let detail = if step == Step::Up { "moved up" } else { "moved down" };
+            self.log_activity(ActivityAction::Reordered, path, detail);
+        }
+    }
+    
+    /// Mark a change to the file's colors, goals or study progress, which
+    /// isn't logged, as unsaved, and keep the next undo from taking it back
+    fn meta_changed(&mut self) {
+        self.dirty = true;
+        self.history.keep_meta(&self.microfiche.meta);
+    }
+    
+    fn log_activity(&mut self, action: ActivityAction, path: String, detail: &str) {
+        self.activity.record(action, path, excerpt(detail, 80), &self.session_note);
+    }
+    
+    /// Post `event` on `note` to the webhooks subscribed to it; `previous` is
+    /// the text an edit replaced
+    fn notify(&mut self, event: webhook::Event, (cat, sub, con): (&str, &str, &str), note: &Note, previous: Option<&str>) {
+        let urls: Vec<String> = self.settings.webhooks.iter()
+            .filter(|hook| hook.wants(event, cat))
+            .map(|hook| hook.url.clone())
+            .collect();
+        if urls.is_empty() {
+            return;
+        }
+        let body = webhook::payload(event, self.current_file.as_deref(), (cat, sub, con), note, previous);
+        for url in urls {
+            self.post_webhook(&url, &body, false);
+        }
+    }
+    
+    /// The Publishing row of Settings: the service and its token
+    fn render_publish_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let label = ui.label(tr("publish-to"));
+            egui::ComboBox::from_id_salt("publish_service")
+                .selected_text(self.settings.publish_service.label())
+                .show_ui(ui, |ui| {
+                    for service in publish::Service::ALL {
+                        ui.selectable_value(&mut self.settings.publish_service, service, service.label());
+                    }
+                })
+                .response
+                .labelled_by(label.id);
+            accessible(
+                ui.add(egui::TextEdit::singleline(&mut self.settings.publish_token)
+                    .password(true)
+                    .hint_text(self.settings.publish_service.token_hint())
+                    .desired_width(220.0)),
+                tr("publish-token"),
+            ).on_hover_text(tr("publish-token-saved-with-settings"));
+        });
+    }
+    
+    fn render_theme_editor(&mut self, ctx: &egui::Context) {
+        let Some((editing, draft)) = &mut self.theme_editor else {
+            return;
+        };
+        let editing = *editing;
+        let mut open = true;
+        let mut save = false;
+        let mut cancel = false;
+        let mut delete = false;
+        egui::Window::new(tr("theme-editor"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let label = ui.label(tr("theme-name"));
+                    ui.text_edit_singleline(&mut draft.name).labelled_by(label.id);
+                });
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut draft.dark, true, tr("theme-dark"));
+                    ui.radio_value(&mut draft.dark, false, tr("theme-light"));
+                });
+                ui.separator();
+                egui::Grid::new("theme_editor_colors").num_columns(2).striped(true).show(ui, |ui| {
+                    for (label, color) in draft.colors_mut() {
+                        let label = ui.label(label);
+                        ui.color_edit_button_srgb(color).labelled_by(label.id);
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!draft.name.trim().is_empty(), egui::Button::new(tr("save"))).clicked() {
+                        save = true;
+                    }
+                    cancel = ui.button(tr("cancel")).clicked();
+                    if editing.is_some() && ui.button(tr("delete")).clicked() {
+                        delete = true;
+                    }
+                });
+            });
+        
+        if save {
+            let Some((_, mut draft)) = self.theme_editor.take() else {
+                return;
+            };
+            draft.name = draft.name.trim().to_string();
+            let index = match editing {
+                Some(index) => {
+                    self.settings.custom_themes[index] = draft;
+                    index
+                },
+                None => {
+                    self.settings.custom_themes.push(draft);
+                    self.settings.custom_themes.len() - 1
+                },
+            };
+            self.current_theme = Theme::Custom(index);
+        } else if delete && let Some(index) = editing {
+            self.theme_editor = None;
+            self.settings.custom_themes.remove(index);
+            // Later themes move up one place
+            self.current_theme = match self.current_theme {
+                Theme::Custom(current) if current == index => Theme::Monokai,
+                Theme::Custom(current) if current > index => Theme::Custom(current - 1),
+                theme => theme,
+            };
+        } else if cancel || !open {
+            self.theme_editor = None;
+        }
+    }
+    
+    fn render_publish_window(&mut self, ctx: &egui::Context) {
+        let Some(concept_ref) = self.publish_concept.clone() else {
+            return;
+        };
+        let Some(concept) = self.microfiche.concept(&concept_ref.category, &concept_ref.subcategory, &concept_ref.concept) else {
+            self.publish_concept = None;
+            return;
+        };
+        
+        let text = markdown_export::concept(&concept_ref.category, &concept_ref.subcategory, concept);
+        let uploading = self.upload.is_some();
+        let mut open = true;
+        let mut publish = false;
+        egui::Window::new(tr("publish-concept"))
+            .collapsible(false)
+            .open(&mut open)
+            .default_size([480.0, 360.0])
+            .show(ctx, |ui| {
+                ui.strong(note_path(&concept_ref.category, &concept_ref.subcategory, &concept_ref.concept));
+                ui.label(egui::RichText::new(tr("publish-hint")).size(11.0).color(egui::Color32::GRAY));
+                ui.separator();
+                self.render_publish_settings(ui);
+                egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
+                    ui.add(egui::Label::new(egui::RichText::new(&text).monospace()).wrap());
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let ready = !uploading && !self.settings.publish_token.trim().is_empty();
+                    publish = ui.add_enabled(ready, egui::Button::new(tr("publish")))
+                        .on_disabled_hover_text(tr("publish-needs-a-token"))
+                        .clicked();
+                    if uploading {
+                        ui.spinner();
+                        ui.label(tr("publishing"));
+                    }
+                });
+            });
+        
+        if publish {
+            let title = note_path(&concept_ref.category, &concept_ref.subcategory, &concept_ref.concept);
+            let file_name = category_file_name(&concept_ref.concept, "concept", "md");
+            self.upload = Some(publish::Upload::start(self.settings.publish_service, &self.settings.publish_token, &title, &file_name, &text));
+        }
+        if !open {
+            self.publish_concept = None;
+        }
+    }
+    
+    /// Finish a Publish upload: its link goes to the clipboard
+    fn poll_upload(&mut self, ctx: &egui::Context) {
+        let Some(upload) = &mut self.upload else {
+            return;
+        };
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        let Some(result) = upload.poll() else {
+            return;
+        };
+        self.upload = None;
+        match result {
+            Ok(url) => {
+                self.status_message = tr_args("published", &[("url", url.clone().into())]);
+                self.to_clipboard = Some(url);
+                self.publish_concept = None;
+            },
+            Err(e) => self.status_message = tr_args("publish-failed", &[("error", e.into())]),
+        }
+    }
+    
+    fn post_webhook(&mut self, url: &str, body: &str, test: bool) {
+        match webhook::Delivery::send(url, body) {
+            Ok(delivery) => self.webhook_deliveries.push((delivery, test)),
+            Err(e) => self.status_message = tr_args("webhook-failed", &[("url", url.into()), ("error", e.into())]),
+        }
+    }
+    
+    fn poll_webhooks(&mut self, ctx: &egui::Context) {
+        if self.webhook_deliveries.is_empty() {
+            return;
+        }
+        self.webhook_deliveries.retain_mut(|(delivery, test)| {
+            let Some(result) = delivery.poll() else { return true };
+            let url = delivery.url.clone();
+            match result {
+                Ok(()) if *test => self.status_message = tr_args("webhook-test-sent", &[("url", url.into())]),
+                Ok(()) => {},
+                Err(e) => self.status_message = tr_args("webhook-failed", &[("url", url.into()), ("error", e.into())]),
+            }
+            false
+        });
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+    }
+    
+    /// The Webhooks section of Settings
+    fn render_webhook_settings(&mut self, ui: &mut egui::Ui) {
+        let mut categories: Vec<String> = self.microfiche.categories.keys().cloned().collect();
+        categories.sort_by(|a, b| collate(a, b));
+        let mut to_remove: Option<usize> = None;
+        let mut to_test: Option<String> = None;
+        let heading = tr_args("webhooks-heading", &[("count", self.settings.webhooks.len().to_string().into())]);
+        egui::CollapsingHeader::new(heading).id_salt("webhook_settings").show(ui, |ui| {
+            ui.label(egui::RichText::new(tr("webhooks-hint")).size(11.0).weak());
+            for (index, hook) in self.settings.webhooks.iter_mut().enumerate() {
+                ui.push_id(index, |ui| {
+                    ui.horizontal(|ui| {
+                        accessible(ui.checkbox(&mut hook.enabled, ""), tr("webhook-enabled"))
+                            .on_hover_text(tr("webhook-enabled"));
+                        accessible(
+                            ui.add(egui::TextEdit::singleline(&mut hook.url).hint_text("https://hooks.slack.com/…").desired_width(260.0)),
+                            tr("webhook-url"),
+                        );
+                        if ui.small_button(tr("webhook-send-test")).clicked() {
+                            to_test = Some(hook.url.clone());
+                        }
+                        if accessible(ui.small_button("🗑"), tr("remove-webhook")).clicked() {
+                            to_remove = Some(index);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        for event in webhook::Event::ALL {
+                            let mut on = hook.events.contains(&event);
+                            if ui.checkbox(&mut on, event.label()).changed() {
+                                if on {
+                                    hook.events.push(event);
+                                } else {
+                                    hook.events.retain(|e| *e != event);
+                                }
+                            }
+                        }
+                        let selected = if hook.category.is_empty() { tr("any-category") } else { hook.category.clone() };
+                        egui::ComboBox::from_id_salt("webhook_category")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut hook.category, String::new(), tr("any-category"));
+                                for cat in &categories {
+                                    ui.selectable_value(&mut hook.category, cat.clone(), cat);
+                                }
+                            });
+                    });
+                });
+            }
+            if ui.button(tr("add-webhook")).clicked() {
+                self.settings.webhooks.push(webhook::Webhook::default());
+            }
+        });
+        if let Some(index) = to_remove {
+            self.settings.webhooks.remove(index);
+        }
+        if let Some(url) = to_test.filter(|url| !url.trim().is_empty()) {
+            self.post_webhook(&url, &webhook::test_payload(), true);
+        }
+    }
+    
+    /// Remember the card under the pointer for the next frame; while a menu
+    /// is open the pointer is over the menu, so the card keeps its buttons
+    fn track_hovered_card(&mut self, ctx: &egui::Context, hovered: Option<String>) {
+        if hovered.is_some() || !egui::Popup::is_any_open(ctx) {
+            self.hovered_card = hovered;
+        }
+    }
+    
+    fn open_note_tool(&mut self, tool: NoteTool, note: (String, String, String, String)) {
+        match tool {
+            NoteTool::Split => {
+                self.split_draft = note.3.clone();
+                self.split_note = Some(note);
+            },
+            NoteTool::Append => {
+                self.append_draft.clear();
+                self.append_note = Some(note);
+            },
+            NoteTool::ToggleReview => {
+                let (cat, sub, con, text) = note;
+                let flagged = self.microfiche.concept(&cat, &sub, &con)
+                    .and_then(|c| c.notes.iter().find(|n| n.text == text))
+                    .is_some_and(|n| n.needs_review);
+                if self.microfiche.set_needs_review(&cat, &sub, &con, &text, !flagged) {
+                    let action = if flagged { ActivityAction::Reviewed } else { ActivityAction::FlaggedForReview };
+                    self.log_activity(action, note_path(&cat, &sub, &con), &text);
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                }
+            },
+            NoteTool::ToggleDefinition => {
+                let (cat, sub, con, text) = note;
+                let pinned = self.microfiche.concept(&cat, &sub, &con)
+                    .and_then(|c| c.notes.iter().find(|n| n.text == text))
+                    .is_some_and(|n| n.definition);
+                if self.microfiche.set_definition(&cat, &sub, &con, &text, !pinned) {
+                    let detail = format!("{}: {}", if pinned { "unpinned" } else { "pinned" }, text);
+                    self.log_activity(ActivityAction::DefinitionChanged, note_path(&cat, &sub, &con), &detail);
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                }
+            },
+            NoteTool::ToggleMonospace | NoteTool::ToggleWrap | NoteTool::ToggleIndent | NoteTool::Highlight(_) => {
+                let (cat, sub, con, text) = note;
+                let Some(mut display) = self.microfiche.concept(&cat, &sub, &con)
+                    .and_then(|c| c.notes.iter().find(|n| n.text == text))
+                    .map(|n| n.display)
+                else {
+                    return;
+                };
+                match tool {
+                    NoteTool::ToggleMonospace => display.monospace = !display.monospace,
+                    NoteTool::ToggleWrap => display.no_wrap = !display.no_wrap,
+                    NoteTool::Highlight(highlight) => display.highlight = highlight,
+                    _ => display.preserve_indent = !display.preserve_indent,
+                }
+                if self.microfiche.set_display(&cat, &sub, &con, &text, display) {
+                    let flags = if display == NoteDisplay::default() { "plain".to_string() } else { display.to_column() };
+                    self.log_activity(ActivityAction::DisplayChanged, note_path(&cat, &sub, &con), &format!("{}: {}", flags, text));
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                }
+            },
+            NoteTool::CopyAsQuote => {
+                let (cat, sub, con, text) = note;
+                self.to_clipboard = Some(markdown_quote(&cat, &sub, &con, &text));
+                self.status_message = tr("copied-as-quote");
+            },
+            NoteTool::ShareByEmail => {
+                let (cat, sub, con, text) = note;
+                let body = format!("{}\n\n— {}", text.trim(), note_path(&cat, &sub, &con));
+                self.status_message = match share::email(&con, &body) {
+                    Ok(()) => tr("opened-an-email-with-the-note"),
+                    Err(e) => tr_args("could-not-open-an-email", &[("error", e.into())]),
+                };
+            },
+            NoteTool::ShowQr => self.qr_popup = Some(QrPopup::new(note, false)),
+            NoteTool::Details => self.details_note = Some(note),
+            NoteTool::EditExternally => {
+                if self.external_edits.iter().any(|(editing, _)| *editing == note) {
+                    self.status_message = tr("this-note-is-already-open-in-the-editor");
+                    return;
+                }
+                match external_editor::Session::start(&self.settings.external_editor, &note.3) {
+                    Ok(session) => {
+                        self.external_edits.push((note, session));
+                        self.status_message = tr("editing-in-external-editor");
+                    },
+                    Err(e) => self.status_message = tr_args("could-not-start-the-editor", &[("error", e.into())]),
+                }
+            },
+            NoteTool::Comment => self.comment_draft = Some((note, String::new())),
+            NoteTool::Purge => self.purge_note = Some(note),
+        }
+    }
+    
+    fn render_split_window(&mut self, ctx: &egui::Context) {
+        let Some((cat, sub, con, original)) = self.split_note.clone() else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(tr("split-note"))
+            .collapsible(false)
+            .resizable(true)
+            .default_width(500.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("{} > {} > {}", cat, sub, con));
+                ui.label(egui::RichText::new(tr_args("put-a-line-containing-only-wherever", &[("split_marker", SPLIT_MARKER.to_string().into())]))
+                    .size(11.0)
+                    .color(egui::Color32::GRAY));
+                
+                egui::ScrollArea::vertical()
+                    .id_salt("split_scroll")
+                    .max_height(350.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.split_draft)
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(12)
+                        );
+                    });
+                
+                if ui.small_button(tr("insert-markers-at-blank-lines")).clicked() {
+                    self.split_draft = insert_split_markers(&self.split_draft);
+                }
+                
+                let parts = split_parts(&self.split_draft);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(tr_args("notes", &[("count", parts.len().to_string().into())]));
+                    if ui.add_enabled(parts.len() > 1, egui::Button::new(tr("split"))).clicked() {
+                        let count = parts.len();
+                        if self.microfiche.split_note(&cat, &sub, &con, &original, parts) {
+                            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                            self.status_message = tr_args("note-split-into-notes", &[("count", count.to_string().into())]);
+                            self.log_activity(ActivityAction::Split, note_path(&cat, &sub, &con), &format!("into {} notes: {}", count, original));
+                        } else {
+                            self.status_message = tr("note-no-longer-exists");
+                        }
+                        close = true;
+                    }
+                    if ui.button(tr("cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        
+        if !open || close {
+            self.split_note = None;
+        }
+    }
+    
+    fn render_append_window(&mut self, ctx: &egui::Context) {
+        let Some((cat, sub, con, original)) = self.append_note.clone() else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(tr("append-to-note"))
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("{} > {} > {}", cat, sub, con));
+                ui.add(egui::Label::new(egui::RichText::new(&original).color(egui::Color32::GRAY)).truncate());
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.append_prepend, false, "Append");
+                    ui.radio_value(&mut self.append_prepend, true, "Prepend");
+                    ui.separator();
+                    ui.checkbox(&mut self.append_timestamp, tr("add-timestamp"));
+                });
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.append_draft)
+                        .hint_text(tr("text-to-add"))
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(6)
+                );
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let label = if self.append_prepend { "Prepend" } else { "Append" };
+                    if ui.add_enabled(!self.append_draft.trim().is_empty(), egui::Button::new(label)).clicked() {
+                        let mut addition = self.append_draft.trim().to_string();
+                        if self.append_timestamp {
+                            addition = format!("[{}]\n{}", chrono::Local::now().format("%Y-%m-%d %H:%M"), addition);
+                        }
+                        if self.microfiche.extend_note(&cat, &sub, &con, &original, &addition, self.append_prepend) {
+                            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                            self.status_message = tr("note-updated");
+                            let action = if self.append_prepend { "prepended" } else { "appended" };
+                            self.log_activity(ActivityAction::Appended, note_path(&cat, &sub, &con), &format!("{}: {}", action, addition));
+                        } else {
+                            self.status_message = tr("note-no-longer-exists");
+                        }
+                        close = true;
+                    }
+                    if ui.button(tr("cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        
+        if !open || close {
+            self.append_note = None;
+        }
+    }
+    
+    /// Bring back text saved in external editors, and forget editors that closed
+    fn sync_external_edits(&mut self, ctx: &egui::Context) {
+        if self.external_edits.is_empty() {
+            return;
+        }
+        let mut changed = false;
+        let mut edited: Vec<(NoteTarget, Note)> = Vec::new();
+        let mut index = 0;
+        while index < self.external_edits.len() {
+            let (note, session) = &mut self.external_edits[index];
+            let mut lost = false;
+            if let Some(text) = session.poll() {
+                // Editors usually end the file with a newline the note didn't have
+                let text = text.strip_suffix('\n').unwrap_or(&text).to_string();
+                let (cat, sub, con, old) = note.clone();
+                match self.microfiche.find_note_mut(&cat, &sub, &con, &old) {
+                    Some(existing) => {
+                        existing.text = text.clone();
+                        existing.modified = Some(timestamp_now());
+                        edited.push(((cat.clone(), sub.clone(), con.clone(), old.clone()), existing.clone()));
+                        self.microfiche.reindex_note(&cat, &sub, &con, &old, &text);
+                        note.3 = text.clone();
+                        changed = true;
+                        self.activity.record(ActivityAction::Edited, note_path(&cat, &sub, &con), excerpt(&text, 80), &self.session_note);
+                        self.status_message = tr("note-updated-from-the-external-editor");
+                    },
+                    None => {
+                        self.status_message = tr("note-no-longer-exists");
+                        lost = true;
+                    },
+                }
+            }
+            if lost || session.finished() {
+                self.external_edits.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+        if changed {
+            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        }
+        for ((cat, sub, con, old), note) in edited {
+            self.notify(webhook::Event::Edited, (&cat, &sub, &con), &note, Some(&old));
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+    }
+    
+    fn render_qr_window(&mut self, ctx: &egui::Context) {
+        let Some(popup) = &self.qr_popup else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut path_only = popup.path_only;
+        egui::Window::new(tr("qr-code"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let (cat, sub, con, _) = &popup.note;
+                ui.label(note_path(cat, sub, con));
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut path_only, false, tr("qr-note-text"));
+                    ui.radio_value(&mut path_only, true, tr("qr-path-only"));
+                });
+                ui.separator();
+                match &popup.code {
+                    Some(code) => qr_code_widget(ui, code, 320.0),
+                    None => {
+                        ui.label(egui::RichText::new(tr("too-long-for-a-qr-code")).color(ui.visuals().warn_fg_color));
+                    },
+                }
+            });
+        
+        if !open {
+            self.qr_popup = None;
+        } else if path_only != popup.path_only {
+            let note = popup.note.clone();
+            self.qr_popup = Some(QrPopup::new(note, path_only));
+        }
+    }
+    
+    fn render_details_window(&mut self, ctx: &egui::Context) {
+        let Some((cat, sub, con, text)) = &self.details_note else {
+            return;
+        };
+        // Gone once the note is edited or deleted
+        let Some(note) = self.microfiche.concept(cat, sub, con).and_then(|c| c.notes.iter().find(|n| n.text == *text)) else {
+            self.details_note = None;
+            return;
+        };
+        
+        let words = text.split_whitespace().count();
+        let terms: BTreeSet<String> = search_index::terms(text).into_iter().collect();
+        let id = note_key(cat, sub, con, text);
+        let revisions = 1 + self.activity.revisions(&note_path(cat, sub, con), text);
+        let mut open = true;
+        let mut copy_id = false;
+        egui::Window::new(tr("note-details"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(note_path(cat, sub, con));
+                ui.label(egui::RichText::new(excerpt(text, 80)).italics());
+                ui.separator();
+                egui::Grid::new("note_details").num_columns(2).striped(true).show(ui, |ui| {
+                    let rows = [
+                        (tr("details-words"), words.to_string()),
+                        (tr("details-characters"), text.chars().count().to_string()),
+                        (tr("details-characters-no-spaces"), text.chars().filter(|c| !c.is_whitespace()).count().to_string()),
+                        (tr("details-unique-terms"), terms.len().to_string()),
+                        (tr("details-reading-time"), tr_args("details-minutes", &[("minutes", words.div_ceil(READING_WORDS_PER_MINUTE).to_string().into())])),
+                        (tr("details-created"), note.created.clone().unwrap_or_else(|| "—".to_string())),
+                        (tr("details-modified"), note.modified.clone().unwrap_or_else(|| "—".to_string())),
+                    ];
+                    for (label, value) in rows {
+                        ui.label(label);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                    ui.label(tr("details-revisions"));
+                    ui.label(revisions.to_string()).on_hover_text(tr("details-revisions-hint"));
+                    ui.end_row();
+                    ui.label(tr("details-id"));
+                    ui.horizontal(|ui| {
+                        ui.monospace(&id).on_hover_text(tr("details-id-hint"));
+                        copy_id = accessible(ui.small_button("📋"), tr("copy-id")).on_hover_text(tr("copy-id")).clicked();
+                    });
+                    ui.end_row();
+                });
+            });
+        
+        if copy_id {
+            self.to_clipboard = Some(id);
+        }
+        if !open {
+            self.details_note = None;
+        }
+    }
+    
+    fn open_subcategory_info(&mut self, cat: &str, sub: &str) {
+        let Some(subcat) = self.microfiche.categories.get(cat)
+            .and_then(|category| category.subcategories.iter().find(|s| s.name == sub))
+        else {
+            return;
+        };
+        
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut dated: Vec<(&str, &str)> = Vec::new();
+        let mut notes = 0;
+        for concept in &subcat.concepts {
+            for note in &concept.notes {
+                notes += 1;
+                for word in extract_words(&note.text) {
+                    *counts.entry(word).or_insert(0) += 1;
+                }
+                if let Some(created) = &note.created {
+                    dated.push((created, &note.text));
+                }
+            }
+        }
+        let mut top_terms: Vec<(String, usize)> = counts.into_iter().collect();
+        top_terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_terms.truncate(SUBCATEGORY_INFO_TERMS);
+        dated.sort();
+        let dated_note = |(created, text): &(&str, &str)| (excerpt(text, 80), created.get(..10).unwrap_or(created).to_string());
+        
+        // Activity per day over the last weeks, for the sparkline
+        let prefix = format!("{} > {} > ", cat, sub);
+        let today = chrono::Local::now().date_naive();
+        let mut activity = vec![0; SUBCATEGORY_INFO_DAYS];
+        for entry in &self.activity.entries {
+            if !entry.path.starts_with(&prefix) {
+                continue;
+            }
+            if let Ok(day) = chrono::NaiveDate::parse_from_str(entry.day(), "%Y-%m-%d")
+                && let Ok(ago) = usize::try_from((today - day).num_days())
+                && ago < SUBCATEGORY_INFO_DAYS
+            {
+                activity[SUBCATEGORY_INFO_DAYS - 1 - ago] += 1;
+            }
+        }
+        
+        self.subcategory_info = Some(SubcategoryInfo {
+            category: cat.to_string(),
+            subcategory: sub.to_string(),
+            concepts: subcat.concepts.len(),
+            notes,
+            top_terms,
+            newest: dated.last().map(dated_note),
+            oldest: dated.first().map(dated_note),
+            activity,
+        });
+    }
+    
+    fn render_subcategory_info_window(&mut self, ctx: &egui::Context) {
+        let Some(info) = &self.subcategory_info else {
+            return;
+        };
+        
+        let mut open = true;
+        egui::Window::new(tr("subcategory-info-title"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong(format!("{} > {}", info.category, info.subcategory));
+                ui.separator();
+                egui::Grid::new("subcategory_info").num_columns(2).striped(true).show(ui, |ui| {
+                    ui.label(tr("info-concepts"));
+                    ui.label(info.concepts.to_string());
+                    ui.end_row();
+                    ui.label(tr("info-notes"));
+                    ui.label(info.notes.to_string());
+                    ui.end_row();
+                    for (label, note) in [(tr("info-newest-note"), &info.newest), (tr("info-oldest-note"), &info.oldest)] {
+                        ui.label(label);
+                        match note {
+                            Some((text, created)) => ui.label(format!("{} · {}", created, text)),
+                            None => ui.label("—"),
+                        };
+                        ui.end_row();
+                    }
+                });
+                
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new(tr("info-top-terms")).strong());
+                if info.top_terms.is_empty() {
+                    ui.label("—");
+                } else {
+                    ui.horizontal_wrapped(|ui| {
+                        for (term, count) in &info.top_terms {
+                            ui.label(format!("{} ({})", term, count));
+                        }
+                    });
+                }
+                
+                ui.add_space(6.0);
+                let total: usize = info.activity.iter().sum();
+                ui.label(egui::RichText::new(tr_args("info-activity", &[
+                    ("days", SUBCATEGORY_INFO_DAYS.into()),
+                    ("count", total.into()),
+                ])).strong());
+                sparkline(ui, &info.activity, egui::vec2(240.0, 32.0));
+            });
+        
+        if !open {
+            self.subcategory_info = None;
+        }
+    }
+    
+    fn render_purge_window(&mut self, ctx: &egui::Context) {
+        let Some((cat, sub, con, note)) = self.purge_note.clone() else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(tr("purge-note"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong(note_path(&cat, &sub, &con));
+                note_label(ui, &excerpt(&note, 200), NoteDisplay::default(), self.settings.masks(&cat), false);
+                ui.separator();
+                ui.label(tr("purge-removes-the-note-and"));
+                ui.horizontal(|ui| {
+                    if ui.button(egui::RichText::new(tr("purge")).color(ui.visuals().error_fg_color)).clicked() {
+                        self.purge((cat.clone(), sub.clone(), con.clone(), note.clone()));
+                        close = true;
+                    }
+                    if ui.button(tr("cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        
+        if !open || close {
+            self.purge_note = None;
+        }
+    }
+    
+    /// Delete a note along with every trace of it kept outside the notes:
+    /// activity entries quoting it, its study record, the undo history and
+    /// the merge undo snapshot. The file is saved straight away so the text leaves the disk.
+    fn purge(&mut self, (cat, sub, con, text): NoteTarget) {
+        if self.job_running() {
+            return;
+        }
+        if self.microfiche.delete_note(&cat, &sub, &con, &text).is_none() {
+            self.status_message = tr("note-no-longer-exists");
+            return;
+        }
+        
+        let path = note_path(&cat, &sub, &con);
+        let entries = self.activity.purge(&path, &text);
+        let key = note_key(&cat, &sub, &con, &text);
+        self.microfiche.meta.study.studied.remove(&key);
+        self.microfiche.meta.aging.forget(&key);
+        let in_undo = self.merge_undo.as_ref().is_some_and(|undo| {
+            undo.source_concept.notes.iter().chain(&undo.target_notes).any(|n| n.text == text)
+        });
+        if in_undo {
+            self.merge_undo = None;
+        }
+        if self.editing_note.as_deref() == Some(text.as_str()) {
+            self.editing_note = None;
+        }
+        self.split_note.take_if(|(_, _, _, t)| *t == text);
+        self.append_note.take_if(|(_, _, _, t)| *t == text);
+        self.vim.confirm_delete.take_if(|(_, _, _, t)| *t == text);
+        // Undo steps would bring the note back; the history starts over
+        // from the purged fiche
+        self.history = History::default();
+        // Recorded without the text so the log shows something was removed
+        self.log_activity(ActivityAction::Purged, path, "");
+        self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        
+        if let Some(file) = self.current_file.clone() {
+            // The saved search index cache holds every note's text; built
+            // here, the save rewrites it without this one
+            self.microfiche.index.get_or_build(&self.microfiche);
+            self.start_save(file, Some(text));
+        }
+        self.status_message = tr_args("purged-note-and-activity-entries", &[("count", entries.to_string().into())]);
+    }
+    
+    fn undo_merge(&mut self) {
+        let Some(undo) = self.merge_undo.take() else {
+            return;
+        };
+        
+        if let Some(category) = self.microfiche.categories.get_mut(&undo.target.category)
+            && let Some(subcat) = category.subcategories.iter_mut().find(|s| s.name == undo.target.subcategory)
+            && let Some(concept) = subcat.concepts.iter_mut().find(|c| c.name == undo.target.concept)
+        {
+            concept.notes = undo.target_notes;
+        }
+        self.microfiche.index.clear();
+        self.microfiche.restore_concept(&undo.source, undo.source_concept);
+        self.microfiche.meta = undo.meta;
+        self.status_message = tr_args("merge-of-undone", &[("concept", undo.source.concept.to_string().into())]);
+        self.log_activity(
+            ActivityAction::MergeUndone,
+            note_path(&undo.source.category, &undo.source.subcategory, &undo.source.concept),
+            "",
+        );
+    }
+    
+    fn edit_note_template(&mut self, cat: &str, sub: &str, con: &str) {
+        self.note_template_draft = self.microfiche.meta.own_note_template(cat, sub, con).unwrap_or_default().to_string();
+        self.editing_note_template = Some((cat.to_string(), sub.to_string(), con.to_string()));
+    }
+    
+    fn render_note_template_window(&mut self, ctx: &egui::Context) {
+        let Some((cat, sub, con)) = self.editing_note_template.clone() else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(tr("note-template-title"))
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let path = if con.is_empty() { format!("{} > {}", cat, sub) } else { note_path(&cat, &sub, &con) };
+                ui.label(&path);
+                let hint = if con.is_empty() { tr("new-notes-in-this-subcategory-start") } else { tr("new-notes-under-this-concept-start") };
+                ui.label(egui::RichText::new(hint).size(11.0).color(egui::Color32::GRAY));
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.note_template_draft)
+                        .hint_text(tr("note-template-hint"))
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(8)
+                );
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(tr("save")).clicked() {
+                        let text = std::mem::take(&mut self.note_template_draft);
+                        self.microfiche.meta.set_note_template(&cat, &sub, &con, text.clone());
+                        self.log_activity(ActivityAction::Described, path.clone(), &format!("note template: {}", text.trim()));
+                        self.status_message = tr("note-template-updated");
+                        close = true;
+                    }
+                    let has_template = self.microfiche.meta.own_note_template(&cat, &sub, &con).is_some();
+                    if ui.add_enabled(has_template, egui::Button::new(tr("remove"))).clicked() {
+                        self.microfiche.meta.set_note_template(&cat, &sub, &con, String::new());
+                        self.log_activity(ActivityAction::Described, path.clone(), "note template removed");
+                        self.status_message = tr("note-template-removed");
+                        close = true;
+                    }
+                    if ui.button(tr("cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        
+        if !open || close {
+            self.editing_note_template = None;
+        }
+    }
+    
+    /// List the open file's snapshots, reading each for its counts
+    fn open_restore_backup(&mut self) {
+        let Some(path) = &self.current_file else {
+            return;
+        };
+        let backups = snapshots::list(path).into_iter()
+            .map(|snapshot| {
+                let counts = Microfiche::open(&snapshot.path.to_string_lossy())
+                    .map(|fiche| (fiche.categories.len(), fiche.note_count()))
+                    .map_err(|e| e.to_string());
+                (snapshot, counts)
+            })
+            .collect();
+        self.restore_backups = Some(backups);
+    }
+    
+    fn render_restore_backup_window(&mut self, ctx: &egui::Context) {
+        let Some(backups) = &self.restore_backups else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut restore: Option<usize> = None;
+        egui::Window::new(tr("restore-backup"))
+            .open(&mut open)
+            .default_size([480.0, 360.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("restore-backup-hint")).size(11.0).color(egui::Color32::GRAY));
+                ui.separator();
+                if backups.is_empty() {
+                    ui.label(tr("no-backups-yet"));
+                    return;
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("restore_backups").striped(true).num_columns(4).show(ui, |ui| {
+                        for (index, (snapshot, counts)) in backups.iter().enumerate() {
+                            ui.label(snapshot.taken.format("%Y-%m-%d %H:%M:%S").to_string());
+                            ui.label(format!("{:.1} KB", snapshot.size as f64 / 1024.0));
+                            match counts {
+                                Ok((categories, notes)) => {
+                                    ui.label(tr_args("backup-counts", &[
+                                        ("categories", categories.to_string().into()),
+                                        ("notes", notes.to_string().into()),
+                                    ]));
+                                    if ui.button(tr("restore")).clicked() {
+                                        restore = Some(index);
+                                    }
+                                },
+                                Err(e) => {
+                                    ui.label(egui::RichText::new(tr("backup-unreadable")).color(ui.visuals().error_fg_color))
+                                        .on_hover_text(e);
+                                    ui.label("");
+                                },
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        
+        if let Some(index) = restore {
+            let backups = self.restore_backups.take().unwrap_or_default();
+            let snapshot = &backups[index].0;
+            match Microfiche::open(&snapshot.path.to_string_lossy()) {
+                Ok(fiche) => {
+                    let taken = snapshot.taken.format("%Y-%m-%d %H:%M:%S").to_string();
+                    self.microfiche = fiche;
+                    self.merge_undo = None;
+                    self.clusters = None;
+                    self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                    let path = self.current_file.clone().unwrap_or_default();
+                    self.log_activity(ActivityAction::Restored, path, &format!("from {}", taken));
+                    self.status_message = tr_args("restored-backup", &[("taken", taken.into())]);
+                },
+                Err(e) => self.status_message = tr_args("error-loading", &[("error", e.to_string().into())]),
+            }
+        } else if !open {
+            self.restore_backups = None;
+        }
+    }
+    
+    fn render_file_info_window(&mut self, ctx: &egui::Context) {
+        let Some(draft) = &mut self.file_info_draft else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut save = false;
+        let mut close = false;
+        egui::Window::new(tr("file-info"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(tr("file-info-hint")).size(11.0).color(egui::Color32::GRAY));
+                egui::Grid::new("file_info").num_columns(2).show(ui, |ui| {
+                    let label = ui.label(tr("file-info-title"));
+                    ui.add(egui::TextEdit::singleline(&mut draft.title).desired_width(300.0)).labelled_by(label.id);
+                    ui.end_row();
+                    let label = ui.label(tr("file-info-author"));
+                    ui.add(egui::TextEdit::singleline(&mut draft.author).desired_width(300.0)).labelled_by(label.id);
+                    ui.end_row();
+                    let label = ui.label(tr("file-info-description"));
+                    ui.add(egui::TextEdit::multiline(&mut draft.description).desired_width(300.0).desired_rows(4)).labelled_by(label.id);
+                    ui.end_row();
+                    let label = ui.label(tr("file-info-schema-version"));
+                    ui.add(egui::TextEdit::singleline(&mut draft.schema_version).hint_text("1.0").desired_width(100.0)).labelled_by(label.id);
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    save = ui.button(tr("save")).clicked();
+                    close = ui.button(tr("cancel")).clicked();
+                });
+            });
+        
+        if save && let Some(info) = self.file_info_draft.take() {
+            if info != self.microfiche.meta.info {
+                let title = info.title.clone();
+                self.microfiche.meta.info = info;
+                self.log_activity(ActivityAction::Described, tr("file-info"), &title);
+                self.status_message = tr("file-info-updated");
+            }
+        } else if !open || close {
+            self.file_info_draft = None;
+        }
+    }
+    
+    /// The scratchpad panel: text kept between runs until it's filed, when
+    /// File This moves the selection (or all of it) into the Create form
+    fn render_scratchpad(&mut self, ctx: &egui::Context) {
+        if !self.show_scratchpad {
+            return;
+        }
+        let mut to_file: Option<std::ops::Range<usize>> = None;
+        egui::SidePanel::right("scratchpad")
+            .resizable(true)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(tr("scratchpad"));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if accessible(ui.small_button("✖"), tr("close")).clicked() {
+                            self.show_scratchpad = false;
+                        }
+                    });
+                });
+                let file = ui.add_enabled(!self.scratchpad.trim().is_empty(), egui::Button::new(tr("file-this")))
+                    .on_hover_text(tr("file-this-hint"))
+                    .clicked();
+                ui.separator();
+                let output = egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::TextEdit::multiline(&mut self.scratchpad)
+                        .hint_text(tr("scratchpad-placeholder"))
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(20)
+                        .show(ui)
+                }).inner;
+                if file {
+                    let selected = output.state.cursor.char_range()
+                        .map(|range| range.as_sorted_char_range())
+                        .filter(|range| !range.is_empty());
+                    to_file = Some(selected.unwrap_or(0..self.scratchpad.chars().count()));
+                }
+            });
+        
+        let Some(range) = to_file else {
+            return;
+        };
+        let chars: Vec<char> = self.scratchpad.chars().collect();
+        let text: String = chars[range.start.min(chars.len())..range.end.min(chars.len())].iter().collect();
+        self.scratchpad = chars[..range.start.min(chars.len())].iter().chain(&chars[range.end.min(chars.len())..]).collect();
+        // Added after what the form already holds, so nothing typed there is lost
+        let text = text.trim();
+        if self.new_note.trim().is_empty() {
+            self.new_note = text.to_string();
+        } else {
+            self.new_note = format!("{}\n\n{}", self.new_note.trim_end(), text);
+        }
+        self.view_mode = ViewMode::Create;
+        self.status_message = tr("moved-to-the-create-form");
+    }
+    
+    /// Put the note template for the Create form's concept into an empty note
+    /// when the concept changes, swapping it out again if it wasn't touched
+    fn prefill_note(&mut self) {
+        let path = (self.new_category.trim().to_string(), self.new_subcategory.trim().to_string(), self.new_concept.trim().to_string());
+        if self.note_prefill.as_ref().is_some_and(|(filled_for, _)| *filled_for == path) {
+            return;
+        }
+        let untouched = self.new_note.is_empty()
+            || self.note_prefill.as_ref().is_some_and(|(_, text)| *text == self.new_note);
+        let template = self.microfiche.meta.note_template(&path.0, &path.1, &path.2).unwrap_or_default().to_string();
+        if untouched {
+            self.new_note = template.clone();
+        }
+        self.note_prefill = Some((path, template));
+    }
+    
+    /// Overview text for a category (sub = None) or subcategory, with inline editing
+    fn render_description(&mut self, ui: &mut egui::Ui, cat: &str, sub: Option<&str>) {
+        let key = (cat.to_string(), sub.map(|s| s.to_string()));
+        
+        if self.editing_description.as_ref() == Some(&key) {
+            ui.add(
+                egui::TextEdit::multiline(&mut self.description_draft)
+                    .hint_text(tr("describe-what-belongs-here"))
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(3)
+            );
+            ui.horizontal(|ui| {
+                if ui.button(tr("save")).clicked() {
+                    let text = std::mem::take(&mut self.description_draft);
+                    self.microfiche.meta.set_description(cat, sub, text.trim().to_string());
+                    self.editing_description = None;
+                    let path = match sub {
+                        Some(sub) => format!("{} > {}", cat, sub),
+                        None => cat.to_string(),
+                    };
+                    self.log_activity(ActivityAction::Described, path, text.trim());
+                    self.status_message = tr("description-updated");
+                }
+                if ui.button(tr("cancel")).clicked() {
+                    self.editing_description = None;
+                }
+            });
+            return;
+        }
+        
+        let description = self.microfiche.meta.description(cat, sub).to_string();
+        if !description.is_empty() {
+            ui.add(egui::Label::new(egui::RichText::new(&description).italics()).wrap());
+        }
+        let button_text = if description.is_empty() { "Add description" } else { "Edit description" };
+        if ui.small_button(button_text).clicked() {
+            self.description_draft = description;
+            self.editing_description = Some(key);
+        }
+    }
+    
+    fn render_category_label_menu(&mut self, ui: &mut egui::Ui, cat_name: &str) {
+        let meta = self.microfiche.meta.categories.entry(cat_name.to_string()).or_default();
+        
+        ui.label(egui::RichText::new(cat_name).strong());
+        ui.separator();
+        
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(tr("color-label"));
+            let mut rgb = meta.color.unwrap_or([100, 149, 237]);
+            if ui.color_edit_button_srgb(&mut rgb).changed() {
+                meta.color = Some(rgb);
+                changed = true;
+            }
+            if meta.color.is_some() && ui.small_button(tr("clear")).clicked() {
+                meta.color = None;
+                changed = true;
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            let label = ui.label(tr("icon-label"));
+            changed |= ui.add(egui::TextEdit::singleline(&mut meta.icon)
+                .hint_text(tr("icon-hint"))
+                .desired_width(60.0)
+                .char_limit(4))
+                .labelled_by(label.id)
+                .changed();
+        });
+        if changed {
+            self.meta_changed();
+        }
+        
+        ui.label(egui::RichText::new(tr("saved-with-the-file"))
+            .size(10.0)
+            .color(egui::Color32::GRAY));
+        ui.separator();
+        
+        let mut shown = self.settings.privacy_exceptions.contains(cat_name);
+        if ui.checkbox(&mut shown, tr("show-notes-in-privacy-mode")).changed() {
+            if shown {
+                self.settings.privacy_exceptions.insert(cat_name.to_string());
+            } else {
+                self.settings.privacy_exceptions.remove(cat_name);
+            }
+        }
+        
+        ui.separator();
+        let index = self.microfiche.categories.get_index_of(cat_name).unwrap_or_default();
+        if let Some(step) = move_buttons(ui, index, self.microfiche.categories.len()) {
+            self.reorder(cat_name, None, None, step);
+        }
+    }
+    
+    fn render_search_view(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let label = ui.label(tr("search-label"));
+            let response = ui.text_edit_singleline(&mut self.search_query).labelled_by(label.id);
+            if std::mem::take(&mut self.vim.focus_search) {
+                response.request_focus();
+            }
+            
+            let mut filter_changed = false;
+            ui.label(tr("min-rating-label"));
+            egui::ComboBox::from_id_salt("search_min_rating")
+                .selected_text(rating_filter_label(self.search_filter.min_rating))
+                .show_ui(ui, |ui| {
+                    for min in 0..=5 {
+                        filter_changed |= ui.selectable_value(
+                            &mut self.search_filter.min_rating, min, rating_filter_label(min)
+                        ).changed();
+                    }
+                });
+            
+            ui.label(tr("category-label"));
+            let mut categories: Vec<&String> = self.microfiche.categories.keys().collect();
+            categories.sort_by(|a, b| collate(a, b));
+            egui::ComboBox::from_id_salt("search_category")
+                .selected_text(self.search_filter.category.clone().unwrap_or_else(|| tr("any-category")))
+                .show_ui(ui, |ui| {
+                    filter_changed |= ui.selectable_value(&mut self.search_filter.category, None, tr("any-category")).changed();
+                    for cat in categories {
+                        filter_changed |= ui.selectable_value(&mut self.search_filter.category, Some(cat.clone()), cat).changed();
+                    }
+                });
+            
+            ui.label(tr("flag-label"));
+            egui::ComboBox::from_id_salt("search_flag")
+                .selected_text(self.search_filter.flag.map_or_else(|| tr("any-flag"), |flag| format!("{} {}", flag.icon(), flag.label())))
+                .show_ui(ui, |ui| {
+                    filter_changed |= ui.selectable_value(&mut self.search_filter.flag, None, tr("any-flag")).changed();
+                    for flag in NoteFlag::ALL {
+                        filter_changed |= ui.selectable_value(&mut self.search_filter.flag, Some(flag), format!("{} {}", flag.icon(), flag.label())).changed();
+                    }
+                });
+            
+            let authors = self.microfiche.author_counts();
+            if authors.keys().any(|author| !author.is_empty()) || self.search_filter.author.is_some() {
+                let author_label = |author: &str| match author {
+                    "" => tr("unknown-author"),
+                    author => author.to_string(),
+                };
+                ui.label(tr("author-label"));
+                egui::ComboBox::from_id_salt("search_author")
+                    .selected_text(self.search_filter.author.as_deref().map_or_else(|| tr("any-author"), author_label))
+                    .show_ui(ui, |ui| {
+                        filter_changed |= ui.selectable_value(&mut self.search_filter.author, None, tr("any-author")).changed();
+                        for (author, count) in &authors {
+                            let text = format!("{} ({})", author_label(author), count);
+                            filter_changed |= ui.selectable_value(&mut self.search_filter.author, Some(author.clone()), text).changed();
                         }
-                    } else {
-                        None
+                    });
+            }
+            
+            filter_changed |= note_order_combo(ui, "search_order", &mut self.search_filter.order);
+            
+            filter_changed |= ui.checkbox(&mut self.search_all_files, tr("search-all-files"))
+                .on_hover_text(tr("search-all-files-hint"))
+                .changed();
+            
+            if response.changed() || filter_changed || ui.button(tr("search")).clicked() {
+                self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                self.search_library();
+            }
+        });
+        let mut dates_changed = false;
+        ui.horizontal(|ui| {
+            let label = ui.label(tr("created-from-label"));
+            dates_changed |= ui.add(egui::TextEdit::singleline(&mut self.search_filter.created_from)
+                .hint_text(tr("date-hint"))
+                .desired_width(90.0))
+                .labelled_by(label.id)
+                .changed();
+            let label = ui.label(tr("created-to-label"));
+            dates_changed |= ui.add(egui::TextEdit::singleline(&mut self.search_filter.created_to)
+                .hint_text(tr("date-hint"))
+                .desired_width(90.0))
+                .labelled_by(label.id)
+                .changed();
+            if self.search_filter.has_date_range() && ui.small_button(tr("clear-dates")).clicked() {
+                self.search_filter.created_from.clear();
+                self.search_filter.created_to.clear();
+                dates_changed = true;
+            }
+        });
+        let tag_counts = self.microfiche.tag_counts();
+        if dates_changed
+            || (!tag_counts.is_empty() || !self.search_filter.tags.is_empty())
+                && tag_chips(ui, &tag_counts, &mut self.search_filter.tags)
+        {
+            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+            self.search_library();
+        }
+        
+        ui.separator();
+        
+        if self.search_all_files {
+            ui.label(tr_args("found-results-in-files", &[
+                ("count", self.search_results.len().to_string().into()),
+                ("other", self.library_results.len().to_string().into()),
+            ]));
+        } else {
+            ui.label(tr_args("found-results", &[("count", self.search_results.len().to_string().into())]));
+        }
+        
+        // Clone results to avoid borrow issues
+        let results = self.search_results.clone();
+        let mut to_delete: Option<(String, String, String, String)> = None;
+        let mut to_edit: Option<(String, String, String, String)> = None;
+        let mut to_template: Option<(String, String, String, Note)> = None;
+        let mut to_rate: Option<(String, String, String, String, Option<u8>)> = None;
+        let mut to_flag: Option<(NoteTarget, BTreeSet<NoteFlag>)> = None;
+        let mut to_comment: Option<(NoteTarget, CommentAction)> = None;
+        let mut to_filter_tag: Option<String> = None;
+        let mut to_toggle_raw: Option<String> = None;
+        let mut to_open: Option<(String, String, String, String)> = None;
+        let mut to_tool: Option<(NoteTool, (String, String, String, String))> = None;
+        let mut to_toggle_expanded: Option<String> = None;
+        let mut hovered_card: Option<String> = None;
+        
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            for (index, (cat, sub, con, note)) in results.iter().enumerate() {
+                let masked = self.settings.masks(cat);
+                let key = note_key(cat, sub, con, &note.text);
+                let expanded = !self.settings.compact_cards || self.expanded_cards.contains(&key);
+                let show_actions = !self.settings.card_actions_on_hover || self.hovered_card.as_ref() == Some(&key);
+                let card = note_card(ui, note).show(ui, |ui| {
+                    ui.vertical(|ui| {
+                        let cat_color = self.microfiche.meta.category_color(cat)
+                            .unwrap_or(ui.visuals().strong_text_color());
+                        ui.horizontal(|ui| {
+                            ui.strong(egui::RichText::new(self.microfiche.meta.category_label(cat)).color(cat_color));
+                            ui.strong(format!("> {} > {}", sub, con));
+                        });
+                        if !expanded {
+                            if compact_note_line(ui, note, masked) {
+                                to_toggle_expanded = Some(key.clone());
+                            }
+                            return;
+                        }
+                        let markdown = self.settings.render_markdown && note.display.is_plain_text()
+                            && markdown::has_markup(&note.text);
+                        let raw = self.raw_notes.contains(&key);
+                        note_label(ui, &note.text, note.display, masked, markdown && !raw);
+                        if !note.fields.is_empty() && !masked {
+                            ui.label(egui::RichText::new(format_fields(&note.fields))
+                                .size(11.0)
+                                .color(egui::Color32::GRAY));
+                        }
+                        note_byline(ui, note);
+                        if let Some(tag) = note_tag_links(ui, &note.tags) {
+                            to_filter_tag = Some(tag);
+                        }
+                        ui.horizontal(|ui| {
+                            if self.settings.compact_cards && ui.small_button("⏶").on_hover_text(tr("collapse")).clicked() {
+                                to_toggle_expanded = Some(key.clone());
+                            }
+                            if let Some(rating) = rating_widget(ui, note.rating) {
+                                to_rate = Some((cat.clone(), sub.clone(), con.clone(), note.text.clone(), rating));
+                            }
+                            if let Some(flags) = flags_widget(ui, &note.flags) {
+                                to_flag = Some(((cat.clone(), sub.clone(), con.clone(), note.text.clone()), flags));
+                            }
+                            ui.separator();
+                            if note.needs_review {
+                                review_badge(ui);
+                            }
+                            secret_badge(ui, &note.text);
+                            ui.scope_builder(card_actions(show_actions), |ui| {
+                                if markdown && !masked && raw_toggle(ui, raw) {
+                                    to_toggle_raw = Some(key.clone());
+                                }
+                                
+                                if ui.button(tr("delete")).clicked() {
+                                    to_delete = Some((cat.clone(), sub.clone(), con.clone(), note.text.clone()));
+                                }
+                                
+                                if ui.button(tr("edit")).clicked() {
+                                    to_edit = Some((cat.clone(), sub.clone(), con.clone(), note.text.clone()));
+                                }
+                                
+                                if ui.button(tr("template")).clicked() {
+                                    to_template = Some((cat.clone(), sub.clone(), con.clone(), note.clone()));
+                                }
+                                
+                                if let Some(tool) = note_tools_menu(ui, note) {
+                                    to_tool = Some((tool, (cat.clone(), sub.clone(), con.clone(), note.text.clone())));
+                                }
+                            });
+                        });
+                        let target = (cat.clone(), sub.clone(), con.clone(), note.text.clone());
+                        let reply = self.comment_draft.as_mut()
+                            .filter(|(draft_target, _)| *draft_target == target)
+                            .map(|(_, reply)| reply);
+                        if let Some(action) = comment_thread(ui, &target, &note.comments, reply) {
+                            to_comment = Some((target, action));
+                        }
+                    });
+                });
+                if card.response.contains_pointer() {
+                    hovered_card = Some(key.clone());
+                }
+                if self.settings.vim_mode && index == self.vim.cursor {
+                    self.vim.mark(ui, card.response.rect, (cat.clone(), sub.clone(), con.clone(), note.text.clone()));
+                }
+                ui.add_space(self.settings.card_spacing);
+            }
+            
+            if self.search_all_files {
+                to_open = self.render_library_results(ui);
+            }
+        });
+        self.vim.list_len = results.len();
+        self.track_hovered_card(ui.ctx(), hovered_card);
+        
+        // Handle actions after the scroll area
+        if let Some((cat, sub, con, note)) = to_delete {
+            if let Some(removed) = self.microfiche.delete_note(&cat, &sub, &con, &note) {
+                self.log_activity(ActivityAction::Deleted, note_path(&cat, &sub, &con), &note);
+                self.notify(webhook::Event::Deleted, (&cat, &sub, &con), &removed, None);
+                self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+                self.status_message = tr("entry-deleted");
+            }
+        }
+        
+        if let Some((cat, sub, con, note, rating)) = to_rate
+            && self.microfiche.set_rating(&cat, &sub, &con, &note, rating)
+        {
+            let stars = rating.map(|r| format!("{}★", r)).unwrap_or_else(|| "unrated".to_string());
+            self.log_activity(ActivityAction::Rated, note_path(&cat, &sub, &con), &format!("{}: {}", stars, note));
+            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        }
+        
+        if let Some((note, flags)) = to_flag {
+            self.set_note_flags(note, flags);
+        }
+        if let Some((note, action)) = to_comment {
+            self.comment_action(note, action);
+        }
+        if let Some(key) = to_toggle_raw
+            && !self.raw_notes.remove(&key)
+        {
+            self.raw_notes.insert(key);
+        }
+        if let Some(key) = to_toggle_expanded
+            && !self.expanded_cards.remove(&key)
+        {
+            self.expanded_cards.insert(key);
+        }
+        
+        if let Some(tag) = to_filter_tag {
+            self.search_filter.tags.insert(tag);
+            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+            self.search_library();
+        }
+        
+        if let Some((file, cat, sub, con)) = to_open {
+            self.load_file(file.clone());
+            if self.current_file.as_ref() == Some(&file) {
+                self.view_mode = ViewMode::Browse;
+                self.selected_category = Some(cat);
+                self.selected_subcategory = Some(sub);
+                self.selected_concept = Some(con);
+                self.search_library();
+            }
+        }
+        
+        if let Some((tool, note)) = to_tool {
+            self.open_note_tool(tool, note);
+        }
+        
+        if let Some((cat, sub, con, note)) = to_edit {
+            // Delete the old entry
+            if let Some(old) = self.microfiche.delete_note(&cat, &sub, &con, &note) {
+                self.editing_note = Some(note.clone());
+                // Populate the create form with the old data
+                self.new_category = cat;
+                self.new_subcategory = sub;
+                self.new_concept = con;
+                self.new_note = old.text;
+                self.new_fields = old.fields.into_iter().collect();
+                self.new_rating = old.rating;
+                self.new_created = old.created;
+                self.new_display = old.display;
+                self.new_definition = old.definition;
+                self.new_flags = old.flags;
+                self.new_tags = tags_column(&old.tags);
+                self.new_comments = old.comments;
+                self.new_author = old.author;
+                
+                // Switch to create view
+                self.view_mode = ViewMode::Create;
+                self.status_message = tr("entry-loaded-for-editing-modify-and");
+                
+                // Refresh search results
+                self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+            }
+        }
+        
+        if let Some((cat, sub, con, note)) = to_template {
+            self.load_template(cat, sub, con, &note);
+        }
+    }
+    
+    /// Fill the Create form with a note's category, subcategory and concept,
+    /// and keep its structure on offer as a skeleton for the new note
+    fn load_template(&mut self, cat: String, sub: String, con: String, note: &Note) {
+        // Populate the create form but leave note empty
+        self.new_category = cat;
+        self.new_subcategory = sub;
+        self.new_concept = con;
+        self.new_note.clear();
+        self.new_fields.clear();
+        self.new_rating = None;
+        self.new_created = None;
+        self.new_display = NoteDisplay::default();
+        self.new_definition = false;
+        self.new_flags.clear();
+        self.new_tags.clear();
+        self.new_comments.clear();
+        self.new_author.clear();
+        self.editing_note = None;
+        self.note_prefill = None;
+        self.template_skeleton = note_skeleton(&note.text)
+            .or_else(|| (!note.fields.is_empty()).then(String::new))
+            .map(|skeleton| (skeleton, note.fields.keys().cloned().collect()));
+        
+        // Switch to create view
+        self.view_mode = ViewMode::Create;
+        self.status_message = tr("template-loaded-add-your-new-note");
+    }
+    
+    fn render_create_view(&mut self, ui: &mut egui::Ui) {
+        self.prefill_note();
+        ui.heading(tr("create-new-entry"));
+        ui.separator();
+        
+        egui::Grid::new("create_grid")
+            .num_columns(2)
+            .spacing([10.0, 10.0])
+            .show(ui, |ui| {
+                let label = ui.label(tr("category-label"));
+                ui.add(egui::TextEdit::singleline(&mut self.new_category).desired_width(f32::INFINITY)).labelled_by(label.id);
+                ui.end_row();
+                
+                let label = ui.label(tr("subcategory-label"));
+                ui.add(egui::TextEdit::singleline(&mut self.new_subcategory).desired_width(f32::INFINITY)).labelled_by(label.id);
+                ui.end_row();
+                
+                let label = ui.label(tr("concept-label"));
+                ui.add(egui::TextEdit::singleline(&mut self.new_concept).desired_width(f32::INFINITY)).labelled_by(label.id);
+                ui.end_row();
+                
+                let label = ui.label(tr("tags-label"));
+                ui.add(egui::TextEdit::singleline(&mut self.new_tags)
+                    .hint_text(tr("tags-hint"))
+                    .desired_width(f32::INFINITY))
+                    .labelled_by(label.id);
+                ui.end_row();
+                
+                let label = ui.label(tr("author-label"));
+                let user_name = self.user_name();
+                ui.add(egui::TextEdit::singleline(&mut self.new_author)
+                    .hint_text(user_name)
+                    .desired_width(f32::INFINITY))
+                    .labelled_by(label.id);
+                ui.end_row();
+                
+                ui.label(tr("rating-label"));
+                ui.horizontal(|ui| {
+                    if let Some(rating) = rating_widget(ui, self.new_rating) {
+                        self.new_rating = rating;
                     }
-                } else {
-                    None
+                });
+                ui.end_row();
+                
+                ui.label("");
+                ui.checkbox(&mut self.new_definition, tr("pin-as-definition"))
+                    .on_hover_text(tr("pin-as-definition-hint"));
+                ui.end_row();
+            });
+        
+        ui.separator();
+        if let Some((skeleton, fields)) = &self.template_skeleton {
+            let mut insert = false;
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(tr("the-template-note-has-a-structure")).color(egui::Color32::GRAY));
+                insert = ui.small_button(tr("insert-skeleton"))
+                    .on_hover_text(if skeleton.is_empty() { fields.join(", ") } else { skeleton.clone() })
+                    .clicked();
+            });
+            if insert {
+                if !self.new_note.trim().is_empty() && !skeleton.is_empty() {
+                    self.new_note.push_str("\n\n");
+                }
+                self.new_note.push_str(skeleton);
+                for name in fields {
+                    if !self.new_fields.iter().any(|(existing, _)| existing == name) {
+                        self.new_fields.push((name.clone(), String::new()));
+                    }
+                }
+                self.template_skeleton = None;
+            }
+        }
+        let label = ui.label(tr("note-label"));
+        if self.note_prefill.as_ref().is_some_and(|(_, text)| !text.is_empty() && *text == self.new_note) {
+            ui.label(egui::RichText::new(tr("started-from-the-note-template")).size(11.0).color(egui::Color32::GRAY));
+        }
+        let fiche = &self.microfiche;
+        link_complete::multiline(ui, "create_note", &mut self.new_note, 10, || {
+            fiche.categories.values()
+                .flat_map(|c| &c.subcategories)
+                .flat_map(|s| &s.concepts)
+                .map(|c| c.name.clone())
+                .collect()
+        })
+            .labelled_by(label.id)
+            .on_hover_text(tr("note-editor-link-hint"));
+        let secrets = scan_secrets(&self.new_note);
+        if !secrets.is_empty() {
+            ui.label(egui::RichText::new(tr_args("note-looks-like-it-contains", &[("kinds", secrets.join(", ").into())]))
+                .color(ui.visuals().error_fg_color));
+        }
+        self.update_similar_notes(ui.ctx());
+        if !self.similar_notes.is_empty() {
+            let mut go_to = None;
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.label(egui::RichText::new(tr("similar-notes-exist")).color(ui.visuals().warn_fg_color))
+                    .on_hover_text(tr("similar-notes-exist-hint"));
+                for ((cat, sub, con, text), similarity) in &self.similar_notes {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(format!("{:.0}%", similarity * 100.0)).monospace().color(egui::Color32::GRAY));
+                        if ui.link(note_path(cat, sub, con)).on_hover_text(tr("similar-note-go-to")).clicked() {
+                            go_to = Some((cat.clone(), sub.clone(), con.clone()));
+                        }
+                        let text = if self.settings.masks(cat) { masked_text(text) } else { excerpt(text, 80) };
+                        ui.label(egui::RichText::new(text).color(egui::Color32::GRAY));
+                    });
+                }
+            });
+            if let Some((cat, sub, con)) = go_to {
+                self.view_mode = ViewMode::Browse;
+                self.selected_category = Some(cat);
+                self.selected_subcategory = Some(sub);
+                self.selected_concept = Some(con);
+            }
+        }
+        
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(tr("fields-label"));
+            if ui.small_button(tr("add-field")).clicked() {
+                self.new_fields.push((String::new(), String::new()));
+            }
+        });
+        
+        let mut field_to_remove = None;
+        egui::Grid::new("fields_grid")
+            .num_columns(3)
+            .spacing([10.0, 5.0])
+            .show(ui, |ui| {
+                for (idx, (name, value)) in self.new_fields.iter_mut().enumerate() {
+                    ui.add(egui::TextEdit::singleline(name).hint_text(tr("name")).desired_width(150.0));
+                    ui.add(egui::TextEdit::singleline(value).hint_text(tr("value")).desired_width(200.0));
+                    if accessible(ui.small_button("✖"), tr("remove-field")).on_hover_text(tr("remove-field")).clicked() {
+                        field_to_remove = Some(idx);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(idx) = field_to_remove {
+            self.new_fields.remove(idx);
+        }
+        
+        ui.separator();
+        
+        if ui.button(tr("create")).clicked() {
+            if !self.new_category.is_empty() 
+                && !self.new_subcategory.is_empty() 
+                && !self.new_concept.is_empty() 
+                && !self.new_note.is_empty() 
+            {
+                self.microfiche.add_row(FicheRow {
+                    category: self.new_category.clone(),
+                    subcategory: self.new_subcategory.clone(),
+                    concept: self.new_concept.clone(),
+                    note: self.new_note.clone(),
+                    rating: self.new_rating,
+                    needs_review: false,
+                    created: self.new_created.clone().or_else(|| Some(timestamp_now())),
+                    modified: self.editing_note.is_some().then(timestamp_now),
+                    fields: self.new_fields.iter()
+                        .filter(|(name, value)| {
+                            let name = name.trim();
+                            !name.is_empty() && !value.is_empty() && !is_reserved_column(name)
+                        })
+                        .map(|(name, value)| (name.trim().to_string(), value.clone()))
+                        .collect(),
+                    display: self.new_display,
+                    definition: self.new_definition,
+                    flags: self.new_flags.clone(),
+                    tags: parse_tags(&self.new_tags),
+                    author: match self.new_author.trim() {
+                        "" => self.user_name(),
+                        author => author.to_string(),
+                    },
+                    comments: std::mem::take(&mut self.new_comments),
+                });
+                
+                let path = note_path(&self.new_category, &self.new_subcategory, &self.new_concept);
+                let note = self.new_note.clone();
+                let previous = self.editing_note.take();
+                match &previous {
+                    Some(old) if *old != note => self.log_activity(ActivityAction::Edited, path, &format!("{} → {}", excerpt(old, 40), note)),
+                    Some(_) => self.log_activity(ActivityAction::Edited, path, &note),
+                    None => self.log_activity(ActivityAction::Created, path, &note),
                 }
+                let (cat, sub, con) = (self.new_category.clone(), self.new_subcategory.clone(), self.new_concept.clone());
+                if let Some(added) = self.microfiche.concept(&cat, &sub, &con)
+                    .and_then(|c| c.notes.iter().find(|n| n.text == note))
+                    .cloned()
+                {
+                    let event = if previous.is_some() { webhook::Event::Edited } else { webhook::Event::Created };
+                    self.notify(event, (&cat, &sub, &con), &added, previous.as_deref());
+                }
+                self.status_message = tr("entry-created-successfully");
+                let secrets = scan_secrets(&note);
+                if !secrets.is_empty() {
+                    self.status_message = tr_args("created-but-looks-like-it-contains", &[("kinds", secrets.join(", ").into())]);
+                }
+                
+                // Clear form
+                self.new_category.clear();
+                self.new_subcategory.clear();
+                self.new_concept.clear();
+                self.new_note.clear();
+                self.new_fields.clear();
+                self.new_rating = None;
+                self.new_created = None;
+                self.new_display = NoteDisplay::default();
+                self.new_definition = false;
+                self.new_flags.clear();
+                self.new_tags.clear();
+                self.new_author.clear();
+                self.template_skeleton = None;
+                self.note_prefill = None;
             } else {
-                None
-            };
+                self.status_message = tr("all-fields-are-required");
+            }
+        }
+    }
+    
+    fn render_review_view(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading(tr("review"));
+            if ui.button(tr("secret-scan-report")).clicked() {
+                self.show_secret_report = true;
+            }
+            if ui.button(tr("aging")).on_hover_text(tr("aging-hint")).clicked() {
+                self.show_aging = true;
+            }
+        });
+        ui.separator();
+        
+        let queue = self.microfiche.review_queue();
+        if queue.is_empty() {
+            self.review_skip = 0;
+            ui.centered_and_justified(|ui| {
+                let message = if self.review_done > 0 {
+                    format!("All done! {} notes reviewed this session.", self.review_done)
+                } else {
+                    "No notes need review".to_string()
+                };
+                ui.label(egui::RichText::new(message).size(14.0));
+            });
+            return;
+        }
+        
+        if self.review_skip >= queue.len() {
+            self.review_skip = 0;
+        }
+        let total = self.review_done + queue.len();
+        ui.add(egui::ProgressBar::new(self.review_done as f32 / total as f32)
+            .text(tr_args("of-reviewed", &[("review_done", self.review_done.to_string().into()), ("total", total.to_string().into())])));
+        ui.add_space(10.0);
+        
+        let (cat, sub, con, note) = queue[self.review_skip].clone();
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.strong(note_path(&cat, &sub, &con));
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .id_salt("review_note_scroll")
+                .max_height(ui.available_height() - 80.0)
+                .show(ui, |ui| {
+                    note_label(ui, &note.text, note.display, self.settings.masks(&cat), self.settings.render_markdown);
+                    if !note.fields.is_empty() && !self.settings.masks(&cat) {
+                        ui.label(egui::RichText::new(format_fields(&note.fields))
+                            .size(11.0)
+                            .color(egui::Color32::GRAY));
+                    }
+                });
+        });
+        ui.add_space(10.0);
+        
+        ui.horizontal(|ui| {
+            if ui.button(tr("approve")).clicked()
+                && self.microfiche.set_needs_review(&cat, &sub, &con, &note.text, false)
+            {
+                self.review_done += 1;
+                self.log_activity(ActivityAction::Reviewed, note_path(&cat, &sub, &con), &note.text);
+            }
+            
+            if ui.button(tr("edit")).clicked()
+                && let Some(old) = self.microfiche.delete_note(&cat, &sub, &con, &note.text)
+            {
+                self.review_done += 1;
+                self.editing_note = Some(old.text.clone());
+                self.new_category = cat.clone();
+                self.new_subcategory = sub.clone();
+                self.new_concept = con.clone();
+                self.new_note = old.text;
+                self.template_skeleton = None;
+                self.new_fields = old.fields.into_iter().collect();
+                self.new_rating = old.rating;
+                self.new_created = old.created;
+                self.new_display = old.display;
+                self.new_definition = old.definition;
+                self.new_flags = old.flags;
+                self.new_tags = tags_column(&old.tags);
+                self.new_comments = old.comments;
+                self.new_author = old.author;
+                
+                self.view_mode = ViewMode::Create;
+                self.status_message = tr("entry-loaded-for-editing-modify-and");
+            }
+            
+            if ui.button(tr("delete")).clicked()
+                && self.microfiche.delete_note(&cat, &sub, &con, &note.text).is_some()
+            {
+                self.review_done += 1;
+                self.log_activity(ActivityAction::Deleted, note_path(&cat, &sub, &con), &note.text);
+                self.notify(webhook::Event::Deleted, (&cat, &sub, &con), &note, None);
+                self.status_message = tr("entry-deleted");
+            }
+            
+            ui.separator();
+            if ui.add_enabled(queue.len() > 1, egui::Button::new(tr("skip"))).clicked() {
+                self.review_skip = (self.review_skip + 1) % queue.len();
+            }
+        });
+    }
+    
+    fn render_study_view(&mut self, ui: &mut egui::Ui) {
+        let today_date = chrono::Local::now().date_naive();
+        let today = today_date.format("%Y-%m-%d").to_string();
+        
+        ui.heading(tr("study"));
+        ui.separator();
+        
+        ui.collapsing("Session settings", |ui| {
+            ui.horizontal(|ui| {
+                let label = ui.label(tr("notes-per-day-label"));
+                if ui.add(egui::DragValue::new(&mut self.microfiche.meta.study.daily_goal).range(1..=500)).labelled_by(label.id).changed() {
+                    self.meta_changed();
+                }
+            });
+            ui.label(tr("categories-none-selected-all-label"));
+            let mut cat_names: Vec<String> = self.microfiche.categories.keys().cloned().collect();
+            cat_names.sort_by(|a, b| collate(a, b));
+            ui.horizontal_wrapped(|ui| {
+                for cat in cat_names {
+                    let mut selected = self.microfiche.meta.study.categories.contains(&cat);
+                    if ui.checkbox(&mut selected, self.microfiche.meta.category_label(&cat)).changed() {
+                        if selected {
+                            self.microfiche.meta.study.categories.insert(cat);
+                        } else {
+                            self.microfiche.meta.study.categories.remove(&cat);
+                        }
+                        self.meta_changed();
+                        self.study_skip = 0;
+                    }
+                }
+            });
+        });
+        
+        let study = &self.microfiche.meta.study;
+        let done_today = study.studied_on(&today);
+        let goal = study.daily_goal.max(1);
+        let streak = study.streak(today_date);
+        
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(tr_args("day-streak", &[("streak", streak.to_string().into())]))
+                .strong()
+                .size(15.0)
+                .color(ui.visuals().warn_fg_color));
+            ui.separator();
+            ui.add(egui::ProgressBar::new((done_today as f32 / goal as f32).min(1.0))
+                .desired_width(250.0)
+                .text(tr_args("today", &[("done_today", done_today.to_string().into()), ("goal", goal.to_string().into())])));
+        });
+        ui.separator();
         
-        egui::CentralPanel::default().show_inside(ui, |ui| {
-            if let Some((cat_name, sub_name, concepts)) = display_data {
-                ui.heading(format!("{} > {}", cat_name, sub_name));
-                ui.separator();
-                
-                let mut to_delete: Option<(String, String, String, String)> = None;
-                let mut to_edit: Option<(String, String, String, String)> = None;
-                let mut to_template: Option<(String, String, String)> = None;
+        if done_today >= goal {
+            ui.label(egui::RichText::new(tr("daily-goal-reached-come-back-tomorrow"))
+                .color(egui::Color32::GRAY));
+        }
+        
+        let queue = self.microfiche.study_queue(&today);
+        if queue.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label(egui::RichText::new(tr("nothing-left-to-study-today")).size(14.0));
+            });
+            return;
+        }
+        if self.study_skip >= queue.len() {
+            self.study_skip = 0;
+        }
+        
+        let (cat, sub, con, note) = queue[self.study_skip].clone();
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.label(egui::RichText::new(format!("{} > {}", cat, sub)).color(egui::Color32::GRAY));
+            ui.heading(&con);
+            ui.separator();
+            if self.study_revealed {
+                egui::ScrollArea::vertical()
+                    .id_salt("study_note_scroll")
+                    .max_height(ui.available_height() - 80.0)
+                    .show(ui, |ui| {
+                        note_label(ui, &note.text, note.display, self.settings.masks(&cat), self.settings.render_markdown);
+                    });
+            } else if ui.button(tr("show-note")).clicked() {
+                self.study_revealed = true;
+            }
+        });
+        ui.add_space(10.0);
+        
+        ui.horizontal(|ui| {
+            if ui.button(tr("done")).clicked() {
+                self.microfiche.meta.study.record(note_key(&cat, &sub, &con, &note.text), &today);
+                self.meta_changed();
+                self.study_revealed = false;
+            }
+            if ui.add_enabled(queue.len() > 1, egui::Button::new(tr("skip"))).clicked() {
+                self.study_skip = (self.study_skip + 1) % queue.len();
+                self.study_revealed = false;
+            }
+        });
+    }
+    
+    fn render_activity_view(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("activity"));
+        ui.separator();
+        
+        ui.horizontal(|ui| {
+            let label = ui.label(tr("session-note-label"));
+            ui.add(egui::TextEdit::singleline(&mut self.session_note)
+                .hint_text(tr("attached-to-changes-you-make-from"))
+                .desired_width(300.0))
+                .labelled_by(label.id);
+            ui.separator();
+            let label = ui.label(tr("filter-label"));
+            ui.add(egui::TextEdit::singleline(&mut self.activity_filter)
+                .hint_text(tr("text-or-date-e-g-2025"))
+                .desired_width(200.0))
+                .labelled_by(label.id);
+        });
+        if self.activity.unsaved > 0 {
+            ui.label(egui::RichText::new(tr_args("changes-not-saved-yet", &[("unsaved", self.activity.unsaved.to_string().into())]))
+                .size(11.0)
+                .color(ui.visuals().warn_fg_color));
+        }
+        ui.separator();
+        
+        let filter = self.activity_filter.to_lowercase();
+        let entries: Vec<&ActivityEntry> = self.activity.entries.iter().rev()
+            .filter(|e| {
+                filter.is_empty()
+                    || format!("{} {} {} {} {}", e.timestamp, e.action.label(), e.path, e.detail, e.session_note)
+                        .to_lowercase()
+                        .contains(&filter)
+            })
+            .collect();
+        
+        if entries.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label(egui::RichText::new(tr("no-activity-recorded")).color(egui::Color32::GRAY));
+            });
+            return;
+        }
+        
+        let accent_color = ui.visuals().hyperlink_color;
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            let mut current_day = "";
+            for entry in entries {
+                // Group by day: the date part of the RFC 3339 timestamp
+                let day = entry.timestamp.get(..10).unwrap_or(&entry.timestamp);
+                if day != current_day {
+                    current_day = day;
+                    ui.add_space(8.0);
+                    ui.strong(format_day(day));
+                    ui.separator();
+                }
                 
-                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
-                    for (concept_name, notes) in concepts {
-                        ui.group(|ui| {
-                            ui.strong(egui::RichText::new(&concept_name).color(egui::Color32::from_rgb(100, 149, 237)));
-                            ui.separator();
-                            
-                            for note in notes {
-                                ui.group(|ui| {
-                                    ui.vertical(|ui| {
-                                        ui.add(egui::Label::new(&note).wrap());
-                                        ui.horizontal(|ui| {
-                                            if ui.button("Template").clicked() {
-                                                to_template = Some((
-                                                    cat_name.clone(),
-                                                    sub_name.clone(),
-                                                    concept_name.clone(),
-                                                ));
-                                            }
-                                            
-                                            if ui.button("Edit").clicked() {
-                                                to_edit = Some((
-                                                    cat_name.clone(),
-                                                    sub_name.clone(),
-                                                    concept_name.clone(),
-                                                    note.clone(),
-                                                ));
-                                            }
-                                            
-                                            if ui.button("Delete").clicked() {
-                                                to_delete = Some((
-                                                    cat_name.clone(),
-                                                    sub_name.clone(),
-                                                    concept_name.clone(),
-                                                    note.clone(),
-                                                ));
-                                            }
-                                        });
-                                    });
-                                });
-                            }
-                            ui.add_space(5.0);
-                        });
-                        ui.add_space(10.0);
+                ui.horizontal_wrapped(|ui| {
+                    let time = entry.timestamp.get(11..16).unwrap_or("");
+                    ui.label(egui::RichText::new(time).monospace().color(egui::Color32::GRAY));
+                    ui.label(egui::RichText::new(entry.action.label()).strong().color(accent_color));
+                    ui.label(&entry.path);
+                    if !entry.detail.is_empty() {
+                        let detail = if self.settings.masks(entry.category()) {
+                            masked_text(&entry.detail)
+                        } else {
+                            entry.detail.clone()
+                        };
+                        ui.label(egui::RichText::new(detail).color(egui::Color32::GRAY));
                     }
-                });
-                
-                // Handle actions after the scroll area
-                if let Some((cat, sub, con, note)) = to_delete {
-                    if self.microfiche.delete_note(&cat, &sub, &con, &note) {
-                        self.status_message = "Entry deleted".to_string();
+                    if !entry.session_note.is_empty() {
+                        ui.label(egui::RichText::new(format!("[{}]", entry.session_note)).italics().size(11.0));
                     }
+                });
+            }
+        });
+    }
+    
+    fn render_goals(&mut self, ui: &mut egui::Ui, accent_color: egui::Color32) {
+        use chrono::Datelike;
+        
+        let fmt = |d: chrono::NaiveDate| d.format("%Y-%m-%d").to_string();
+        let today = chrono::Local::now().date_naive();
+        let week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        let month_start = today.with_day(1).unwrap_or(today);
+        let captures = self.activity.captures_by_day();
+        
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.heading(tr("goals"));
+            ui.horizontal(|ui| {
+                let label = ui.label(tr("notes-per-day-label"));
+                let mut changed = ui.add(egui::DragValue::new(&mut self.microfiche.meta.goals.notes_per_day).range(0..=100))
+                    .labelled_by(label.id)
+                    .on_hover_text(tr("daily-goal-off-hint"))
+                    .changed();
+                ui.separator();
+                changed |= ui.checkbox(&mut self.microfiche.meta.goals.touch_categories_monthly, tr("touch-every-category-monthly")).changed();
+                // As meta_changed, which would need all of self while the
+                // captures are borrowed
+                if changed {
+                    self.dirty = true;
+                    self.history.keep_meta(&self.microfiche.meta);
                 }
-                
-                if let Some((cat, sub, con, note)) = to_edit {
-                    // Delete the old entry
-                    if self.microfiche.delete_note(&cat, &sub, &con, &note) {
-                        // Populate the create form with the old data
-                        self.new_category = cat;
-                        self.new_subcategory = sub;
-                        self.new_concept = con;
-                        self.new_note = note;
+            });
+            ui.separator();
+            
+            let goals = self.microfiche.meta.goals.clone();
+            if goals.notes_per_day == 0 && !goals.touch_categories_monthly {
+                ui.label(egui::RichText::new(tr("no-goals-set")).color(egui::Color32::GRAY));
+            } else {
+                ui.horizontal(|ui| {
+                    if goals.notes_per_day > 0 {
+                        let done = captures.get(fmt(today).as_str()).copied().unwrap_or(0);
+                        progress_ring(ui, done as f32 / goals.notes_per_day as f32,
+                            &format!("{}/{}", done, goals.notes_per_day), "today", accent_color);
                         
-                        // Switch to create view
-                        self.view_mode = ViewMode::Create;
-                        self.status_message = "Entry loaded for editing. Modify and click Create to save.".to_string();
+                        let week_done: usize = captures.range(fmt(week_start).as_str()..).map(|(_, n)| n).sum();
+                        let week_goal = goals.notes_per_day * 7;
+                        progress_ring(ui, week_done as f32 / week_goal as f32,
+                            &format!("{}/{}", week_done, week_goal), "this week", accent_color);
                     }
-                }
-                
-                if let Some((cat, sub, con)) = to_template {
-                    // Populate the create form but leave note empty
-                    self.new_category = cat;
-                    self.new_subcategory = sub;
-                    self.new_concept = con;
-                    self.new_note.clear();
-                    
-                    // Switch to create view
-                    self.view_mode = ViewMode::Create;
-                    self.status_message = "Template loaded. Add your new note and click Create.".to_string();
-                }
-            } else if self.selected_category.is_some() && self.selected_subcategory.is_none() {
-                ui.centered_and_justified(|ui| {
-                    ui.label("Select a subcategory to view its contents");
-                });
-            } else {
-                if self.microfiche.categories.is_empty() {
-                    ui.vertical_centered(|ui| {
-                        ui.add_space(ui.available_height() / 2.0 - 50.0);
-                        ui.label(egui::RichText::new("No data loaded").size(14.0));
-                        ui.add_space(10.0);
-                        if ui.button(egui::RichText::new("Open File").size(12.0)).clicked() {
-                            self.open_file();
+                    if goals.touch_categories_monthly {
+                        let touched = self.activity.categories_touched(&fmt(month_start), &fmt(today));
+                        let total = self.microfiche.categories.len();
+                        let done = self.microfiche.categories.keys().filter(|c| touched.contains_key(c.as_str())).count();
+                        progress_ring(ui, done as f32 / total.max(1) as f32,
+                            &format!("{}/{}", done, total), "categories this month", accent_color);
+                        
+                        let mut untouched: Vec<&String> = self.microfiche.categories.keys()
+                            .filter(|c| !touched.contains_key(c.as_str()))
+                            .collect();
+                        if !untouched.is_empty() {
+                            untouched.sort_by(|a, b| collate(a, b));
+                            ui.vertical(|ui| {
+                                ui.label(egui::RichText::new(tr("not-touched-yet-this-month-label")).color(egui::Color32::GRAY));
+                                for cat in untouched.iter().take(8) {
+                                    ui.label(self.microfiche.meta.category_label(cat));
+                                }
+                                if untouched.len() > 8 {
+                                    ui.label(format!("and {} more", untouched.len() - 8));
+                                }
+                            });
                         }
-                    });
-                } else {
-                    ui.centered_and_justified(|ui| {
-                        ui.label("Select a category from the left panel");
-                    });
-                }
+                    }
+                });
             }
+            
+            ui.separator();
+            ui.label(egui::RichText::new(tr("weekly-summary")).strong());
+            egui::Grid::new("weekly_summary_grid")
+                .num_columns(4)
+                .spacing([20.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label(egui::RichText::new(tr("notes-captured")).strong());
+                    ui.label(egui::RichText::new(tr("days-goal-met")).strong());
+                    ui.label(egui::RichText::new(tr("most-active-category")).strong());
+                    ui.end_row();
+                    
+                    let weeks = [
+                        ("This week", week_start, today),
+                        ("Last week", week_start - chrono::Duration::days(7), week_start - chrono::Duration::days(1)),
+                    ];
+                    for (label, start, end) in weeks {
+                        let (from, to) = (fmt(start), fmt(end));
+                        let in_week = captures.range(from.as_str()..=to.as_str());
+                        let total: usize = in_week.clone().map(|(_, n)| n).sum();
+                        let days_met = in_week.filter(|(_, n)| goals.notes_per_day > 0 && **n >= goals.notes_per_day).count();
+                        let busiest = self.activity.categories_touched(&from, &to)
+                            .into_iter()
+                            .max_by_key(|(_, n)| *n)
+                            .map(|(cat, _)| cat.to_string())
+                            .unwrap_or_else(|| "-".to_string());
+                        
+                        ui.label(label);
+                        ui.label(total.to_string());
+                        if goals.notes_per_day > 0 {
+                            ui.label(format!("{} / {}", days_met, (end - start).num_days() + 1));
+                        } else {
+                            ui.label("-");
+                        }
+                        ui.label(busiest);
+                        ui.end_row();
+                    }
+                });
         });
+        
+        ui.add_space(10.0);
     }
     
-    fn render_search_view(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.label("Search:");
-            let response = ui.text_edit_singleline(&mut self.search_query);
+    fn render_term_trends(&mut self, ui: &mut egui::Ui, word_freq: &HashMap<String, usize>) {
+        use egui_plot::{Legend, Line, Plot, PlotPoints};
+        
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.heading(tr("term-trends"));
+            ui.horizontal(|ui| {
+                let label = ui.label(tr("terms-label"));
+                ui.add(egui::TextEdit::singleline(&mut self.trend_terms)
+                    .hint_text(tr("comma-separated-e-g-rust-egui"))
+                    .desired_width(300.0))
+                    .labelled_by(label.id);
+                ui.checkbox(&mut self.trend_relative, tr("as-of-notes-that-month"));
+            });
+            
+            let mut terms: Vec<String> = self.trend_terms.split(',')
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect();
+            if terms.is_empty() {
+                // Default to the most frequent words
+                let mut top: Vec<(&String, &usize)> = word_freq.iter().collect();
+                top.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+                terms = top.into_iter().take(3).map(|(w, _)| w.clone()).collect();
+            }
             
-            if response.changed() || ui.button("Search").clicked() {
-                self.search_results = self.microfiche.search(&self.search_query);
+            let trends = self.microfiche.term_trends(&terms);
+            if trends.undated > 0 {
+                ui.label(egui::RichText::new(tr_args("notes-without-a-creation-date-are", &[("undated", trends.undated.to_string().into())]))
+                    .size(11.0)
+                    .color(egui::Color32::GRAY));
             }
+            if trends.months.is_empty() {
+                ui.label(egui::RichText::new(tr("no-dated-notes-yet-notes-get"))
+                    .color(egui::Color32::GRAY));
+                return;
+            }
+            
+            let months = trends.months.clone();
+            let relative = self.trend_relative;
+            Plot::new("term_trends_plot")
+                .height(220.0)
+                .legend(Legend::default())
+                .x_axis_formatter(move |mark, _| {
+                    let index = mark.value.round();
+                    if (mark.value - index).abs() > 0.01 || index < 0.0 {
+                        return String::new();
+                    }
+                    months.get(index as usize).cloned().unwrap_or_default()
+                })
+                .y_axis_label(if relative { "% of notes" } else { "notes" })
+                .include_y(0.0)
+                .allow_scroll(false)
+                .show(ui, |plot_ui| {
+                    for (i, term) in terms.iter().enumerate() {
+                        let points: PlotPoints = trends.counts.iter().zip(&trends.totals).enumerate()
+                            .map(|(month, (counts, total))| {
+                                let y = if relative {
+                                    100.0 * counts[i] as f64 / (*total).max(1) as f64
+                                } else {
+                                    counts[i] as f64
+                                };
+                                [month as f64, y]
+                            })
+                            .collect();
+                        plot_ui.line(Line::new(term.clone(), points).width(2.0));
+                    }
+                });
         });
         
-        ui.separator();
+        ui.add_space(10.0);
+    }
+    
+    fn render_category_comparison(&mut self, ui: &mut egui::Ui, accent_color: egui::Color32, secondary_color: egui::Color32) {
+        let mut cat_names: Vec<String> = self.microfiche.categories.keys().cloned().collect();
+        cat_names.sort_by(|a, b| collate(a, b));
         
-        ui.label(format!("Found {} results", self.search_results.len()));
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.heading(tr("compare-categories"));
+            ui.horizontal(|ui| {
+                for (i, selected) in [&mut self.compare_categories.0, &mut self.compare_categories.1].into_iter().enumerate() {
+                    if i == 1 {
+                        ui.label(tr("vs"));
+                    }
+                    egui::ComboBox::from_id_salt(("compare_category", i))
+                        .selected_text(if selected.is_empty() { "Select category" } else { selected.as_str() })
+                        .show_ui(ui, |ui| {
+                            for cat in &cat_names {
+                                ui.selectable_value(selected, cat.clone(), cat);
+                            }
+                        });
+                }
+            });
+            
+            let (left_name, right_name) = &self.compare_categories;
+            let (Some(left), Some(right)) = (
+                self.microfiche.category_profile(left_name),
+                self.microfiche.category_profile(right_name),
+            ) else {
+                return;
+            };
+            if left_name == right_name {
+                ui.label(egui::RichText::new(tr("pick-two-different-categories")).color(egui::Color32::GRAY));
+                return;
+            }
+            ui.separator();
+            
+            egui::Grid::new("compare_counts_grid")
+                .num_columns(3)
+                .spacing([30.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label(egui::RichText::new(left_name).strong().color(accent_color));
+                    ui.label(egui::RichText::new(right_name).strong().color(secondary_color));
+                    ui.end_row();
+                    
+                    let rows = [
+                        ("Subcategories", left.subcategories.to_string(), right.subcategories.to_string()),
+                        ("Concepts", left.concepts.to_string(), right.concepts.to_string()),
+                        ("Notes", left.notes.to_string(), right.notes.to_string()),
+                        ("Average note length", format!("{:.0} chars", left.average_length), format!("{:.0} chars", right.average_length)),
+                        ("Distinct terms", left.terms.len().to_string(), right.terms.len().to_string()),
+                    ];
+                    for (label, a, b) in rows {
+                        ui.label(label);
+                        ui.label(a);
+                        ui.label(b);
+                        ui.end_row();
+                    }
+                });
+            
+            let overlap = left.vocabulary_overlap(&right);
+            ui.label(format!("Vocabulary overlap: {:.0}%", overlap * 100.0));
+            if overlap >= 0.3 {
+                ui.label(egui::RichText::new(tr("these-categories-share-much-of-their"))
+                    .color(ui.visuals().warn_fg_color));
+            }
+            ui.separator();
+            
+            let term_list = |ui: &mut egui::Ui, terms: Vec<String>| {
+                if terms.is_empty() {
+                    ui.label(egui::RichText::new(tr("none")).color(egui::Color32::GRAY));
+                }
+                for term in terms {
+                    ui.label(term);
+                }
+            };
+            ui.columns(3, |columns| {
+                columns[0].label(egui::RichText::new(tr_args("only-in", &[("left_name", left_name.to_string().into())])).strong().color(accent_color));
+                term_list(&mut columns[0], left.exclusive_terms(&right, 15).into_iter()
+                    .map(|(term, count)| format!("{} ({})", term, count))
+                    .collect());
+                
+                columns[1].label(egui::RichText::new(tr("shared")).strong());
+                term_list(&mut columns[1], left.shared_terms(&right, 15).into_iter()
+                    .map(|(term, a, b)| format!("{} ({} / {})", term, a, b))
+                    .collect());
+                
+                columns[2].label(egui::RichText::new(tr_args("only-in", &[("left_name", right_name.to_string().into())])).strong().color(secondary_color));
+                term_list(&mut columns[2], right.exclusive_terms(&left, 15).into_iter()
+                    .map(|(term, count)| format!("{} ({})", term, count))
+                    .collect());
+            });
+        });
         
-        // Clone results to avoid borrow issues
-        let results = self.search_results.clone();
-        let mut to_delete: Option<(String, String, String, String)> = None;
-        let mut to_edit: Option<(String, String, String, String)> = None;
-        let mut to_template: Option<(String, String, String)> = None;
+        ui.add_space(10.0);
+    }
+    
+    fn render_bridge_terms(&mut self, ui: &mut egui::Ui, accent_color: egui::Color32) {
+        let bridges = self.microfiche.bridge_terms(self.bridge_min_categories);
+        let mut to_search: Option<String> = None;
         
-        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
-            for (cat, sub, con, note) in &results {
-                ui.group(|ui| {
-                    ui.vertical(|ui| {
-                        ui.strong(format!("{} > {} > {}", cat, sub, con));
-                        ui.add(egui::Label::new(note).wrap());
-                        ui.horizontal(|ui| {
-                            if ui.button("Delete").clicked() {
-                                to_delete = Some((cat.clone(), sub.clone(), con.clone(), note.clone()));
-                            }
-                            
-                            if ui.button("Edit").clicked() {
-                                to_edit = Some((cat.clone(), sub.clone(), con.clone(), note.clone()));
-                            }
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.heading(tr("bridge-terms"));
+            ui.label(tr("terms-spread-evenly-over-many-categories"));
+            ui.horizontal(|ui| {
+                ui.label(tr("found-in-at-least"));
+                let max = self.microfiche.categories.len().max(2);
+                ui.add(egui::DragValue::new(&mut self.bridge_min_categories).range(2..=max));
+                ui.label(tr("categories-unit"));
+            });
+            ui.separator();
+            
+            if bridges.is_empty() {
+                ui.label(egui::RichText::new(tr("no-terms-span-that-many-categories")).color(egui::Color32::GRAY));
+                return;
+            }
+            
+            egui::ScrollArea::vertical()
+                .id_salt("bridge_terms_scroll")
+                .max_height(250.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("bridge_terms_grid")
+                        .num_columns(3)
+                        .spacing([20.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new(tr("term")).strong());
+                            ui.label(egui::RichText::new(tr("entropy")).strong());
+                            ui.label(egui::RichText::new(tr("categories-notes")).strong());
+                            ui.end_row();
                             
-                            if ui.button("Template").clicked() {
-                                to_template = Some((cat.clone(), sub.clone(), con.clone()));
+                            for bridge in bridges.iter().take(30) {
+                                if ui.link(egui::RichText::new(&bridge.term).color(accent_color)).clicked() {
+                                    to_search = Some(bridge.term.clone());
+                                }
+                                ui.label(format!("{:.2} bits", bridge.entropy));
+                                ui.horizontal_wrapped(|ui| {
+                                    for (cat, count) in &bridge.categories {
+                                        let mut text = egui::RichText::new(format!("{} ({})", cat, count)).size(11.0);
+                                        if let Some(color) = self.microfiche.meta.category_color(cat) {
+                                            text = text.color(color);
+                                        }
+                                        ui.label(text);
+                                    }
+                                });
+                                ui.end_row();
                             }
                         });
-                    });
                 });
-                ui.add_space(5.0);
-            }
         });
         
-        // Handle actions after the scroll area
-        if let Some((cat, sub, con, note)) = to_delete {
-            if self.microfiche.delete_note(&cat, &sub, &con, &note) {
-                self.search_results = self.microfiche.search(&self.search_query);
-                self.status_message = "Entry deleted".to_string();
-            }
+        if let Some(term) = to_search {
+            self.search_for(term, None);
         }
+        ui.add_space(10.0);
+    }
+    
+    fn render_clusters(&mut self, ui: &mut egui::Ui, accent_color: egui::Color32) {
+        let mut to_graph: Option<ConceptRef> = None;
         
-        if let Some((cat, sub, con, note)) = to_edit {
-            // Delete the old entry
-            if self.microfiche.delete_note(&cat, &sub, &con, &note) {
-                // Populate the create form with the old data
-                self.new_category = cat;
-                self.new_subcategory = sub;
-                self.new_concept = con;
-                self.new_note = note;
-                
-                // Switch to create view
-                self.view_mode = ViewMode::Create;
-                self.status_message = "Entry loaded for editing. Modify and click Create to save.".to_string();
-                
-                // Refresh search results
-                self.search_results = self.microfiche.search(&self.search_query);
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.heading(tr("concept-clusters"));
+            ui.label(tr("concepts-grouped-by-shared-terms-named"));
+            ui.horizontal(|ui| {
+                ui.label(tr("minimum-similarity-label"));
+                ui.add(egui::Slider::new(&mut self.cluster_threshold, 0.05..=0.5).fixed_decimals(2));
+                if ui.button(tr("analyze")).clicked() {
+                    self.clusters = Some(self.microfiche.cluster_concepts(self.cluster_threshold));
+                }
+            });
+            
+            let Some(clusters) = &self.clusters else {
+                return;
+            };
+            ui.separator();
+            if clusters.is_empty() {
+                ui.label(egui::RichText::new(tr("no-clusters-at-this-threshold-try")).color(egui::Color32::GRAY));
+                return;
             }
+            
+            egui::ScrollArea::vertical()
+                .id_salt("clusters_scroll")
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for (i, cluster) in clusters.iter().enumerate() {
+                        egui::CollapsingHeader::new(egui::RichText::new(format!(
+                            "{}  ({} concepts, {} categories)",
+                            cluster.name(), cluster.concepts.len(), cluster.categories.len()
+                        )).color(accent_color))
+                            .id_salt(("cluster", i))
+                            .show(ui, |ui| {
+                                if let Some(majority) = cluster.majority_category() {
+                                    let suggestion = format!(
+                                        "💡 Spans {} categories. Consider gathering these under \"{}\", or moving the outliers into {}.",
+                                        cluster.categories.len(),
+                                        cluster.top_terms.first().map(String::as_str).unwrap_or("a new category"),
+                                        majority,
+                                    );
+                                    ui.label(egui::RichText::new(suggestion).color(ui.visuals().warn_fg_color));
+                                }
+                                for concept in &cluster.concepts {
+                                    ui.horizontal(|ui| {
+                                        let outlier = cluster.majority_category().is_some_and(|m| m != concept.category);
+                                        let text = note_path(&concept.category, &concept.subcategory, &concept.concept);
+                                        let label = if outlier { egui::RichText::new(text).italics() } else { egui::RichText::new(text) };
+                                        ui.label(label);
+                                        if accessible(ui.small_button("🕸"), tr("show-local-graph")).on_hover_text(tr("show-local-graph")).clicked() {
+                                            to_graph = Some(concept.clone());
+                                        }
+                                    });
+                                }
+                            });
+                    }
+                });
+        });
+        
+        if to_graph.is_some() {
+            self.graph_center = to_graph;
         }
+        ui.add_space(10.0);
+    }
+    
+    fn render_stats_view(&mut self, ui: &mut egui::Ui) {
+        self.render_size_ranking_window(&ui.ctx().clone());
         
-        if let Some((cat, sub, con)) = to_template {
-            // Populate the create form but leave note empty
-            self.new_category = cat;
-            self.new_subcategory = sub;
-            self.new_concept = con;
-            self.new_note.clear();
+        // Header
+        ui.heading(tr("knowledge-statistics-word-associations"));
+        ui.horizontal(|ui| {
+            ui.label(tr("scope-label"));
+            let (scope_cat, scope_sub) = &mut self.stats_scope;
+            let mut categories: Vec<String> = self.microfiche.categories.keys().cloned().collect();
+            categories.sort_by(|a, b| collate(a, b));
+            
+            egui::ComboBox::from_id_salt("stats_scope_category")
+                .selected_text(scope_cat.as_deref().unwrap_or("Whole fiche"))
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(scope_cat.is_none(), tr("whole-fiche")).clicked() {
+                        *scope_cat = None;
+                        *scope_sub = None;
+                    }
+                    for cat in &categories {
+                        if ui.selectable_label(scope_cat.as_ref() == Some(cat), cat).clicked() {
+                            *scope_cat = Some(cat.clone());
+                            *scope_sub = None;
+                        }
+                    }
+                });
+            
+            if let Some(category) = scope_cat.as_ref().and_then(|c| self.microfiche.categories.get(c)) {
+                egui::ComboBox::from_id_salt("stats_scope_subcategory")
+                    .selected_text(scope_sub.as_deref().unwrap_or("All subcategories"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(scope_sub, None, tr("all-subcategories"));
+                        for subcat in &category.subcategories {
+                            ui.selectable_value(scope_sub, Some(subcat.name.clone()), &subcat.name);
+                        }
+                    });
+            }
             
-            // Switch to create view
-            self.view_mode = ViewMode::Create;
-            self.status_message = "Template loaded. Add your new note and click Create.".to_string();
+            if let Some(cat) = self.selected_category.clone()
+                && self.stats_scope.0.as_ref() != Some(&cat)
+                && ui.small_button(tr("use-browse-selection")).clicked()
+            {
+                self.stats_scope = (Some(cat), self.selected_subcategory.clone());
+            }
+        });
+        ui.separator();
+        ui.add_space(5.0);
+        
+        // Run every analysis against the scoped subtree, then put the full
+        // fiche back, keeping any sidecar edits (goals, study settings)
+        let Some(scoped) = self.microfiche.subtree(&self.stats_scope.0, &self.stats_scope.1) else {
+            self.render_stats(ui);
+            return;
+        };
+        let full = std::mem::replace(&mut self.microfiche, scoped);
+        self.render_stats(ui);
+        let scoped = std::mem::replace(&mut self.microfiche, full);
+        self.microfiche.meta = scoped.meta;
+        // A term clicked in the scoped stats was searched in the scoped notes only
+        if self.view_mode == ViewMode::Search {
+            self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
         }
     }
     
-    fn render_create_view(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Create New Entry");
+    /// Run the current search over the library files (all but the open one)
+    /// when Search all files is on
+    fn search_library(&mut self) {
+        if !self.search_all_files {
+            self.library_results.clear();
+            return;
+        }
+        let (results, errors) = self.library.search(&self.settings.library, self.current_file.as_deref(), &self.search_query, &self.search_filter);
+        self.library_results = results;
+        if !errors.is_empty() {
+            self.status_message = tr_args("library-files-not-read", &[("files", errors.join("; ").into())]);
+        }
+    }
+    
+    /// Matches from the other library files, grouped by file; returns the
+    /// file and concept to open when a result's Open is clicked
+    fn render_library_results(&self, ui: &mut egui::Ui) -> Option<(String, String, String, String)> {
+        let mut to_open = None;
+        let mut last_file: Option<&str> = None;
+        let unsaved = self.activity.unsaved > 0;
+        for result in &self.library_results {
+            if last_file != Some(result.file.as_str()) {
+                last_file = Some(&result.file);
+                ui.add_space(5.0);
+                ui.heading(format!("📁 {}", file_name(&result.file))).on_hover_text(&result.file);
+            }
+            ui.group(|ui| {
+                ui.vertical(|ui| {
+                    ui.strong(note_path(&result.category, &result.subcategory, &result.concept));
+                    note_label(ui, &result.note.text, result.note.display, self.settings.masks(&result.category), self.settings.render_markdown);
+                    let open = ui.button(tr("open-in-its-file"));
+                    let open = if unsaved { open.on_hover_text(tr("open-in-its-file-unsaved")) } else { open };
+                    if open.clicked() {
+                        to_open = Some((result.file.clone(), result.category.clone(), result.subcategory.clone(), result.concept.clone()));
+                    }
+                });
+            });
+            ui.add_space(5.0);
+        }
+        to_open
+    }
+    
+    /// Pick files to add to the library
+    fn pick_library_files(&mut self) {
+        let Some(paths) = rfd::FileDialog::new()
+            .add_filter(&tr("fisha-or-csv"), &[fiche_file::EXTENSION, "db", "sqlite", "csv", "gz", "zip"])
+            .pick_files()
+        else {
+            return;
+        };
+        for path in paths {
+            let path = absolute_path(&path.to_string_lossy());
+            if !self.settings.library.contains(&path) {
+                self.settings.library.push(path);
+            }
+        }
+    }
+    
+    /// The Library tab: every registered file with its size and last change,
+    /// and buttons to open it, merge it into the open file or save a copy
+    fn render_library_view(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("library"));
+        ui.separator();
+        
+        let current = self.current_file.as_deref().map(absolute_path);
+        ui.horizontal(|ui| {
+            if let Some(current) = current.clone().filter(|file| !self.settings.library.contains(file))
+                && ui.button(tr("add-current-file")).clicked()
+            {
+                self.settings.library.push(current);
+            }
+            if ui.button(tr("add-files")).clicked() {
+                self.pick_library_files();
+            }
+        });
         ui.separator();
         
-        egui::Grid::new("create_grid")
-            .num_columns(2)
-            .spacing([10.0, 10.0])
-            .show(ui, |ui| {
-                ui.label("Category:");
-                ui.add(egui::TextEdit::singleline(&mut self.new_category).desired_width(f32::INFINITY));
-                ui.end_row();
-                
-                ui.label("Subcategory:");
-                ui.add(egui::TextEdit::singleline(&mut self.new_subcategory).desired_width(f32::INFINITY));
+        if self.settings.library.is_empty() {
+            ui.label(tr("library-empty"));
+            return;
+        }
+        
+        let mut action = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("library_files").striped(true).num_columns(7).show(ui, |ui| {
+                for header in ["library-file", "file-info-title", "categories", "library-concepts", "library-notes", "library-modified"] {
+                    ui.strong(tr(header));
+                }
                 ui.end_row();
                 
-                ui.label("Concept:");
-                ui.add(egui::TextEdit::singleline(&mut self.new_concept).desired_width(f32::INFINITY));
-                ui.end_row();
+                for (index, file) in self.settings.library.iter().enumerate() {
+                    let is_open = current.as_deref() == Some(file.as_str());
+                    let name = if is_open { tr_args("library-file-open", &[("name", file_name(file).into())]) } else { file_name(file) };
+                    ui.label(name).on_hover_text(file);
+                    match self.library.summary(file) {
+                        Ok(summary) => {
+                            let details = summary.info.details();
+                            let title = ui.label(summary.info.title.trim());
+                            if !details.is_empty() {
+                                title.on_hover_text(details);
+                            }
+                            ui.label(summary.categories.to_string());
+                            ui.label(summary.concepts.to_string());
+                            ui.label(summary.notes.to_string());
+                            let modified: chrono::DateTime<chrono::Local> = summary.modified.into();
+                            ui.label(modified.format("%Y-%m-%d %H:%M").to_string());
+                        },
+                        Err(e) => {
+                            ui.label("");
+                            ui.colored_label(ui.visuals().error_fg_color, tr("library-unreadable")).on_hover_text(e);
+                            for _ in 0..3 {
+                                ui.label("");
+                            }
+                        },
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(!is_open, egui::Button::new(tr("library-open"))).clicked() {
+                            action = Some(LibraryAction::Open(file.clone()));
+                        }
+                        if ui.add_enabled(!is_open, egui::Button::new(tr("merge")))
+                            .on_hover_text(tr("library-merge-hint"))
+                            .clicked()
+                        {
+                            action = Some(LibraryAction::Merge(file.clone()));
+                        }
+                        if ui.button(tr("library-export")).on_hover_text(tr("library-export-hint")).clicked() {
+                            action = Some(LibraryAction::Export(file.clone()));
+                        }
+                        if accessible(ui.small_button("🗑"), tr("remove-from-library")).on_hover_text(tr("remove-from-library")).clicked() {
+                            action = Some(LibraryAction::Remove(index));
+                        }
+                    });
+                    ui.end_row();
+                }
             });
+        });
         
-        ui.separator();
-        ui.label("Note:");
-        ui.add(
-            egui::TextEdit::multiline(&mut self.new_note)
-                .desired_width(f32::INFINITY)
-                .desired_rows(10)
-        );
+        match action {
+            Some(LibraryAction::Open(file)) if !self.job_running() => self.load_file(file),
+            Some(LibraryAction::Merge(file)) if !self.job_running() => self.import_path(file),
+            Some(LibraryAction::Export(file)) => self.export_library_file(file),
+            Some(LibraryAction::Remove(index)) => {
+                self.settings.library.remove(index);
+                self.library.retain(&self.settings.library);
+            },
+            _ => {},
+        }
+    }
+    
+    /// Save a copy of a library file in another format, picked by extension
+    fn export_library_file(&mut self, file: String) {
+        if self.job_running() {
+            return;
+        }
+        let stem = Path::new(&file).file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Fisha", &[fiche_file::EXTENSION])
+            .add_filter("SQLite", &sqlite_store::EXTENSIONS)
+            .add_filter("CSV", &["csv"])
+            .add_filter(&tr("compressed-csv"), &["gz", "zip"])
+            .set_file_name(&stem)
+            .save_file()
+        else {
+            return;
+        };
+        let target = path.to_string_lossy().to_string();
+        if absolute_path(&target) == absolute_path(&file) {
+            return;
+        }
+        let label = tr_args("exporting", &[("path", target.clone().into())]);
+        let done = tr_args("library-exported", &[("file", file_name(&file).into()), ("path", target.clone().into())]);
+        self.start_job(JobKind::Export, label, move |progress| {
+            Microfiche::open(&file)?.save(&target, progress)?;
+            Ok(done)
+        });
+    }
+    
+    /// Switch to Search with `query` filled in, in one category or all of them
+    fn search_for(&mut self, query: String, category: Option<String>) {
+        self.search_query = query;
+        self.search_filter.category = category;
+        self.search_results = self.microfiche.search(&self.search_query, &self.search_filter);
+        self.search_library();
+        self.view_mode = ViewMode::Search;
+    }
+    
+    fn render_size_ranking_window(&mut self, ctx: &egui::Context) {
+        let Some(ranking) = &self.size_ranking else {
+            return;
+        };
+        let title = match ranking.level {
+            HierarchyLevel::Category => tr("categories-by-size"),
+            HierarchyLevel::Subcategory => tr("subcategories-by-size"),
+            HierarchyLevel::Concept => tr("concepts-by-size"),
+        };
         
-        ui.separator();
+        let mut open = true;
+        let mut go_to: Option<Vec<String>> = None;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("size_ranking_scroll")
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("size_ranking_grid")
+                            .num_columns(4)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (rank, (path, count)) in ranking.entries.iter().enumerate() {
+                                    ui.label(format!("{}.", rank + 1));
+                                    ui.label(path.join(" > "));
+                                    ui.label(tr_args("notes", &[("count", count.to_string().into())]));
+                                    if ui.small_button(tr("browse")).clicked() {
+                                        go_to = Some(path.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
         
-        if ui.button("Create").clicked() {
-            if !self.new_category.is_empty() 
-                && !self.new_subcategory.is_empty() 
-                && !self.new_concept.is_empty() 
-                && !self.new_note.is_empty() 
-            {
-                self.microfiche.add_row(FicheRow {
-                    category: self.new_category.clone(),
-                    subcategory: self.new_subcategory.clone(),
-                    concept: self.new_concept.clone(),
-                    note: self.new_note.clone(),
-                });
-                
-                self.status_message = "Entry created successfully".to_string();
-                
-                // Clear form
-                self.new_category.clear();
-                self.new_subcategory.clear();
-                self.new_concept.clear();
-                self.new_note.clear();
-            } else {
-                self.status_message = "All fields are required".to_string();
-            }
+        if let Some(path) = go_to {
+            let mut path = path.into_iter();
+            self.selected_category = path.next();
+            self.selected_subcategory = path.next();
+            self.selected_concept = path.next();
+            self.view_mode = ViewMode::Browse;
+            open = false;
+        }
+        if !open {
+            self.size_ranking = None;
         }
     }
     
-    fn render_stats_view(&mut self, ui: &mut egui::Ui) {
+    fn render_stats(&mut self, ui: &mut egui::Ui) {
         use std::collections::{HashMap, HashSet};
         
-        // Helper function to extract words from text
-        fn extract_words(text: &str) -> Vec<String> {
-            let stop_words: HashSet<&str> = [
-                "the", "a", "an", "and", "or", "but", "in", "on", "at", "to", "for",
-                "of", "with", "by", "from", "as", "is", "was", "are", "were", "be",
-                "been", "being", "have", "has", "had", "do", "does", "did", "will",
-                "would", "should", "could", "may", "might", "must", "can", "this",
-                "that", "these", "those", "i", "you", "he", "she", "it", "we", "they",
-                "what", "which", "who", "when", "where", "why", "how", "all", "each",
-                "every", "both", "few", "more", "most", "other", "some", "such", "no",
-                "not", "only", "own", "same", "so", "than", "too", "very", "just",
-                "www", "youtube", "https", "com", "github", "http", "watch", "conference",
-                "commit", "src", "main"
-            ].iter().cloned().collect();
-            
-            text.to_lowercase()
-                .split(|c: char| !c.is_alphanumeric())
-                .filter(|w| w.len() > 2 && !stop_words.contains(w))
-                .map(|w| w.to_string())
-                .collect()
-        }
-        
         // Analyze all text content
-        let mut word_freq: HashMap<String, usize> = HashMap::new();
+        let TermNetwork { frequencies: word_freq, pairs: co_occurrences } = self.microfiche.term_network();
         let mut category_terms: HashMap<String, HashSet<String>> = HashMap::new();
         let mut term_categories: HashMap<String, HashSet<String>> = HashMap::new();
-        let mut co_occurrences: HashMap<(String, String), usize> = HashMap::new();
         
         for (cat_name, category) in &self.microfiche.categories {
             let mut cat_words = HashSet::new();
@@ -845,7 +11113,6 @@ impl MicroficheApp {
                 for concept in &subcat.concepts {
                     // Extract words from concept name
                     for word in extract_words(&concept.name) {
-                        *word_freq.entry(word.clone()).or_insert(0) += 1;
                         cat_words.insert(word.clone());
                         term_categories.entry(word.clone())
                             .or_insert_with(HashSet::new)
@@ -854,28 +11121,12 @@ impl MicroficheApp {
                     
                     // Extract words from all notes
                     for note in &concept.notes {
-                        let words = extract_words(note);
-                        for word in &words {
-                            *word_freq.entry(word.clone()).or_insert(0) += 1;
+                        for word in extract_words(&note.text) {
                             cat_words.insert(word.clone());
                             term_categories.entry(word.clone())
                                 .or_insert_with(HashSet::new)
                                 .insert(cat_name.clone());
                         }
-                        
-                        // Calculate co-occurrences
-                        for i in 0..words.len() {
-                            for j in (i + 1)..words.len() {
-                                if words[i] != words[j] {
-                                    let pair = if words[i] < words[j] {
-                                        (words[i].clone(), words[j].clone())
-                                    } else {
-                                        (words[j].clone(), words[i].clone())
-                                    };
-                                    *co_occurrences.entry(pair).or_insert(0) += 1;
-                                }
-                            }
-                        }
                     }
                 }
             }
@@ -894,29 +11145,29 @@ impl MicroficheApp {
             }
         });
         
-        // Pagination constants
-        const ITEMS_PER_PAGE: usize = 10;
+        // Pagination
+        let items_per_page = self.settings.items_per_page.max(1);
+        let infinite_scroll = self.settings.infinite_scroll;
         let total_cooccur = top_cooccur.len();
-        let total_cooccur_pages = (total_cooccur + ITEMS_PER_PAGE - 1) / ITEMS_PER_PAGE;
+        let total_cooccur_pages = total_cooccur.div_ceil(items_per_page);
+        self.cooccurrence_shown = self.cooccurrence_shown.max(items_per_page);
+        self.category_shown = self.category_shown.max(items_per_page);
         
         let stats = self.microfiche.stats();
         let visuals = ui.ctx().style().visuals.clone();
-        let accent_color = visuals.hyperlink_color;
-        let secondary_color = visuals.selection.stroke.color;
-        let tertiary_color = visuals.warn_fg_color;
-        let error_color = visuals.error_fg_color;
+        let [accent_color, secondary_color, tertiary_color, error_color] = self.settings.stats_colors(&visuals);
+        let mut show_ranking = None;
+        let mut to_search: Option<(String, Option<String>)> = None;
+        let mut to_flag_search: Option<NoteFlag> = None;
+        let flag_counts = self.microfiche.flag_counts();
         
         // Main container
         ui.vertical(|ui| {
-            // Header
-            ui.heading("Knowledge Statistics & Word Associations");
-            ui.separator();
-            ui.add_space(5.0);
             
             // Overview panel - this establishes our width
             ui.group(|ui| {
                 ui.set_width(ui.available_width());
-                ui.heading("Overview");
+                ui.heading(tr("overview"));
                 ui.separator();
                 ui.add_space(5.0);
                 
@@ -926,45 +11177,208 @@ impl MicroficheApp {
                     .striped(true)
                     .min_col_width(ui.available_width() / 2.0 - 10.0)
                     .show(ui, |ui| {
-                        ui.label(egui::RichText::new("Categories:").strong());
+                        ui.label(egui::RichText::new(tr("categories-label")).strong());
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            ui.label(egui::RichText::new(stats.get("categories").unwrap_or(&0).to_string())
-                                .strong().size(15.0).color(accent_color));
+                            let count = egui::RichText::new(stats.get("categories").unwrap_or(&0).to_string())
+                                .strong().size(15.0).color(accent_color);
+                            if count_link(ui, count).clicked() {
+                                show_ranking = Some(HierarchyLevel::Category);
+                            }
                         });
                         ui.end_row();
                         
-                        ui.label(egui::RichText::new("Subcategories:").strong());
+                        ui.label(egui::RichText::new(tr("subcategories-label")).strong());
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            ui.label(egui::RichText::new(stats.get("subcategories").unwrap_or(&0).to_string())
-                                .size(15.0).color(secondary_color));
+                            let count = egui::RichText::new(stats.get("subcategories").unwrap_or(&0).to_string())
+                                .size(15.0).color(secondary_color);
+                            if count_link(ui, count).clicked() {
+                                show_ranking = Some(HierarchyLevel::Subcategory);
+                            }
                         });
                         ui.end_row();
                         
-                        ui.label(egui::RichText::new("Concepts:").strong());
+                        ui.label(egui::RichText::new(tr("concepts-label")).strong());
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            ui.label(egui::RichText::new(stats.get("concepts").unwrap_or(&0).to_string())
-                                .size(15.0).color(tertiary_color));
+                            let count = egui::RichText::new(stats.get("concepts").unwrap_or(&0).to_string())
+                                .size(15.0).color(tertiary_color);
+                            if count_link(ui, count).clicked() {
+                                show_ranking = Some(HierarchyLevel::Concept);
+                            }
                         });
                         ui.end_row();
                         
-                        ui.label(egui::RichText::new("Total Notes:").strong());
+                        ui.label(egui::RichText::new(tr("total-notes-label")).strong());
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             ui.label(egui::RichText::new(stats.get("total_notes").unwrap_or(&0).to_string())
                                 .strong().size(15.0).color(error_color));
                         });
                         ui.end_row();
                         
-                        ui.label(egui::RichText::new("Unique Terms:").strong());
+                        ui.label(egui::RichText::new(tr("unique-terms-label")).strong());
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             ui.label(egui::RichText::new(word_freq.len().to_string())
                                 .size(15.0).color(accent_color));
                         });
                         ui.end_row();
+                        
+                        for (flag, count) in &flag_counts {
+                            ui.label(egui::RichText::new(format!("{} {}", flag.icon(), flag.label())).strong());
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                let count = egui::RichText::new(count.to_string()).size(15.0).color(flag.color(&visuals));
+                                if count_link(ui, count).on_hover_text(tr("search-flagged-notes")).clicked() {
+                                    to_flag_search = Some(*flag);
+                                }
+                            });
+                            ui.end_row();
+                        }
                     });
             });
             
+            if let Some(level) = show_ranking {
+                self.size_ranking = Some(SizeRanking { level, entries: self.microfiche.ranked_by_size(level) });
+            }
             ui.add_space(10.0);
             
+            self.render_goals(ui, accent_color);
+            render_frequency_plots(ui, &word_freq, accent_color, secondary_color);
+            self.render_term_trends(ui, &word_freq);
+            
+            self.render_category_comparison(ui, accent_color, secondary_color);
+            self.render_bridge_terms(ui, accent_color);
+            self.render_clusters(ui, accent_color);
+            
+            // Study streak and per-category coverage
+            let study = &self.microfiche.meta.study;
+            if !study.studied.is_empty() {
+                let coverage = self.microfiche.study_coverage();
+                let streak = study.streak(chrono::Local::now().date_naive());
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        ui.heading(tr("study-coverage"));
+                        ui.label(egui::RichText::new(tr_args("day-streak", &[("streak", streak.to_string().into())])).color(tertiary_color));
+                    });
+                    ui.label(tr("notes-studied-at-least-once-per"));
+                    ui.separator();
+                    
+                    egui::Grid::new("study_coverage_grid")
+                        .num_columns(2)
+                        .spacing([20.0, 4.0])
+                        .show(ui, |ui| {
+                            for (cat_name, (studied, total)) in &coverage {
+                                ui.label(egui::RichText::new(self.microfiche.meta.category_label(cat_name))
+                                    .color(self.microfiche.meta.category_color(cat_name).unwrap_or(accent_color)));
+                                ui.add(egui::ProgressBar::new(*studied as f32 / (*total).max(1) as f32)
+                                    .desired_width(200.0)
+                                    .text(format!("{} / {}", studied, total)));
+                                ui.end_row();
+                            }
+                        });
+                });
+                
+                ui.add_space(10.0);
+            }
+            
+            // Average rating per category
+            let rating_stats = self.microfiche.rating_stats();
+            if !rating_stats.is_empty() {
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.heading(tr("ratings"));
+                    ui.label(tr("average-rating-per-category"));
+                    ui.separator();
+                    
+                    ui.horizontal_wrapped(|ui| {
+                        for (cat_name, (average, count)) in &rating_stats {
+                            ui.label(egui::RichText::new(self.microfiche.meta.category_label(cat_name))
+                                .strong()
+                                .color(self.microfiche.meta.category_color(cat_name).unwrap_or(accent_color)));
+                            ui.label(egui::RichText::new(format!("★ {:.1}", average)).color(tertiary_color));
+                            ui.label(egui::RichText::new(tr_args("rated", &[("count", count.to_string().into())]))
+                                .size(11.0)
+                                .color(egui::Color32::GRAY));
+                            ui.add_space(15.0);
+                        }
+                    });
+                });
+                
+                ui.add_space(10.0);
+            }
+            
+            // Numeric custom fields, aggregated per category
+            let numeric_stats = self.microfiche.numeric_field_stats();
+            if !numeric_stats.is_empty() {
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.heading(tr("numeric-fields"));
+                    ui.label(tr("sum-and-average-per-category-click"));
+                    ui.separator();
+                    
+                    let mut cat_names: Vec<&String> = self.microfiche.categories.keys()
+                        .filter(|c| numeric_stats.values().any(|per_cat| per_cat.contains_key(*c)))
+                        .collect();
+                    match &self.numeric_sort_field {
+                        Some(field) if numeric_stats.contains_key(field) => {
+                            let per_cat = &numeric_stats[field];
+                            let sum_of = |c: &String| per_cat.get(c).map(|(sum, _)| *sum).unwrap_or(0.0);
+                            cat_names.sort_by(|a, b| {
+                                let ord = sum_of(a).total_cmp(&sum_of(b)).then_with(|| b.cmp(a));
+                                if self.numeric_sort_desc { ord.reverse() } else { ord }
+                            });
+                        },
+                        _ => cat_names.sort_by(|a, b| collate(a, b)),
+                    }
+                    
+                    egui::ScrollArea::horizontal()
+                        .id_salt("numeric_fields_scroll")
+                        .show(ui, |ui| {
+                            egui::Grid::new("numeric_fields_grid")
+                                .num_columns(numeric_stats.len() + 1)
+                                .spacing([20.0, 6.0])
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label(egui::RichText::new(tr("category")).strong());
+                                    for field in numeric_stats.keys() {
+                                        let arrow = match &self.numeric_sort_field {
+                                            Some(f) if f == field => if self.numeric_sort_desc { " ⏷" } else { " ⏶" },
+                                            _ => "",
+                                        };
+                                        let header = egui::RichText::new(format!("{}{}", field, arrow)).strong();
+                                        if ui.add(egui::Button::new(header).frame(false)).clicked() {
+                                            if self.numeric_sort_field.as_ref() == Some(field) {
+                                                self.numeric_sort_desc = !self.numeric_sort_desc;
+                                            } else {
+                                                self.numeric_sort_field = Some(field.clone());
+                                                self.numeric_sort_desc = true;
+                                            }
+                                        }
+                                    }
+                                    ui.end_row();
+                                    
+                                    for cat_name in &cat_names {
+                                        ui.label(egui::RichText::new(self.microfiche.meta.category_label(cat_name))
+                                            .color(self.microfiche.meta.category_color(cat_name).unwrap_or(accent_color)));
+                                        for per_cat in numeric_stats.values() {
+                                            match per_cat.get(*cat_name) {
+                                                Some((sum, count)) => {
+                                                    ui.label(format!("Σ {}  ·  avg {}",
+                                                        format_number(*sum),
+                                                        format_number(sum / *count as f64)));
+                                                },
+                                                None => {
+                                                    ui.label(egui::RichText::new("—").color(egui::Color32::GRAY));
+                                                },
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                });
+                
+                ui.add_space(10.0);
+            }
+            
             // Calculate available height for the two panels
             let available_height = ui.available_height() - 20.0;
             let total_width = ui.available_width();
@@ -982,28 +11396,25 @@ impl MicroficheApp {
                         ui.set_height(available_height);
                         
                         ui.vertical(|ui| {
-                            ui.heading("Term Co-occurrences");
-                            ui.label("Pairs appearing together");
+                            ui.heading(tr("term-co-occurrences"));
+                            ui.label(tr("pairs-appearing-together"));
                             
                             if top_cooccur.is_empty() {
                                 ui.separator();
                                 ui.centered_and_justified(|ui| {
-                                    ui.label(egui::RichText::new("No co-occurrences found")
+                                    ui.label(egui::RichText::new(tr("no-co-occurrences-found"))
                                         .size(14.0).color(egui::Color32::GRAY));
                                 });
                             } else {
                                 ui.separator();
                                 
                                 // Pagination controls
-                                ui.horizontal(|ui| {
-                                    if ui.button("◀ Prev").clicked() && self.cooccurrence_page > 0 {
-                                        self.cooccurrence_page -= 1;
-                                    }
-                                    ui.label(format!("Page {} / {}", self.cooccurrence_page + 1, total_cooccur_pages.max(1)));
-                                    if ui.button("Next ▶").clicked() && self.cooccurrence_page < total_cooccur_pages.saturating_sub(1) {
-                                        self.cooccurrence_page += 1;
-                                    }
-                                });
+                                if infinite_scroll {
+                                    ui.label(format!("Showing {} of {}", self.cooccurrence_shown.min(total_cooccur), total_cooccur));
+                                } else {
+                                    let hovered = ui.rect_contains_pointer(ui.max_rect());
+                                    pager(ui, &mut self.cooccurrence_page, total_cooccur_pages, hovered);
+                                }
                                 
                                 ui.separator();
                                 
@@ -1012,22 +11423,26 @@ impl MicroficheApp {
                                     self.cooccurrence_page = total_cooccur_pages.saturating_sub(1);
                                 }
                                 
-                                let start_idx = self.cooccurrence_page * ITEMS_PER_PAGE;
-                                let end_idx = (start_idx + ITEMS_PER_PAGE).min(total_cooccur);
+                                let (start_idx, end_idx) = if infinite_scroll {
+                                    (0, self.cooccurrence_shown.min(total_cooccur))
+                                } else {
+                                    let start_idx = self.cooccurrence_page * items_per_page;
+                                    (start_idx, (start_idx + items_per_page).min(total_cooccur))
+                                };
                                 
-                                egui::ScrollArea::vertical()
+                                let output = egui::ScrollArea::vertical()
                                     .id_source("cooccurrence_scroll")
                                     .auto_shrink([false, false])
                                     .show(ui, |ui| {
                                         for ((term1, term2), count) in &top_cooccur[start_idx..end_idx] {
-                                            ui.group(|ui| {
+                                            let card = ui.group(|ui| {
                                                 ui.set_width(ui.available_width());
                                                 ui.horizontal(|ui| {
                                                     ui.strong(egui::RichText::new(term1.as_str()).color(accent_color));
                                                     ui.label("↔");
                                                     ui.strong(egui::RichText::new(term2.as_str()).color(secondary_color));
                                                 });
-                                                ui.label(egui::RichText::new(format!("{} occurrences", count))
+                                                ui.label(egui::RichText::new(tr_args("occurrences", &[("count", count.to_string().into())]))
                                                     .size(11.0)
                                                     .color(tertiary_color));
                                                 
@@ -1051,9 +11466,19 @@ impl MicroficheApp {
                                                         .color(egui::Color32::GRAY));
                                                 }
                                             });
+                                            if card.response.interact(egui::Sense::click())
+                                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                .on_hover_text(tr("search-notes-with-both-terms"))
+                                                .clicked()
+                                            {
+                                                to_search = Some((format!("{} {}", term1, term2), self.stats_scope.0.clone()));
+                                            }
                                             ui.add_space(3.0);
                                         }
                                     });
+                                if infinite_scroll && end_idx < total_cooccur && scrolled_to_bottom(&output) {
+                                    self.cooccurrence_shown += items_per_page;
+                                }
                             }
                         });
                     });
@@ -1068,34 +11493,31 @@ impl MicroficheApp {
                         ui.set_height(available_height);
                         
                         ui.vertical(|ui| {
-                            ui.heading("Category-Term Distribution");
-                            ui.label("Top terms per category");
+                            ui.heading(tr("category-term-distribution"));
+                            ui.label(tr("top-terms-per-category"));
                             
                             if category_terms.is_empty() {
                                 ui.separator();
                                 ui.centered_and_justified(|ui| {
-                                    ui.label(egui::RichText::new("No categories yet")
+                                    ui.label(egui::RichText::new(tr("no-categories-yet"))
                                         .size(14.0).color(egui::Color32::GRAY));
                                 });
                             } else {
                                 ui.separator();
                                 
                                 let mut sorted_cats: Vec<_> = category_terms.iter().collect();
-                                sorted_cats.sort_by(|a, b| a.0.cmp(b.0));
+                                sorted_cats.sort_by(|a, b| collate(a.0, b.0));
                                 
                                 let total_cats = sorted_cats.len();
-                                let total_cat_pages = (total_cats + ITEMS_PER_PAGE - 1) / ITEMS_PER_PAGE;
+                                let total_cat_pages = total_cats.div_ceil(items_per_page);
                                 
                                 // Pagination controls
-                                ui.horizontal(|ui| {
-                                    if ui.button("◀ Prev").clicked() && self.category_page > 0 {
-                                        self.category_page -= 1;
-                                    }
-                                    ui.label(format!("Page {} / {}", self.category_page + 1, total_cat_pages.max(1)));
-                                    if ui.button("Next ▶").clicked() && self.category_page < total_cat_pages.saturating_sub(1) {
-                                        self.category_page += 1;
-                                    }
-                                });
+                                if infinite_scroll {
+                                    ui.label(format!("Showing {} of {}", self.category_shown.min(total_cats), total_cats));
+                                } else {
+                                    let hovered = ui.rect_contains_pointer(ui.max_rect());
+                                    pager(ui, &mut self.category_page, total_cat_pages, hovered);
+                                }
                                 
                                 ui.separator();
                                 
@@ -1104,18 +11526,23 @@ impl MicroficheApp {
                                     self.category_page = total_cat_pages.saturating_sub(1);
                                 }
                                 
-                                let start_idx = self.category_page * ITEMS_PER_PAGE;
-                                let end_idx = (start_idx + ITEMS_PER_PAGE).min(total_cats);
+                                let (start_idx, end_idx) = if infinite_scroll {
+                                    (0, self.category_shown.min(total_cats))
+                                } else {
+                                    let start_idx = self.category_page * items_per_page;
+                                    (start_idx, (start_idx + items_per_page).min(total_cats))
+                                };
                                 
-                                egui::ScrollArea::vertical()
+                                let output = egui::ScrollArea::vertical()
                                     .id_source("category_terms_scroll")
                                     .auto_shrink([false, false])
                                     .show(ui, |ui| {
                                         for (cat_name, terms) in &sorted_cats[start_idx..end_idx] {
                                             ui.group(|ui| {
                                                 ui.set_width(ui.available_width());
-                                                ui.strong(egui::RichText::new(cat_name.as_str()).color(accent_color));
-                                                ui.label(egui::RichText::new(format!("{} unique terms", terms.len()))
+                                                ui.strong(egui::RichText::new(self.microfiche.meta.category_label(cat_name))
+                                                    .color(self.microfiche.meta.category_color(cat_name).unwrap_or(accent_color)));
+                                                ui.label(egui::RichText::new(tr_args("unique-terms", &[("count", terms.len().to_string().into())]))
                                                     .size(11.0)
                                                     .color(egui::Color32::GRAY));
                                                 ui.separator();
@@ -1135,7 +11562,7 @@ impl MicroficheApp {
                                                     ui.set_max_width(ui.available_width());
                                                     for (term, freq) in cat_terms.iter().take(12) {
                                                         let tag = format!("{} ({})", term, freq);
-                                                        ui.label(egui::RichText::new(tag)
+                                                        let chip = egui::Label::new(egui::RichText::new(tag)
                                                             .size(11.0)
                                                             .color(secondary_color)
                                                             .background_color(egui::Color32::from_rgba_unmultiplied(
@@ -1143,81 +11570,1432 @@ impl MicroficheApp {
                                                                 secondary_color.g(),
                                                                 secondary_color.b(),
                                                                 40
-                                                            )));
+                                                            )))
+                                                            .sense(egui::Sense::click());
+                                                        if ui.add(chip)
+                                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                            .on_hover_text(tr("search-this-term-in-the-category"))
+                                                            .clicked()
+                                                        {
+                                                            to_search = Some((term.clone(), Some(cat_name.to_string())));
+                                                        }
                                                     }
                                                 });
                                             });
                                             ui.add_space(3.0);
                                         }
                                     });
+                                if infinite_scroll && end_idx < total_cats && scrolled_to_bottom(&output) {
+                                    self.category_shown += items_per_page;
+                                }
                             }
                         });
                     });
                 });
             });
         });
+        
+        if let Some((query, category)) = to_search {
+            self.search_for(query, category);
+        }
+        if let Some(flag) = to_flag_search {
+            self.search_filter.flag = Some(flag);
+            self.search_for(String::new(), self.stats_scope.0.clone());
+        }
+    }
+}
+
+impl eframe::App for MicroficheApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.save_profile(storage);
+    }
+    
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if let Some(profile) = self.switch_profile.take() {
+            self.change_profile(frame, ctx, profile);
+        }
+        match &self.theme_editor {
+            Some((_, draft)) => ctx.set_visuals(draft.visuals()),
+            None => self.current_theme.apply(ctx, &self.settings.custom_themes),
+        }
+        self.handle_vim_keys(ctx);
+        self.handle_shortcuts(ctx);
+        if ctx.input(|input| input.viewport().close_requested()) && self.dirty {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.confirm_discard = Some(PendingDiscard::Exit);
+        }
+        self.update_title(ctx);
+        self.sync_external_edits(ctx);
+        self.check_file_changes(ctx);
+        self.poll_webhooks(ctx);
+        self.poll_upload(ctx);
+        self.tick_focus(ctx);
+        
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            self.render_top_bar(ui, ctx);
+        });
+        if let Some(notice) = &self.encoding_notice {
+            let mut dismissed = false;
+            egui::TopBottomPanel::top("encoding_notice").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("⚠ {}", notice)).color(ui.visuals().warn_fg_color));
+                    dismissed = accessible(ui.small_button("✖"), tr("dismiss")).clicked();
+                });
+            });
+            if dismissed {
+                self.encoding_notice = None;
+            }
+        }
+        self.render_external_change_banner(ctx);
+        
+        self.render_split_window(ctx);
+        self.render_graph_window(ctx);
+        self.render_append_window(ctx);
+        self.render_purge_window(ctx);
+        self.render_qr_window(ctx);
+        self.render_details_window(ctx);
+        self.render_command_palette(ctx);
+        self.render_shortcuts_window(ctx);
+        self.render_vim_delete_confirm(ctx);
+        self.finish_job();
+        if self.job.is_none() && let Some(pending) = self.after_save.take() && !self.dirty {
+            self.discard_and(ctx, pending);
+        }
+        self.autosave(ctx);
+        self.render_job_window(ctx);
+        self.render_unsaved_prompt(ctx);
+        self.render_selective_open_window(ctx);
+        self.render_secret_report(ctx);
+        self.render_aging_window(ctx);
+        self.render_file_info_window(ctx);
+        self.render_validation_window(ctx);
+        self.render_import_wizard(ctx);
+        self.render_link_import_window(ctx);
+        self.render_pack_export_window(ctx);
+        self.render_feed_export_window(ctx);
+        self.render_calendar_export_window(ctx);
+        self.render_markdown_export_window(ctx);
+        self.render_restore_backup_window(ctx);
+        self.render_subcategory_info_window(ctx);
+        self.render_publish_window(ctx);
+        self.render_theme_editor(ctx);
+        self.render_focus_capture_window(ctx);
+        self.render_pack_import_window(ctx);
+        self.render_new_workspace_window(ctx);
+        self.render_profile_windows(ctx);
+        self.render_welcome_window(ctx);
+        self.render_tour(ctx);
+        if let Some(text) = self.to_clipboard.take() {
+            ctx.copy_text(text);
+        }
+        self.render_scratchpad(ctx);
+        
+        egui::CentralPanel::default().show(ctx, |ui| {
+            match self.view_mode {
+                ViewMode::Browse => self.render_browse_view(ui),
+                ViewMode::Search => self.render_search_view(ui),
+                ViewMode::Create => self.render_create_view(ui),
+                ViewMode::Stats => self.render_stats_view(ui),
+                ViewMode::Activity => self.render_activity_view(ui),
+                ViewMode::Review => self.render_review_view(ui),
+                ViewMode::Study => self.render_study_view(ui),
+                ViewMode::Library => self.render_library_view(ui),
+            }
+        });
+        self.dirty |= self.history.track(&self.microfiche, &self.activity);
+    }
+}
+
+fn main() -> Result<(), eframe::Error> {
+    // `--profile <name>` skips the profile picker; the next argument is a file
+    // to open, or the file association installer flag
+    let mut args = std::env::args().skip(1).peekable();
+    let profile = if args.peek().map(String::as_str) == Some("--profile") {
+        args.nth(1)
+    } else {
+        None
+    };
+    let startup_file = args.next();
+    if let Some(command) = startup_file.as_deref() && cli::is_command(command) {
+        cli::run(std::iter::once(command.to_string()).chain(args).collect());
+        return Ok(());
+    }
+    if startup_file.as_deref() == Some(file_types::REGISTER_FLAG) {
+        match file_types::register() {
+            Ok(message) => println!("{}", message),
+            Err(e) => {
+                eprintln!("Could not register file types: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1200.0, 800.0])
+            .with_min_inner_size([800.0, 600.0])
+            .with_icon(load_icon()),
+        ..Default::default()
+    };
+    
+    eframe::run_native(
+        APP_TITLE,
+        options,
+        Box::new(|cc| Ok(Box::new(MicroficheApp::new(cc, startup_file, profile)))),
+    )
+}
+
+fn stored_value<T: serde::de::DeserializeOwned>(storage: Option<&dyn eframe::Storage>, key: &str) -> Option<T> {
+    eframe::get_value(storage?, key)
+}
+
+/// Where a profile's `key` is kept in the app storage; the default profile
+/// uses the plain keys from before profiles existed
+fn profile_key(profile: &str, key: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        key.to_string()
+    } else {
+        format!("profile:{}:{}", profile, key)
+    }
+}
+
+/// The frame around a note in Browse and Search: tinted when highlighted,
+/// outlined when it's its concept's definition
+fn note_card(ui: &egui::Ui, note: &Note) -> egui::Frame {
+    let mut frame = egui::Frame::group(ui.style());
+    if note.definition {
+        frame = frame.fill(ui.visuals().faint_bg_color).stroke(ui.visuals().selection.stroke);
+    }
+    if let Some(highlight) = note.display.highlight {
+        frame = frame.fill(highlight.tint(ui.visuals()));
+    }
+    frame
+}
+
+/// Where a card's action buttons go: laid out but invisible until hovered
+fn card_actions(visible: bool) -> egui::UiBuilder {
+    let builder = egui::UiBuilder::new();
+    if visible { builder } else { builder.invisible() }
+}
+
+/// A compact card's single line: the note's start, cut to the width, and its
+/// rating; true when it's clicked to show the whole card
+fn compact_note_line(ui: &mut egui::Ui, note: &Note, masked: bool) -> bool {
+    let text = if masked { masked_text(&note.text) } else { excerpt(&note.text, 300) };
+    ui.horizontal(|ui| {
+        if note.definition {
+            ui.label(egui::RichText::new("📌").color(ui.visuals().selection.stroke.color))
+                .on_hover_text(tr("definition"));
+        }
+        if let Some(rating) = note.rating {
+            ui.label(egui::RichText::new(format!("{}★", rating)).color(egui::Color32::GRAY));
+        }
+        let line = egui::Label::new(text).truncate().sense(egui::Sense::click());
+        ui.add(line).on_hover_text(tr("click-to-show-the-whole-note")).clicked()
+    }).inner
+}
+
+/// Render custom fields as a compact "name: value" line for note cards
+fn format_fields(fields: &BTreeMap<String, String>) -> String {
+    fields.iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect::<Vec<_>>()
+        .join("  ·  ")
+}
+
+/// Category/subcategory (and optionally concept) combo boxes laid out as a grid.
+/// Changing a level clears the levels below it.
+fn hierarchy_picker(
+    ui: &mut egui::Ui,
+    id: &str,
+    categories: &IndexMap<String, Category>,
+    cat: &mut String,
+    sub: &mut String,
+    mut concept: Option<&mut String>,
+) {
+    let mut cat_names: Vec<&String> = categories.keys().collect();
+    cat_names.sort_by(|a, b| collate(a, b));
+    let category = categories.get(cat.as_str());
+    let subcat = category.and_then(|c| c.subcategories.iter().find(|s| &s.name == sub));
+    
+    egui::Grid::new(format!("{}_grid", id)).num_columns(2).show(ui, |ui| {
+        ui.label(tr("category-label"));
+        egui::ComboBox::from_id_salt(format!("{}_category", id))
+            .selected_text(cat.as_str())
+            .show_ui(ui, |ui| {
+                for name in &cat_names {
+                    if ui.selectable_label(cat == *name, name.as_str()).clicked() {
+                        *cat = name.to_string();
+                        sub.clear();
+                        if let Some(concept) = concept.as_deref_mut() {
+                            concept.clear();
+                        }
+                    }
+                }
+            });
+        ui.end_row();
+        
+        ui.label(tr("subcategory-label"));
+        egui::ComboBox::from_id_salt(format!("{}_subcategory", id))
+            .selected_text(sub.as_str())
+            .show_ui(ui, |ui| {
+                for s in category.map(|c| c.subcategories.as_slice()).unwrap_or_default() {
+                    if ui.selectable_label(sub == &s.name, &s.name).clicked() {
+                        *sub = s.name.clone();
+                        if let Some(concept) = concept.as_deref_mut() {
+                            concept.clear();
+                        }
+                    }
+                }
+            });
+        ui.end_row();
+        
+        if let Some(concept) = concept {
+            ui.label(tr("concept-label"));
+            egui::ComboBox::from_id_salt(format!("{}_concept", id))
+                .selected_text(concept.as_str())
+                .show_ui(ui, |ui| {
+                    for c in subcat.map(|s| s.concepts.as_slice()).unwrap_or_default() {
+                        ui.selectable_value(concept, c.name.clone(), &c.name);
+                    }
+                });
+            ui.end_row();
+        }
+    });
+}
+
+/// Stable identifier for a note (FNV-1a of its path and text), used to
+/// reference notes from the sidecar without copying their text
+fn note_key(cat: &str, sub: &str, con: &str, text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in [cat, sub, con, text] {
+        for byte in part.bytes().chain(std::iter::once(0x1f)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+const STOP_WORDS: &[&str] = &[
+    "the", "a", "an", "and", "or", "but", "in", "on", "at", "to", "for",
+    "of", "with", "by", "from", "as", "is", "was", "are", "were", "be",
+    "been", "being", "have", "has", "had", "do", "does", "did", "will",
+    "would", "should", "could", "may", "might", "must", "can", "this",
+    "that", "these", "those", "i", "you", "he", "she", "it", "we", "they",
+    "what", "which", "who", "when", "where", "why", "how", "all", "each",
+    "every", "both", "few", "more", "most", "other", "some", "such", "no",
+    "not", "only", "own", "same", "so", "than", "too", "very", "just",
+    "www", "youtube", "https", "com", "github", "http", "watch", "conference",
+    "commit", "src", "main"
+];
+
+/// Lowercased words, split on Unicode word boundaries (with dictionary
+/// segmentation for Chinese and Japanese), minus stop words. Words need three
+/// characters, or two in CJK scripts.
+fn extract_words(text: &str) -> Vec<String> {
+    let lower = text.to_lowercase();
+    let segmenter = icu_segmenter::WordSegmenter::new_auto(Default::default());
+    let mut boundaries = segmenter.segment_str(&lower);
+    
+    let mut words = Vec::new();
+    let mut start = 0;
+    while let Some(end) = boundaries.next() {
+        if end > start && boundaries.is_word_like() {
+            let word = &lower[start..end];
+            let min_chars = if word.chars().any(is_cjk) { 2 } else { 3 };
+            if word.chars().count() >= min_chars && !STOP_WORDS.contains(&word) {
+                words.push(word.to_string());
+            }
+        }
+        start = end;
+    }
+    words
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'     // Hiragana, Katakana
+        | '\u{3400}'..='\u{4dbf}'   // CJK Extension A
+        | '\u{4e00}'..='\u{9fff}'   // CJK Unified Ideographs
+        | '\u{ac00}'..='\u{d7af}'   // Hangul syllables
+    )
+}
+
+/// Order names by the Unicode Collation Algorithm, so "Äpfel" sorts with
+/// "Apfel" rather than after "Zucker"
+fn collate(a: &str, b: &str) -> std::cmp::Ordering {
+    thread_local! {
+        static COLLATOR: std::cell::RefCell<feruca::Collator> = std::cell::RefCell::new(feruca::Collator::default());
+    }
+    COLLATOR.with(|collator| collator.borrow_mut().collate(a, b))
+}
+
+/// Lowercase and strip diacritics, so "Café" and "cafe" compare equal
+fn fold_text(text: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    
+    text.nfd()
+        .filter(|c| !unicode_normalization::char::is_combining_mark(*c))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Terms of a concept's name and all its notes
+fn concept_terms(concept: &Concept) -> HashSet<String> {
+    let mut terms: HashSet<String> = extract_words(&concept.name).into_iter().collect();
+    for note in &concept.notes {
+        terms.extend(extract_words(&note.text));
+    }
+    terms
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        0.0
+    } else {
+        a.intersection(b).count() as f32 / union as f32
+    }
+}
+
+/// Targets of `[[Concept]]` links, lowercased
+fn wiki_links(text: &str) -> Vec<String> {
+    let mut links = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else { break };
+        let target = rest[..end].trim();
+        if !target.is_empty() {
+            links.push(target.to_lowercase());
+        }
+        rest = &rest[end + 2..];
+    }
+    links
+}
+
+/// `#tags` in a note, lowercased and without the `#`
+fn note_tags(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '-' && c != '_'))
+        .filter(|tag| !tag.is_empty() && !tag.starts_with('#'))
+        .map(|tag| tag.to_lowercase())
+        .collect()
+}
+
+fn note_path(cat: &str, sub: &str, con: &str) -> String {
+    format!("{} > {} > {}", cat, sub, con)
+}
+
+/// Move Up and Move Down items for the context menu of the `index`th of
+/// `len` entries, greyed out at the ends; a move closes the menu
+fn move_buttons(ui: &mut egui::Ui, index: usize, len: usize) -> Option<Step> {
+    let mut moved = None;
+    for step in [Step::Up, Step::Down] {
+        if ui.add_enabled(step.from(index, len).is_some(), egui::Button::new(step.label())).clicked() {
+            moved = Some(step);
+            ui.close();
+        }
     }
+    moved
 }
 
-impl eframe::App for MicroficheApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.current_theme.apply(ctx);
+/// First `max_chars` characters of a single-line version of `text`
+fn excerpt(text: &str, max_chars: usize) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= max_chars {
+        flat
+    } else {
+        format!("{}…", flat.chars().take(max_chars).collect::<String>())
+    }
+}
+
+/// A note as a Markdown block quote followed by where it comes from:
+///
+/// ```text
+/// > note text
+///
+/// — Category/Subcategory/Concept
+/// ```
+fn markdown_quote(cat: &str, sub: &str, con: &str, text: &str) -> String {
+    let mut quote = String::new();
+    for line in text.trim().lines() {
+        let line = line.trim_end();
+        quote.push('>');
+        if !line.is_empty() {
+            quote.push(' ');
+            quote.push_str(line);
+        }
+        quote.push('\n');
+    }
+    quote.push_str(&format!("\n— {}/{}/{}\n", cat, sub, con));
+    quote
+}
+
+/// The structure of a note with its content blanked: headings and
+/// "Label:" lines are kept, list items are emptied and prose is dropped.
+/// None when the note is just prose.
+fn note_skeleton(text: &str) -> Option<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut structured = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        let bullet_len = list_marker_len(trimmed);
+        let (marker, item) = trimmed.split_at(bullet_len);
         
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            self.render_top_bar(ui, ctx);
+        let kept = if trimmed.starts_with('#') {
+            Some(line.trim_end().to_string())
+        } else if let Some((label, _)) = item.split_once(':').filter(|(label, rest)| is_label(label) && !rest.starts_with("//")) {
+            Some(format!("{}{}{}: ", indent, marker, label.trim()))
+        } else if bullet_len > 0 {
+            let marker = if marker.starts_with("- [") { "- [ ] " } else { marker };
+            Some(format!("{}{}", indent, marker))
+        } else {
+            None
+        };
+        
+        match kept {
+            Some(kept) => {
+                structured = true;
+                lines.push(kept);
+            },
+            // Blank lines and dropped prose leave a single gap
+            None => if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            },
+        }
+    }
+    
+    // Consecutive identical empty bullets only need to appear once
+    lines.dedup_by(|a, b| a == b && list_marker_len(a.trim_start()) == a.trim_start().len());
+    let skeleton = lines.join("\n").trim_end_matches('\n').to_string();
+    structured.then_some(skeleton)
+}
+
+/// Length of a leading "- ", "* ", "+ ", "1. ", "1) " or "- [x] " marker
+fn list_marker_len(line: &str) -> usize {
+    for bullet in ["- [ ] ", "- [x] ", "- [X] ", "- ", "* ", "+ "] {
+        if line.starts_with(bullet) {
+            return bullet.len();
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") ")) {
+        return digits + 2;
+    }
+    0
+}
+
+/// Whether the text before a colon reads as a field label ("Author",
+/// "Key takeaway") rather than part of a sentence or a URL
+fn is_label(label: &str) -> bool {
+    let label = label.trim();
+    !label.is_empty()
+        && label.chars().count() <= 30
+        && label.split_whitespace().count() <= 4
+        && label.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' || c == '/')
+}
+
+/// Plain text of an HTML fragment: line and paragraph breaks become
+/// newlines, other tags are dropped and the common entities decoded
+fn strip_html(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        plain.push_str(&rest[..start]);
+        // "a < b" is text, not a tag
+        let opens_tag = rest[start + 1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        let Some(end) = rest[start..].find('>').filter(|_| opens_tag) else {
+            plain.push('<');
+            rest = &rest[start + 1..];
+            continue;
+        };
+        let tag = rest[start + 1..start + end].trim_start_matches('/').to_ascii_lowercase();
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+        if matches!(name, "br" | "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6") {
+            plain.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    plain.push_str(rest);
+    
+    for (entity, replacement) in [("&nbsp;", " "), ("&lt;", "<"), ("&gt;", ">"), ("&quot;", "\""), ("&#39;", "'"), ("&apos;", "'"), ("&amp;", "&")] {
+        plain = plain.replace(entity, replacement);
+    }
+    plain
+}
+
+/// Whether an activity entry's detail repeats part of a note: it contains
+/// the note's opening words, or what follows its "label: " is in the note
+fn quotes_note(detail: &str, text: &str) -> bool {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let detail = detail.trim_end_matches('…');
+    let opening: String = flat.chars().take(24).collect();
+    let quoted = detail.split_once(": ").map_or(detail, |(_, rest)| rest);
+    !opening.is_empty() && detail.contains(&opening) || !quoted.is_empty() && flat.contains(quoted)
+}
+
+/// "2025-03-04" -> "Tuesday, 4 March 2025"
+fn format_day(day: &str) -> String {
+    chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .map(|d| d.format("%A, %-d %B %Y").to_string())
+        .unwrap_or_else(|_| day.to_string())
+}
+
+/// Log-log rank/frequency plot (Zipf) next to the cumulative share of all
+/// word occurrences covered by the top-ranked words
+fn render_frequency_plots(
+    ui: &mut egui::Ui,
+    word_freq: &HashMap<String, usize>,
+    accent_color: egui::Color32,
+    secondary_color: egui::Color32,
+) {
+    use egui_plot::{Line, Plot, PlotPoints, Points};
+    
+    let mut ranked: Vec<(&String, usize)> = word_freq.iter().map(|(w, c)| (w, *c)).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let total: usize = ranked.iter().map(|(_, c)| c).sum();
+    
+    ui.group(|ui| {
+        ui.set_width(ui.available_width());
+        ui.heading(tr("word-frequency-distribution"));
+        if ranked.is_empty() {
+            ui.label(egui::RichText::new(tr("no-words-yet")).color(egui::Color32::GRAY));
+            return;
+        }
+        
+        let coverage_at = |n: usize| {
+            let covered: usize = ranked.iter().take(n).map(|(_, c)| c).sum();
+            100.0 * covered as f64 / total as f64
+        };
+        ui.label(tr_args("word-frequency-summary", &[
+            ("words", ranked.len().to_string().into()),
+            ("occurrences", total.to_string().into()),
+            ("top10", format!("{:.1}", coverage_at(10)).into()),
+            ("top100", format!("{:.1}", coverage_at(100)).into()),
+        ]));
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new(tr("most-frequent-stop-word-candidates-label")).color(egui::Color32::GRAY));
+            for (word, count) in ranked.iter().take(10) {
+                ui.label(egui::RichText::new(format!("{} ({})", word, count)).color(accent_color));
+            }
         });
+        ui.separator();
         
-        egui::CentralPanel::default().show(ctx, |ui| {
-            match self.view_mode {
-                ViewMode::Browse => self.render_browse_view(ui),
-                ViewMode::Search => self.render_search_view(ui),
-                ViewMode::Create => self.render_create_view(ui),
-                ViewMode::Stats => self.render_stats_view(ui),
+        let word_at_rank = |rank: f64| {
+            let index = rank.round() as usize;
+            ranked.get(index.saturating_sub(1)).map(|(w, c)| format!("#{} {} ({})", index, w, c)).unwrap_or_default()
+        };
+        let plot_width = (ui.available_width() - 10.0) / 2.0;
+        
+        ui.horizontal(|ui| {
+            let zipf: PlotPoints = ranked.iter().enumerate()
+                .map(|(i, (_, c))| [((i + 1) as f64).log10(), (*c as f64).log10()])
+                .collect();
+            // Ideal Zipf law: frequency inversely proportional to rank
+            let top = ranked[0].1 as f64;
+            let ideal: PlotPoints = [[0.0, top.log10()], [(ranked.len() as f64).log10(), (top / ranked.len() as f64).log10()]]
+                .into_iter()
+                .collect();
+            Plot::new("zipf_plot")
+                .width(plot_width)
+                .height(220.0)
+                .x_axis_label(tr("zipf-rank-axis"))
+                .y_axis_label(tr("zipf-frequency-axis"))
+                .x_axis_formatter(|mark, _| format!("{:.0}", 10f64.powf(mark.value)))
+                .y_axis_formatter(|mark, _| format!("{:.0}", 10f64.powf(mark.value)))
+                .label_formatter(|_, point| word_at_rank(10f64.powf(point.x)))
+                .allow_scroll(false)
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(tr("zipf-law"), ideal).color(egui::Color32::GRAY).width(1.0));
+                    plot_ui.points(Points::new(tr("zipf-words"), zipf).color(accent_color).radius(2.0));
+                });
+            
+            let mut covered = 0;
+            let coverage: PlotPoints = ranked.iter().enumerate()
+                .map(|(i, (_, c))| {
+                    covered += c;
+                    [(i + 1) as f64, 100.0 * covered as f64 / total as f64]
+                })
+                .collect();
+            Plot::new("coverage_plot")
+                .width(plot_width)
+                .height(220.0)
+                .x_axis_label(tr("coverage-top-axis"))
+                .y_axis_label(tr("coverage-share-axis"))
+                .include_y(0.0)
+                .include_y(100.0)
+                .label_formatter(|_, point| format!("{}\n{}", word_at_rank(point.x), tr_args("coverage-covered", &[("percent", format!("{:.1}", point.y).into())])))
+                .allow_scroll(false)
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(tr("coverage-line"), coverage).color(secondary_color).width(2.0));
+                });
+        });
+    });
+    
+    ui.add_space(10.0);
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// System fonts tried, in order, for Chinese/Japanese/Korean text
+const CJK_FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\malgun.ttf",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/AppleSDGothicNeo.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+];
+
+/// Outline emoji fonts (egui can't draw color bitmap emoji) covering more
+/// than the small emoji set egui ships with
+const EMOJI_FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+];
+
+/// Install egui's fonts plus fallbacks: the user's font, then the first CJK
+/// and emoji fonts found on the system. A custom code font goes first in the
+/// monospace family.
+fn apply_fonts(ctx: &egui::Context, settings: &Settings) {
+    use std::sync::Arc;
+    
+    let mut fonts = egui::FontDefinitions::default();
+    let mut fallbacks: Vec<String> = Vec::new();
+    let load = |fonts: &mut egui::FontDefinitions, name: &str, path: &str| -> bool {
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                fonts.font_data.insert(name.to_string(), Arc::new(egui::FontData::from_owned(bytes)));
+                true
+            },
+            Err(_) => false,
+        }
+    };
+    
+    if let Some(path) = &settings.custom_font
+        && load(&mut fonts, "custom", path)
+    {
+        fallbacks.push("custom".to_string());
+    }
+    if CJK_FONT_CANDIDATES.iter().any(|path| load(&mut fonts, "cjk", path)) {
+        fallbacks.push("cjk".to_string());
+    }
+    if EMOJI_FONT_CANDIDATES.iter().any(|path| load(&mut fonts, "emoji", path)) {
+        fallbacks.push("emoji".to_string());
+    }
+    for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+        fonts.families.entry(family).or_default().extend(fallbacks.iter().cloned());
+    }
+    
+    if let Some(path) = &settings.monospace_font
+        && load(&mut fonts, "code", path)
+    {
+        fonts.families.entry(egui::FontFamily::Monospace).or_default().insert(0, "code".to_string());
+    }
+    
+    ctx.set_fonts(fonts);
+    ctx.style_mut(|style| {
+        style.text_styles.insert(egui::TextStyle::Monospace, egui::FontId::monospace(settings.monospace_size));
+    });
+}
+
+/// Note text, wrapped. Notes fenced with ``` are code notes and use the
+/// monospace font.
+fn note_label(ui: &mut egui::Ui, text: &str, display: NoteDisplay, masked: bool, markdown: bool) {
+    if masked {
+        ui.label(egui::RichText::new(masked_text(text)).italics().color(egui::Color32::GRAY));
+        return;
+    }
+    if markdown && display.is_plain_text() && markdown::has_markup(text) {
+        markdown::show(ui, text);
+        return;
+    }
+    let (text, monospace) = match text.trim().strip_prefix("```") {
+        Some(code) => {
+            // Drop the language tag line and the closing fence
+            let code = code.split_once('\n').map(|(_, rest)| rest).unwrap_or("");
+            (code.trim_end().strip_suffix("```").unwrap_or(code).trim_end(), true)
+        },
+        None => (text, display.monospace),
+    };
+    let text = if display.preserve_indent { keep_indentation(text) } else { text.to_string() };
+    let mut rich = egui::RichText::new(text);
+    if monospace {
+        rich = rich.monospace();
+    }
+    if display.no_wrap {
+        // Long lines scroll sideways instead of stretching the panel
+        egui::ScrollArea::horizontal()
+            .id_salt(ui.next_auto_id())
+            .show(ui, |ui| ui.add(egui::Label::new(rich).extend()));
+    } else {
+        ui.add(egui::Label::new(rich).wrap());
+    }
+}
+
+/// Make each line's leading whitespace unbreakable, with tabs as four spaces,
+/// so wrapping never swallows it
+fn keep_indentation(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let body = line.trim_start_matches([' ', '\t']);
+            let indent = &line[..line.len() - body.len()];
+            let width: usize = indent.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum();
+            format!("{}{}", "\u{a0}".repeat(width), body)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The first few words of a note, for privacy mode
+fn masked_text(text: &str) -> String {
+    const SHOWN_WORDS: usize = 3;
+    let words: Vec<&str> = text.split_whitespace().take(SHOWN_WORDS + 1).collect();
+    let mut masked = words[..words.len().min(SHOWN_WORDS)].join(" ");
+    if words.len() > SHOWN_WORDS {
+        masked.push_str(" ••••••");
+    }
+    masked
+}
+
+/// Whether a scroll area has been scrolled to (near) its end
+fn scrolled_to_bottom<R>(output: &egui::scroll_area::ScrollAreaOutput<R>) -> bool {
+    output.state.offset.y + output.inner_rect.height() >= output.content_size.y - 40.0
+}
+
+/// Prev / Page [n] / m / Next controls, where the page number can be typed
+/// in. With `keys`, PageUp, PageDown, Home and End turn the pages too
+fn pager(ui: &mut egui::Ui, page: &mut usize, pages: usize, keys: bool) {
+    let last = pages.saturating_sub(1);
+    if keys && !ui.ctx().wants_keyboard_input() {
+        ui.input_mut(|i| {
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::PageUp) {
+                *page = page.saturating_sub(1);
+            }
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::PageDown) {
+                *page = (*page + 1).min(last);
+            }
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Home) {
+                *page = 0;
+            }
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::End) {
+                *page = last;
             }
         });
     }
+    ui.horizontal(|ui| {
+        if ui.button(tr("prev")).clicked() && *page > 0 {
+            *page -= 1;
+        }
+        ui.label(tr("page"));
+        let mut number = *page + 1;
+        if ui.add(egui::DragValue::new(&mut number).range(1..=pages.max(1)))
+            .on_hover_text(tr("type-a-page-number-to-jump-to"))
+            .changed()
+        {
+            *page = number - 1;
+        }
+        ui.label(format!("/ {}", pages.max(1)));
+        if ui.button(tr("next")).clicked() && *page < last {
+            *page += 1;
+        }
+    });
 }
 
-fn main() -> Result<(), eframe::Error> {
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1200.0, 800.0])
-            .with_min_inner_size([800.0, 600.0])
-            .with_icon(load_icon()),
-        ..Default::default()
+/// A number that opens more detail when clicked, styled like a plain label
+fn count_link(ui: &mut egui::Ui, text: egui::RichText) -> egui::Response {
+    ui.add(egui::Button::new(text).frame(false))
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .on_hover_text(tr("click-for-a-ranked-list"))
+}
+
+/// Circular progress indicator with a value in the middle and a caption below
+fn progress_ring(ui: &mut egui::Ui, fraction: f32, value: &str, caption: &str, color: egui::Color32) {
+    let fraction = fraction.clamp(0.0, 1.0);
+    ui.vertical(|ui| {
+        ui.set_width(90.0);
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(90.0, 70.0), egui::Sense::hover());
+        response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::ProgressIndicator, true, format!("{}: {}", caption, value)));
+        let painter = ui.painter_at(rect);
+        let center = rect.center();
+        let radius = 28.0;
+        
+        painter.circle_stroke(center, radius, egui::Stroke::new(6.0, ui.visuals().faint_bg_color));
+        if fraction > 0.0 {
+            let steps = (64.0 * fraction).ceil() as usize;
+            let points: Vec<egui::Pos2> = (0..=steps)
+                .map(|i| {
+                    let angle = -std::f32::consts::FRAC_PI_2 + std::f32::consts::TAU * fraction * i as f32 / steps as f32;
+                    center + radius * egui::vec2(angle.cos(), angle.sin())
+                })
+                .collect();
+            let stroke_color = if fraction >= 1.0 { egui::Color32::from_rgb(80, 180, 100) } else { color };
+            painter.add(egui::Shape::line(points, egui::Stroke::new(6.0, stroke_color)));
+        }
+        painter.text(center, egui::Align2::CENTER_CENTER, value, egui::FontId::proportional(13.0), ui.visuals().text_color());
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new(caption).size(11.0).color(egui::Color32::GRAY));
+        });
+    });
+}
+
+/// Small line chart of daily counts, oldest first, with the peak on hover
+fn sparkline(ui: &mut egui::Ui, counts: &[usize], size: egui::Vec2) {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let peak = counts.iter().copied().max().unwrap_or(0);
+    response.on_hover_text(tr_args("sparkline-peak", &[("count", peak.into())]));
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().faint_bg_color);
+    if counts.len() < 2 {
+        return;
+    }
+    let step = rect.width() / (counts.len() - 1) as f32;
+    let points: Vec<egui::Pos2> = counts.iter().enumerate()
+        .map(|(i, &count)| {
+            let height = if peak == 0 { 0.0 } else { count as f32 / peak as f32 };
+            egui::pos2(rect.left() + step * i as f32, rect.bottom() - 2.0 - height * (rect.height() - 4.0))
+        })
+        .collect();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, ui.visuals().selection.bg_fill)));
+}
+
+/// A file in File > Open Recent: a star to pin or unpin it and its name, with
+/// the full path on hover; returns whether the name and the star were clicked
+fn recent_file_row(ui: &mut egui::Ui, file: &str, pinned: bool) -> (bool, bool) {
+    ui.horizontal(|ui| {
+        let (star, hint) = if pinned { ("★", tr("unpin-file")) } else { ("☆", tr("pin-file")) };
+        let toggle = accessible(ui.small_button(star), hint.clone()).on_hover_text(hint).clicked();
+        let open = ui.add_enabled(Path::new(file).exists(), egui::Button::new(file_name(file)).frame(false))
+            .on_hover_text(file)
+            .on_disabled_hover_text(tr_args("recent-file-missing", &[("path", file.into())]))
+            .clicked();
+        (open, toggle)
+    }).inner
+}
+
+fn review_badge(ui: &mut egui::Ui) {
+    let color = ui.visuals().warn_fg_color;
+    ui.label(egui::RichText::new(tr("needs-review")).size(11.0).color(color));
+}
+
+/// Filter chip bar: one toggle per tag in use with its note count, plus any
+/// selected tag no note has anymore; returns true when the selection changed
+fn tag_chips(ui: &mut egui::Ui, counts: &BTreeMap<String, usize>, selected: &mut BTreeSet<String>) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        ui.label(tr("tags-label"));
+        let gone: Vec<String> = selected.iter().filter(|tag| !counts.contains_key(*tag)).cloned().collect();
+        let chips = counts.iter().map(|(tag, count)| (tag.clone(), *count)).chain(gone.into_iter().map(|tag| (tag, 0)));
+        for (tag, count) in chips {
+            let on = selected.contains(&tag);
+            if ui.selectable_label(on, format!("#{} ({})", tag, count)).clicked() {
+                if on {
+                    selected.remove(&tag);
+                } else {
+                    selected.insert(tag);
+                }
+                changed = true;
+            }
+        }
+        if !selected.is_empty() && ui.small_button(tr("clear-tags")).clicked() {
+            selected.clear();
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Who wrote a note and when it was created and last edited, by day, with
+/// the full times on hover
+fn note_byline(ui: &mut egui::Ui, note: &Note) {
+    let day = |timestamp: &str| timestamp.get(..10).unwrap_or(timestamp).to_string();
+    let mut parts = Vec::new();
+    let mut full = Vec::new();
+    if !note.author.is_empty() {
+        parts.push(tr_args("file-info-by", &[("author", note.author.clone().into())]));
+        full.push(tr_args("file-info-by", &[("author", note.author.clone().into())]));
+    }
+    if let Some(created) = &note.created {
+        parts.push(tr_args("created-on", &[("date", day(created).into())]));
+        full.push(tr_args("created-on", &[("date", created.clone().into())]));
+    }
+    if let Some(modified) = &note.modified {
+        parts.push(tr_args("edited-on", &[("date", day(modified).into())]));
+        full.push(tr_args("edited-on", &[("date", modified.clone().into())]));
+    }
+    if !parts.is_empty() {
+        ui.label(egui::RichText::new(parts.join(" · ")).size(11.0).color(egui::Color32::GRAY))
+            .on_hover_text(full.join("\n"));
+    }
+}
+
+/// Sort order picker; returns true when it changed
+fn note_order_combo(ui: &mut egui::Ui, id: &str, order: &mut NoteOrder) -> bool {
+    let mut changed = false;
+    let label = ui.label(tr("order-label"));
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(order.label())
+        .show_ui(ui, |ui| {
+            for option in NoteOrder::ALL {
+                changed |= ui.selectable_value(order, option, option.label()).changed();
+            }
+        })
+        .response
+        .labelled_by(label.id);
+    changed
+}
+
+/// A note's tags as small links; returns the one clicked
+fn note_tag_links(ui: &mut egui::Ui, tags: &BTreeSet<String>) -> Option<String> {
+    if tags.is_empty() {
+        return None;
+    }
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        for tag in tags {
+            let text = egui::RichText::new(format!("#{}", tag)).size(11.0).color(ui.visuals().hyperlink_color);
+            if ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+                .on_hover_text(tr("filter-by-tag"))
+                .clicked()
+            {
+                clicked = Some(tag.clone());
+            }
+        }
+    });
+    clicked
+}
+
+/// One toggle per flag, lit when the note has it; returns the new flags
+/// when one was clicked
+fn flags_widget(ui: &mut egui::Ui, flags: &BTreeSet<NoteFlag>) -> Option<BTreeSet<NoteFlag>> {
+    let mut new_flags = None;
+    let spacing = ui.spacing().item_spacing.x;
+    ui.spacing_mut().item_spacing.x = 2.0;
+    for flag in NoteFlag::ALL {
+        let set = flags.contains(&flag);
+        let color = if set { flag.color(ui.visuals()) } else { ui.visuals().weak_text_color() };
+        let button = egui::Button::new(egui::RichText::new(flag.icon()).color(color)).frame(false);
+        let name = flag.label();
+        let response = accessible(ui.add(button), name.clone()).on_hover_text(name);
+        if response.clicked() {
+            let mut flags = flags.clone();
+            if set {
+                flags.remove(&flag);
+            } else {
+                flags.insert(flag);
+            }
+            new_flags = Some(flags);
+        }
+    }
+    ui.spacing_mut().item_spacing.x = spacing;
+    new_flags
+}
+
+/// Warning badge for notes that look like they contain credentials
+fn secret_badge(ui: &mut egui::Ui, text: &str) {
+    let kinds = scan_secrets(text);
+    if kinds.is_empty() {
+        return;
+    }
+    let color = ui.visuals().error_fg_color;
+    ui.label(egui::RichText::new(tr("possible-secret")).size(11.0).color(color))
+        .on_hover_text(kinds.join("\n"));
+}
+
+/// Button switching a Markdown note between its rendered and raw text;
+/// true when clicked
+fn raw_toggle(ui: &mut egui::Ui, raw: bool) -> bool {
+    let (label, hint) = if raw { ("show-rendered", "show-rendered-hint") } else { ("show-raw", "show-raw-hint") };
+    ui.small_button(tr(label)).on_hover_text(tr(hint)).clicked()
+}
+
+/// Kinds of credentials `text` appears to contain: well-known token
+/// prefixes, private key blocks, `password = ...` style assignments and
+/// long random-looking strings
+fn scan_secrets(text: &str) -> Vec<&'static str> {
+    let mut found = Vec::new();
+    let mut add = |kind: &'static str| {
+        if !found.contains(&kind) {
+            found.push(kind);
+        }
     };
     
-    eframe::run_native(
-        "Fisha GUI",
-        options,
-        Box::new(|cc| Ok(Box::new(MicroficheApp::new(cc)))),
-    )
+    if text.contains("PRIVATE KEY-----") {
+        add("Private key");
+    }
+    for token in text.split(|c: char| !(c.is_ascii_alphanumeric() || "_-.".contains(c))) {
+        if let Some(kind) = secret_token_kind(token.trim_matches(|c| c == '.' || c == '-')) {
+            add(kind);
+        }
+    }
+    
+    // Same byte offsets as `text`, unlike to_lowercase()
+    let lower = text.to_ascii_lowercase();
+    for key in SECRET_KEYS {
+        for (start, _) in lower.match_indices(key) {
+            let preceded_by_word = lower[..start].chars().next_back().is_some_and(|c| c.is_alphanumeric());
+            let rest = lower[start + key.len()..].trim_start_matches([' ', '"', '\'']);
+            let Some(value) = rest.strip_prefix([':', '=']) else { continue };
+            let value: String = value.trim_start_matches([' ', '"', '\''])
+                .chars()
+                .take_while(|c| !c.is_whitespace() && *c != '"' && *c != '\'')
+                .collect();
+            if !preceded_by_word && value.chars().count() >= 6 {
+                add("Password or secret assignment");
+            }
+        }
+    }
+    found
 }
 
-fn load_icon() -> egui::IconData {
-    const ICON_DATA: &str = include_str!("../assets/icon_rgba.txt");
-    
-    let mut lines = ICON_DATA.lines();
+/// Names that introduce a credential in `name = value` or `name: value` text
+const SECRET_KEYS: [&str; 10] = [
+    "password", "passwd", "pwd", "secret", "api_key", "apikey", "api-key", "access_token", "auth_token", "token",
+];
+
+fn secret_token_kind(token: &str) -> Option<&'static str> {
+    let len = token.len();
+    let upper_alnum = |s: &str| s.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
     
-    // Parse dimensions
-    let dims_line = lines.next().expect("Missing dimensions line");
-    let mut dims = dims_line.split(',');
-    let width: u32 = dims.next().unwrap().trim().parse().unwrap();
-    let height: u32 = dims.next().unwrap().trim().parse().unwrap();
+    if (token.starts_with("AKIA") || token.starts_with("ASIA")) && len == 20 && upper_alnum(token) {
+        Some("AWS access key")
+    } else if ["ghp_", "gho_", "ghu_", "ghs_", "ghr_"].iter().any(|p| token.starts_with(p)) && len >= 36
+        || token.starts_with("github_pat_") && len >= 40
+    {
+        Some("GitHub token")
+    } else if ["xoxb-", "xoxp-", "xoxa-", "xoxr-", "xoxs-"].iter().any(|p| token.starts_with(p)) && len >= 20 {
+        Some("Slack token")
+    } else if (token.starts_with("sk_live_") || token.starts_with("rk_live_")) && len >= 20 {
+        Some("Stripe key")
+    } else if token.starts_with("sk-") && len >= 23 {
+        Some("API secret key")
+    } else if token.starts_with("AIza") && len == 39 {
+        Some("Google API key")
+    } else if token.starts_with("eyJ") && token.matches('.').count() == 2 && len >= 30 {
+        Some("JSON Web Token")
+    } else if len >= 32 && !token.contains('.') && looks_random(token) {
+        Some("High-entropy string")
+    } else {
+        None
+    }
+}
+
+/// Mixed-case letters and digits with high Shannon entropy; plain hex
+/// (commit hashes, checksums) doesn't qualify
+fn looks_random(token: &str) -> bool {
+    if !(token.chars().any(|c| c.is_ascii_uppercase())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && token.chars().any(|c| c.is_ascii_digit()))
+    {
+        return false;
+    }
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in token.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = token.chars().count() as f64;
+    let entropy: f64 = counts.values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    entropy >= 4.0
+}
+
+/// Boolean CSV cells: anything like "yes"/"true"/"1" counts as set
+fn absolute_path(file: &str) -> String {
+    std::path::absolute(file).map_or_else(|_| file.to_string(), |path| path.to_string_lossy().to_string())
+}
+
+/// Just the name of a file, for labels
+fn file_name(file: &str) -> String {
+    Path::new(file).file_name().map_or_else(|| file.to_string(), |name| name.to_string_lossy().to_string())
+}
+
+/// A file name for `category`'s own file in an export, keeping letters,
+/// digits, `-` and `_`, or `fallback` when none are left
+fn category_file_name(category: &str, fallback: &str, extension: &str) -> String {
+    let name = category.split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("{}.{}", if name.is_empty() { fallback } else { &name }, extension)
+}
+
+/// The current local time as stored in `Created` and `Modified`
+fn timestamp_now() -> String {
+    chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+}
+
+fn parse_flag(value: &str) -> bool {
+    matches!(value.trim().to_lowercase().as_str(), "yes" | "true" | "1" | "x")
+}
+
+/// Line that separates parts in the Split tool
+const SPLIT_MARKER: &str = "---";
+
+/// Break text on marker lines, dropping empty parts
+fn split_parts(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if line.trim() == SPLIT_MARKER {
+            parts.push(std::mem::take(&mut current));
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    parts.push(current);
     
-    // Parse RGBA data
-    let rgba_line = lines.next().expect("Missing RGBA data line");
-    let rgba: Vec<u8> = rgba_line
-        .split(',')
-        .map(|s| s.trim().parse().expect("Invalid RGBA value"))
+    parts.into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Turn paragraph breaks into split markers
+fn insert_split_markers(text: &str) -> String {
+    let paragraphs: Vec<&str> = text.split("\n\n")
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
         .collect();
+    paragraphs.join(&format!("\n{}\n", SPLIT_MARKER))
+}
+
+/// Name an icon-only or painted widget for screen readers
+fn accessible(response: egui::Response, name: String) -> egui::Response {
+    let enabled = response.enabled();
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, enabled, &name));
+    response
+}
+
+/// What was clicked in a note's comment thread
+enum CommentAction {
+    Reply,
+    Post,
+    Cancel,
+    Remove(usize),
+}
+
+/// A note's comments as a collapsible thread, with the reply being written
+/// when `reply` is given; shows nothing for a note without comments and no
+/// reply under way
+fn comment_thread(ui: &mut egui::Ui, id: impl std::hash::Hash, comments: &[NoteComment], reply: Option<&mut String>) -> Option<CommentAction> {
+    if comments.is_empty() && reply.is_none() {
+        return None;
+    }
+    let mut action = None;
+    let replying = reply.is_some();
+    egui::CollapsingHeader::new(tr_args("comment-thread", &[("count", comments.len().to_string().into())]))
+        .id_salt(("comments", id))
+        .open(replying.then_some(true))
+        .show(ui, |ui| {
+            for (index, comment) in comments.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.strong(&comment.author);
+                    let day = comment.posted.get(..10).unwrap_or(&comment.posted);
+                    ui.label(egui::RichText::new(day).size(11.0).color(egui::Color32::GRAY))
+                        .on_hover_text(&comment.posted);
+                    if accessible(ui.small_button("🗑"), tr("delete-comment")).clicked() {
+                        action = Some(CommentAction::Remove(index));
+                    }
+                });
+                ui.label(&comment.text);
+            }
+            match reply {
+                Some(reply) => {
+                    ui.add(egui::TextEdit::multiline(reply).hint_text(tr("comment-hint")).desired_rows(2));
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(!reply.trim().is_empty(), egui::Button::new(tr("post-comment"))).clicked() {
+                            action = Some(CommentAction::Post);
+                        }
+                        if ui.button(tr("cancel")).clicked() {
+                            action = Some(CommentAction::Cancel);
+                        }
+                    });
+                },
+                None => {
+                    if ui.small_button(tr("reply")).clicked() {
+                        action = Some(CommentAction::Reply);
+                    }
+                },
+            }
+        });
+    action
+}
+
+fn note_tools_menu(ui: &mut egui::Ui, note: &Note) -> Option<NoteTool> {
+    let mut picked = None;
+    let menu = ui.menu_button("⋯", |ui| {
+        for tool in NoteTool::ALL {
+            if ui.button(tool.label(note)).clicked() {
+                picked = Some(tool);
+                ui.close();
+            }
+        }
+        ui.menu_button(tr("highlight"), |ui| {
+            for highlight in Highlight::ALL {
+                let swatch = egui::RichText::new("⏺").color(highlight.color());
+                let current = note.display.highlight == Some(highlight);
+                if ui.selectable_label(current, (swatch, highlight.label())).clicked() {
+                    picked = Some(NoteTool::Highlight(Some(highlight)));
+                    ui.close();
+                }
+            }
+            ui.separator();
+            if ui.selectable_label(note.display.highlight.is_none(), tr("highlight-none")).clicked() {
+                picked = Some(NoteTool::Highlight(None));
+                ui.close();
+            }
+        });
+    });
+    accessible(menu.response, tr("note-tools"));
+    picked
+}
+
+/// Draw a QR code as dark modules on white, with the four-module quiet zone
+/// scanners need, scaled to whole pixels per module within `max_size`
+fn qr_code_widget(ui: &mut egui::Ui, code: &qrcodegen::QrCode, max_size: f32) {
+    const QUIET_ZONE: i32 = 4;
+    let modules = code.size() + QUIET_ZONE * 2;
+    let module_size = (max_size / modules as f32).floor().max(1.0);
+    let side = module_size * modules as f32;
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::hover());
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Other, true, tr("qr-code")));
+    
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::WHITE);
+    for y in 0..code.size() {
+        for x in 0..code.size() {
+            if code.get_module(x, y) {
+                let min = rect.min + egui::vec2((x + QUIET_ZONE) as f32, (y + QUIET_ZONE) as f32) * module_size;
+                painter.rect_filled(egui::Rect::from_min_size(min, egui::vec2(module_size, module_size)), 0.0, egui::Color32::BLACK);
+            }
+        }
+    }
+}
+
+/// Draw five clickable stars; returns the new rating when one is clicked.
+/// Clicking the current rating again clears it.
+fn rating_widget(ui: &mut egui::Ui, rating: Option<u8>) -> Option<Option<u8>> {
+    let current = rating.unwrap_or(0);
+    let star_color = ui.visuals().warn_fg_color;
+    let mut new_rating = None;
     
-    // Verify size
-    assert_eq!(rgba.len(), (width * height * 4) as usize, "RGBA data size mismatch");
+    let spacing = ui.spacing().item_spacing.x;
+    ui.spacing_mut().item_spacing.x = 0.0;
+    for star in 1..=5u8 {
+        let (symbol, color) = if star <= current {
+            ("★", star_color)
+        } else {
+            ("☆", egui::Color32::GRAY)
+        };
+        let button = egui::Button::new(egui::RichText::new(symbol).color(color)).frame(false);
+        let name = tr_args("rate-of-5", &[("star", star.to_string().into())]);
+        let response = accessible(ui.add(button), name.clone()).on_hover_text(name);
+        if response.clicked() {
+            new_rating = Some(if star == current { None } else { Some(star) });
+        }
+    }
+    ui.spacing_mut().item_spacing.x = spacing;
     
+    new_rating
+}
+
+fn rating_filter_label(min_rating: u8) -> String {
+    match min_rating {
+        0 => "Any".to_string(),
+        5 => "★★★★★".to_string(),
+        n => format!("{}+", "★".repeat(n as usize)),
+    }
+}
+
+fn parse_rating(value: &str) -> Option<u8> {
+    value.trim().parse::<i64>().ok().and_then(valid_rating)
+}
+
+/// A stored rating if it is 1-5 stars; anything else reads as unrated
+fn valid_rating(value: i64) -> Option<u8> {
+    u8::try_from(value).ok().filter(|r| (1..=5).contains(r))
+}
+
+/// Read a stored note's rating, dropping one outside 1-5
+fn deserialize_rating<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+    Ok(Option::<i64>::deserialize(deserializer)?.and_then(valid_rating))
+}
+
+/// Format an aggregate without trailing zeros (e.g. 3 instead of 3.00)
+fn format_number(value: f64) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// The built-in window icon, or a plain drawing of it should the embedded
+/// PNG ever fail to decode
+fn load_icon() -> egui::IconData {
+    eframe::icon_data::from_png_bytes(include_bytes!("../assets/icon.png")).unwrap_or_else(|_| fallback_icon())
+}
+
+/// A white "F" on black, as in assets/icon.png
+fn fallback_icon() -> egui::IconData {
+    const SIZE: u32 = 64;
+    let letter = |x: u32, y: u32| (22..30).contains(&x) && (17..47).contains(&y)
+        || (22..45).contains(&x) && (17..25).contains(&y)
+        || (22..41).contains(&x) && (29..36).contains(&y);
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let shade = if letter(x, y) { 255 } else { 10 };
+            rgba.extend_from_slice(&[shade, shade, shade, 255]);
+        }
+    }
     egui::IconData {
         rgba,
-        width,
-        height,
+        width: SIZE,
+        height: SIZE,
     }
+}
+
+/// The window title Settings gives, or the default one
+fn app_title(settings: &Settings) -> &str {
+    let title = settings.window_title.trim();
+    if title.is_empty() { APP_TITLE } else { title }
+}
+
+/// Set the window icon from Settings (the title follows each frame, see
+/// `update_title`). An icon that can't be read leaves the built-in one in
+/// place and is reported
+fn apply_branding(ctx: &egui::Context, settings: &Settings) -> Result<(), String> {
+    let custom = settings.custom_icon.as_ref().map(|path| {
+        std::fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| eframe::icon_data::from_png_bytes(&bytes).map_err(|e| e.to_string()))
+            .map_err(|e| tr_args("icon-not-loaded", &[("path", path.clone().into()), ("error", e.into())]))
+    });
+    let (icon, result) = match custom {
+        Some(Ok(icon)) => (icon, Ok(())),
+        Some(Err(e)) => (load_icon(), Err(e)),
+        None => (load_icon(), Ok(())),
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::Icon(Some(std::sync::Arc::new(icon))));
+    result
 }
\ No newline at end of file
@@ -0,0 +1,234 @@
+//! Note text drawn as Markdown: headings, emphasis, lists, links, inline
+//! code, fenced code blocks, quotes and rules, made of plain egui labels.
+//! Anything else (HTML, images) shows as its text.
+
+use eframe::egui;
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+const LIST_INDENT: f32 = 18.0;
+const QUOTE_INDENT: f32 = 12.0;
+
+fn options() -> Options {
+    Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS
+}
+
+/// Whether `text` uses any Markdown beyond plain paragraphs, so showing it
+/// raw would look different
+pub fn has_markup(text: &str) -> bool {
+    Parser::new_ext(text, options()).any(|event| match event {
+        Event::Start(tag) => !matches!(tag, Tag::Paragraph),
+        Event::Code(_) | Event::Rule | Event::TaskListMarker(_) | Event::Html(_) | Event::InlineHtml(_) => true,
+        _ => false,
+    })
+}
+
+/// Draw `text` as Markdown, one block under another
+pub fn show(ui: &mut egui::Ui, text: &str) {
+    ui.vertical(|ui| {
+        let mut renderer = Renderer::default();
+        for event in Parser::new_ext(text, options()) {
+            renderer.event(ui, event);
+        }
+        renderer.flush(ui);
+    });
+}
+
+/// A run of text sharing one style
+struct Span {
+    text: String,
+    code: bool,
+    strong: bool,
+    emphasis: bool,
+    strikethrough: bool,
+    heading: Option<HeadingLevel>,
+    link: Option<String>,
+}
+
+impl Span {
+    fn show(self, ui: &mut egui::Ui) {
+        let mut rich = egui::RichText::new(self.text);
+        if let Some(level) = self.heading {
+            let size = match level {
+                HeadingLevel::H1 => 22.0,
+                HeadingLevel::H2 => 19.0,
+                HeadingLevel::H3 => 16.0,
+                _ => 14.0,
+            };
+            rich = rich.size(size).strong();
+        }
+        if self.code {
+            rich = rich.code();
+        }
+        if self.strong {
+            rich = rich.strong();
+        }
+        if self.emphasis {
+            rich = rich.italics();
+        }
+        if self.strikethrough {
+            rich = rich.strikethrough();
+        }
+        match self.link {
+            Some(url) => {
+                ui.hyperlink_to(rich, &url).on_hover_text(url);
+            },
+            None => {
+                ui.add(egui::Label::new(rich).wrap());
+            },
+        }
+    }
+}
+
+/// Walks the parser's events, collecting a block's spans and drawing them
+/// when the block ends
+#[derive(Default)]
+struct Renderer {
+    spans: Vec<Span>,
+    strong: usize,
+    emphasis: usize,
+    strikethrough: usize,
+    heading: Option<HeadingLevel>,
+    link: Option<String>,
+    // Next number of each open list, None for bulleted ones
+    lists: Vec<Option<u64>>,
+    // Bullet or number for the list item whose first line isn't drawn yet
+    marker: Option<String>,
+    quote: usize,
+    // Text of the fenced or indented code block being read
+    code: Option<String>,
+}
+
+impl Renderer {
+    fn event(&mut self, ui: &mut egui::Ui, event: Event) {
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Heading { level, .. } => {
+                    self.flush(ui);
+                    self.heading = Some(level);
+                },
+                Tag::BlockQuote(_) => {
+                    self.flush(ui);
+                    self.quote += 1;
+                },
+                Tag::CodeBlock(_) => {
+                    self.flush(ui);
+                    self.code = Some(String::new());
+                },
+                Tag::List(start) => {
+                    self.flush(ui);
+                    self.lists.push(start);
+                },
+                Tag::Item => {
+                    self.flush(ui);
+                    self.marker = Some(match self.lists.last_mut() {
+                        Some(Some(number)) => {
+                            *number += 1;
+                            format!("{}.", *number - 1)
+                        },
+                        _ => "•".to_string(),
+                    });
+                },
+                Tag::Emphasis => self.emphasis += 1,
+                Tag::Strong => self.strong += 1,
+                Tag::Strikethrough => self.strikethrough += 1,
+                Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => self.link = Some(dest_url.to_string()),
+                _ => {},
+            },
+            Event::End(tag) => match tag {
+                TagEnd::Paragraph | TagEnd::Item => self.flush(ui),
+                TagEnd::Heading(_) => {
+                    self.flush(ui);
+                    self.heading = None;
+                },
+                TagEnd::BlockQuote(_) => {
+                    self.flush(ui);
+                    self.quote = self.quote.saturating_sub(1);
+                },
+                TagEnd::CodeBlock => {
+                    let code = self.code.take().unwrap_or_default();
+                    self.indented(ui, |ui| {
+                        egui::Frame::new()
+                            .fill(ui.visuals().code_bg_color)
+                            .inner_margin(4.0)
+                            .corner_radius(3.0)
+                            .show(ui, |ui| {
+                                ui.add(egui::Label::new(egui::RichText::new(code.trim_end_matches('\n')).monospace()).wrap());
+                            });
+                    });
+                },
+                TagEnd::List(_) => {
+                    self.flush(ui);
+                    self.lists.pop();
+                },
+                TagEnd::Emphasis => self.emphasis = self.emphasis.saturating_sub(1),
+                TagEnd::Strong => self.strong = self.strong.saturating_sub(1),
+                TagEnd::Strikethrough => self.strikethrough = self.strikethrough.saturating_sub(1),
+                TagEnd::Link | TagEnd::Image => self.link = None,
+                _ => {},
+            },
+            Event::Text(text) => match &mut self.code {
+                Some(code) => code.push_str(&text),
+                None => self.push(text.to_string(), false),
+            },
+            Event::Code(code) => self.push(code.to_string(), true),
+            Event::Html(html) | Event::InlineHtml(html) => self.push(html.to_string(), false),
+            // Notes are written line by line, so a single newline still breaks
+            Event::SoftBreak | Event::HardBreak => self.push("\n".to_string(), false),
+            Event::TaskListMarker(done) => self.push(if done { "☑ " } else { "☐ " }.to_string(), false),
+            Event::Rule => {
+                self.flush(ui);
+                ui.separator();
+            },
+            _ => {},
+        }
+    }
+    
+    fn push(&mut self, text: String, code: bool) {
+        self.spans.push(Span {
+            text,
+            code,
+            strong: self.strong > 0,
+            emphasis: self.emphasis > 0,
+            strikethrough: self.strikethrough > 0,
+            heading: self.heading,
+            link: self.link.clone(),
+        });
+    }
+    
+    /// Draw the spans collected so far as one wrapped line, behind the list
+    /// marker and quote bars they belong under
+    fn flush(&mut self, ui: &mut egui::Ui) {
+        let marker = self.marker.take();
+        if self.spans.is_empty() && marker.is_none() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.spans);
+        self.indented(ui, |ui| {
+            if let Some(marker) = marker {
+                ui.add_sized([LIST_INDENT, ui.text_style_height(&egui::TextStyle::Body)], egui::Label::new(marker));
+            } else if !self.lists.is_empty() {
+                // A later paragraph of the item lines up with its first
+                ui.add_space(LIST_INDENT);
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing.x = 0.0;
+                for span in spans {
+                    span.show(ui);
+                }
+            });
+        });
+    }
+    
+    /// Lay out `add_contents` to the right of the quote bars and list indent
+    fn indented(&self, ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui)) {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            for _ in 0..self.quote {
+                ui.label(egui::RichText::new("▏").color(ui.visuals().weak_text_color()));
+                ui.add_space(QUOTE_INDENT - 4.0);
+            }
+            ui.add_space(self.lists.len().saturating_sub(1) as f32 * LIST_INDENT);
+            add_contents(ui);
+        });
+    }
+}
@@ -0,0 +1,97 @@
+//! Markdown files of a fiche, for publishing it or reading it in Obsidian:
+//! `# Category`, `## Subcategory` and `### Concept` headings, with one-line
+//! notes as a bulleted list and longer ones as paragraphs of body text.
+
+use crate::{Concept, Microfiche, Note, collate};
+use std::fmt::Write;
+
+/// What goes into a file
+pub struct Options<'a> {
+    // Only this category; None for the whole fiche
+    pub category: Option<&'a str>,
+    // Only this subcategory of the category; None for all of them
+    pub subcategory: Option<&'a str>,
+}
+
+/// The Markdown, and how many notes it holds
+pub fn document(fiche: &Microfiche, options: &Options) -> (String, usize) {
+    let mut categories: Vec<&String> = fiche.categories.keys()
+        .filter(|cat| options.category.is_none_or(|only| only == cat.as_str()))
+        .collect();
+    categories.sort_by(|a, b| collate(a, b));
+
+    let mut out = String::new();
+    let mut count = 0;
+    for cat in categories {
+        let category = &fiche.categories[cat];
+        paragraph(&mut out, &format!("# {}", cat));
+        paragraph(&mut out, fiche.meta.description(cat, None));
+        for subcat in &category.subcategories {
+            if options.subcategory.is_some_and(|only| only != subcat.name) {
+                continue;
+            }
+            paragraph(&mut out, &format!("## {}", subcat.name));
+            paragraph(&mut out, fiche.meta.description(cat, Some(&subcat.name)));
+            for concept in &subcat.concepts {
+                paragraph(&mut out, &format!("### {}", concept.name));
+                write_notes(&mut out, concept);
+                count += concept.notes.len();
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    (out, count)
+}
+
+/// A single concept as a document of its own, for publishing: its name as
+/// the title and where it sits in the fiche, then its notes
+pub fn concept(category: &str, subcategory: &str, concept: &Concept) -> String {
+    let mut out = String::new();
+    paragraph(&mut out, &format!("# {}", concept.name));
+    paragraph(&mut out, &format!("*{} > {}*", category, subcategory));
+    write_notes(&mut out, concept);
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// A concept's notes, the definition leading as in Browse
+fn write_notes(out: &mut String, concept: &Concept) {
+    let notes = concept.notes.iter().filter(|n| n.definition)
+        .chain(concept.notes.iter().filter(|n| !n.definition));
+    for note in notes {
+        write_note(out, note);
+    }
+}
+
+/// A one-line note as a list item, anything longer as its own paragraphs;
+/// tags follow as `#tag`, which Obsidian picks up
+fn write_note(out: &mut String, note: &Note) {
+    let text = note.text.trim();
+    let tags: Vec<String> = note.tags.iter().map(|tag| format!("#{}", tag.replace(' ', "-"))).collect();
+    if text.lines().count() <= 1 {
+        // Writing to a String can't fail
+        let _ = write!(out, "- {}", text);
+        for tag in &tags {
+            let _ = write!(out, " {}", tag);
+        }
+        out.push('\n');
+    } else {
+        paragraph(out, text);
+        paragraph(out, &tags.join(" "));
+    }
+}
+
+/// Append `text` set off by blank lines; nothing when it's empty
+fn paragraph(out: &mut String, text: &str) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+    out.push_str(text);
+    out.push_str("\n\n");
+}
@@ -0,0 +1,239 @@
+//! Fiche packs: chosen categories or subcategories of a fiche, with their
+//! colors, descriptions, templates, cross-listings and attachments, in one
+//! file to hand to someone else ("Onboarding notes"). A pack is a `.fiche`
+//! container under its own extension; study progress and goals stay behind.
+
+use crate::i18n::tr;
+use crate::{ConceptRef, FicheRow, FileInfo, Microfiche, collate};
+use std::collections::{BTreeSet, HashMap};
+
+pub const EXTENSION: &str = "fichepack";
+
+/// The subcategories going into a pack, as (category, subcategory)
+pub type Selection = BTreeSet<(String, String)>;
+
+/// What to do with a pack concept that already exists in the fiche
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Conflict {
+    // Add the notes the concept doesn't have yet
+    Merge,
+    // Import it next to the existing one as "Concept (2)"
+    KeepBoth,
+    // Leave the existing concept as it is
+    Skip,
+    // Swap the existing notes for the pack's
+    Replace,
+}
+
+impl Conflict {
+    pub const ALL: [Conflict; 4] = [Conflict::Merge, Conflict::KeepBoth, Conflict::Skip, Conflict::Replace];
+    
+    pub fn label(&self) -> String {
+        match self {
+            Conflict::Merge => tr("pack-conflict-merge"),
+            Conflict::KeepBoth => tr("pack-conflict-keep-both"),
+            Conflict::Skip => tr("pack-conflict-skip"),
+            Conflict::Replace => tr("pack-conflict-replace"),
+        }
+    }
+}
+
+/// What importing a pack did
+#[derive(Default)]
+pub struct Outcome {
+    pub notes: usize,
+    pub merged: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+    pub replaced: usize,
+    pub attachments: usize,
+    // Attachments stored under a new name because theirs was taken, as (old, new)
+    pub renamed_attachments: Vec<(String, String)>,
+}
+
+/// A copy of `fiche` holding only `selection`, the metadata that applies to
+/// it and, when `attachments` is set, every attachment
+pub fn extract(fiche: &Microfiche, selection: &Selection, info: FileInfo, attachments: bool) -> Microfiche {
+    let mut pack = Microfiche::new();
+    for (cat, category) in &fiche.categories {
+        let mut category = category.clone();
+        category.subcategories.retain(|s| selection.contains(&(cat.clone(), s.name.clone())));
+        if !category.subcategories.is_empty() {
+            pack.categories.insert(cat.clone(), category);
+        }
+    }
+    pack.meta.categories = fiche.meta.categories.clone();
+    pack.meta.cross_listings = fiche.meta.cross_listings.clone();
+    pack.meta.concept_aliases = fiche.meta.concept_aliases.clone();
+    pack.meta.note_templates = fiche.meta.note_templates.clone();
+    pack.meta.prune(&pack.categories);
+    pack.meta.info = info;
+    if attachments {
+        pack.attachments = fiche.attachments.clone();
+    }
+    pack
+}
+
+/// The name a pack category gets in the fiche, under `namespace` if one is given
+pub fn namespaced(namespace: &str, cat: &str) -> String {
+    match namespace.trim() {
+        "" => cat.to_string(),
+        namespace => format!("{} / {}", namespace, cat),
+    }
+}
+
+/// How many concepts of `pack` already exist in `fiche` under `namespace`
+pub fn conflicts(fiche: &Microfiche, pack: &Microfiche, namespace: &str) -> usize {
+    pack.iter_concepts()
+        .filter(|(c, _)| fiche.concept(&namespaced(namespace, &c.category), &c.subcategory, &c.concept).is_some())
+        .count()
+}
+
+/// Add `pack` to `fiche` with its categories under `namespace`, settling
+/// concepts that exist in both as `conflict` says; metadata and attachments
+/// already in the fiche are kept, and a pack attachment whose name is taken
+/// is stored as "name (2)" without touching the notes that mention it
+pub fn apply(fiche: &mut Microfiche, pack: &Microfiche, namespace: &str, conflict: Conflict, flag_for_review: bool) -> Outcome {
+    let mut outcome = Outcome::default();
+    let mut renamed: HashMap<(String, String, String), String> = HashMap::new();
+    let mut cat_names: Vec<&String> = pack.categories.keys().collect();
+    cat_names.sort_by(|a, b| collate(a, b));
+    
+    for cat_name in cat_names {
+        let cat = namespaced(namespace, cat_name);
+        for subcat in &pack.categories[cat_name].subcategories {
+            for concept in &subcat.concepts {
+                let mut name = concept.name.clone();
+                let mut notes = concept.notes.clone();
+                if let Some(existing) = fiche.concept(&cat, &subcat.name, &concept.name) {
+                    match conflict {
+                        Conflict::Merge => {
+                            let has_definition = existing.notes.iter().any(|n| n.definition);
+                            notes.retain(|note| !existing.notes.iter().any(|n| n.text == note.text));
+                            for note in &mut notes {
+                                note.definition &= !has_definition;
+                            }
+                            outcome.merged += 1;
+                        },
+                        Conflict::KeepBoth => {
+                            name = (2..)
+                                .map(|n| format!("{} ({})", concept.name, n))
+                                .find(|candidate| fiche.concept(&cat, &subcat.name, candidate).is_none())
+                                .unwrap();
+                            renamed.insert((cat_name.clone(), subcat.name.clone(), concept.name.clone()), name.clone());
+                            outcome.renamed += 1;
+                        },
+                        Conflict::Skip => {
+                            outcome.skipped += 1;
+                            continue;
+                        },
+                        Conflict::Replace => {
+                            if let Some(existing) = fiche.categories.get_mut(&cat)
+                                .and_then(|c| c.subcategories.iter_mut().find(|s| s.name == subcat.name))
+                                .and_then(|s| s.concepts.iter_mut().find(|c| c.name == concept.name))
+                            {
+                                existing.notes.clear();
+                            }
+                            fiche.index.clear();
+                            outcome.replaced += 1;
+                        },
+                    }
+                }
+                for note in &notes {
+                    let mut row = FicheRow::from_note(&cat, &subcat.name, &name, note);
+                    row.needs_review |= flag_for_review;
+                    fiche.add_row(row);
+                    outcome.notes += 1;
+                }
+            }
+        }
+    }
+    
+    let place = |concept: &ConceptRef| ConceptRef {
+        category: namespaced(namespace, &concept.category),
+        subcategory: concept.subcategory.clone(),
+        concept: renamed.get(&(concept.category.clone(), concept.subcategory.clone(), concept.concept.clone()))
+            .cloned()
+            .unwrap_or_else(|| concept.concept.clone()),
+    };
+    let meta = &mut fiche.meta;
+    for (cat, style) in &pack.meta.categories {
+        let existing = meta.categories.entry(namespaced(namespace, cat)).or_default();
+        if existing.color.is_none() {
+            existing.color = style.color;
+        }
+        if existing.icon.is_empty() {
+            existing.icon = style.icon.clone();
+        }
+        if existing.description.trim().is_empty() {
+            existing.description = style.description.clone();
+        }
+        for (sub, text) in &style.subcategories {
+            existing.subcategories.entry(sub.clone()).or_insert_with(|| text.clone());
+        }
+    }
+    for link in &pack.meta.cross_listings {
+        let mut link = link.clone();
+        link.category = namespaced(namespace, &link.category);
+        link.source = place(&link.source);
+        if !meta.cross_listings.contains(&link) {
+            meta.cross_listings.push(link);
+        }
+    }
+    for aliases in &pack.meta.concept_aliases {
+        let concept = place(&aliases.concept);
+        match meta.concept_aliases.iter_mut().find(|a| a.concept == concept) {
+            Some(existing) => {
+                for name in &aliases.names {
+                    if !existing.names.contains(name) {
+                        existing.names.push(name.clone());
+                    }
+                }
+            },
+            None => {
+                let mut aliases = aliases.clone();
+                aliases.concept = concept;
+                meta.concept_aliases.push(aliases);
+            },
+        }
+    }
+    for template in &pack.meta.note_templates {
+        let mut template = template.clone();
+        if !template.concept.is_empty() {
+            template.concept = place(&ConceptRef {
+                category: template.category.clone(),
+                subcategory: template.subcategory.clone(),
+                concept: template.concept.clone(),
+            }).concept;
+        }
+        template.category = namespaced(namespace, &template.category);
+        let taken = meta.note_templates.iter().any(|t| {
+            t.category == template.category && t.subcategory == template.subcategory && t.concept == template.concept
+        });
+        if !taken {
+            meta.note_templates.push(template);
+        }
+    }
+    
+    for (name, bytes) in &pack.attachments {
+        match fiche.attachments.get(name) {
+            Some(existing) if existing == bytes => continue,
+            Some(_) => {
+                let path = std::path::Path::new(name);
+                let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+                let free = (2..)
+                    .map(|n| format!("{} ({}){}", stem, n, extension))
+                    .find(|candidate| !fiche.attachments.contains_key(candidate))
+                    .unwrap();
+                fiche.attachments.insert(free.clone(), bytes.clone());
+                outcome.renamed_attachments.push((name.clone(), free));
+            },
+            None => {
+                fiche.attachments.insert(name.clone(), bytes.clone());
+            },
+        }
+        outcome.attachments += 1;
+    }
+    outcome
+}
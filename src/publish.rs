@@ -0,0 +1,144 @@
+//! Publishing a concept's notes as a GitHub Gist or a Pastebin paste, for
+//! sharing a reference in a code review or chat. Gists are secret and pastes
+//! unlisted, so only people given the link find them. The upload is made by
+//! `curl`, as webhooks are, on a thread that's checked on later frames. The
+//! token and the text go to curl as a config on its stdin, so they don't
+//! show in the process list.
+
+use crate::i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread::JoinHandle;
+
+/// Seconds curl waits for the service before giving up
+const TIMEOUT: &str = "30";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Service {
+    #[default]
+    Gist,
+    Pastebin,
+}
+
+impl Service {
+    pub const ALL: [Service; 2] = [Service::Gist, Service::Pastebin];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Service::Gist => "GitHub Gist",
+            Service::Pastebin => "Pastebin",
+        }
+    }
+
+    /// What the token setting holds for this service
+    pub fn token_hint(&self) -> String {
+        match self {
+            Service::Gist => tr("publish-token-hint-gist"),
+            Service::Pastebin => tr("publish-token-hint-pastebin"),
+        }
+    }
+}
+
+/// An upload on its way
+pub struct Upload {
+    handle: Option<JoinHandle<Result<String, String>>>,
+}
+
+impl Upload {
+    /// Start uploading `text` as `file_name`, described by `title`
+    pub fn start(service: Service, token: &str, title: &str, file_name: &str, text: &str) -> Upload {
+        let (token, title, file_name, text) = (token.trim().to_string(), title.to_string(), file_name.to_string(), text.to_string());
+        let handle = std::thread::spawn(move || match service {
+            Service::Gist => gist(&token, &title, &file_name, &text),
+            Service::Pastebin => paste(&token, &title, &text),
+        });
+        Upload { handle: Some(handle) }
+    }
+
+    /// None while the upload is still going, then the link or what went wrong
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let handle = self.handle.take()?;
+        Some(handle.join().unwrap_or_else(|_| Err("the upload thread panicked".to_string())))
+    }
+}
+
+fn gist(token: &str, title: &str, file_name: &str, text: &str) -> Result<String, String> {
+    let body = serde_json::json!({
+        "description": title,
+        "public": false,
+        "files": { file_name: { "content": text } },
+    }).to_string();
+    let response = curl(&[
+        ("header", "Accept: application/vnd.github+json"),
+        ("header", &format!("Authorization: Bearer {}", token)),
+        ("header", "Content-Type: application/json"),
+        ("data-binary", &body),
+        ("url", "https://api.github.com/gists"),
+    ])?;
+    let response: serde_json::Value = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    response["html_url"].as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "GitHub didn't return a link".to_string())
+}
+
+fn paste(dev_key: &str, title: &str, text: &str) -> Result<String, String> {
+    let response = curl(&[
+        ("data-urlencode", &format!("api_dev_key={}", dev_key)),
+        ("data-urlencode", "api_option=paste"),
+        ("data-urlencode", "api_paste_private=1"),
+        ("data-urlencode", "api_paste_format=markdown"),
+        ("data-urlencode", &format!("api_paste_name={}", title)),
+        ("data-urlencode", &format!("api_paste_code={}", text)),
+        ("url", "https://pastebin.com/api/api_post.php"),
+    ])?;
+    // Pastebin answers errors with a message rather than a failing status
+    let response = response.trim();
+    if response.starts_with("https://") {
+        Ok(response.to_string())
+    } else {
+        Err(response.to_string())
+    }
+}
+
+/// Run curl with `options` (long option names without the dashes, and their
+/// values) and return what it printed
+fn curl(options: &[(&str, &str)]) -> Result<String, String> {
+    let input: String = options.iter().map(|(option, value)| config_line(option, value)).collect();
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--max-time", TIMEOUT, "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("curl: {}", e))?;
+    // Dropping stdin once written ends the config
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).map_err(|e| format!("curl: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("curl: {}", e))?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: ").to_string();
+        return Err(if error.is_empty() { output.status.to_string() } else { error });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// A curl config line setting `option` to `value`, quoted and escaped
+fn config_line(option: &str, value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    format!("{} = \"{}\"\n", option, quoted)
+}
@@ -0,0 +1,247 @@
+//! The knowledge report behind File > Export Report: overview counts, the
+//! most frequent terms in each category, recent activity and health warnings,
+//! written as Markdown or as a plain PDF laid out from that Markdown.
+
+use crate::{ActivityEntry, JobProgress, Microfiche, collate, extract_words, fold_text, note_path, scan_secrets};
+use encoding_rs::WINDOWS_1252;
+use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+const TOP_TERMS: usize = 10;
+/// Activity summarized in the report, counted back from today
+const RECENT_DAYS: i64 = 30;
+const LATEST_ENTRIES: usize = 20;
+/// Categories without activity for this long are reported as neglected
+const STALE_DAYS: i64 = 90;
+/// Paths listed per warning before the rest are just counted
+const LISTED_PATHS: usize = 10;
+
+pub fn markdown(fiche: &Microfiche, activity: &[ActivityEntry], title: &str, progress: &JobProgress) -> std::io::Result<String> {
+    let today = chrono::Local::now().date_naive();
+    let days_ago = |days: i64| (today - chrono::Duration::days(days)).format("%Y-%m-%d").to_string();
+    let mut categories: Vec<&String> = fiche.categories.keys().collect();
+    categories.sort_by(|a, b| collate(a, b));
+    progress.start(categories.len() + 1);
+    
+    let mut out = String::new();
+    // Writing to a String can't fail
+    let _ = writeln!(out, "# {}\n\n_Report for {}_\n", title, today.format("%Y-%m-%d"));
+    
+    let stats = fiche.stats();
+    let rated: Vec<u8> = fiche.iter_notes().filter_map(|(_, _, _, note)| note.rating).collect();
+    let _ = writeln!(out, "## Overview\n\n| | Count |\n|---|---:|");
+    for (label, key) in [("Categories", "categories"), ("Subcategories", "subcategories"), ("Concepts", "concepts"), ("Notes", "total_notes")] {
+        let _ = writeln!(out, "| {} | {} |", label, stats.get(key).unwrap_or(&0));
+    }
+    let _ = writeln!(out, "| Unique terms | {} |", fiche.term_network().frequencies.len());
+    if !rated.is_empty() {
+        let average = rated.iter().map(|&r| r as f32).sum::<f32>() / rated.len() as f32;
+        let _ = writeln!(out, "| Rated notes | {} (average {:.1} stars) |", rated.len(), average);
+    }
+    
+    let _ = writeln!(out, "\n## Top terms per category");
+    for cat in &categories {
+        progress.advance(1)?;
+        let mut frequencies: HashMap<String, usize> = HashMap::new();
+        let mut notes = 0;
+        for subcat in &fiche.categories[*cat].subcategories {
+            for concept in &subcat.concepts {
+                notes += concept.notes.len();
+                for note in &concept.notes {
+                    for word in extract_words(&note.text) {
+                        *frequencies.entry(word).or_default() += 1;
+                    }
+                }
+            }
+        }
+        let mut terms: Vec<(String, usize)> = frequencies.into_iter().collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let terms: Vec<String> = terms.iter().take(TOP_TERMS).map(|(term, count)| format!("{} ({})", term, count)).collect();
+        let _ = writeln!(out, "\n### {} ({} notes)\n\n{}", cat, notes, if terms.is_empty() { "No terms".to_string() } else { terms.join(", ") });
+    }
+    
+    let since = days_ago(RECENT_DAYS);
+    let recent: Vec<&ActivityEntry> = activity.iter().filter(|e| e.day() >= since.as_str()).collect();
+    let _ = writeln!(out, "\n## Recent activity (last {} days)\n", RECENT_DAYS);
+    if recent.is_empty() {
+        let _ = writeln!(out, "No activity.");
+    } else {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for entry in &recent {
+            *counts.entry(entry.action.label()).or_default() += 1;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        for (action, count) in counts {
+            let _ = writeln!(out, "- {}: {}", action, count);
+        }
+        let _ = writeln!(out, "\n### Latest\n");
+        for entry in recent.iter().rev().take(LATEST_ENTRIES) {
+            let time = entry.timestamp.get(..16).unwrap_or(&entry.timestamp).replace('T', " ");
+            let _ = writeln!(out, "- {} {}: {}", time, entry.action.label(), entry.path);
+        }
+    }
+    
+    progress.advance(1)?;
+    let _ = writeln!(out, "\n## Health\n");
+    let mut warnings = 0;
+    let mut warn = |out: &mut String, heading: String, paths: &[String]| {
+        warnings += 1;
+        let _ = writeln!(out, "- {}", heading);
+        for path in paths.iter().take(LISTED_PATHS) {
+            let _ = writeln!(out, "  - {}", path);
+        }
+        if paths.len() > LISTED_PATHS {
+            let _ = writeln!(out, "  - and {} more", paths.len() - LISTED_PATHS);
+        }
+    };
+    
+    let review = fiche.review_count();
+    if review > 0 {
+        warn(&mut out, format!("{} notes are flagged for review", review), &[]);
+    }
+    let secrets: Vec<String> = fiche.iter_notes()
+        .filter(|(_, _, _, note)| !scan_secrets(&note.text).is_empty())
+        .map(|(cat, sub, con, _)| note_path(cat, sub, con))
+        .collect();
+    if !secrets.is_empty() {
+        warn(&mut out, format!("{} notes may contain API keys, tokens or passwords", secrets.len()), &secrets);
+    }
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for (cat, sub, con, note) in fiche.iter_notes() {
+        let count = seen.entry(fold_text(note.text.trim())).or_default();
+        *count += 1;
+        if *count == 2 {
+            duplicates.push(note_path(cat, sub, con));
+        }
+    }
+    if !duplicates.is_empty() {
+        warn(&mut out, format!("{} notes appear more than once", duplicates.len()), &duplicates);
+    }
+    // Only meaningful once the log goes back far enough
+    let stale_since = days_ago(STALE_DAYS);
+    if activity.first().is_some_and(|e| e.day() < stale_since.as_str()) {
+        let stale: Vec<String> = categories.iter()
+            .filter(|cat| !activity.iter().any(|e| e.category() == cat.as_str() && e.day() >= stale_since.as_str()))
+            .map(|cat| cat.to_string())
+            .collect();
+        if !stale.is_empty() {
+            warn(&mut out, format!("{} categories have had no activity in {} days", stale.len(), STALE_DAYS), &stale);
+        }
+    }
+    if warnings == 0 {
+        let _ = writeln!(out, "No problems found.");
+    }
+    
+    Ok(out)
+}
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const TABLE_COLUMN_WIDTH: f32 = 160.0;
+
+/// Lay out `markdown` on A4 pages in Helvetica: headings in bold, tables as
+/// spaced columns, everything else as wrapped plain text. Characters outside
+/// Windows-1252 (the built-in fonts' encoding) print as `?`
+pub fn pdf(markdown: &str) -> Vec<u8> {
+    let mut pages: Vec<Content> = Vec::new();
+    let mut content = Content::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    content.begin_text();
+    
+    for line in markdown.lines() {
+        let (text, font, size) = match line.trim_start_matches('#') {
+            _ if line.starts_with("|---") => continue,
+            rest if line.starts_with("### ") => (rest.trim().to_string(), Name(b"F2"), 12.0),
+            rest if line.starts_with("## ") => (rest.trim().to_string(), Name(b"F2"), 14.0),
+            rest if line.starts_with("# ") => (rest.trim().to_string(), Name(b"F2"), 18.0),
+            _ if line.starts_with('|') => (line.trim_matches('|').to_string(), Name(b"F1"), 10.0),
+            _ => (line.trim_matches('_').to_string(), Name(b"F1"), 10.0),
+        };
+        let leading = size * 1.4;
+        // Helvetica averages about half an em per character
+        let max_chars = ((PAGE_WIDTH - 2.0 * MARGIN) / (size * 0.5)) as usize;
+        let rows: Vec<Vec<String>> = if line.starts_with('|') {
+            vec![text.split('|').map(|cell| cell.trim().to_string()).collect()]
+        } else {
+            wrap(&text, max_chars).into_iter().map(|row| vec![row]).collect()
+        };
+        for cells in rows {
+            if y - leading < MARGIN {
+                content.end_text();
+                pages.push(std::mem::replace(&mut content, Content::new()));
+                content.begin_text();
+                y = PAGE_HEIGHT - MARGIN;
+            }
+            y -= leading;
+            content.set_font(font, size);
+            for (column, cell) in cells.iter().enumerate() {
+                content.set_text_matrix([1.0, 0.0, 0.0, 1.0, MARGIN + column as f32 * TABLE_COLUMN_WIDTH, y]);
+                content.show(Str(&win_ansi(cell)));
+            }
+        }
+    }
+    content.end_text();
+    pages.push(content);
+    
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let page_ids: Vec<Ref> = (0..pages.len() as i32).map(|i| Ref::new(5 + i * 2)).collect();
+    
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id).kids(page_ids.iter().copied()).count(pages.len() as i32);
+    pdf.type1_font(regular_id).base_font(Name(b"Helvetica")).encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_id).base_font(Name(b"Helvetica-Bold")).encoding_predefined(Name(b"WinAnsiEncoding"));
+    for (page_id, content) in page_ids.into_iter().zip(pages) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(tree_id);
+        page.contents(content_id);
+        let mut resources = page.resources();
+        resources.fonts().pair(Name(b"F1"), regular_id).pair(Name(b"F2"), bold_id);
+        drop(resources);
+        drop(page);
+        pdf.stream(content_id, &content.finish());
+    }
+    pdf.finish()
+}
+
+/// Break `text` into rows of at most `max_chars`, at spaces where possible,
+/// keeping a list item's indentation on its continuation rows
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let indent = text.len() - text.trim_start().len();
+    let hanging = " ".repeat(indent + if text.trim_start().starts_with("- ") { 2 } else { 0 });
+    let mut rows = Vec::new();
+    let mut row = text[..indent].to_string();
+    let mut empty = true;
+    for word in text.split_whitespace() {
+        if !empty && row.chars().count() + 1 + word.chars().count() > max_chars {
+            rows.push(std::mem::replace(&mut row, hanging.clone()));
+            empty = true;
+        }
+        if !empty {
+            row.push(' ');
+        }
+        row.push_str(word);
+        empty = false;
+    }
+    rows.push(row);
+    rows
+}
+
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| {
+            let mut buf = [0; 4];
+            let (bytes, _, unmappable) = WINDOWS_1252.encode(c.encode_utf8(&mut buf));
+            if unmappable || bytes.len() != 1 { b'?' } else { bytes[0] }
+        })
+        .collect()
+}
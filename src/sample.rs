@@ -0,0 +1,59 @@
+//! The sample fiche offered on first run: a handful of notes in a few
+//! categories, with enough shared words, links, ratings and templates that
+//! every tab has something to show.
+
+use crate::{FicheRow, Microfiche};
+
+const NOTES: &[(&str, &str, &str, &str, Option<u8>)] = &[
+    ("Science", "Physics", "Newton's Laws", "An object stays at rest or in uniform motion unless a force acts on it. #mechanics", Some(5)),
+    ("Science", "Physics", "Newton's Laws", "Force equals mass times acceleration: F = ma. See [[Energy]] for work done by a force. #mechanics", Some(4)),
+    ("Science", "Physics", "Energy", "Kinetic energy is ½mv²; potential energy near the Earth's surface is mgh. #mechanics", Some(4)),
+    ("Science", "Physics", "Energy", "Energy is conserved: it changes form but the total in a closed system stays the same.", None),
+    ("Science", "Biology", "Cells", "The cell is the basic unit of life; every cell comes from an existing cell.", Some(3)),
+    ("Science", "Biology", "Cells", "Mitochondria turn glucose and oxygen into usable energy (ATP) for the cell. See [[Energy]].", None),
+    ("Science", "Biology", "Photosynthesis", "Plants use light energy to turn carbon dioxide and water into glucose and oxygen.", Some(4)),
+    ("Languages", "Spanish", "Greetings", "Hola = hello; buenos días = good morning; buenas noches = good night", Some(5)),
+    ("Languages", "Spanish", "Greetings", "¿Qué tal? is an informal \"how are you?\"; answer with bien, gracias", None),
+    ("Languages", "Spanish", "Ser vs Estar", "Ser for lasting traits (soy alto), estar for states and places (estoy cansado, estoy en casa)", Some(4)),
+    ("Cooking", "Recipes", "Pancakes", "1 cup flour, 1 egg, 1 cup milk, 1 tbsp sugar, pinch of salt. Rest the batter 10 minutes. #breakfast", Some(5)),
+    ("Cooking", "Recipes", "Tomato Sauce", "Soften garlic in olive oil, add crushed tomatoes and salt, simmer 20 minutes, finish with basil.", Some(4)),
+    ("Cooking", "Techniques", "Resting Dough", "Resting lets the flour absorb the liquid and the gluten relax, so pancakes and pastry come out tender.", None),
+    ("Books", "Summaries", "Atomic Habits", "Author: James Clear\nKey ideas:\n- Small habits compound over time\n- Make good habits obvious, attractive, easy and satisfying\nFavourite quote: \"You do not rise to the level of your goals. You fall to the level of your systems.\"", Some(5)),
+    ("Books", "Summaries", "Deep Work", "Author: Cal Newport\nKey ideas:\n- Focused work without distraction is rare and valuable\n- Schedule deep work in blocks and protect them\nFavourite quote: \"Clarity about what matters provides clarity about what does not.\"", Some(4)),
+];
+
+pub fn fiche() -> Microfiche {
+    let mut fiche = Microfiche::new();
+    let created = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    for &(category, subcategory, concept, note, rating) in NOTES {
+        fiche.add_row(FicheRow {
+            category: category.to_string(),
+            subcategory: subcategory.to_string(),
+            concept: concept.to_string(),
+            note: note.to_string(),
+            rating,
+            needs_review: false,
+            created: Some(created.clone()),
+            modified: None,
+            fields: Default::default(),
+            display: Default::default(),
+            definition: false,
+            flags: Default::default(),
+            tags: Default::default(),
+            author: Default::default(),
+            comments: Default::default(),
+        });
+    }
+    fiche.set_definition("Science", "Biology", "Cells", NOTES[4].3, true);
+    
+    let meta = &mut fiche.meta;
+    meta.set_description("Science", None, "Notes from physics and biology classes".to_string());
+    meta.set_description("Books", Some("Summaries"), "One concept per book; new notes start from the summary template".to_string());
+    meta.set_note_template("Books", "Summaries", "", "Author: \nKey ideas:\n- \nFavourite quote: ".to_string());
+    for (category, icon, color) in [("Science", "🔬", [100, 149, 237]), ("Languages", "🗣", [60, 179, 113]), ("Cooking", "🍳", [230, 159, 0]), ("Books", "📚", [186, 85, 211])] {
+        let style = meta.categories.entry(category.to_string()).or_default();
+        style.icon = icon.to_string();
+        style.color = Some(color);
+    }
+    fiche
+}
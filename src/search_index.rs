@@ -0,0 +1,449 @@
+//! The inverted index behind Search: every term of every note's text with
+//! the notes and positions it occurs at, so a query only looks at the notes
+//! holding its words and can rank them (BM25) instead of scanning them all.
+//!
+//! A fiche builds its index on the first search and keeps it up to date as
+//! notes are added and removed. After a save the index is written to the app's
+//! cache folder, and reused when the same file is opened again unchanged.
+
+use crate::{Microfiche, fold_text};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+/// Bumped whenever the stored layout changes, so old caches are rebuilt
+const VERSION: u32 = 1;
+
+// BM25 parameters
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+// Weight of a query word found as a whole term, at the start of one, or inside one
+const EXACT: f32 = 1.0;
+const PREFIX: f32 = 0.6;
+const INFIX: f32 = 0.3;
+// Score a query word earns by matching the note's category, subcategory,
+// concept or an alias of it
+const PATH_MATCH: f32 = 2.0;
+// Most notes `similar` compares in full, out of those sharing the most terms
+const SIMILAR_CANDIDATES: usize = 200;
+
+/// One part of a query: a word matched anywhere in a term, or a quoted
+/// phrase whose terms must follow each other
+#[derive(Debug, PartialEq)]
+pub enum Clause {
+    Word(String),
+    Phrase(Vec<String>),
+}
+
+/// Split a query into clauses: `"quoted text"` is a phrase, anything else is
+/// words. A word that folds into several terms (`e-mail`) is a phrase too.
+pub fn parse(query: &str) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+    for (i, part) in query.split('"').enumerate() {
+        let quoted = i % 2 == 1;
+        let words: Vec<Vec<String>> = if quoted {
+            vec![terms(part)]
+        } else {
+            part.split_whitespace().map(terms).collect()
+        };
+        for mut words in words {
+            match words.len() {
+                0 => {},
+                1 => clauses.push(Clause::Word(words.remove(0))),
+                _ => clauses.push(Clause::Phrase(words)),
+            }
+        }
+    }
+    clauses
+}
+
+/// The searchable terms of `text`: folded to lowercase without accents and
+/// split at anything that isn't a letter or digit
+pub fn terms(text: &str) -> Vec<String> {
+    fold_text(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Doc {
+    category: String,
+    subcategory: String,
+    concept: String,
+    text: String,
+    length: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Posting {
+    doc: u32,
+    positions: Vec<u32>,
+}
+
+type ConceptPath = (String, String, String);
+
+/// A note found by `SearchIndex::search`, best match first
+pub struct Hit<'a> {
+    pub category: &'a str,
+    pub subcategory: &'a str,
+    pub concept: &'a str,
+    pub text: &'a str,
+}
+
+/// A fiche's index once built; copies of the fiche (undo snapshots, save
+/// jobs) start without one
+#[derive(Default)]
+pub struct Cache(OnceLock<Arc<SearchIndex>>);
+
+impl Clone for Cache {
+    fn clone(&self) -> Self {
+        Cache::default()
+    }
+}
+
+impl Cache {
+    pub fn get_or_build(&self, fiche: &Microfiche) -> &SearchIndex {
+        self.0.get_or_init(|| Arc::new(SearchIndex::build(fiche)))
+    }
+    
+    /// The index to update in place, if it has been built
+    pub fn get_mut(&mut self) -> Option<&mut SearchIndex> {
+        self.0.get_mut().map(Arc::make_mut)
+    }
+    
+    pub fn set(&mut self, index: SearchIndex) {
+        self.0 = OnceLock::from(Arc::new(index));
+    }
+    
+    /// Drop the index, to be built again on the next search
+    pub fn clear(&mut self) {
+        self.0 = OnceLock::new();
+    }
+    
+    /// The index as it is now, shared with a save running in the background
+    pub fn shared(&self) -> Option<Arc<SearchIndex>> {
+        self.0.get().cloned()
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    // By id; None once the note is removed, ids are never reused
+    docs: Vec<Option<Doc>>,
+    // Ids of the notes holding each term, ascending, with its positions
+    postings: HashMap<String, Vec<Posting>>,
+    // Note ids by path and text hash, several for a concept's repeated note
+    by_note: HashMap<u64, Vec<u32>>,
+    by_concept: HashMap<ConceptPath, Vec<u32>>,
+    live: u32,
+    total_length: u64,
+}
+
+impl SearchIndex {
+    pub fn build(fiche: &Microfiche) -> Self {
+        let mut index = SearchIndex::default();
+        for (cat, category) in &fiche.categories {
+            for subcat in &category.subcategories {
+                for concept in &subcat.concepts {
+                    for note in &concept.notes {
+                        index.insert(cat, &subcat.name, &concept.name, &note.text);
+                    }
+                }
+            }
+        }
+        index
+    }
+    
+    pub fn insert(&mut self, cat: &str, sub: &str, con: &str, text: &str) {
+        let id = self.docs.len() as u32;
+        let terms = terms(text);
+        for (position, term) in terms.iter().enumerate() {
+            let postings = self.postings.entry(term.clone()).or_default();
+            match postings.last_mut() {
+                Some(last) if last.doc == id => last.positions.push(position as u32),
+                _ => postings.push(Posting { doc: id, positions: vec![position as u32] }),
+            }
+        }
+        self.docs.push(Some(Doc {
+            category: cat.to_string(),
+            subcategory: sub.to_string(),
+            concept: con.to_string(),
+            text: text.to_string(),
+            length: terms.len() as u32,
+        }));
+        self.by_note.entry(note_hash(cat, sub, con, text)).or_default().push(id);
+        self.by_concept.entry((cat.to_string(), sub.to_string(), con.to_string())).or_default().push(id);
+        self.live += 1;
+        self.total_length += terms.len() as u64;
+    }
+    
+    /// Forget one copy of the note; false if it wasn't indexed
+    pub fn remove(&mut self, cat: &str, sub: &str, con: &str, text: &str) -> bool {
+        let hash = note_hash(cat, sub, con, text);
+        let Some(ids) = self.by_note.get_mut(&hash) else { return false };
+        let Some(at) = ids.iter().position(|&id| {
+            self.docs[id as usize].as_ref().is_some_and(|doc| {
+                doc.category == cat && doc.subcategory == sub && doc.concept == con && doc.text == text
+            })
+        }) else {
+            return false;
+        };
+        let id = ids.remove(at);
+        if ids.is_empty() {
+            self.by_note.remove(&hash);
+        }
+        let path = (cat.to_string(), sub.to_string(), con.to_string());
+        if let Some(ids) = self.by_concept.get_mut(&path) {
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                self.by_concept.remove(&path);
+            }
+        }
+        let doc = self.docs[id as usize].take().unwrap();
+        for term in terms(&doc.text).into_iter().collect::<HashSet<_>>() {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                if let Ok(at) = postings.binary_search_by_key(&id, |p| p.doc) {
+                    postings.remove(at);
+                }
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        self.live -= 1;
+        self.total_length -= doc.length as u64;
+        true
+    }
+    
+    /// Notes matching every clause, best first. `concept_text` gives the
+    /// folded path and aliases of a concept, which count as part of each of
+    /// its notes
+    pub fn search(&self, clauses: &[Clause], concept_text: impl Fn(&str, &str, &str) -> String) -> Vec<Hit<'_>> {
+        if clauses.is_empty() {
+            return Vec::new();
+        }
+        let concept_texts: Vec<(&ConceptPath, String)> = self.by_concept.keys()
+            .map(|path| (path, concept_text(&path.0, &path.1, &path.2)))
+            .collect();
+        
+        // Each clause's score per note; a note must appear in all of them
+        let mut scores: Option<HashMap<u32, f32>> = None;
+        for clause in clauses {
+            let mut matched = self.clause_scores(clause);
+            let needle = match clause {
+                Clause::Word(word) => word.clone(),
+                Clause::Phrase(words) => words.join(" "),
+            };
+            for (path, text) in &concept_texts {
+                if text.contains(&needle) {
+                    for &id in &self.by_concept[*path] {
+                        *matched.entry(id).or_default() += PATH_MATCH;
+                    }
+                }
+            }
+            scores = Some(match scores {
+                None => matched,
+                Some(mut scores) => {
+                    scores.retain(|id, score| match matched.get(id) {
+                        Some(more) => {
+                            *score += more;
+                            true
+                        },
+                        None => false,
+                    });
+                    scores
+                },
+            });
+            if scores.as_ref().is_some_and(HashMap::is_empty) {
+                break;
+            }
+        }
+        
+        let mut hits: Vec<(u32, f32)> = scores.unwrap_or_default().into_iter().collect();
+        // Ties keep the order the notes were added in
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        hits.into_iter()
+            .filter_map(|(id, _)| {
+                let doc = self.docs[id as usize].as_ref()?;
+                Some(Hit {
+                    category: &doc.category,
+                    subcategory: &doc.subcategory,
+                    concept: &doc.concept,
+                    text: &doc.text,
+                })
+            })
+            .collect()
+    }
+    
+    /// Notes sharing the most of `text`'s terms, most alike first, with how
+    /// alike they are from 0 to 1: the weighted Jaccard similarity of their
+    /// term sets, each term weighted by its rarity
+    pub fn similar(&self, text: &str, min_similarity: f32, limit: usize) -> Vec<(Hit<'_>, f32)> {
+        let notes = self.live.max(1) as f32;
+        let idf = |holders: usize| ((notes - holders as f32 + 0.5) / (holders as f32 + 0.5) + 1.0).ln();
+        let query: HashSet<String> = terms(text).into_iter().collect();
+        let query_weight: f32 = query.iter().map(|term| idf(self.postings.get(term).map_or(0, Vec::len))).sum();
+        if query_weight == 0.0 {
+            return Vec::new();
+        }
+        
+        let mut shared: HashMap<u32, f32> = HashMap::new();
+        for term in &query {
+            if let Some(postings) = self.postings.get(term) {
+                let weight = idf(postings.len());
+                for posting in postings {
+                    *shared.entry(posting.doc).or_default() += weight;
+                }
+            }
+        }
+        // A note can't be more alike than the share of the text it holds, so
+        // only the notes holding the most of it get their own terms weighed
+        let mut candidates: Vec<(u32, f32)> = shared.into_iter()
+            .filter(|(_, weight)| weight / query_weight >= min_similarity)
+            .collect();
+        if candidates.len() > SIMILAR_CANDIDATES {
+            candidates.select_nth_unstable_by(SIMILAR_CANDIDATES, |a, b| b.1.total_cmp(&a.1));
+            candidates.truncate(SIMILAR_CANDIDATES);
+        }
+        let mut hits: Vec<(u32, f32)> = candidates.into_iter()
+            .filter_map(|(id, weight)| {
+                let doc = self.docs[id as usize].as_ref()?;
+                let doc_weight: f32 = terms(&doc.text).into_iter()
+                    .collect::<HashSet<_>>()
+                    .iter()
+                    .map(|term| idf(self.postings.get(term).map_or(0, Vec::len)))
+                    .sum();
+                let similarity = weight / (query_weight + doc_weight - weight);
+                (similarity >= min_similarity).then_some((id, similarity))
+            })
+            .collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        hits.truncate(limit);
+        hits.into_iter()
+            .filter_map(|(id, similarity)| {
+                let doc = self.docs[id as usize].as_ref()?;
+                Some((Hit {
+                    category: &doc.category,
+                    subcategory: &doc.subcategory,
+                    concept: &doc.concept,
+                    text: &doc.text,
+                }, similarity))
+            })
+            .collect()
+    }
+    
+    /// BM25 score of each note holding `clause` in its text
+    fn clause_scores(&self, clause: &Clause) -> HashMap<u32, f32> {
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+        match clause {
+            Clause::Word(word) => {
+                for (term, postings) in &self.postings {
+                    let weight = if term == word {
+                        EXACT
+                    } else if term.starts_with(word.as_str()) {
+                        PREFIX
+                    } else if term.contains(word.as_str()) {
+                        INFIX
+                    } else {
+                        continue;
+                    };
+                    for posting in postings {
+                        let score = weight * self.term_score(postings.len(), posting.positions.len(), posting.doc);
+                        let best = scores.entry(posting.doc).or_default();
+                        *best = best.max(score);
+                    }
+                }
+            },
+            Clause::Phrase(words) => {
+                let Some(lists) = words.iter().map(|w| self.postings.get(w)).collect::<Option<Vec<_>>>() else {
+                    return scores;
+                };
+                let rarest = lists.iter().map(|l| l.len()).min().unwrap_or(0);
+                for first in lists[0] {
+                    let rest: Option<Vec<&Posting>> = lists[1..].iter()
+                        .map(|list| list.binary_search_by_key(&first.doc, |p| p.doc).ok().map(|at| &list[at]))
+                        .collect();
+                    let Some(rest) = rest else { continue };
+                    let occurrences = first.positions.iter()
+                        .filter(|&&start| {
+                            rest.iter().enumerate().all(|(i, p)| p.positions.binary_search(&(start + i as u32 + 1)).is_ok())
+                        })
+                        .count();
+                    if occurrences > 0 {
+                        let score = words.len() as f32 * self.term_score(rarest, occurrences, first.doc);
+                        scores.insert(first.doc, score);
+                    }
+                }
+            },
+        }
+        scores
+    }
+    
+    /// BM25 weight of a term found in `holders` notes, `count` times in `doc`
+    fn term_score(&self, holders: usize, count: usize, doc: u32) -> f32 {
+        let notes = self.live.max(1) as f32;
+        let idf = ((notes - holders as f32 + 0.5) / (holders as f32 + 0.5) + 1.0).ln();
+        let average = (self.total_length as f32 / notes).max(1.0);
+        let length = self.docs[doc as usize].as_ref().map_or(0, |d| d.length) as f32;
+        let count = count as f32;
+        idf * count * (K1 + 1.0) / (count + K1 * (1.0 - B + B * length / average))
+    }
+    
+    /// Write the index to the cache for the file at `path`, stamped with the
+    /// notes of `fiche` it was built from
+    pub fn persist(&self, path: &str, fiche: &Microfiche) -> Result<(), String> {
+        let file = cache_file(path).ok_or("no cache folder")?;
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let bytes = bincode::serialize(&(VERSION, stamp(fiche), self)).map_err(|e| e.to_string())?;
+        std::fs::write(file, bytes).map_err(|e| e.to_string())
+    }
+    
+    /// The cached index of the file at `path`, if there is one and it was
+    /// built from exactly the notes in `fiche`
+    pub fn restore(path: &str, fiche: &Microfiche) -> Option<Self> {
+        let bytes = std::fs::read(cache_file(path)?).ok()?;
+        let (version, stamp_then, index): (u32, u64, SearchIndex) = bincode::deserialize(&bytes).ok()?;
+        (version == VERSION && stamp_then == stamp(fiche)).then_some(index)
+    }
+}
+
+/// Where the index of the file at `path` is cached, named after its absolute path
+fn cache_file(path: &str) -> Option<PathBuf> {
+    let dir = eframe::storage_dir(crate::APP_TITLE)?;
+    let name = format!("{:016x}.bin", fnv(FNV_OFFSET, crate::absolute_path(path).as_bytes()));
+    Some(dir.join("search-index").join(name))
+}
+
+/// Fingerprint of all the notes' paths and text, in any order
+fn stamp(fiche: &Microfiche) -> u64 {
+    let mut stamp: u64 = 0;
+    for (cat, category) in &fiche.categories {
+        for subcat in &category.subcategories {
+            for concept in &subcat.concepts {
+                for note in &concept.notes {
+                    stamp = stamp.wrapping_add(note_hash(cat, &subcat.name, &concept.name, &note.text));
+                }
+            }
+        }
+    }
+    stamp
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+fn fnv(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn note_hash(cat: &str, sub: &str, con: &str, text: &str) -> u64 {
+    [cat, sub, con, text].iter().fold(FNV_OFFSET, |hash, part| fnv(fnv(hash, part.as_bytes()), &[0x1f]))
+}
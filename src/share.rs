@@ -0,0 +1,49 @@
+//! Handing a note to other apps: a pre-filled email opened through the
+//! system's `mailto:` handler, the same way a link in a browser would.
+
+/// Open a new email in the default mail app with `subject` and `body` filled in
+pub fn email(subject: &str, body: &str) -> Result<(), String> {
+    let url = format!("mailto:?subject={}&body={}", percent_encode(subject), percent_encode(body));
+    open_url(&url)
+}
+
+/// Escape everything but unreserved characters, as `mailto:` fields require
+/// (RFC 6068); line breaks become `%0D%0A`
+fn percent_encode(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\n', "\r\n");
+    let mut encoded = String::with_capacity(text.len() * 3);
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(target_os = "linux")]
+fn open_url(url: &str) -> Result<(), String> {
+    spawn(std::process::Command::new("xdg-open").arg(url))
+}
+
+#[cfg(target_os = "macos")]
+fn open_url(url: &str) -> Result<(), String> {
+    spawn(std::process::Command::new("open").arg(url))
+}
+
+#[cfg(target_os = "windows")]
+fn open_url(url: &str) -> Result<(), String> {
+    // Unlike `cmd /C start`, this passes the URL through without reinterpreting `&`
+    spawn(std::process::Command::new("rundll32").args(["url.dll,FileProtocolHandler", url]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn open_url(_url: &str) -> Result<(), String> {
+    Err("Opening links is not supported on this platform".to_string())
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn spawn(command: &mut std::process::Command) -> Result<(), String> {
+    command.spawn().map(|_| ()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,85 @@
+//! Timestamped copies of a fiche taken on every save, kept in a
+//! `.fisha_backups` folder beside the file so an earlier state can be
+//! restored. A CSV file's `.meta.json` sidecar is copied along, and only the
+//! newest snapshots up to the configured count are kept.
+
+use crate::FicheMeta;
+use chrono::NaiveDateTime;
+use std::path::{Path, PathBuf};
+
+pub const DIR: &str = ".fisha_backups";
+const STAMP: &str = "%Y%m%d-%H%M%S";
+
+/// A copy of the file as it was saved
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken: NaiveDateTime,
+    pub size: u64,
+}
+
+/// The folder and the name parts around the timestamp: `notes.csv.gz` is
+/// snapshotted as `notes.20250301-093000.csv.gz`
+fn naming(path: &str) -> Option<(PathBuf, String, String)> {
+    let file = Path::new(path);
+    let name = file.file_name()?.to_string_lossy().to_string();
+    let (stem, extension) = match name.find('.') {
+        Some(dot) => (name[..dot].to_string(), name[dot..].to_string()),
+        None => (name, String::new()),
+    };
+    Some((file.parent().unwrap_or(Path::new("")).join(DIR), stem, extension))
+}
+
+/// Copy the file at `path` into the backups folder, then remove the oldest
+/// snapshots beyond `keep`
+pub fn take(path: &str, keep: usize) -> std::io::Result<()> {
+    let Some((dir, stem, extension)) = naming(path) else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir)?;
+    let target = dir.join(format!("{}.{}{}", stem, chrono::Local::now().format(STAMP), extension));
+    std::fs::copy(path, &target)?;
+    let sidecar = FicheMeta::sidecar_path(path);
+    if sidecar.is_file() {
+        std::fs::copy(sidecar, FicheMeta::sidecar_path(&target.to_string_lossy()))?;
+    }
+    for old in list(path).into_iter().skip(keep) {
+        std::fs::remove_file(&old.path)?;
+        let _ = std::fs::remove_file(FicheMeta::sidecar_path(&old.path.to_string_lossy()));
+    }
+    Ok(())
+}
+
+/// Delete the snapshots of the file at `path` for which `holds` is true,
+/// along with their sidecars
+pub fn remove_where(path: &str, holds: impl Fn(&Path) -> bool) -> std::io::Result<()> {
+    for snapshot in list(path) {
+        if holds(&snapshot.path) {
+            std::fs::remove_file(&snapshot.path)?;
+            let _ = std::fs::remove_file(FicheMeta::sidecar_path(&snapshot.path.to_string_lossy()));
+        }
+    }
+    Ok(())
+}
+
+/// The snapshots of the file at `path`, newest first
+pub fn list(path: &str) -> Vec<Snapshot> {
+    let Some((dir, stem, extension)) = naming(path) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<Snapshot> = entries.flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stamp = name.strip_prefix(&stem)?.strip_prefix('.')?.strip_suffix(extension.as_str())?;
+            Some(Snapshot {
+                taken: NaiveDateTime::parse_from_str(stamp, STAMP).ok()?,
+                size: entry.metadata().ok()?.len(),
+                path: entry.path(),
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.taken));
+    snapshots
+}
@@ -0,0 +1,322 @@
+//! The SQLite backend (`.db`): one row per category, subcategory, concept
+//! and note, so a save only touches what changed.
+//!
+//! ```text
+//! categories      name, position
+//! subcategories   category, name, position
+//! concepts        subcategory, name, position, signature of its notes
+//! notes           concept, position, the note columns, extra fields as JSON
+//! meta            the sidecar data (colors, goals, ...) as JSON
+//! attachments     files kept alongside the notes
+//! ```
+//!
+//! Each save is a single transaction. A concept whose notes hash to the
+//! stored signature is left alone; the others have their notes rewritten.
+
+use crate::{Category, Concept, FicheMeta, JobProgress, Microfiche, Note, NoteDisplay, NoteFlag, Subcategory, comments_column, parse_comments, parse_tags, tags_column, valid_rating};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+pub const EXTENSIONS: [&str; 2] = ["db", "sqlite"];
+
+/// Bump when the tables change shape, and add a step to `MIGRATIONS`
+const SCHEMA_VERSION: i64 = 5;
+
+/// `MIGRATIONS[i]` upgrades the tables from version `i + 1` to `i + 2`.
+/// A step adding a note column clears the signatures so the next save
+/// fills it in.
+const MIGRATIONS: [&str; (SCHEMA_VERSION - 1) as usize] = [
+    "ALTER TABLE notes ADD COLUMN tags TEXT NOT NULL DEFAULT '';
+     UPDATE concepts SET signature = '';",
+    "ALTER TABLE notes ADD COLUMN comments TEXT NOT NULL DEFAULT '';
+     UPDATE concepts SET signature = '';",
+    "ALTER TABLE notes ADD COLUMN author TEXT NOT NULL DEFAULT '';
+     UPDATE concepts SET signature = '';",
+    "ALTER TABLE categories ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+     UPDATE categories SET position = id;",
+];
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS categories (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        position INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS subcategories (
+        id INTEGER PRIMARY KEY,
+        category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        UNIQUE (category_id, name)
+    );
+    CREATE TABLE IF NOT EXISTS concepts (
+        id INTEGER PRIMARY KEY,
+        subcategory_id INTEGER NOT NULL REFERENCES subcategories(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        signature TEXT NOT NULL,
+        UNIQUE (subcategory_id, name)
+    );
+    CREATE TABLE IF NOT EXISTS notes (
+        id INTEGER PRIMARY KEY,
+        concept_id INTEGER NOT NULL REFERENCES concepts(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        rating INTEGER,
+        needs_review INTEGER NOT NULL DEFAULT 0,
+        created TEXT,
+        modified TEXT,
+        display TEXT NOT NULL DEFAULT '',
+        definition INTEGER NOT NULL DEFAULT 0,
+        flags TEXT NOT NULL DEFAULT '',
+        fields TEXT NOT NULL DEFAULT '{}',
+        tags TEXT NOT NULL DEFAULT '',
+        comments TEXT NOT NULL DEFAULT '',
+        author TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX IF NOT EXISTS notes_by_concept ON notes (concept_id, position);
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS attachments (
+        name TEXT PRIMARY KEY,
+        data BLOB NOT NULL
+    );
+";
+
+/// Whether `path` should be read and written as a SQLite database
+pub fn is_db_path(path: &str) -> bool {
+    std::path::Path::new(path).extension()
+        .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+fn connect(path: &str) -> Result<Connection, Box<dyn Error>> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    // Deleted notes are overwritten with zeros rather than left in free
+    // pages, so a purged note can't be read back from the file
+    conn.pragma_update(None, "secure_delete", true)?;
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "{} uses database version {}, this version of Fisha reads up to {}",
+            path, version, SCHEMA_VERSION
+        ).into());
+    }
+    if version > 0 {
+        for migration in &MIGRATIONS[(version - 1) as usize..] {
+            conn.execute_batch(migration)?;
+        }
+    }
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    Ok(conn)
+}
+
+pub fn read(path: &str) -> Result<Microfiche, Box<dyn Error>> {
+    if !std::path::Path::new(path).exists() {
+        return Err(format!("{} not found", path).into());
+    }
+    let conn = connect(path)?;
+    let mut fiche = Microfiche::new();
+    
+    let mut notes: HashMap<i64, Vec<Note>> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT concept_id, text, rating, needs_review, created, modified, display, definition, flags, fields, tags, comments, author
+         FROM notes ORDER BY concept_id, position"
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let fields: String = row.get(9)?;
+        notes.entry(row.get(0)?).or_default().push(Note {
+            text: row.get(1)?,
+            rating: row.get::<_, Option<i64>>(2)?.and_then(valid_rating),
+            needs_review: row.get(3)?,
+            created: row.get(4)?,
+            modified: row.get(5)?,
+            display: NoteDisplay::parse(&row.get::<_, String>(6)?),
+            definition: row.get(7)?,
+            flags: NoteFlag::parse(&row.get::<_, String>(8)?),
+            fields: serde_json::from_str(&fields)?,
+            tags: parse_tags(&row.get::<_, String>(10)?),
+            comments: parse_comments(&row.get::<_, String>(11)?),
+            author: row.get(12)?,
+        });
+    }
+    
+    let mut stmt = conn.prepare(
+        "SELECT c.name, s.name, k.id, k.name
+         FROM categories c
+         LEFT JOIN subcategories s ON s.category_id = c.id
+         LEFT JOIN concepts k ON k.subcategory_id = s.id
+         ORDER BY c.position, s.position, k.position"
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let category_name: String = row.get(0)?;
+        let category = fiche.categories.entry(category_name.clone()).or_insert_with(|| Category {
+            name: category_name,
+            subcategories: Vec::new(),
+        });
+        let Some(subcategory_name) = row.get::<_, Option<String>>(1)? else {
+            continue;
+        };
+        if category.subcategories.last().is_none_or(|s| s.name != subcategory_name) {
+            category.subcategories.push(Subcategory { name: subcategory_name, concepts: Vec::new() });
+        }
+        let subcategory = category.subcategories.last_mut().unwrap();
+        if let (Some(id), Some(name)) = (row.get::<_, Option<i64>>(2)?, row.get::<_, Option<String>>(3)?) {
+            subcategory.concepts.push(Concept { name, notes: notes.remove(&id).unwrap_or_default() });
+        }
+    }
+    
+    let meta: Option<String> = conn.query_row("SELECT value FROM meta WHERE key = 'meta'", [], |row| row.get(0)).optional()?;
+    fiche.meta = match meta {
+        Some(json) => serde_json::from_str(&json)?,
+        None => FicheMeta::default(),
+    };
+    
+    let mut stmt = conn.prepare("SELECT name, data FROM attachments")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        fiche.attachments.insert(row.get(0)?, row.get(1)?);
+    }
+    
+    Ok(fiche)
+}
+
+/// Bring the database at `path` in line with `fiche` in one transaction,
+/// counting one step of `progress` per note. Cancelling rolls it back.
+pub fn write(fiche: &Microfiche, path: &str, progress: &JobProgress) -> Result<(), Box<dyn Error>> {
+    let mut conn = connect(path)?;
+    let tx = conn.transaction()?;
+    
+    let mut category_ids = HashMap::new();
+    for (position, name) in fiche.categories.keys().enumerate() {
+        let id: i64 = tx.query_row(
+            "INSERT INTO categories (name, position) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET position = excluded.position
+             RETURNING id",
+            params![name, position],
+            |row| row.get(0),
+        )?;
+        category_ids.insert(name.as_str(), id);
+    }
+    delete_missing(&tx, "categories", category_ids.values().copied().collect())?;
+    
+    let stored: HashMap<i64, String> = {
+        let mut stmt = tx.prepare("SELECT id, signature FROM concepts")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    
+    let mut subcategory_ids = HashSet::new();
+    let mut concept_ids = HashSet::new();
+    for category in fiche.categories.values() {
+        for (position, subcategory) in category.subcategories.iter().enumerate() {
+            let subcategory_id: i64 = tx.query_row(
+                "INSERT INTO subcategories (category_id, name, position) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (category_id, name) DO UPDATE SET position = excluded.position
+                 RETURNING id",
+                params![category_ids[category.name.as_str()], subcategory.name, position],
+                |row| row.get(0),
+            )?;
+            subcategory_ids.insert(subcategory_id);
+            
+            for (position, concept) in subcategory.concepts.iter().enumerate() {
+                let signature = signature(&concept.notes)?;
+                let concept_id: i64 = tx.query_row(
+                    "INSERT INTO concepts (subcategory_id, name, position, signature) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (subcategory_id, name) DO UPDATE SET position = excluded.position, signature = excluded.signature
+                     RETURNING id",
+                    params![subcategory_id, concept.name, position, signature],
+                    |row| row.get(0),
+                )?;
+                concept_ids.insert(concept_id);
+                if stored.get(&concept_id) != Some(&signature) {
+                    write_notes(&tx, concept_id, &concept.notes)?;
+                }
+                progress.advance(concept.notes.len())?;
+            }
+        }
+    }
+    delete_missing(&tx, "subcategories", subcategory_ids)?;
+    delete_missing(&tx, "concepts", concept_ids)?;
+    
+    let mut meta = fiche.meta.clone();
+    meta.prune(&fiche.categories);
+    tx.execute(
+        "INSERT INTO meta (key, value) VALUES ('meta', ?1) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        [serde_json::to_string(&meta)?],
+    )?;
+    
+    // Attachments are never edited in place, so only additions and removals are written
+    let stored: HashSet<String> = {
+        let mut stmt = tx.prepare("SELECT name FROM attachments")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for name in stored.iter().filter(|name| !fiche.attachments.contains_key(*name)) {
+        tx.execute("DELETE FROM attachments WHERE name = ?1", [name])?;
+    }
+    for (name, bytes) in fiche.attachments.iter().filter(|(name, _)| !stored.contains(*name)) {
+        tx.execute("INSERT INTO attachments (name, data) VALUES (?1, ?2)", params![name, bytes])?;
+    }
+    
+    tx.commit()?;
+    Ok(())
+}
+
+fn write_notes(tx: &Transaction, concept_id: i64, notes: &[Note]) -> Result<(), Box<dyn Error>> {
+    tx.execute("DELETE FROM notes WHERE concept_id = ?1", [concept_id])?;
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO notes (concept_id, position, text, rating, needs_review, created, modified, display, definition, flags, fields, tags, comments, author)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"
+    )?;
+    for (position, note) in notes.iter().enumerate() {
+        stmt.execute(params![
+            concept_id,
+            position,
+            note.text,
+            note.rating,
+            note.needs_review,
+            note.created,
+            note.modified,
+            note.display.to_column(),
+            note.definition,
+            NoteFlag::to_column(&note.flags),
+            serde_json::to_string(&note.fields)?,
+            tags_column(&note.tags),
+            comments_column(&note.comments),
+            note.author,
+        ])?;
+    }
+    Ok(())
+}
+
+/// Remove the rows of `table` whose id isn't in `keep`; rows below them go
+/// with them through `ON DELETE CASCADE`
+fn delete_missing(tx: &Transaction, table: &str, keep: HashSet<i64>) -> Result<(), Box<dyn Error>> {
+    let ids: Vec<i64> = {
+        let mut stmt = tx.prepare(&format!("SELECT id FROM {}", table))?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for id in ids.into_iter().filter(|id| !keep.contains(id)) {
+        tx.execute(&format!("DELETE FROM {} WHERE id = ?1", table), [id])?;
+    }
+    Ok(())
+}
+
+/// FNV-1a of the notes as JSON; a changed signature means the concept's
+/// notes need rewriting
+fn signature(notes: &[Note]) -> Result<String, Box<dyn Error>> {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in serde_json::to_vec(notes)? {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Ok(format!("{:016x}", hash))
+}
@@ -0,0 +1,98 @@
+//! Detecting the text encoding of CSV files written by older tools and
+//! transcoding them to UTF-8 while they are read. UTF-8 files pass through
+//! untouched; UTF-16 is recognized by its byte order mark or by the zero
+//! bytes of mostly-ASCII text, and anything else that isn't valid UTF-8 is
+//! taken to be Windows-1252.
+
+use encoding_rs::{Decoder, Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use std::io::{self, Cursor, Read};
+
+/// How much of the start of a file is looked at to pick the encoding
+const SAMPLE_SIZE: usize = 64 * 1024;
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Wrap `input` so it reads as UTF-8, along with the name of the encoding it
+/// was converted from, if any
+pub fn to_utf8<'a>(input: &'a mut dyn Read) -> io::Result<(Box<dyn Read + 'a>, Option<&'static str>)> {
+    let mut sample = Vec::with_capacity(SAMPLE_SIZE);
+    (&mut *input).take(SAMPLE_SIZE as u64).read_to_end(&mut sample)?;
+    let encoding = detect(&sample);
+    let rest = Cursor::new(sample).chain(input);
+    Ok(match encoding {
+        None => (Box::new(rest), None),
+        Some(encoding) => (Box::new(Transcoder::new(rest, encoding)), Some(encoding.name())),
+    })
+}
+
+/// The encoding `sample` is in, or None for UTF-8
+fn detect(sample: &[u8]) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return (encoding != UTF_8).then_some(encoding);
+    }
+    
+    // ASCII text in UTF-16 has a zero in every other byte
+    let pairs = sample.len().min(4096) / 2;
+    if pairs > 0 {
+        let zeros_at = |offset: usize| (0..pairs).filter(|i| sample[i * 2 + offset] == 0).count();
+        if zeros_at(1) * 3 > pairs * 2 {
+            return Some(UTF_16LE);
+        }
+        if zeros_at(0) * 3 > pairs * 2 {
+            return Some(UTF_16BE);
+        }
+    }
+    
+    match std::str::from_utf8(sample) {
+        Ok(_) => None,
+        // Only cut off mid-character by the end of the sample
+        Err(e) if e.error_len().is_none() => None,
+        Err(_) => Some(WINDOWS_1252),
+    }
+}
+
+struct Transcoder<R> {
+    inner: R,
+    decoder: Decoder,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    // Decoded bytes not yet handed out are output[pos..len]
+    pos: usize,
+    len: usize,
+    finished: bool,
+}
+
+impl<R: Read> Transcoder<R> {
+    fn new(inner: R, encoding: &'static Encoding) -> Self {
+        Transcoder {
+            inner,
+            decoder: encoding.new_decoder_with_bom_removal(),
+            input: vec![0; CHUNK_SIZE],
+            output: Vec::new(),
+            pos: 0,
+            len: 0,
+            finished: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Transcoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.len {
+            if self.finished {
+                return Ok(0);
+            }
+            let read = self.inner.read(&mut self.input)?;
+            let last = read == 0;
+            let needed = self.decoder.max_utf8_buffer_length(read).unwrap_or(read * 3 + 16);
+            self.output.resize(needed, 0);
+            let (_, _, written, _) = self.decoder.decode_to_utf8(&self.input[..read], &mut self.output, last);
+            self.pos = 0;
+            self.len = written;
+            self.finished = last;
+        }
+        let count = buf.len().min(self.len - self.pos);
+        buf[..count].copy_from_slice(&self.output[self.pos..self.pos + count]);
+        self.pos += count;
+        Ok(count)
+    }
+}
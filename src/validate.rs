@@ -0,0 +1,161 @@
+//! Structural checks for hand-edited CSV files, run before trusting their
+//! contents: every row is read leniently, problems are reported by line, and
+//! the rows are repaired where the intent is clear.
+
+use crate::CORE_COLUMNS;
+use crate::{compressed, text_encoding};
+use crate::i18n::{tr, tr_args};
+use csv::{ByteRecord, ReaderBuilder, StringRecord};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Put in place of an empty category, subcategory or concept
+const PLACEHOLDER: &str = "Unsorted";
+
+pub enum IssueKind {
+    MissingColumn(&'static str),
+    InvalidUtf8,
+    // More fields than headers, usually commas in an unquoted note
+    ExtraFields(usize),
+    MissingFields(usize),
+    EmptyField(&'static str),
+    EmptyNote,
+    BadRating(String),
+    // Same path and note as the row on this line
+    Duplicate(u64),
+}
+
+impl IssueKind {
+    pub fn describe(&self) -> String {
+        match self {
+            IssueKind::MissingColumn(name) => tr_args("validate-missing-column", &[("column", name.to_string().into())]),
+            IssueKind::InvalidUtf8 => tr("validate-invalid-utf8"),
+            IssueKind::ExtraFields(count) => tr_args("validate-extra-fields", &[("count", count.to_string().into())]),
+            IssueKind::MissingFields(count) => tr_args("validate-missing-fields", &[("count", count.to_string().into())]),
+            IssueKind::EmptyField(name) => tr_args("validate-empty-field", &[("column", name.to_string().into())]),
+            IssueKind::EmptyNote => tr("validate-empty-note"),
+            IssueKind::BadRating(value) => tr_args("validate-bad-rating", &[("value", value.to_string().into())]),
+            IssueKind::Duplicate(line) => tr_args("validate-duplicate", &[("line", line.to_string().into())]),
+        }
+    }
+    
+    /// What applying the fixes does about it, or None if it can't be fixed
+    pub fn fix(&self) -> Option<String> {
+        let fix = match self {
+            IssueKind::MissingColumn(_) => return None,
+            IssueKind::InvalidUtf8 => tr("validate-fix-invalid-utf8"),
+            IssueKind::ExtraFields(_) => tr("validate-fix-extra-fields"),
+            IssueKind::MissingFields(_) => tr("validate-fix-missing-fields"),
+            IssueKind::EmptyField(_) => tr_args("validate-fix-empty-field", &[("placeholder", PLACEHOLDER.into())]),
+            IssueKind::EmptyNote | IssueKind::Duplicate(_) => tr("validate-fix-drop-row"),
+            IssueKind::BadRating(_) => tr("validate-fix-bad-rating"),
+        };
+        Some(fix)
+    }
+}
+
+pub struct Issue {
+    pub line: u64,
+    pub kind: IssueKind,
+}
+
+pub struct Report {
+    pub path: String,
+    pub rows: usize,
+    pub issues: Vec<Issue>,
+    pub headers: StringRecord,
+    // Every row with the fixes applied
+    pub fixed: Vec<StringRecord>,
+}
+
+impl Report {
+    /// Whether applying the fixes leaves a file that loads
+    pub fn fixable(&self) -> bool {
+        !self.issues.is_empty() && self.issues.iter().all(|issue| issue.kind.fix().is_some())
+    }
+}
+
+pub fn check(path: &str) -> Result<Report, Box<dyn Error>> {
+    compressed::read_with(path, |input| {
+        let (input, _) = text_encoding::to_utf8(input)?;
+        let mut rdr = ReaderBuilder::new().flexible(true).from_reader(input);
+        let mut issues = Vec::new();
+        
+        let headers = rdr.byte_headers()?.clone();
+        if std::str::from_utf8(headers.as_slice()).is_err() {
+            issues.push(Issue { line: 1, kind: IssueKind::InvalidUtf8 });
+        }
+        let headers = lossy_record(&headers);
+        let mut columns = [0; CORE_COLUMNS.len()];
+        for (column, name) in columns.iter_mut().zip(CORE_COLUMNS) {
+            match headers.iter().position(|h| h == name) {
+                Some(index) => *column = index,
+                None => issues.push(Issue { line: 1, kind: IssueKind::MissingColumn(name) }),
+            }
+        }
+        let rating_column = headers.iter().position(|h| h == "Rating");
+        let missing_columns = issues.iter().any(|i| matches!(i.kind, IssueKind::MissingColumn(_)));
+        
+        let mut rows = 0;
+        let mut fixed = Vec::new();
+        // Path and note -> line of the first row holding them
+        let mut seen: HashMap<Vec<String>, u64> = HashMap::new();
+        let mut record = ByteRecord::new();
+        while rdr.read_byte_record(&mut record)? {
+            rows += 1;
+            let line = record.position().map_or(0, |p| p.line());
+            let mut issue = |kind| issues.push(Issue { line, kind });
+            
+            if std::str::from_utf8(record.as_slice()).is_err() {
+                issue(IssueKind::InvalidUtf8);
+            }
+            let mut fields: Vec<String> = lossy_record(&record).iter().map(str::to_string).collect();
+            if missing_columns {
+                continue;
+            }
+            
+            if fields.len() > headers.len() {
+                let extra = fields.len() - headers.len();
+                issue(IssueKind::ExtraFields(extra));
+                let note = columns[3];
+                let joined = fields.drain(note..=note + extra).collect::<Vec<_>>().join(",");
+                fields.insert(note, joined);
+            } else if fields.len() < headers.len() {
+                issue(IssueKind::MissingFields(headers.len() - fields.len()));
+                fields.resize(headers.len(), String::new());
+            }
+            
+            if fields[columns[3]].trim().is_empty() {
+                issue(IssueKind::EmptyNote);
+                continue;
+            }
+            for (&column, name) in columns[..3].iter().zip(CORE_COLUMNS) {
+                if fields[column].trim().is_empty() {
+                    issue(IssueKind::EmptyField(name));
+                    fields[column] = PLACEHOLDER.to_string();
+                }
+            }
+            if let Some(column) = rating_column {
+                let value = fields[column].trim();
+                if !value.is_empty() && crate::parse_rating(value).is_none() {
+                    issue(IssueKind::BadRating(value.to_string()));
+                    fields[column].clear();
+                }
+            }
+            
+            let key: Vec<String> = columns.iter().map(|&c| fields[c].clone()).collect();
+            if let Some(&first) = seen.get(&key) {
+                issue(IssueKind::Duplicate(first));
+                continue;
+            }
+            seen.insert(key, line);
+            fixed.push(StringRecord::from(fields));
+        }
+        
+        Ok(Report { path: path.to_string(), rows, issues, headers, fixed })
+    })
+}
+
+fn lossy_record(record: &ByteRecord) -> StringRecord {
+    record.iter().map(|field| String::from_utf8_lossy(field)).collect()
+}
@@ -0,0 +1,265 @@
+//! Folders of Markdown files, such as an Obsidian vault, read as a fiche for
+//! File > Import Markdown Folder. Each folder is a category; either each file
+//! is a concept, or its headings give the subcategories and concepts.
+//! Paragraphs and list items become notes, and `tags` in a file's front
+//! matter go on all of its notes. Hidden folders (`.obsidian`, `.trash`) are
+//! skipped.
+
+use crate::i18n::tr;
+use crate::{FicheRow, Microfiche, text_encoding};
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Subcategory for files right inside a category's folder
+const GENERAL: &str = "General";
+
+/// Where a file's notes go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mapping {
+    // Folder > subfolders > file
+    Files,
+    // Folders > first heading level > second heading level
+    Headings,
+}
+
+impl Mapping {
+    pub const ALL: [Mapping; 2] = [Mapping::Files, Mapping::Headings];
+
+    pub fn label(&self) -> String {
+        match self {
+            Mapping::Files => tr("vault-mapping-files"),
+            Mapping::Headings => tr("vault-mapping-headings"),
+        }
+    }
+}
+
+/// A piece of a file's body
+#[derive(Debug)]
+enum Block {
+    Heading(usize, String),
+    Note(String),
+}
+
+/// Read every `.md` file under `dir` into a fiche
+pub fn read(dir: &Path, mapping: Mapping) -> std::io::Result<Microfiche> {
+    let mut files = Vec::new();
+    markdown_files(dir, &mut files)?;
+    files.sort();
+    let vault = dir.file_name().map_or_else(|| "Vault".to_string(), |name| name.to_string_lossy().to_string());
+
+    let mut fiche = Microfiche::new();
+    for path in files {
+        let mut file = std::fs::File::open(&path)?;
+        let (mut reader, _) = text_encoding::to_utf8(&mut file)?;
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+
+        let relative = path.strip_prefix(dir).unwrap_or(&path);
+        let folders: Vec<String> = relative.parent()
+            .map(|parent| parent.iter().map(|part| part.to_string_lossy().to_string()).collect())
+            .unwrap_or_default();
+        let stem = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().to_string());
+        let (tags, body) = front_matter(&text);
+        for (category, subcategory, concept, note) in place_notes(mapping, &vault, &folders, &stem, &blocks(body)) {
+            fiche.add_row(FicheRow {
+                category,
+                subcategory,
+                concept,
+                note,
+                rating: None,
+                needs_review: false,
+                created: None,
+                modified: None,
+                fields: Default::default(),
+                display: Default::default(),
+                definition: false,
+                flags: Default::default(),
+                tags: tags.clone(),
+                author: Default::default(),
+                comments: Default::default(),
+            });
+        }
+    }
+    Ok(fiche)
+}
+
+/// Collect the Markdown files under `dir`, leaving out hidden entries
+fn markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+            continue;
+        }
+        if path.is_dir() {
+            markdown_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The tags in a leading `---` YAML block, and the text after it. Both
+/// `tags: [a, b]` and a `tags:` list of `- a` lines are understood.
+fn front_matter(text: &str) -> (BTreeSet<String>, &str) {
+    let mut tags = BTreeSet::new();
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (tags, text);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (tags, text);
+    };
+    let body = rest[end + 4..].split_once('\n').map_or("", |(_, body)| body);
+
+    let mut in_tags = false;
+    for line in rest[..end].lines() {
+        if let Some(item) = line.trim_start().strip_prefix("- ").filter(|_| in_tags && line.starts_with(char::is_whitespace)) {
+            add_tags(&mut tags, item);
+            continue;
+        }
+        in_tags = false;
+        if let Some((key, value)) = line.split_once(':')
+            && matches!(key.trim(), "tags" | "tag")
+        {
+            in_tags = value.trim().is_empty();
+            add_tags(&mut tags, value.trim().trim_start_matches('[').trim_end_matches(']'));
+        }
+    }
+    (tags, body)
+}
+
+/// Add the comma-separated tags in `value`, without quotes or `#`
+fn add_tags(tags: &mut BTreeSet<String>, value: &str) {
+    for tag in value.split(',') {
+        let tag = tag.trim().trim_matches(|c| c == '"' || c == '\'').trim_start_matches('#');
+        if !tag.is_empty() {
+            tags.insert(tag.to_lowercase());
+        }
+    }
+}
+
+/// Whether `line` starts a list item, and the item's text without its marker
+fn list_item(line: &str) -> Option<&str> {
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(marker) {
+            return Some(item);
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        return rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "));
+    }
+    None
+}
+
+/// Split a file's body into headings and notes: paragraphs, top-level list
+/// items with the lines under them, and fenced code blocks kept whole
+fn blocks(body: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    let flush = |current: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        let note = current.join("\n").trim().to_string();
+        if !note.is_empty() {
+            blocks.push(Block::Note(note));
+        }
+        current.clear();
+    };
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            current.push(line);
+            continue;
+        }
+        if in_fence {
+            current.push(line);
+            continue;
+        }
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+            flush(&mut current, &mut blocks);
+            let heading = line[hashes..].trim().trim_end_matches('#').trim();
+            if !heading.is_empty() {
+                blocks.push(Block::Heading(hashes, heading.to_string()));
+            }
+        } else if trimmed.is_empty() || ["---", "***", "___"].contains(&line.trim()) {
+            flush(&mut current, &mut blocks);
+        } else if let Some(item) = list_item(line) {
+            flush(&mut current, &mut blocks);
+            current.push(item);
+        } else {
+            // Lines under an item, indented or not, belong to it
+            current.push(line);
+        }
+    }
+    flush(&mut current, &mut blocks);
+    blocks
+}
+
+/// Each note with its category, subcategory and concept
+fn place_notes(mapping: Mapping, vault: &str, folders: &[String], stem: &str, blocks: &[Block]) -> Vec<(String, String, String, String)> {
+    let mut placed = Vec::new();
+    match mapping {
+        Mapping::Files => {
+            let category = folders.first().map_or(vault, String::as_str).to_string();
+            let subcategory = match folders.get(1..) {
+                Some(rest) if !rest.is_empty() => rest.join(" / "),
+                _ => GENERAL.to_string(),
+            };
+            // Headings lead the note after them, except a title repeating the file name
+            let mut heading: Option<String> = None;
+            for block in blocks {
+                match block {
+                    Block::Heading(_, text) if text.eq_ignore_ascii_case(stem) => heading = None,
+                    Block::Heading(level, text) => heading = Some(format!("{} {}", "#".repeat(*level), text)),
+                    Block::Note(note) => {
+                        let note = match heading.take() {
+                            Some(heading) => format!("{}\n{}", heading, note),
+                            None => note.clone(),
+                        };
+                        placed.push((category.clone(), subcategory.clone(), stem.to_string(), note));
+                    },
+                }
+            }
+        },
+        Mapping::Headings => {
+            let category = if folders.is_empty() { vault.to_string() } else { folders.join(" / ") };
+            let levels: BTreeSet<usize> = blocks.iter()
+                .filter_map(|block| match block {
+                    Block::Heading(level, _) => Some(*level),
+                    Block::Note(_) => None,
+                })
+                .collect();
+            let mut levels = levels.into_iter();
+            let (top, second) = (levels.next(), levels.next());
+            let (mut subcategory, mut concept) = (stem.to_string(), stem.to_string());
+            let mut heading: Option<String> = None;
+            for block in blocks {
+                match block {
+                    Block::Heading(level, text) if Some(*level) == top => {
+                        subcategory = text.clone();
+                        concept = text.clone();
+                        heading = None;
+                    },
+                    Block::Heading(level, text) if Some(*level) == second => {
+                        concept = text.clone();
+                        heading = None;
+                    },
+                    Block::Heading(level, text) => heading = Some(format!("{} {}", "#".repeat(*level), text)),
+                    Block::Note(note) => {
+                        let note = match heading.take() {
+                            Some(heading) => format!("{}\n{}", heading, note),
+                            None => note.clone(),
+                        };
+                        placed.push((category.clone(), subcategory.clone(), concept.clone(), note));
+                    },
+                }
+            }
+        },
+    }
+    placed
+}
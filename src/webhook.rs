@@ -0,0 +1,159 @@
+//! Webhooks: a JSON POST to a URL of the user's choosing whenever a note is
+//! created, edited or deleted, for piping notes into Slack or a personal
+//! automation. Each request is sent by `curl` (part of macOS, Windows 10 and
+//! later, and most Linux systems) without waiting for it, and is checked on
+//! later frames the same way external editors are.
+//!
+//! The payload's top-level `text` is a one-line summary, which is what
+//! Slack's incoming webhooks post; the note itself is under `note`.
+
+use crate::i18n::tr;
+use crate::{Note, note_path, timestamp_now};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+
+/// Seconds curl waits for a webhook before giving up
+const TIMEOUT: &str = "10";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    Created,
+    Edited,
+    Deleted,
+}
+
+impl Event {
+    pub const ALL: [Event; 3] = [Event::Created, Event::Edited, Event::Deleted];
+    
+    /// The payload's `event` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Created => "note.created",
+            Event::Edited => "note.edited",
+            Event::Deleted => "note.deleted",
+        }
+    }
+    
+    pub fn label(&self) -> String {
+        match self {
+            Event::Created => tr("webhook-event-created"),
+            Event::Edited => tr("webhook-event-edited"),
+            Event::Deleted => tr("webhook-event-deleted"),
+        }
+    }
+}
+
+/// A URL told about the events it subscribes to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Webhook {
+    pub url: String,
+    pub enabled: bool,
+    pub events: Vec<Event>,
+    // Only notes in this category; empty sends every category's
+    pub category: String,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Webhook {
+            url: String::new(),
+            enabled: true,
+            events: Event::ALL.to_vec(),
+            category: String::new(),
+        }
+    }
+}
+
+impl Webhook {
+    /// Whether `event` on a note in `category` should be sent here
+    pub fn wants(&self, event: Event, category: &str) -> bool {
+        self.enabled
+            && !self.url.trim().is_empty()
+            && self.events.contains(&event)
+            && (self.category.is_empty() || self.category == category)
+    }
+}
+
+/// The JSON body for `event` on `note`; `previous` is the text an edit replaced
+pub fn payload(event: Event, file: Option<&str>, (cat, sub, con): (&str, &str, &str), note: &Note, previous: Option<&str>) -> String {
+    let summary = match event {
+        Event::Created => "Created",
+        Event::Edited => "Edited",
+        Event::Deleted => "Deleted",
+    };
+    serde_json::json!({
+        "event": event.name(),
+        "text": format!("{} in {}: {}", summary, note_path(cat, sub, con), note.text),
+        "file": file,
+        "sent": timestamp_now(),
+        "note": {
+            "category": cat,
+            "subcategory": sub,
+            "concept": con,
+            "text": note.text,
+            "previous_text": previous,
+            "rating": note.rating,
+            "tags": note.tags,
+            "author": note.author,
+            "created": note.created,
+            "modified": note.modified,
+            "needs_review": note.needs_review,
+            "fields": note.fields,
+        },
+    }).to_string()
+}
+
+/// The body Send test posts
+pub fn test_payload() -> String {
+    serde_json::json!({
+        "event": "test",
+        "text": "Test message from Fisha",
+        "sent": timestamp_now(),
+    }).to_string()
+}
+
+/// A POST on its way
+pub struct Delivery {
+    pub url: String,
+    child: Child,
+}
+
+impl Delivery {
+    /// Start posting `body` to `url`
+    pub fn send(url: &str, body: &str) -> Result<Delivery, String> {
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--max-time", TIMEOUT])
+            .args(["--header", "Content-Type: application/json", "--data-binary", "@-"])
+            .arg("--url")
+            .arg(url.trim())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("curl: {}", e))?;
+        // Dropping stdin once written ends the body
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(body.as_bytes()).map_err(|e| format!("curl: {}", e))?;
+        }
+        Ok(Delivery { url: url.trim().to_string(), child })
+    }
+    
+    /// None while the request is still going, then whether it succeeded
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        match self.child.try_wait() {
+            Ok(None) => None,
+            Ok(Some(status)) if status.success() => Some(Ok(())),
+            Ok(Some(status)) => {
+                let mut error = String::new();
+                if let Some(stderr) = self.child.stderr.as_mut() {
+                    let _ = stderr.read_to_string(&mut error);
+                }
+                let error = error.trim().trim_start_matches("curl: ").to_string();
+                Some(Err(if error.is_empty() { status.to_string() } else { error }))
+            },
+            Err(e) => Some(Err(e.to_string())),
+        }
+    }
+}